@@ -1,9 +1,77 @@
+use crate::game::{self, GameData};
+use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fmt;
+
+/// Word length the letter stats and the wordlist are counted for.
+pub const WORD_LENGTH: usize = 5;
+
+/// Version of the letter stats format, written as `schema_version`.
+/// 1: the per-position `counts` only.
+/// 2: adds the `wordlist_size` and `wordlist_hash` of the words counted.
+/// 3: adds `schema_version`.
+pub const STATS_SCHEMA_VERSION: u32 = 3;
+
+/// Why letter stats can't score a wordlist. Every variant means the rankings would be
+/// garbage, so the solver refuses to start.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StatsMismatch {
+    /// The JSON is not letter stats at all.
+    Unreadable(String),
+    /// The stats were counted over words of another length.
+    WordLength { stats: usize, wordlist: usize },
+    /// The wordlist uses letters the stats have no counts for.
+    Alphabet { missing: Vec<char> },
+    /// A field the upgrade can't recompute from the rest of the file.
+    Missing(&'static str),
+    /// The stats were written by a newer build.
+    NewerSchema(u64),
+}
+
+impl fmt::Display for StatsMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StatsMismatch::Unreadable(e) => write!(f, "cannot parse letter stats: {}", e),
+            StatsMismatch::WordLength { stats, wordlist } => write!(
+                f,
+                "letter stats are for {}-letter words, the wordlist has {}-letter words",
+                stats, wordlist
+            ),
+            StatsMismatch::Alphabet { missing } => write!(
+                f,
+                "wordlist letters missing from the letter stats: {}",
+                missing.iter().collect::<String>()
+            ),
+            StatsMismatch::Missing(field) => write!(
+                f,
+                "letter stats have no `{}`, which can't be recomputed; regenerate them with `wordle-bot analyze`",
+                field
+            ),
+            StatsMismatch::NewerSchema(version) => write!(
+                f,
+                "letter stats have schema version {}, but this build reads up to version {}; update wordle-bot or regenerate them with `wordle-bot analyze`",
+                version, STATS_SCHEMA_VERSION
+            ),
+        }
+    }
+}
+
+impl std::error::Error for StatsMismatch {}
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct LetterStats {
+    /// Format of the file; `parse` upgrades older ones to `STATS_SCHEMA_VERSION`.
+    #[serde(default)]
+    pub schema_version: u32,
     pub counts: BTreeMap<char, [u32; 5]>,
+    /// Number of words counted, to tell when the wordlist changed. Missing in stats
+    /// written by older versions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wordlist_size: Option<usize>,
+    /// `wordlist_hash` of the words counted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wordlist_hash: Option<String>,
 }
 
 impl LetterStats {
@@ -15,25 +83,1062 @@ impl LetterStats {
             counts.insert(ch, [0; 5]);
         }
 
-        Self { counts }
+        Self {
+            schema_version: STATS_SCHEMA_VERSION,
+            counts,
+            wordlist_size: None,
+            wordlist_hash: None,
+        }
     }
 
     pub fn from_words(words: &[&str]) -> Self {
         let mut stats = Self::new();
 
-        for word in words {
-            let chars: Vec<char> = word.chars().collect();
-            if chars.len() != 5 {
-                continue; // skip non-5-letter words
+        let counted: Vec<&str> = words
+            .iter()
+            .copied()
+            .filter(|word| word.chars().count() == 5) // skip non-5-letter words
+            .collect();
+        for word in &counted {
+            // Characters beyond a-z (e.g. kept punctuation) join the alphabet
+            for (i, ch) in word.chars().enumerate() {
+                stats.counts.entry(ch).or_insert([0; 5])[i] += 1;
             }
+        }
+
+        stats.wordlist_size = Some(counted.len());
+        stats.wordlist_hash = Some(wordlist_hash(&counted));
+        stats
+    }
 
-            for (i, &ch) in chars.iter().enumerate() {
-                if let Some(counts) = stats.counts.get_mut(&ch) {
-                    counts[i] += 1;
+    /// Updates stats counted over a previous wordlist to `current`, given the words
+    /// added and removed since. The counts come out exactly as `from_words(current)`
+    /// would produce them; the diff is rejected when it doesn't lead to `current`.
+    pub fn apply_diff(
+        &mut self,
+        added: &[String],
+        removed: &[String],
+        current: &[String],
+    ) -> Result<()> {
+        let current_set: HashSet<&str> = current.iter().map(|w| w.as_str()).collect();
+        let added_set: HashSet<&str> = added.iter().map(|w| w.as_str()).collect();
+        if let Some(word) = added.iter().find(|w| !current_set.contains(w.as_str())) {
+            return Err(anyhow!(
+                "'{}' is added by the diff but not in the wordlist",
+                word
+            ));
+        }
+        if let Some(word) = removed
+            .iter()
+            .find(|w| current_set.contains(w.as_str()) && !added_set.contains(w.as_str()))
+        {
+            return Err(anyhow!(
+                "'{}' is removed by the diff but still in the wordlist",
+                word
+            ));
+        }
+
+        for word in removed {
+            for (i, ch) in word.chars().enumerate() {
+                let count = self
+                    .counts
+                    .get_mut(&ch)
+                    .map(|counts| &mut counts[i])
+                    .filter(|count| **count > 0)
+                    .ok_or_else(|| {
+                        anyhow!("'{}' is removed by the diff but was never counted", word)
+                    })?;
+                *count -= 1;
+            }
+        }
+        for word in added {
+            for (i, ch) in word.chars().enumerate() {
+                self.counts.entry(ch).or_insert([0; 5])[i] += 1;
+            }
+        }
+        // A recount only lists characters beyond a-z that still occur
+        self.counts
+            .retain(|ch, counts| ch.is_ascii_lowercase() || counts.iter().any(|&c| c > 0));
+
+        let size = (self.wordlist_size.unwrap_or_else(|| self.word_count()) + added.len())
+            .saturating_sub(removed.len());
+        if size != current.len() {
+            return Err(anyhow!(
+                "the diff leaves {} words, the wordlist has {}",
+                size,
+                current.len()
+            ));
+        }
+        self.wordlist_size = Some(size);
+        self.wordlist_hash = Some(wordlist_hash(current));
+
+        Ok(())
+    }
+
+    /// Parses letter stats JSON, upgrading stats of an older schema version. Stats
+    /// counted over words of another length are told apart from invalid JSON, since
+    /// both fail to fit the per-position arrays.
+    pub fn parse(json: &str) -> Result<Self, StatsMismatch> {
+        let value: serde_json::Value =
+            serde_json::from_str(json).map_err(|e| StatsMismatch::Unreadable(e.to_string()))?;
+        if !value.is_object() {
+            return Err(StatsMismatch::Unreadable(
+                "letter stats are not a JSON object".to_string(),
+            ));
+        }
+        if value.get("counts").is_none() {
+            return Err(StatsMismatch::Missing("counts"));
+        }
+        let length = value
+            .get("counts")
+            .and_then(|counts| counts.as_object())
+            .and_then(|counts| counts.values().find_map(|c| c.as_array()))
+            .map(|positions| positions.len());
+        if let Some(length) = length.filter(|&n| n != WORD_LENGTH) {
+            return Err(StatsMismatch::WordLength {
+                stats: length,
+                wordlist: WORD_LENGTH,
+            });
+        }
+        let version = match value.get("schema_version") {
+            Some(version) => version.as_u64().ok_or_else(|| {
+                StatsMismatch::Unreadable(format!("invalid schema_version {}", version))
+            })?,
+            None if value.get("wordlist_size").is_some() => 2,
+            None => 1,
+        };
+        if version > u64::from(STATS_SCHEMA_VERSION) {
+            return Err(StatsMismatch::NewerSchema(version));
+        }
+        let mut stats: LetterStats =
+            serde_json::from_value(value).map_err(|e| StatsMismatch::Unreadable(e.to_string()))?;
+        stats.upgrade(version);
+        Ok(stats)
+    }
+
+    /// Recomputes what stats of schema `version` lack from their counts. The hash of
+    /// the words counted can't be recovered, so version 1 stats stay without one and
+    /// are only checked against the wordlist by size.
+    fn upgrade(&mut self, version: u64) {
+        if version < 2 {
+            self.wordlist_size = Some(self.word_count());
+        }
+        self.schema_version = STATS_SCHEMA_VERSION;
+    }
+
+    /// Letters the stats have counts for.
+    pub fn alphabet(&self) -> BTreeSet<char> {
+        self.counts.keys().copied().collect()
+    }
+
+    /// Checks that the stats can score `words`. Hard mismatches are errors; a soft one,
+    /// where the stats were merely counted over a different list, comes back as a
+    /// reason to warn about.
+    pub fn check_wordlist<S: AsRef<str>>(
+        &self,
+        words: &[S],
+    ) -> Result<Option<String>, StatsMismatch> {
+        if let Some(word) = words
+            .iter()
+            .map(|w| w.as_ref())
+            .find(|w| w.chars().count() != WORD_LENGTH)
+        {
+            return Err(StatsMismatch::WordLength {
+                stats: WORD_LENGTH,
+                wordlist: word.chars().count(),
+            });
+        }
+
+        let alphabet = self.alphabet();
+        let missing: BTreeSet<char> = words
+            .iter()
+            .flat_map(|w| w.as_ref().chars())
+            .filter(|c| !alphabet.contains(c))
+            .collect();
+        if !missing.is_empty() {
+            return Err(StatsMismatch::Alphabet {
+                missing: missing.into_iter().collect(),
+            });
+        }
+
+        Ok(self.stale_reason(words))
+    }
+
+    /// Describes how `words` differ from the words the stats were counted over, going
+    /// by the stored size and hash. `None` when they match or the stats predate them.
+    pub fn stale_reason<S: AsRef<str>>(&self, words: &[S]) -> Option<String> {
+        let size = self.wordlist_size?;
+        if size != words.len() {
+            return Some(format!(
+                "stats were built from {} words, the wordlist has {}",
+                size,
+                words.len()
+            ));
+        }
+        let hash = self.wordlist_hash.as_deref()?;
+        (hash != wordlist_hash(words))
+            .then(|| format!("stats were built from a different list of {} words", size))
+    }
+
+    /// Number of words the stats were counted over (every word has a first letter).
+    pub fn word_count(&self) -> usize {
+        self.counts.values().map(|c| c[0] as usize).sum()
+    }
+
+    /// Pretty JSON with each per-position count array kept on a single line.
+    pub fn to_pretty_json(&self) -> Result<String> {
+        let json = serde_json::to_string_pretty(self)?;
+
+        // This regex joins lines between '[' and ']'
+        let re = regex::Regex::new(r"\[\s*((?:\d+,\s*)*\d+)\s*\]").unwrap();
+        Ok(re
+            .replace_all(&json, |caps: &regex::Captures| {
+                let inner = caps[1].split_whitespace().collect::<Vec<_>>().join(" ");
+                format!("[{}]", inner)
+            })
+            .to_string())
+    }
+
+    /// One row per letter: per-position counts and the total, with a header.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("letter,pos1,pos2,pos3,pos4,pos5,total\n");
+        for (ch, counts) in &self.counts {
+            let total: u32 = counts.iter().sum();
+            let cells: Vec<String> = counts.iter().map(|c| c.to_string()).collect();
+            csv.push_str(&format!("{},{},{}\n", ch, cells.join(","), total));
+        }
+        csv
+    }
+
+    /// Aligned table for reading in a terminal.
+    pub fn to_text(&self) -> String {
+        let mut text = format!(
+            "{:<7}{:>7}{:>7}{:>7}{:>7}{:>7}{:>8}\n",
+            "Letter", "1", "2", "3", "4", "5", "Total"
+        );
+        for (ch, counts) in &self.counts {
+            let total: u32 = counts.iter().sum();
+            text.push_str(&format!("{:<7}", ch));
+            for count in counts {
+                text.push_str(&format!("{:>7}", count));
+            }
+            text.push_str(&format!("{:>8}\n", total));
+        }
+        text
+    }
+
+    /// Compares `self` (the previous stats) with `new`. A per-position count is listed
+    /// when it changed by at least `threshold` of its previous value; counts of added
+    /// and removed letters are left to the alphabet lists.
+    pub fn diff(&self, new: &LetterStats, threshold: f64) -> StatsDiff {
+        let (before, after) = (self.alphabet(), new.alphabet());
+        let mut changes = Vec::new();
+        for (ch, old_counts) in &self.counts {
+            let Some(new_counts) = new.counts.get(ch) else {
+                continue;
+            };
+            for (position, (&old, &new)) in old_counts.iter().zip(new_counts).enumerate() {
+                let change = old.abs_diff(new) as f64;
+                if old != new && change >= threshold * old.max(1) as f64 {
+                    changes.push((*ch, position, old, new));
                 }
             }
         }
+        StatsDiff {
+            words_before: self.word_count(),
+            words_after: new.word_count(),
+            added_letters: after.difference(&before).copied().collect(),
+            removed_letters: before.difference(&after).copied().collect(),
+            changes,
+        }
+    }
+}
 
-        stats
+/// How new letter stats differ from the previous ones (`analyze --dry-run`).
+#[derive(Debug, Default, PartialEq)]
+pub struct StatsDiff {
+    pub words_before: usize,
+    pub words_after: usize,
+    pub added_letters: Vec<char>,
+    pub removed_letters: Vec<char>,
+    /// Letter, position (from 0) and counts before and after, for every count that
+    /// changed by at least the threshold.
+    pub changes: Vec<(char, usize, u32, u32)>,
+}
+
+impl StatsDiff {
+    /// Summary of the differences, one line per changed count.
+    pub fn to_text(&self) -> String {
+        let mut text = format!(
+            "Words: {} -> {} ({:+})\n",
+            self.words_before,
+            self.words_after,
+            self.words_after as i64 - self.words_before as i64
+        );
+        if !self.added_letters.is_empty() {
+            text.push_str(&format!(
+                "Letters added: {}\n",
+                self.added_letters.iter().collect::<String>()
+            ));
+        }
+        if !self.removed_letters.is_empty() {
+            text.push_str(&format!(
+                "Letters removed: {}\n",
+                self.removed_letters.iter().collect::<String>()
+            ));
+        }
+        if self.changes.is_empty() {
+            text.push_str("No per-position count changed beyond the threshold.\n");
+        } else {
+            text.push_str("Per-position counts:\n");
+            for (ch, position, old, new) in &self.changes {
+                text.push_str(&format!(
+                    "  {} at {}: {} -> {} ({:+.1}%)\n",
+                    ch,
+                    position + 1,
+                    old,
+                    new,
+                    (*new as f64 - *old as f64) / (*old).max(1) as f64 * 100.0
+                ));
+            }
+        }
+        text
+    }
+}
+
+/// Clusters `candidates` by the letters at the positions `game` hasn't confirmed yet.
+/// The unknown position with the most distinct letters is left blank, so each group
+/// holds the words that differ only there; with one unknown position left, that is
+/// the single group. Keys read like `C R A _ E`, with known and grouping letters in
+/// capitals. Largest groups come first, ties by key.
+pub fn group_candidates(game: &GameData, candidates: &[String]) -> Vec<(String, Vec<String>)> {
+    let unknown: Vec<usize> = (0..WORD_LENGTH)
+        .filter(|&i| game.correct_positions[i].is_none())
+        .collect();
+    let blank = unknown.iter().copied().max_by_key(|&i| {
+        let letters: HashSet<char> = candidates.iter().filter_map(|w| w.chars().nth(i)).collect();
+        // Equal counts go to the leftmost position
+        (letters.len(), std::cmp::Reverse(i))
+    });
+
+    let mut groups: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for word in candidates {
+        let key: Vec<String> = word
+            .chars()
+            .enumerate()
+            .map(|(i, ch)| match game.correct_positions[i] {
+                Some(known) => known.to_ascii_uppercase().to_string(),
+                None if Some(i) == blank => "_".to_string(),
+                None => ch.to_ascii_uppercase().to_string(),
+            })
+            .collect();
+        groups.entry(key.join(" ")).or_default().push(word.clone());
+    }
+
+    let mut groups: Vec<(String, Vec<String>)> = groups.into_iter().collect();
+    for (_, words) in &mut groups {
+        words.sort();
+    }
+    groups.sort_by(|a, b| b.1.len().cmp(&a.1.len()).then_with(|| a.0.cmp(&b.0)));
+    groups
+}
+
+/// For each position, the share of `candidates` with each letter there. Any alphabet
+/// and word length work: positions run to the longest candidate, and a position only
+/// lists the letters that occur in it.
+pub fn position_probabilities(candidates: &[&str]) -> Vec<BTreeMap<char, f64>> {
+    let mut counts: Vec<BTreeMap<char, usize>> = Vec::new();
+    for word in candidates {
+        for (i, ch) in word.chars().enumerate() {
+            if counts.len() <= i {
+                counts.push(BTreeMap::new());
+            }
+            *counts[i].entry(ch).or_default() += 1;
+        }
+    }
+    let total = candidates.len() as f64;
+    counts
+        .into_iter()
+        .map(|position| {
+            position
+                .into_iter()
+                .map(|(ch, count)| (ch, count as f64 / total))
+                .collect()
+        })
+        .collect()
+}
+
+/// The `count` most likely letters of a position, most likely first, ties by letter.
+pub fn top_letters(position: &BTreeMap<char, f64>, count: usize) -> Vec<(char, f64)> {
+    let mut letters: Vec<(char, f64)> = position.iter().map(|(&ch, &p)| (ch, p)).collect();
+    letters.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+    letters.truncate(count);
+    letters
+}
+
+/// Runners-up listed by `near_miss_report`.
+pub const NEAR_MISSES: usize = 5;
+
+/// Candidate sets listed, those before the last guesses.
+const TRACED_SETS: usize = 3;
+
+/// Words listed per candidate set before it is cut short.
+const TRACED_WORDS: usize = 12;
+
+/// One guess of a finished game with the candidates that fit before it.
+pub struct TraceStep {
+    pub guess: String,
+    /// Feedback in `w`/`m`/`c` form.
+    pub pattern: String,
+    pub candidates: Vec<String>,
+}
+
+/// Positions with the same letter that make two answers too much alike to follow
+/// each other.
+pub const SIMILAR_POSITIONS: usize = 4;
+
+/// Whether `a` and `b` have the same letter in at least `threshold` positions, like
+/// FILLS and HILLS at 4. Used to keep recent answers apart (`simulate
+/// --recent-window`, `play --practice not-like-<word>`).
+pub fn words_similar(a: &str, b: &str, threshold: usize) -> bool {
+    a.chars().zip(b.chars()).filter(|(x, y)| x == y).count() >= threshold
+}
+
+/// Levenshtein distance between two words.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let next = (diagonal + usize::from(ca != cb))
+                .min(row[j] + 1)
+                .min(row[j + 1] + 1);
+            diagonal = row[j + 1];
+            row[j + 1] = next;
+        }
+    }
+    row[b.len()]
+}
+
+/// Post-game look at how close the other candidates came: the candidate sets before
+/// the last guesses, then the `NEAR_MISSES` runners-up closest to `answer` by edit
+/// distance, each with the guess that ruled it out and the feedback it would have
+/// given instead.
+pub fn near_miss_report(trace: &[TraceStep], answer: &str) -> String {
+    let answer = answer.to_lowercase();
+    let mut text = String::from("Near misses:\n");
+    let first = trace.len().saturating_sub(TRACED_SETS);
+    for (i, step) in trace.iter().enumerate().skip(first) {
+        let mut words: Vec<&str> = step.candidates.iter().map(String::as_str).collect();
+        words.sort();
+        let more = words.len().saturating_sub(TRACED_WORDS);
+        words.truncate(TRACED_WORDS);
+        let line = format!(
+            "  Before guess {} ({}): {} candidate(s): {}{}",
+            i + 1,
+            step.guess.to_uppercase(),
+            step.candidates.len(),
+            words.join(", "),
+            if more > 0 {
+                format!(" and {} more", more)
+            } else {
+                String::new()
+            }
+        );
+        text.push_str(line.trim_end());
+        text.push('\n');
+    }
+
+    // Each runner-up with the last guess it survived until. Every word fits before
+    // the first guess, so only those that survived it count, if there are later ones.
+    let mut survived: BTreeMap<&str, usize> = BTreeMap::new();
+    for (i, step) in trace.iter().enumerate().skip(usize::from(trace.len() > 1)) {
+        for word in step.candidates.iter().filter(|w| **w != answer) {
+            survived.insert(word, i);
+        }
+    }
+    let mut runners: Vec<(&str, usize, usize)> = survived
+        .into_iter()
+        .map(|(word, step)| (word, step, edit_distance(word, &answer)))
+        .collect();
+    runners.sort_by(|a, b| a.2.cmp(&b.2).then(b.1.cmp(&a.1)).then(a.0.cmp(b.0)));
+    runners.truncate(NEAR_MISSES);
+    if runners.is_empty() {
+        text.push_str("  No other candidate came close.\n");
+        return text;
+    }
+
+    text.push_str(&format!(
+        "Closest runners-up to {}:\n",
+        answer.to_uppercase()
+    ));
+    for (word, step, _) in runners {
+        let positions: Vec<String> = word
+            .chars()
+            .zip(answer.chars())
+            .enumerate()
+            .filter(|(_, (a, b))| a != b)
+            .map(|(i, _)| (i + 1).to_string())
+            .collect();
+        let differs = match positions.as_slice() {
+            [position] => format!("only at position {}", position),
+            _ => format!("at positions {}", positions.join(", ")),
+        };
+        let guess = &trace[step];
+        let would = game::grade(&guess.guess, word).to_string();
+        if would == guess.pattern {
+            text.push_str(&format!(
+                "  {} still fit after the last guess; it differs from {} {}.\n",
+                word.to_uppercase(),
+                answer.to_uppercase(),
+                differs
+            ));
+            continue;
+        }
+        text.push_str(&format!(
+            "  {} survived until guess {}; it differs from {} {}, which your guess {} ({}) probed: {} instead of {}.\n",
+            word.to_uppercase(),
+            step + 1,
+            answer.to_uppercase(),
+            differs,
+            step + 1,
+            guess.guess.to_uppercase(),
+            would,
+            guess.pattern
+        ));
+    }
+    text
+}
+
+/// Pseudo-answers the base rates count for in `history_prior`: with this many past
+/// answers, history and wordlist weigh the same.
+pub const PRIOR_STRENGTH: f64 = 100.0;
+
+/// Whether a word has one of the features the history prior learns.
+type FeatureTest = fn(&str) -> bool;
+
+/// Word features the history prior learns a rate for.
+const PRIOR_FEATURES: &[(&str, FeatureTest)] = &[
+    ("plural -s", |w| w.ends_with('s') && !w.ends_with("ss")),
+    ("double letter", |w| {
+        let letters: HashSet<char> = w.chars().collect();
+        letters.len() < w.chars().count()
+    }),
+    ("-ed ending", |w| w.ends_with("ed")),
+    ("-er ending", |w| w.ends_with("er")),
+    ("-y ending", |w| w.ends_with('y')),
+];
+
+/// How often one feature shows up among past answers, against the answer pool.
+#[derive(Debug, Clone)]
+pub struct FeatureAdjustment {
+    pub feature: &'static str,
+    /// Share of the answer pool with the feature.
+    pub base_rate: f64,
+    /// Past answers with the feature.
+    pub hits: usize,
+    /// Rate after shrinking the observed one toward `base_rate`.
+    pub estimate: f64,
+    has_feature: FeatureTest,
+}
+
+impl FeatureAdjustment {
+    /// Factor for a word's likelihood: `estimate / base_rate` with the feature,
+    /// `(1 - estimate) / (1 - base_rate)` without. 1 when the pool has no such word
+    /// or only such words.
+    pub fn multiplier(&self, has_feature: bool) -> f64 {
+        if self.base_rate <= 0.0 || self.base_rate >= 1.0 {
+            1.0
+        } else if has_feature {
+            self.estimate / self.base_rate
+        } else {
+            (1.0 - self.estimate) / (1.0 - self.base_rate)
+        }
+    }
+}
+
+/// Answer likelihoods learned from past answers (`--history-prior`).
+#[derive(Debug, Clone)]
+pub struct HistoryPrior {
+    /// Past answers the rates were learned from.
+    pub answers: usize,
+    pub adjustments: Vec<FeatureAdjustment>,
+}
+
+impl HistoryPrior {
+    /// Relative likelihood of `word` being the answer: the product of the feature
+    /// multipliers, 1 for a word no more or less likely than any other.
+    pub fn weight(&self, word: &str) -> f64 {
+        self.adjustments
+            .iter()
+            .map(|adjustment| adjustment.multiplier((adjustment.has_feature)(word)))
+            .product()
+    }
+}
+
+/// Learns how much more or less often each feature appears among `answers` than in
+/// `pool`. Each rate is shrunk toward the pool's with `PRIOR_STRENGTH` pseudo-answers,
+/// so a short history barely moves the weights and none leaves them all at 1.
+pub fn history_prior<S: AsRef<str>>(answers: &[S], pool: &[String]) -> HistoryPrior {
+    let adjustments = PRIOR_FEATURES
+        .iter()
+        .map(|(feature, has)| {
+            let base_rate =
+                pool.iter().filter(|w| has(w)).count() as f64 / pool.len().max(1) as f64;
+            let hits = answers.iter().filter(|w| has(w.as_ref())).count();
+            let estimate = (hits as f64 + PRIOR_STRENGTH * base_rate)
+                / (answers.len() as f64 + PRIOR_STRENGTH);
+            FeatureAdjustment {
+                feature,
+                base_rate,
+                hits,
+                estimate,
+                has_feature: *has,
+            }
+        })
+        .collect();
+    HistoryPrior {
+        answers: answers.len(),
+        adjustments,
+    }
+}
+
+/// Overall letter share below which `rare_letters` counts a letter as rare. Q, X, J
+/// and Z fall below it in the bundled stats.
+pub const RARE_LETTER_FREQUENCY: f64 = 0.01;
+
+/// Share of the candidates that has to contain a rare letter before it is flagged.
+pub const RARE_LETTER_SHARE: f64 = 0.75;
+
+/// Factor on the frequency score of a word containing a flagged rare letter.
+pub const RARE_LETTER_BOOST: f64 = 1.5;
+
+/// Limits of `rare_letters` (`solve --rare-letter-share`, `--rare-letter-frequency`).
+#[derive(Debug, Clone, Copy)]
+pub struct RareLetterLimits {
+    /// Share of the candidates above which a rare letter is flagged.
+    pub share: f64,
+    /// Overall letter share below which a letter is rare.
+    pub frequency: f64,
+}
+
+impl Default for RareLetterLimits {
+    fn default() -> Self {
+        Self {
+            share: RARE_LETTER_SHARE,
+            frequency: RARE_LETTER_FREQUENCY,
+        }
+    }
+}
+
+/// A rare letter most of the remaining candidates contain.
+#[derive(Debug, Clone, Copy)]
+pub struct RareLetter {
+    pub letter: char,
+    /// Share of all letters in `stats` that are this one.
+    pub frequency: f64,
+    /// Share of the candidates containing it.
+    pub share: f64,
+}
+
+/// Letters rarer than `limits.frequency` over the whole wordlist that more than
+/// `limits.share` of `candidates` contain, most common among the candidates first.
+/// The frequency ranking underrates such a letter, since it scores by the stats of
+/// the whole list. A letter every candidate contains is left out: probing it tells
+/// nothing.
+pub fn rare_letters(
+    stats: &LetterStats,
+    candidates: &[String],
+    limits: RareLetterLimits,
+) -> Vec<RareLetter> {
+    let total: u32 = stats.counts.values().flatten().sum();
+    if total == 0 || candidates.is_empty() {
+        return Vec::new();
+    }
+    let mut found: Vec<RareLetter> = stats
+        .counts
+        .iter()
+        .map(|(&letter, counts)| (letter, counts.iter().sum::<u32>() as f64 / total as f64))
+        .filter(|&(_, frequency)| frequency < limits.frequency)
+        .map(|(letter, frequency)| RareLetter {
+            letter,
+            frequency,
+            share: candidates.iter().filter(|w| w.contains(letter)).count() as f64
+                / candidates.len() as f64,
+        })
+        .filter(|rare| rare.share > limits.share && rare.share < 1.0)
+        .collect();
+    found.sort_by(|a, b| b.share.total_cmp(&a.share));
+    found
+}
+
+/// Order-independent fingerprint of a word list: 64-bit FNV-1a over the sorted words,
+/// in hex.
+pub fn wordlist_hash<S: AsRef<str>>(words: &[S]) -> String {
+    let mut sorted: Vec<&str> = words.iter().map(|w| w.as_ref()).collect();
+    sorted.sort_unstable();
+    content_hash(&sorted)
+}
+
+/// 64-bit FNV-1a over `parts` in order, each followed by a newline, in hex.
+pub fn content_hash<S: AsRef<str>>(parts: &[S]) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for part in parts {
+        for byte in part.as_ref().bytes().chain([b'\n']) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    }
+    format!("{:016x}", hash)
+}
+
+/// Added and removed words from a diff: `+word` and `-word` lines, as printed by
+/// `git diff -U0 wordlist.txt`. Headers, hunk markers and context lines are skipped,
+/// and so are entries that aren't 5 letters long since they were never counted.
+pub fn parse_word_diff(diff: &str) -> (Vec<String>, Vec<String>) {
+    let mut added = Vec::new();
+    let mut removed = Vec::new();
+    for line in diff.lines() {
+        if line.starts_with("+++") || line.starts_with("---") {
+            continue;
+        }
+        let (list, word) = match line.split_at_checked(1) {
+            Some(("+", word)) => (&mut added, word),
+            Some(("-", word)) => (&mut removed, word),
+            _ => continue,
+        };
+        let word = word.trim().to_lowercase();
+        if word.chars().count() == 5 {
+            list.push(word);
+        }
+    }
+    (added, removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::Solver;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn fixture_words() -> Vec<String> {
+        Solver::fixture().all_words
+    }
+
+    fn stats_of(words: &[String]) -> LetterStats {
+        let refs: Vec<&str> = words.iter().map(|w| w.as_str()).collect();
+        LetterStats::from_words(&refs)
+    }
+
+    /// Words of `pool` kept with probability `keep`.
+    fn sample(pool: &[String], keep: f64, rng: &mut StdRng) -> Vec<String> {
+        pool.iter()
+            .filter(|_| rng.random_bool(keep))
+            .cloned()
+            .collect()
+    }
+
+    /// Words of `a` that aren't in `b`.
+    fn minus(a: &[String], b: &[String]) -> Vec<String> {
+        a.iter().filter(|w| !b.contains(w)).cloned().collect()
+    }
+
+    #[test]
+    fn incremental_update_matches_a_full_recount() {
+        let mut pool = fixture_words();
+        // A character beyond a-z that the update has to add and drop again
+        pool.push("ne'er".to_string());
+        for seed in 0..20 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let old = sample(&pool, 0.8, &mut rng);
+            let new = sample(&pool, 0.8, &mut rng);
+            for (from, to) in [(&old, &new), (&new, &old)] {
+                let mut updated = stats_of(from);
+                updated
+                    .apply_diff(&minus(to, from), &minus(from, to), to)
+                    .unwrap();
+                assert_eq!(
+                    updated.to_pretty_json().unwrap(),
+                    stats_of(to).to_pretty_json().unwrap(),
+                    "seed {}",
+                    seed
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn diff_that_misses_a_change_is_rejected() {
+        let words = fixture_words();
+        let (old, new) = (&words[..150], &words[..160]);
+        let mut stats = stats_of(old);
+        // Only 9 of the 10 added words
+        assert!(stats.apply_diff(&new[150..159], &[], new).is_err());
+        let mut stats = stats_of(new);
+        assert!(stats.apply_diff(&[], &new[..1], new).is_err());
+    }
+
+    #[test]
+    fn word_diff_lines_are_parsed() {
+        let diff = "--- a/wordlist.txt\n+++ b/wordlist.txt\n@@ -1 +1 @@\n-Crane\n+slate\n+toolong\n context\n";
+        assert_eq!(
+            parse_word_diff(diff),
+            (vec!["slate".to_string()], vec!["crane".to_string()])
+        );
+    }
+
+    /// Fixture stats counted as if every word had a sixth letter.
+    fn six_letter_stats_json() -> String {
+        let mut value = serde_json::to_value(stats_of(&fixture_words())).unwrap();
+        for positions in value["counts"].as_object_mut().unwrap().values_mut() {
+            positions.as_array_mut().unwrap().push(0.into());
+        }
+        value.to_string()
+    }
+
+    #[test]
+    fn six_letter_stats_are_a_word_length_mismatch() {
+        assert_eq!(
+            LetterStats::parse(&six_letter_stats_json()).unwrap_err(),
+            StatsMismatch::WordLength {
+                stats: 6,
+                wordlist: WORD_LENGTH
+            }
+        );
+    }
+
+    #[test]
+    fn wordlist_letters_without_counts_are_an_alphabet_mismatch() {
+        let stats = stats_of(&fixture_words());
+        let mut words = fixture_words();
+        words.push("ne'er".to_string());
+        assert_eq!(
+            stats.check_wordlist(&words).unwrap_err(),
+            StatsMismatch::Alphabet {
+                missing: vec!['\'']
+            }
+        );
+        let longer = ["cranes".to_string()];
+        assert_eq!(
+            stats.check_wordlist(&longer).unwrap_err(),
+            StatsMismatch::WordLength {
+                stats: WORD_LENGTH,
+                wordlist: 6
+            }
+        );
+    }
+
+    #[test]
+    fn embedded_stats_fit_the_embedded_wordlist() {
+        let stats = LetterStats::parse(include_str!("../letter_stats.json")).unwrap();
+        let words: Vec<&str> = include_str!("../wordlist.txt").lines().collect();
+        assert_eq!(stats.check_wordlist(&words), Ok(None));
+    }
+
+    fn words(list: &[&str]) -> Vec<String> {
+        list.iter().map(|w| w.to_string()).collect()
+    }
+
+    #[test]
+    fn candidates_group_around_their_most_varied_unknown_position() {
+        // GRANT leaves R A N confirmed and the first and last letters open
+        let mut game = GameData::new();
+        game.add_line("grant", "wcccw");
+        let candidates = words(&[
+            "prank", "grand", "crane", "drank", "crank", "brand", "brane",
+        ]);
+
+        // Five first letters against three last ones: the first is left blank
+        let groups = group_candidates(&game, &candidates);
+        assert_eq!(
+            groups,
+            [
+                ("_ R A N K".to_string(), words(&["crank", "drank", "prank"])),
+                ("_ R A N D".to_string(), words(&["brand", "grand"])),
+                ("_ R A N E".to_string(), words(&["brane", "crane"])),
+            ]
+        );
+
+        // As many first as last letters: the leftmost position is left blank
+        let groups = group_candidates(&game, &words(&["crank", "brand"]));
+        let keys: Vec<&str> = groups.iter().map(|(key, _)| key.as_str()).collect();
+        assert_eq!(keys, ["_ R A N D", "_ R A N K"]);
+
+        // Blanking the last letter instead when it varies most
+        let groups = group_candidates(&game, &words(&["crank", "crane", "crand", "brand"]));
+        assert_eq!(
+            groups,
+            [
+                ("C R A N _".to_string(), words(&["crand", "crane", "crank"])),
+                ("B R A N _".to_string(), words(&["brand"])),
+            ]
+        );
+    }
+
+    #[test]
+    fn old_stats_fixtures_upgrade_to_the_current_schema() {
+        let words = fixture_words();
+        let current = stats_of(&words);
+        for (json, hash) in [
+            (include_str!("../tests/fixtures/letter_stats_v1.json"), None),
+            (
+                include_str!("../tests/fixtures/letter_stats_v2.json"),
+                current.wordlist_hash.as_ref(),
+            ),
+        ] {
+            let stats = LetterStats::parse(json).unwrap();
+            assert_eq!(stats.schema_version, STATS_SCHEMA_VERSION);
+            assert_eq!(stats.counts, current.counts);
+            assert_eq!(stats.wordlist_size, Some(words.len()));
+            assert_eq!(stats.wordlist_hash.as_ref(), hash);
+            assert_eq!(stats.check_wordlist(&words), Ok(None));
+        }
+    }
+
+    #[test]
+    fn stats_of_another_list_only_warn() {
+        let words = fixture_words();
+        let stats = stats_of(&words[..150]);
+        assert!(stats.check_wordlist(&words).unwrap().is_some());
+    }
+
+    fn adjustment<'a>(prior: &'a HistoryPrior, feature: &str) -> &'a FeatureAdjustment {
+        prior
+            .adjustments
+            .iter()
+            .find(|adjustment| adjustment.feature == feature)
+            .unwrap()
+    }
+
+    #[test]
+    fn prior_without_history_leaves_every_weight_at_one() {
+        let pool = fixture_words();
+        let prior = history_prior::<String>(&[], &pool);
+        for word in &pool {
+            assert!((prior.weight(word) - 1.0).abs() < 1e-12, "{}", word);
+        }
+    }
+
+    #[test]
+    fn prior_rates_shrink_toward_the_pool() {
+        // A quarter of the pool ends in -s; every past answer does
+        let pool: Vec<String> = ["cats", "frog", "bird", "fish"]
+            .iter()
+            .map(|w| w.to_string())
+            .collect();
+        for (answers, expected) in [
+            (10, 35.0 / 110.0),
+            (100, 125.0 / 200.0),
+            (900, 925.0 / 1000.0),
+        ] {
+            let history = vec!["hats"; answers];
+            let plural = adjustment(&history_prior(&history, &pool), "plural -s").clone();
+            assert_eq!(plural.hits, answers);
+            assert!((plural.base_rate - 0.25).abs() < 1e-12);
+            assert!(
+                (plural.estimate - expected).abs() < 1e-12,
+                "{} answers",
+                answers
+            );
+            assert!((plural.multiplier(true) - expected / 0.25).abs() < 1e-12);
+            assert!((plural.multiplier(false) - (1.0 - expected) / 0.75).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn feature_missing_from_the_pool_is_not_weighted() {
+        let pool: Vec<String> = vec!["bird".to_string(), "fish".to_string()];
+        let prior = history_prior(&["hats", "cats"], &pool);
+        let plural = adjustment(&prior, "plural -s");
+        assert_eq!(plural.multiplier(true), 1.0);
+        assert_eq!(plural.multiplier(false), 1.0);
+    }
+
+    #[test]
+    fn stats_diff_lists_count_changes_and_alphabet_changes() {
+        let old = stats_of(&["crane".to_string(), "crate".to_string()]);
+        let new = stats_of(&[
+            "grate".to_string(),
+            "grave".to_string(),
+            "ne'er".to_string(),
+        ]);
+
+        assert_eq!(
+            old.diff(&new, 0.5),
+            StatsDiff {
+                words_before: 2,
+                words_after: 3,
+                added_letters: vec!['\''],
+                removed_letters: vec![],
+                changes: vec![
+                    ('c', 0, 2, 0),
+                    ('e', 1, 0, 1),
+                    ('e', 3, 0, 1),
+                    ('g', 0, 0, 2),
+                    ('n', 0, 0, 1),
+                    ('n', 3, 1, 0),
+                    ('r', 4, 0, 1),
+                    ('v', 3, 0, 1),
+                ],
+            }
+        );
+
+        // Only G's count grew by 1.5 times its previous value (taken as 1 from 0)
+        let diff = old.diff(&new, 1.5);
+        assert_eq!(diff.changes, vec![('g', 0, 0, 2)]);
+        assert_eq!(
+            diff.to_text(),
+            "Words: 2 -> 3 (+1)\nLetters added: '\nPer-position counts:\n  g at 1: 0 -> 2 (+200.0%)\n"
+        );
+
+        // The other way round the apostrophe is removed, and its counts aren't listed
+        let back = new.diff(&old, 0.5);
+        assert_eq!(back.removed_letters, vec!['\'']);
+        assert!(back.added_letters.is_empty());
+        assert!(back.changes.iter().all(|&(ch, ..)| ch != '\''));
+        assert_eq!(
+            old.diff(&old, 0.0),
+            StatsDiff {
+                words_before: 2,
+                words_after: 2,
+                ..StatsDiff::default()
+            }
+        );
+    }
+
+    #[test]
+    fn near_miss_report_replays_the_feedback_runners_up_would_get() {
+        let mut candidates: Vec<String> = [
+            "crane", "shade", "shake", "shale", "shape", "spade", "stale",
+        ]
+        .map(String::from)
+        .to_vec();
+        let mut game = GameData::new();
+        let mut trace = Vec::new();
+        for guess in ["stale", "spade", "shade"] {
+            let pattern = game::grade(guess, "shade").to_string();
+            game.add_line(guess, &pattern);
+            let after = crate::filter::Filter::new(&game, &candidates).filter_words();
+            trace.push(TraceStep {
+                guess: guess.to_string(),
+                pattern,
+                candidates: std::mem::replace(&mut candidates, after),
+            });
+        }
+
+        assert_eq!(
+            near_miss_report(&trace, "SHADE"),
+            "Near misses:\n\
+             \x20 Before guess 1 (STALE): 7 candidate(s): crane, shade, shake, shale, shape, spade, stale\n\
+             \x20 Before guess 2 (SPADE): 4 candidate(s): shade, shake, shape, spade\n\
+             \x20 Before guess 3 (SHADE): 1 candidate(s): shade\n\
+             Closest runners-up to SHADE:\n\
+             \x20 SHAKE survived until guess 2; it differs from SHADE only at position 4, which your guess 2 (SPADE) probed: cwcwc instead of cwccc.\n\
+             \x20 SHAPE survived until guess 2; it differs from SHADE only at position 4, which your guess 2 (SPADE) probed: cmcwc instead of cwccc.\n\
+             \x20 SPADE survived until guess 2; it differs from SHADE only at position 2, which your guess 2 (SPADE) probed: ccccc instead of cwccc.\n"
+        );
     }
 }
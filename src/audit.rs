@@ -0,0 +1,233 @@
+use crate::openers::{self, write_json};
+use crate::output::{CellFormat, Table};
+use crate::simulate;
+use crate::solver::Solver;
+use crate::strategy::{Strategy, MAX_GUESSES};
+use crate::util;
+use crate::wordlist::PoolOptions;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::PathBuf;
+use std::thread;
+
+/// Fewest answers sharing all letters but one for them to form a family.
+pub const MIN_FAMILY_SIZE: usize = 4;
+
+/// Default largest family audited; bigger ones are counted and skipped.
+pub const DEFAULT_MAX_CLUSTER_SIZE: usize = 20;
+
+/// Games each worker plays between two saves of the progress.
+const BATCH_GAMES: usize = 25;
+
+pub struct AuditOptions {
+    pub pool: PoolOptions,
+    /// Families with more answers than this are skipped.
+    pub max_cluster_size: usize,
+}
+
+/// Answers that differ in one position only, e.g. `_ills`: FILLS, HILLS, KILLS, ...
+pub struct Family {
+    /// The shared letters, `_` at the position that varies.
+    pub key: String,
+    pub words: Vec<String>,
+}
+
+/// Groups `words` into families of at least `min_size` members. A word belongs to
+/// one family per position, so it can appear in several. Largest families first,
+/// ties by key.
+pub fn families(words: &[String], min_size: usize) -> Vec<Family> {
+    let mut groups: HashMap<String, Vec<String>> = HashMap::new();
+    for word in words {
+        for position in 0..word.len() {
+            let mut key = word.clone();
+            key.replace_range(position..position + 1, "_");
+            groups.entry(key).or_default().push(word.clone());
+        }
+    }
+    let mut families: Vec<Family> = groups
+        .into_iter()
+        .filter(|(_, words)| words.len() >= min_size.max(2))
+        .map(|(key, mut words)| {
+            words.sort();
+            words.dedup();
+            Family { key, words }
+        })
+        .collect();
+    families.sort_by(|a, b| b.words.len().cmp(&a.words.len()).then(a.key.cmp(&b.key)));
+    families
+}
+
+/// Guess counts so far of an audit, saved after every batch so an interrupted run
+/// picks up where it stopped.
+#[derive(Serialize, Deserialize)]
+struct Progress {
+    wordlist_hash: String,
+    max_cluster_size: usize,
+    guesses: BTreeMap<String, usize>,
+}
+
+/// wordlist_audit.json in the active profile's data dir, present while an audit is
+/// unfinished.
+fn progress_path() -> Option<PathBuf> {
+    util::profile_dir(util::profile()).map(|dir| dir.join("wordlist_audit.json"))
+}
+
+/// Plays every member of the answer families from a cold start with the minimax
+/// strategy and returns the members it can't solve within `MAX_GUESSES`, with the
+/// family and guesses of each. This is the minimax strategy's play, not a proof
+/// that no strategy could do better. Prints a summary of the audit.
+pub fn run_audit(options: AuditOptions) -> Result<Table> {
+    let mut solver = Solver::with_pool(options.pool)?;
+    solver.strategy = Strategy::Minimax;
+    solver.endgame = None;
+    let wordlist_hash = openers::lists_hash(&solver.all_words, &solver.answer_words);
+
+    let all = families(&solver.answer_words, MIN_FAMILY_SIZE);
+    let family_count = all.len();
+    let (audited, skipped): (Vec<Family>, Vec<Family>) = all
+        .into_iter()
+        .partition(|family| family.words.len() <= options.max_cluster_size);
+    let mut members: Vec<String> = audited
+        .iter()
+        .flat_map(|family| family.words.iter().cloned())
+        .collect();
+    members.sort();
+    members.dedup();
+
+    let progress_path = progress_path();
+    let resumed = progress_path
+        .as_ref()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str::<Progress>(&content).ok())
+        .filter(|progress| {
+            progress.wordlist_hash == wordlist_hash
+                && progress.max_cluster_size == options.max_cluster_size
+        });
+    let mut progress = match resumed {
+        Some(mut progress) => {
+            progress.guesses.retain(|word, _| members.contains(word));
+            println!(
+                "Resuming the audit: {} of {} words already played.",
+                progress.guesses.len(),
+                members.len()
+            );
+            progress
+        }
+        None => Progress {
+            wordlist_hash,
+            max_cluster_size: options.max_cluster_size,
+            guesses: BTreeMap::new(),
+        },
+    };
+
+    let remaining: Vec<String> = members
+        .iter()
+        .filter(|word| !progress.guesses.contains_key(*word))
+        .cloned()
+        .collect();
+    let weights = util::read_solver_config()?;
+    let threads = util::threads().min(remaining.len().max(1));
+    let mut workers: Vec<Solver> = (1..threads).map(|_| solver.fork()).collect();
+    workers.push(solver);
+
+    println!(
+        "Auditing {} families of {} to {} answers ({} words, {} threads, minimax from a cold start)...",
+        audited.len(),
+        MIN_FAMILY_SIZE,
+        options.max_cluster_size,
+        members.len(),
+        workers.len()
+    );
+
+    for batch in remaining.chunks(BATCH_GAMES * workers.len()) {
+        let share = batch.len().div_ceil(workers.len());
+        let results: Vec<Result<Vec<usize>>> = thread::scope(|scope| {
+            let handles: Vec<_> = workers
+                .iter_mut()
+                .zip(batch.chunks(share))
+                .map(|(solver, targets)| {
+                    let weights = &weights;
+                    scope.spawn(move || simulate::play_targets(solver, targets, weights))
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|h| h.join().expect("audit worker panicked"))
+                .collect()
+        });
+        for (targets, guesses) in batch.chunks(share).zip(results) {
+            for (word, guesses) in targets.iter().zip(guesses?) {
+                progress.guesses.insert(word.clone(), guesses);
+            }
+        }
+        if let Some(path) = &progress_path {
+            write_json(path, &progress)?;
+        }
+    }
+    if let Some(path) = &progress_path {
+        let _ = fs::remove_file(path);
+    }
+
+    let mut table = Table::new()
+        .column("word", "Word", CellFormat::Text)
+        .column("family", "Family", CellFormat::Text)
+        .column("family_size", "Size", CellFormat::Integer)
+        .column("guesses", "Guesses", CellFormat::Integer);
+    let mut offenders = Vec::new();
+    let mut problem_families = 0;
+    for family in &audited {
+        let mut found = false;
+        for word in &family.words {
+            let guesses = progress.guesses.get(word).copied().unwrap_or(0);
+            if guesses > MAX_GUESSES {
+                found = true;
+                if !offenders.contains(word) {
+                    offenders.push(word.clone());
+                }
+                table.push_row(vec![
+                    word.as_str().into(),
+                    family.key.as_str().into(),
+                    family.words.len().into(),
+                    guesses.into(),
+                ]);
+            }
+        }
+        problem_families += usize::from(found);
+    }
+
+    let worst = progress
+        .guesses
+        .iter()
+        .max_by(|a, b| a.1.cmp(b.1).then(b.0.cmp(a.0)));
+
+    println!(
+        "\n{} of {} families audited ({} larger than {} skipped).",
+        audited.len(),
+        family_count,
+        skipped.len(),
+        options.max_cluster_size
+    );
+    if let Some((word, guesses)) = worst {
+        println!(
+            "Hardest member: {} ({} guesses).",
+            word.to_uppercase(),
+            guesses
+        );
+    }
+    if offenders.is_empty() {
+        println!(
+            "Minimax solves every family member within {} guesses.",
+            MAX_GUESSES
+        );
+    } else {
+        println!(
+            "{} words in {} families need more than {} guesses with minimax:",
+            offenders.len(),
+            problem_families,
+            MAX_GUESSES
+        );
+    }
+    Ok(table)
+}
@@ -0,0 +1,160 @@
+use crate::game::GameData;
+use crate::ranking::weighted_rank;
+use crate::solver::Solver;
+use crate::strategy::Strategy;
+use crate::util;
+use anyhow::{anyhow, Result};
+use std::thread;
+
+/// Outcome of ranking one game state.
+pub struct RankedState {
+    /// Words left in the candidate pool after filtering by the state.
+    pub candidates: usize,
+    /// Best guesses as indices into `Solver::all_words`, with their scores, best first.
+    pub top: Vec<(u32, f64)>,
+}
+
+/// Ranks the guesses for many game states with `strategy`, `top_k` per state. The
+/// states are spread over threads, each with a fork of `solver`, so the wordlist and
+/// stats are loaded once. Nothing is printed or written. `weights` are the
+/// solver_config.json weight sets used by the frequency strategy.
+pub fn batch_rank(
+    states: &[GameData],
+    solver: &Solver,
+    strategy: Strategy,
+    weights: &[(f64, f64, f64)],
+    top_k: usize,
+) -> Result<Vec<RankedState>> {
+    if states.is_empty() {
+        return Ok(Vec::new());
+    }
+    let threads = util::threads().min(states.len());
+    let chunk_size = states.len().div_ceil(threads);
+
+    let chunks: Vec<Result<Vec<RankedState>>> = thread::scope(|scope| {
+        let handles: Vec<_> = states
+            .chunks(chunk_size)
+            .map(|chunk| {
+                let mut worker = solver.fork();
+                worker.strategy = strategy;
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .map(|state| rank_state(&mut worker, state, weights, top_k))
+                        .collect()
+                })
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .map(|h| h.join().expect("ranking worker panicked"))
+            .collect()
+    });
+
+    let mut ranked = Vec::with_capacity(states.len());
+    for chunk in chunks {
+        ranked.extend(chunk?);
+    }
+    Ok(ranked)
+}
+
+/// Ranks a single state the way the solver's suggestion list does. The frequency
+/// strategy ranks the candidates themselves; a detected trap's elimination word is
+/// not part of the ranking.
+pub fn rank_state(
+    solver: &mut Solver,
+    state: &GameData,
+    weights: &[(f64, f64, f64)],
+    top_k: usize,
+) -> Result<RankedState> {
+    solver.game = state.clone();
+    solver.reset_candidates();
+    solver.update_wordlist();
+
+    let top = if solver.active_strategy() == Strategy::Frequency {
+        let attempt = solver.game.lines.len().min(weights.len() - 1);
+        let word_refs: Vec<&str> = solver.current_words.iter().map(|s| s.as_str()).collect();
+        weighted_rank(
+            &word_refs,
+            &solver.stats_json,
+            weights[attempt],
+            solver.position_weights,
+        )?
+        .top(top_k)
+        .iter()
+        .map(|(word, score)| {
+            let index = solver
+                .cache
+                .guess_index(word)
+                .ok_or_else(|| anyhow!("'{}' is not in the guess list", word))?;
+            Ok((index as u32, *score))
+        })
+        .collect::<Result<_>>()?
+    } else {
+        let mut scores = solver.pattern_ranking().scores;
+        scores.truncate(top_k);
+        scores
+            .into_iter()
+            .map(|(guess, score)| (guess as u32, score))
+            .collect()
+    };
+
+    Ok(RankedState {
+        candidates: solver.current_words.len(),
+        top,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::seq::IndexedRandom;
+    use rand::SeedableRng;
+    use std::time::{Duration, Instant};
+
+    /// `count` states of two random guesses graded against a random answer.
+    fn random_states(solver: &Solver, count: usize) -> Vec<GameData> {
+        let mut rng = StdRng::seed_from_u64(7);
+        (0..count)
+            .map(|_| {
+                let answer = solver.answer_words.choose(&mut rng).unwrap();
+                let mut state = GameData::new();
+                for _ in 0..2 {
+                    let guess = solver.all_words.choose(&mut rng).unwrap();
+                    let line = Solver::evaluate_word(guess, answer);
+                    state.add_line(guess, &line.pattern());
+                }
+                state
+            })
+            .collect()
+    }
+
+    #[test]
+    fn batch_matches_ranking_each_state_alone() {
+        let solver = Solver::fixture();
+        let states = random_states(&solver, 1000);
+        for strategy in [Strategy::Frequency, Strategy::Entropy] {
+            let started = Instant::now();
+            let ranked = batch_rank(&states, &solver, strategy, &solver.weights, 5).unwrap();
+            assert!(started.elapsed() < Duration::from_secs(30));
+            assert_eq!(ranked.len(), states.len());
+
+            let mut single = solver.fork();
+            single.strategy = strategy;
+            for (state, batch) in states.iter().zip(&ranked) {
+                let alone = rank_state(&mut single, state, &solver.weights, 5).unwrap();
+                assert_eq!(batch.candidates, alone.candidates);
+                assert_eq!(batch.top, alone.top);
+            }
+        }
+    }
+
+    #[test]
+    fn no_states_rank_to_nothing() {
+        let solver = Solver::fixture();
+        let ranked = batch_rank(&[], &solver, Strategy::Frequency, &solver.weights, 5).unwrap();
+        assert!(ranked.is_empty());
+    }
+}
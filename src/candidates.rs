@@ -0,0 +1,410 @@
+use crate::game::GameData;
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+
+/// Number of distinct feedback patterns for a 5-letter word (3^5).
+pub const PATTERN_COUNT: usize = 243;
+
+/// Default number of guess rows kept by the pattern cache.
+pub const DEFAULT_CACHE_ROWS: usize = 16384;
+
+/// Set of answer-list indices backed by a fixed-size bitset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CandidateSet {
+    bits: Vec<u64>,
+    universe: usize,
+}
+
+impl CandidateSet {
+    pub fn empty(universe: usize) -> Self {
+        Self {
+            bits: vec![0; universe.div_ceil(64)],
+            universe,
+        }
+    }
+
+    pub fn full(universe: usize) -> Self {
+        let mut set = Self::empty(universe);
+        for i in 0..universe {
+            set.insert(i);
+        }
+        set
+    }
+
+    pub fn insert(&mut self, index: usize) {
+        self.bits[index / 64] |= 1 << (index % 64);
+    }
+
+    pub fn contains(&self, index: usize) -> bool {
+        index < self.universe && self.bits[index / 64] & (1 << (index % 64)) != 0
+    }
+
+    pub fn intersect_with(&mut self, other: &CandidateSet) {
+        for (a, b) in self.bits.iter_mut().zip(&other.bits) {
+            *a &= b;
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.bits.iter().map(|b| b.count_ones() as usize).sum()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.bits.iter().enumerate().flat_map(|(block, &bits)| {
+            let mut bits = bits;
+            std::iter::from_fn(move || {
+                if bits == 0 {
+                    return None;
+                }
+                let offset = bits.trailing_zeros() as usize;
+                bits &= bits - 1;
+                Some(block * 64 + offset)
+            })
+        })
+    }
+}
+
+/// Encodes the feedback of `guess` against `target` as a base-3 number
+/// (w = 0, m = 1, c = 2, first letter most significant). Same rules as
+/// `Solver::evaluate_word`, including duplicate letters.
+pub fn pattern_code(guess: &[u8; 5], target: &[u8; 5]) -> u8 {
+    let mut states = [0u8; 5];
+    let mut remaining = [0u8; 28];
+
+    for i in 0..5 {
+        if guess[i] == target[i] {
+            states[i] = 2;
+        } else {
+            remaining[letter_slot(target[i])] += 1;
+        }
+    }
+
+    for i in 0..5 {
+        if states[i] == 2 {
+            continue;
+        }
+        let slot = &mut remaining[letter_slot(guess[i])];
+        if *slot > 0 {
+            states[i] = 1;
+            *slot -= 1;
+        }
+    }
+
+    states.iter().fold(0, |code, &s| code * 3 + s)
+}
+
+/// Counter slot of a word byte: a-z, then the apostrophe and hyphen a wordlist may keep.
+fn letter_slot(b: u8) -> usize {
+    match b {
+        b'a'..=b'z' => (b - b'a') as usize,
+        b'\'' => 26,
+        _ => 27,
+    }
+}
+
+/// Turns a `wmc` pattern string into its code.
+pub fn encode_pattern(pattern: &str) -> u8 {
+    pattern.chars().fold(0, |code, c| {
+        code * 3
+            + match c {
+                'c' => 2,
+                'm' => 1,
+                _ => 0,
+            }
+    })
+}
+
+pub fn word_bytes(word: &str) -> [u8; 5] {
+    let mut bytes = [b'a'; 5];
+    for (slot, b) in bytes.iter_mut().zip(word.bytes()) {
+        *slot = b;
+    }
+    bytes
+}
+
+/// Least-recently-used map with a fixed capacity.
+struct Lru<K, V> {
+    entries: HashMap<K, (V, u64)>,
+    /// Every use of a key with the clock at that use, oldest first. A key used again
+    /// leaves a stale item behind, which eviction skips and `compact` drops, so
+    /// eviction takes amortized constant time rather than a scan of every entry.
+    order: VecDeque<(K, u64)>,
+    capacity: usize,
+    clock: u64,
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> Lru<K, V> {
+    fn new(capacity: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+            capacity: capacity.max(1),
+            clock: 0,
+        }
+    }
+
+    fn get(&mut self, key: &K) -> Option<V> {
+        self.clock += 1;
+        let clock = self.clock;
+        let value = self.entries.get_mut(key).map(|(value, used)| {
+            *used = clock;
+            value.clone()
+        })?;
+        self.order.push_back((key.clone(), clock));
+        self.compact();
+        Some(value)
+    }
+
+    fn insert(&mut self, key: K, value: V) {
+        if self.entries.len() >= self.capacity && !self.entries.contains_key(&key) {
+            while let Some((oldest, used)) = self.order.pop_front() {
+                if Self::is_current(&self.entries, &oldest, used) {
+                    self.entries.remove(&oldest);
+                    break;
+                }
+            }
+        }
+        self.clock += 1;
+        self.entries.insert(key.clone(), (value, self.clock));
+        self.order.push_back((key, self.clock));
+        self.compact();
+    }
+
+    /// Whether `used` is the last use of `key`, not a stale item of `order`.
+    fn is_current(entries: &HashMap<K, (V, u64)>, key: &K, used: u64) -> bool {
+        entries.get(key).is_some_and(|&(_, last)| last == used)
+    }
+
+    /// Drops the stale items once they outnumber the entries, which leaves one item
+    /// per entry. That happens at most once every `capacity` uses.
+    fn compact(&mut self) {
+        if self.order.len() > 2 * self.capacity {
+            let entries = &self.entries;
+            self.order
+                .retain(|(key, used)| Self::is_current(entries, key, *used));
+        }
+    }
+}
+
+/// Lazily computed feedback patterns of guesses against every answer, plus the
+/// survivor sets derived from them. Guesses are addressed by their index in the
+/// guess list, answers by their index in the answer list. Clones share the word
+/// lists and everything computed so far, so forked solvers on other threads fill
+/// and reuse one cache.
+#[derive(Clone)]
+pub struct PatternCache {
+    words: Arc<CacheWords>,
+    rows: SharedLru<usize, Arc<Vec<u8>>>,
+    survivors: SharedLru<(usize, u8), Arc<CandidateSet>>,
+}
+
+/// An LRU map the clones of a pattern cache fill together.
+type SharedLru<K, V> = Arc<Mutex<Lru<K, V>>>;
+
+/// The word lists a pattern cache is built over, which never change.
+struct CacheWords {
+    guesses: Vec<[u8; 5]>,
+    answers: Vec<[u8; 5]>,
+    guess_index: HashMap<String, usize>,
+    guess_answer: Vec<Option<usize>>,
+}
+
+impl PatternCache {
+    pub fn new(guesses: &[String], answers: &[String], capacity: usize) -> Self {
+        let answer_index: HashMap<String, usize> = answers
+            .iter()
+            .enumerate()
+            .map(|(i, w)| (w.clone(), i))
+            .collect();
+
+        Self {
+            words: Arc::new(CacheWords {
+                guesses: guesses.iter().map(|w| word_bytes(w)).collect(),
+                answers: answers.iter().map(|w| word_bytes(w)).collect(),
+                guess_index: guesses
+                    .iter()
+                    .enumerate()
+                    .map(|(i, w)| (w.clone(), i))
+                    .collect(),
+                guess_answer: guesses
+                    .iter()
+                    .map(|w| answer_index.get(w).copied())
+                    .collect(),
+            }),
+            rows: Arc::new(Mutex::new(Lru::new(capacity))),
+            survivors: Arc::new(Mutex::new(Lru::new(capacity))),
+        }
+    }
+
+    pub fn guess_count(&self) -> usize {
+        self.words.guesses.len()
+    }
+
+    pub fn guess_index(&self, word: &str) -> Option<usize> {
+        self.words.guess_index.get(word).copied()
+    }
+
+    /// Answer-list index of a guess, if the guess is also a possible answer.
+    pub fn answer_of_guess(&self, guess: usize) -> Option<usize> {
+        self.words.guess_answer[guess]
+    }
+
+    /// Answers consistent with every recorded guess, built by intersecting survivor sets.
+    pub fn consistent_answers(&mut self, game: &GameData) -> CandidateSet {
+        let mut set = CandidateSet::full(self.words.answers.len());
+        for line in &game.lines {
+            let pattern: String = line.cells.iter().map(|cell| cell.state).collect();
+            match self.guess_index(&line.word) {
+                Some(g) => set.intersect_with(&self.survivors(g, encode_pattern(&pattern))),
+                None => return CandidateSet::empty(self.words.answers.len()),
+            }
+        }
+        set
+    }
+
+    pub fn guess(&self, index: usize) -> String {
+        String::from_utf8_lossy(&self.words.guesses[index]).into_owned()
+    }
+
+    /// Pattern code of guess `guess` against every answer.
+    pub fn row(&mut self, guess: usize) -> Arc<Vec<u8>> {
+        if let Some(row) = self.rows.lock().unwrap().get(&guess) {
+            return row;
+        }
+        let g = self.words.guesses[guess];
+        let row = Arc::new(
+            self.words
+                .answers
+                .iter()
+                .map(|t| pattern_code(&g, t))
+                .collect(),
+        );
+        self.rows.lock().unwrap().insert(guess, Arc::clone(&row));
+        row
+    }
+
+    /// Answers that would produce `pattern` for `guess`.
+    pub fn survivors(&mut self, guess: usize, pattern: u8) -> Arc<CandidateSet> {
+        if let Some(set) = self.survivors.lock().unwrap().get(&(guess, pattern)) {
+            return set;
+        }
+        let row = self.row(guess);
+        let mut set = CandidateSet::empty(self.words.answers.len());
+        for (i, &code) in row.iter().enumerate() {
+            if code == pattern {
+                set.insert(i);
+            }
+        }
+        let set = Arc::new(set);
+        self.survivors
+            .lock()
+            .unwrap()
+            .insert((guess, pattern), Arc::clone(&set));
+        set
+    }
+
+    /// How `guess` partitions the candidates: for each candidate in order, the number
+    /// of its feedback bucket by first appearance. Two guesses with equal signatures
+    /// split the candidates into the same groups and are strategically the same.
+    pub fn partition_signature(&mut self, guess: usize, candidates: &CandidateSet) -> Vec<u8> {
+        let row = self.row(guess);
+        let mut bucket_numbers = [u8::MAX; PATTERN_COUNT];
+        let mut next = 0;
+        candidates
+            .iter()
+            .map(|i| {
+                let number = &mut bucket_numbers[row[i] as usize];
+                if *number == u8::MAX {
+                    *number = next;
+                    next += 1;
+                }
+                *number
+            })
+            .collect()
+    }
+
+    /// Counts how the candidates split into feedback buckets for `guess`.
+    pub fn buckets(&mut self, guess: usize, candidates: &CandidateSet) -> [u32; PATTERN_COUNT] {
+        let row = self.row(guess);
+        let mut counts = [0u32; PATTERN_COUNT];
+        for i in candidates.iter() {
+            counts[row[i] as usize] += 1;
+        }
+        counts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::{entropy_rank, Deadline};
+    use crate::wordlist::Wordlist;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn lru_evicts_the_entry_used_longest_ago() {
+        let mut lru = Lru::new(3);
+        for key in 0..3 {
+            lru.insert(key, key * 10);
+        }
+        assert_eq!(lru.get(&0), Some(0));
+        lru.insert(3, 30);
+        assert_eq!(lru.get(&1), None);
+        // Reinserting a key doesn't evict another
+        lru.insert(3, 31);
+        assert_eq!(
+            [0, 2, 3].map(|key| lru.get(&key)),
+            [Some(0), Some(20), Some(31)]
+        );
+        lru.insert(4, 40);
+        assert_eq!(lru.get(&0), None);
+
+        // Uses far beyond the capacity keep the order bounded and the recency right
+        for _ in 0..100 {
+            lru.get(&2);
+        }
+        assert!(lru.order.len() <= 2 * lru.capacity);
+        lru.insert(5, 50);
+        assert_eq!(lru.get(&3), None);
+        assert_eq!(lru.get(&2), Some(20));
+    }
+
+    #[test]
+    fn full_lru_evicts_without_scanning_every_entry() {
+        let mut lru = Lru::new(DEFAULT_CACHE_ROWS);
+        let started = Instant::now();
+        for key in 0..8 * DEFAULT_CACHE_ROWS {
+            lru.insert(key, key);
+            lru.get(&(key / 2));
+        }
+        // A scan of every entry per insert takes over a minute here
+        assert!(started.elapsed() < Duration::from_secs(10));
+        assert_eq!(lru.entries.len(), DEFAULT_CACHE_ROWS);
+    }
+
+    #[test]
+    fn entropy_ranking_of_the_answer_list_is_timed() {
+        let answers = Wordlist::load().unwrap().answers;
+        let everything = CandidateSet::full(answers.len());
+        let order: Vec<usize> = (0..answers.len()).collect();
+        // Fewer rows than guesses, so the second ranking evicts on every insert
+        let mut cache = PatternCache::new(&answers, &answers, answers.len() / 4);
+        let mut rank = || {
+            let started = Instant::now();
+            let ranking =
+                entropy_rank(&mut cache, &everything, &order, Deadline::after(None), true);
+            (ranking, started.elapsed())
+        };
+        let (first, first_time) = rank();
+        let (second, second_time) = rank();
+        assert!(first.is_complete());
+        assert_eq!(first.scores, second.scores);
+        assert!(
+            first_time + second_time < Duration::from_secs(30),
+            "{:?} and {:?}",
+            first_time,
+            second_time
+        );
+    }
+}
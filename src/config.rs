@@ -0,0 +1,763 @@
+use crate::util::{self, SolverConfig, DEFAULT_PROFILE};
+use anyhow::{anyhow, Result};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+/// A command-line option the config file can set, as `key = value` for `--key value`.
+struct ConfigOption {
+    key: &'static str,
+    commands: &'static [&'static str],
+    /// Takes no value on the command line; set with `true` in the file.
+    switch: bool,
+    /// Shown by `config show` when neither the file nor a flag sets the option.
+    default: &'static str,
+}
+
+const POOL_COMMANDS: &[&str] = &[
+    "analyze",
+    "solve",
+    "simulate",
+    "engine",
+    "tune",
+    "openers",
+    "score",
+    "batch-rank",
+    "race",
+    "import-share",
+];
+const STRATEGY_COMMANDS: &[&str] = &[
+    "solve",
+    "simulate",
+    "engine",
+    "batch-rank",
+    "openers",
+    "import-share",
+];
+const TIMED_COMMANDS: &[&str] = &["solve", "simulate", "engine", "batch-rank", "race"];
+/// Pool commands plus `wordlist`, which loads a list without playing.
+const LIST_COMMANDS: &[&str] = &[
+    "analyze",
+    "solve",
+    "simulate",
+    "engine",
+    "tune",
+    "openers",
+    "score",
+    "batch-rank",
+    "race",
+    "import-share",
+    "wordlist",
+];
+/// Commands printing an `output::Table`.
+const TABLE_COMMANDS: &[&str] = &["tune", "openers", "stats", "review", "prior", "wordlist"];
+const OUTPUT_COMMANDS: &[&str] = &[
+    "analyze", "rank", "race", "tune", "openers", "stats", "review", "prior", "wordlist",
+];
+const ALL_COMMANDS: &[&str] = &[
+    "solve",
+    "play",
+    "simulate",
+    "tune",
+    "openers",
+    "engine",
+    "analyze",
+    "rank",
+    "score",
+    "batch-rank",
+    "race",
+    "stats",
+    "review",
+    "prior",
+    "wordlist",
+    "import-share",
+    "export-share",
+    "verify-commitment",
+    "doctor",
+];
+
+const fn option(
+    key: &'static str,
+    commands: &'static [&'static str],
+    default: &'static str,
+) -> ConfigOption {
+    ConfigOption {
+        key,
+        commands,
+        switch: false,
+        default,
+    }
+}
+
+const fn switch(key: &'static str, commands: &'static [&'static str]) -> ConfigOption {
+    ConfigOption {
+        key,
+        commands,
+        switch: true,
+        default: "false",
+    }
+}
+
+const OPTIONS: &[ConfigOption] = &[
+    option("strategy", STRATEGY_COMMANDS, "frequency"),
+    option("endgame", STRATEGY_COMMANDS, "none"),
+    option("think-ms", TIMED_COMMANDS, "none"),
+    option("exclude-letters", POOL_COMMANDS, ""),
+    option("require-letters", POOL_COMMANDS, ""),
+    switch(
+        "exclude-used",
+        &["solve", "simulate", "engine", "tune", "openers"],
+    ),
+    option("punctuation", LIST_COMMANDS, "reject"),
+    option(
+        "wordlist",
+        &["analyze", "rank", "solve", "simulate"],
+        "built-in",
+    ),
+    switch("dry-run", &["analyze"]),
+    option("threshold", &["analyze"], "0.05"),
+    // Only `wordlist info` takes other lengths; the game commands stop at any but 5
+    option("length", &["wordlist"], "5"),
+    option("probe-threshold", POOL_COMMANDS, "0.9"),
+    switch("minimal", &["solve"]),
+    switch("json", &["solve"]),
+    switch("no-cache", &["solve"]),
+    switch("report", &["solve"]),
+    switch("spoiler-free", &["solve"]),
+    switch("history-prior", &["solve"]),
+    switch("no-prognosis", &["solve"]),
+    switch("assist-on-demand", &["solve"]),
+    switch("no-sanity-checks", &["solve"]),
+    switch("near-misses", &["solve", "export-share"]),
+    switch("stable", &["solve"]),
+    option("stable-epsilon", &["solve"], "0.0001"),
+    option("score-precision", &["solve"], "3"),
+    switch("prefetch", &["solve"]),
+    option("prefetch-patterns", &["solve"], "8"),
+    switch("rare-letters", &["solve"]),
+    option("rare-letter-share", &["solve"], "0.75"),
+    option("rare-letter-frequency", &["solve"], "0.01"),
+    option("answer", &["import-share", "export-share"], "none"),
+    option(
+        "format",
+        OUTPUT_COMMANDS,
+        "json for analyze, text for the others",
+    ),
+    option(
+        "output",
+        &[
+            "analyze", "rank", "race", "tune", "openers", "stats", "review", "prior", "simulate",
+            "wordlist",
+        ],
+        "letter_stats.json for analyze, none for simulate, stdout for the others",
+    ),
+    option("sort-by", TABLE_COMMANDS, "none"),
+    switch("html", &["stats"]),
+    option("since", &["review"], "none"),
+    switch("easy", &["play"]),
+    option("layout", &["play"], "qwerty"),
+    option("practice", &["play"], "none"),
+    option("practice-glob", &["play"], "none"),
+    switch("committed", &["play"]),
+    switch("timed", &["play"]),
+    option("guess-seconds", &["play"], "60"),
+    option(
+        "lang",
+        &["solve", "play"],
+        "LANG environment variable, else en",
+    ),
+    option(
+        "seed",
+        &["play", "simulate", "tune", "race", "openers", "wordlist"],
+        "random",
+    ),
+    option("error-rate", &["simulate"], "0"),
+    switch("check-filter", &["simulate"]),
+    switch("perf", &["simulate"]),
+    option("stratified", &["simulate"], "none"),
+    option("buckets", &["simulate"], "3.5,4.5"),
+    option("per-bucket", &["simulate"], "none"),
+    option("confirm-above", &["simulate"], "100000"),
+    switch("yes", &["simulate"]),
+    option("recent-window", &["simulate"], "0"),
+    option("human-opener", &["simulate"], "none"),
+    option("method", &["tune"], "halving"),
+    option("configs", &["tune"], "16"),
+    option("population", &["tune"], "24"),
+    option("generations", &["tune"], "15"),
+    switch("tune-positions", &["tune"]),
+    option(
+        "sample",
+        &["tune", "race", "openers"],
+        "32 for tune, 300 for race and openers",
+    ),
+    option("candidates", &["openers"], "10"),
+    option("max-cluster-size", &["wordlist"], "20"),
+    option("size", &["wordlist"], "none"),
+    option("stratify-by", &["wordlist"], "uniform"),
+    switch("auto-select", &["openers"]),
+    option("budget", &["tune"], "none"),
+    option(
+        "threads",
+        &["tune", "openers", "batch-rank"],
+        "all cores, one less with background",
+    ),
+    switch("background", &["simulate", "tune", "openers", "batch-rank"]),
+    option("profile", ALL_COMMANDS, "default"),
+    option("width", ALL_COMMANDS, "terminal width, 100 when piped"),
+    switch("no-color", ALL_COMMANDS),
+    switch("verbose", ALL_COMMANDS),
+];
+
+/// Where the effective value of an option comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Source {
+    Default,
+    File,
+    Flag,
+}
+
+impl Source {
+    fn name(self) -> &'static str {
+        match self {
+            Source::Default => "default",
+            Source::File => "file",
+            Source::Flag => "flag",
+        }
+    }
+}
+
+/// Defaults for command-line options from `wordle-bot.toml`. Top-level keys apply to
+/// every command that accepts them; a `[command]` section overrides them for that
+/// command. Explicit flags always win.
+pub struct Config {
+    /// Files read, in order; values of a later file override those of an earlier one.
+    paths: Vec<PathBuf>,
+    /// Values by section (`""` for top level), then by key.
+    sections: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl Config {
+    /// Loads the file given with `--config <path>`, or else wordle-bot.toml in the data
+    /// dir and then the one in the active profile's directory, when they exist. The
+    /// profile is the `--profile` flag, else the data dir file's `profile` key.
+    /// `--config` and its value are removed from `args`.
+    pub fn load(args: &mut Vec<String>) -> Result<Self> {
+        let explicit = match args.iter().position(|a| a == "--config") {
+            Some(i) if i + 1 < args.len() => {
+                let path = PathBuf::from(args.remove(i + 1));
+                args.remove(i);
+                Some(path)
+            }
+            Some(_) => return Err(anyhow!("--config needs a file path.")),
+            None => None,
+        };
+
+        let mut config = Self {
+            paths: Vec::new(),
+            sections: BTreeMap::new(),
+        };
+        match explicit {
+            Some(path) if !path.exists() => {
+                return Err(anyhow!("Config file {} does not exist.", path.display()))
+            }
+            Some(path) => config.read(path)?,
+            None => {
+                if let Some(path) = config_path(DEFAULT_PROFILE).filter(|p| p.exists()) {
+                    config.read(path)?;
+                }
+                let command = args.get(1).map_or("", String::as_str);
+                let profile = args
+                    .iter()
+                    .position(|a| a == "--profile")
+                    .and_then(|i| args.get(i + 1).cloned())
+                    .or_else(|| {
+                        let option = OPTIONS.iter().find(|o| o.key == "profile")?;
+                        config.file_value(option, command).map(String::from)
+                    });
+                if let Some(profile) = profile.filter(|p| p != DEFAULT_PROFILE) {
+                    // Checks the name before it becomes part of a path
+                    util::set_profile(&profile)?;
+                    if let Some(path) = config_path(&profile).filter(|p| p.exists()) {
+                        config.read(path)?;
+                    }
+                }
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// Parses the file at `path` over the values read so far.
+    fn read(&mut self, path: PathBuf) -> Result<()> {
+        let content = fs::read_to_string(&path)
+            .map_err(|e| anyhow!("Failed to read config file {}: {}", path.display(), e))?;
+        for (section, values) in parse(&content, &path.display().to_string())? {
+            self.sections.entry(section).or_default().extend(values);
+        }
+        self.paths.push(path);
+        Ok(())
+    }
+
+    /// Appends the configured values for `command` to `args` as flags, except for
+    /// options `args` already sets.
+    pub fn apply(&self, command: &str, args: &mut Vec<String>) {
+        for option in OPTIONS.iter().filter(|o| o.commands.contains(&command)) {
+            if flag_value(option, args).is_some() {
+                continue;
+            }
+            let Some(value) = self.file_value(option, command) else {
+                continue;
+            };
+            if option.switch {
+                if value == "true" {
+                    args.push(format!("--{}", option.key));
+                }
+            } else {
+                args.push(format!("--{}", option.key));
+                args.push(value.to_string());
+            }
+        }
+    }
+
+    /// Prints the effective value and source of every option `command` accepts (all
+    /// options, from the top level of the file, without a command), given `args`.
+    pub fn show(&self, command: Option<&str>, args: &[String]) -> Result<()> {
+        if let Some(command) = command.filter(|c| !ALL_COMMANDS.contains(c)) {
+            return Err(anyhow!("Unknown command: {}", command));
+        }
+
+        if self.paths.is_empty() {
+            match config_path(util::profile()) {
+                Some(path) => println!("Config file: {} (not found)", path.display()),
+                None => println!("Config file: none (no data directory)"),
+            }
+        }
+        for path in &self.paths {
+            println!("Config file: {}", path.display());
+        }
+        if let Some(path) = util::solver_config_path() {
+            let meta = fs::read_to_string(&path)
+                .ok()
+                .and_then(|content| util::parse_solver_config(&content).ok())
+                .and_then(|config| config.meta);
+            match meta {
+                Some(meta) => println!("Solver config: {} ({})", path.display(), meta.describe()),
+                None => println!("Solver config: {}", path.display()),
+            }
+        }
+        if let Some(command) = command {
+            println!("Options for {}:", command);
+        }
+
+        let options = OPTIONS
+            .iter()
+            .filter(|o| command.is_none_or(|c| o.commands.contains(&c)));
+        for option in options {
+            let (value, source) = if let Some(value) = flag_value(option, args) {
+                (value, Source::Flag)
+            } else if let Some(value) = self.file_value(option, command.unwrap_or("")) {
+                (value.to_string(), Source::File)
+            } else {
+                (option.default.to_string(), Source::Default)
+            };
+            let value = if value.is_empty() { "(none)" } else { &value };
+            println!("  {:<16} {:<24} ({})", option.key, value, source.name());
+        }
+
+        Ok(())
+    }
+
+    fn file_value(&self, option: &ConfigOption, command: &str) -> Option<&str> {
+        [command, ""]
+            .iter()
+            .find_map(|section| self.sections.get(*section)?.get(option.key))
+            .map(|value| value.as_str())
+    }
+}
+
+/// Reads a solver config file for `config diff`.
+pub fn load_solver_config(path: &str) -> Result<SolverConfig> {
+    let content =
+        fs::read_to_string(path).map_err(|e| anyhow!("Failed to read {}: {}", path, e))?;
+    util::parse_solver_config(&content).map_err(|e| anyhow!("{}: {}", path, e))
+}
+
+/// Field by field comparison of two solver configs (`config diff`): each attempt's
+/// three weights, then the position multipliers. Changed rows start with `!` and are
+/// highlighted when `colors` is on. A weight set only one config has shows `-`.
+pub fn diff_solver_configs(
+    (a_name, a): (&str, &SolverConfig),
+    (b_name, b): (&str, &SolverConfig),
+    colors: bool,
+) -> String {
+    let describe = |config: &SolverConfig| {
+        config
+            .meta
+            .as_ref()
+            .map_or("no meta block".to_string(), |meta| meta.describe())
+    };
+    let mut text = format!(
+        "--- {} ({})\n+++ {} ({})\n\n",
+        a_name,
+        describe(a),
+        b_name,
+        describe(b)
+    );
+    let width = a_name.len().max(b_name.len()).max(8);
+    text.push_str(&format!(
+        "  {:<9}{:<12}{:>width$}  {:>width$}  {:>8}\n",
+        "Attempt", "Field", a_name, b_name, "Change"
+    ));
+
+    let mut rows: Vec<(String, &str, Option<f64>, Option<f64>)> = Vec::new();
+    for attempt in 0..a.weights.len().max(b.weights.len()) {
+        let fields = |config: &SolverConfig| {
+            config
+                .weights
+                .get(attempt)
+                .map(|&(pos, overall, unique)| [pos, overall, unique])
+        };
+        let (x, y) = (fields(a), fields(b));
+        for (i, field) in ["positional", "overall", "unique"].into_iter().enumerate() {
+            rows.push((
+                (attempt + 1).to_string(),
+                field,
+                x.map(|w| w[i]),
+                y.map(|w| w[i]),
+            ));
+        }
+    }
+    for position in 0..5 {
+        rows.push((
+            format!("pos {}", position + 1),
+            "multiplier",
+            Some(a.positions[position]),
+            Some(b.positions[position]),
+        ));
+    }
+
+    let mut changed = 0;
+    for (attempt, field, x, y) in &rows {
+        let cell = |value: &Option<f64>| value.map_or("-".to_string(), |v| v.to_string());
+        let change = match (x, y) {
+            (Some(x), Some(y)) if x != y => format!("{:+.3}", y - x),
+            (Some(_), Some(_)) => String::new(),
+            _ => "only one".to_string(),
+        };
+        let line = format!(
+            "{:<9}{:<12}{:>width$}  {:>width$}  {:>8}",
+            attempt,
+            field,
+            cell(x),
+            cell(y),
+            change
+        )
+        .trim_end()
+        .to_string();
+        if change.is_empty() {
+            text.push_str(&format!("  {}\n", line));
+        } else {
+            changed += 1;
+            if colors {
+                text.push_str(&format!("! \x1b[33m{}\x1b[0m\n", line));
+            } else {
+                text.push_str(&format!("! {}\n", line));
+            }
+        }
+    }
+    text.push_str(&format!("\n{} of {} values differ.\n", changed, rows.len()));
+    text
+}
+
+/// Whether `flag` (e.g. `--threads`) is a known option followed by a value, so that
+/// value isn't taken for a positional argument.
+pub fn takes_value(flag: &str) -> bool {
+    flag.strip_prefix("--")
+        .and_then(|key| OPTIONS.iter().find(|option| option.key == key))
+        .is_some_and(|option| !option.switch)
+}
+
+/// Location of a profile's wordle-bot.toml, if a data dir is available.
+pub fn config_path(profile: &str) -> Option<PathBuf> {
+    util::profile_dir(profile).map(|dir| dir.join("wordle-bot.toml"))
+}
+
+/// Value of `option` given on the command line; `"true"` for a present switch.
+fn flag_value(option: &ConfigOption, args: &[String]) -> Option<String> {
+    let flag = format!("--{}", option.key);
+    let position = args
+        .iter()
+        .position(|a| *a == flag || (option.key == "verbose" && (a == "-v" || a == "-vv")))?;
+    if option.switch {
+        Some("true".to_string())
+    } else {
+        args.get(position + 1).cloned()
+    }
+}
+
+/// Parses the supported TOML subset: `[section]` headers and `key = value` lines with
+/// string, number or boolean values, plus `#` comments. Errors start with the line
+/// number; unknown sections and keys only print a warning. `origin` names the file in
+/// messages.
+fn parse(content: &str, origin: &str) -> Result<BTreeMap<String, BTreeMap<String, String>>> {
+    let mut sections: BTreeMap<String, BTreeMap<String, String>> = BTreeMap::new();
+    let mut section = String::new();
+
+    for (index, raw) in content.lines().enumerate() {
+        let number = index + 1;
+        let line = raw.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if let Some(header) = line.strip_prefix('[') {
+            let name = strip_comment(header)
+                .trim_end()
+                .strip_suffix(']')
+                .ok_or_else(|| anyhow!("{}:{}: missing ']' in section header", origin, number))?
+                .trim();
+            if !ALL_COMMANDS.contains(&name) {
+                eprintln!(
+                    "warning: {}:{}: unknown section [{}] is ignored",
+                    origin, number, name
+                );
+            }
+            section = name.to_string();
+            continue;
+        }
+
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| anyhow!("{}:{}: expected 'key = value'", origin, number))?;
+        let key = key.trim();
+        if key.is_empty() {
+            return Err(anyhow!("{}:{}: missing key before '='", origin, number));
+        }
+        let value =
+            parse_value(value.trim()).map_err(|e| anyhow!("{}:{}: {}", origin, number, e))?;
+
+        match OPTIONS.iter().find(|o| o.key == key) {
+            None => eprintln!(
+                "warning: {}:{}: unknown option '{}' is ignored",
+                origin, number, key
+            ),
+            Some(option) if option.switch && value != "true" && value != "false" => {
+                return Err(anyhow!(
+                    "{}:{}: '{}' must be true or false",
+                    origin,
+                    number,
+                    key
+                ));
+            }
+            Some(option) if !section.is_empty() && !option.commands.contains(&&*section) => {
+                eprintln!(
+                    "warning: {}:{}: '{}' does not apply to {} and is ignored",
+                    origin, number, key, section
+                )
+            }
+            Some(_) => {}
+        }
+
+        if sections
+            .entry(section.clone())
+            .or_default()
+            .insert(key.to_string(), value)
+            .is_some()
+        {
+            return Err(anyhow!("{}:{}: '{}' is set twice", origin, number, key));
+        }
+    }
+
+    Ok(sections)
+}
+
+/// Parses a quoted string, a boolean or a number, ignoring a trailing comment.
+fn parse_value(raw: &str) -> Result<String> {
+    if let Some(rest) = raw.strip_prefix('"') {
+        let mut value = String::new();
+        let mut chars = rest.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => return expect_end(chars.as_str()).map(|_| value),
+                '\\' => match chars.next() {
+                    Some('"') => value.push('"'),
+                    Some('\\') => value.push('\\'),
+                    Some('n') => value.push('\n'),
+                    Some('t') => value.push('\t'),
+                    _ => return Err(anyhow!("unsupported escape in string")),
+                },
+                c => value.push(c),
+            }
+        }
+        return Err(anyhow!("unterminated string"));
+    }
+    if let Some(rest) = raw.strip_prefix('\'') {
+        let (value, after) = rest
+            .split_once('\'')
+            .ok_or_else(|| anyhow!("unterminated string"))?;
+        expect_end(after)?;
+        return Ok(value.to_string());
+    }
+
+    let value = strip_comment(raw).trim();
+    let is_number = value.parse::<f64>().is_ok() && !value.starts_with(['+', '.']);
+    if value == "true" || value == "false" || is_number {
+        Ok(value.to_string())
+    } else if value.is_empty() {
+        Err(anyhow!("missing value"))
+    } else {
+        Err(anyhow!(
+            "unsupported value '{}': use a quoted string, a number or true/false",
+            value
+        ))
+    }
+}
+
+fn expect_end(rest: &str) -> Result<()> {
+    if strip_comment(rest).trim().is_empty() {
+        Ok(())
+    } else {
+        Err(anyhow!("unexpected text after value"))
+    }
+}
+
+fn strip_comment(text: &str) -> &str {
+    text.split_once('#').map_or(text, |(before, _)| before)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(content: &str) -> Config {
+        Config {
+            paths: Vec::new(),
+            sections: parse(content, "test.toml").unwrap(),
+        }
+    }
+
+    fn applied(config: &Config, args: &[&str]) -> Vec<String> {
+        let mut args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+        config.apply(&args[0].clone(), &mut args);
+        args
+    }
+
+    fn option(key: &str) -> &'static ConfigOption {
+        OPTIONS.iter().find(|o| o.key == key).unwrap()
+    }
+
+    const FILE: &str = "\
+# Defaults for every command
+strategy = \"entropy\"
+think-ms = 50
+
+[solve]
+strategy = 'winprob'  # only for solve
+minimal = true
+json = false
+";
+
+    #[test]
+    fn flags_beat_the_section_which_beats_the_top_level() {
+        let config = config(FILE);
+        assert_eq!(
+            applied(&config, &["solve"]),
+            [
+                "solve",
+                "--strategy",
+                "winprob",
+                "--think-ms",
+                "50",
+                "--minimal"
+            ]
+        );
+        assert_eq!(
+            applied(&config, &["simulate", "100"]),
+            [
+                "simulate",
+                "100",
+                "--strategy",
+                "entropy",
+                "--think-ms",
+                "50"
+            ]
+        );
+        assert_eq!(
+            applied(
+                &config,
+                &["solve", "--strategy", "minimax", "--think-ms", "5"]
+            ),
+            [
+                "solve",
+                "--strategy",
+                "minimax",
+                "--think-ms",
+                "5",
+                "--minimal"
+            ]
+        );
+
+        assert_eq!(
+            config.file_value(option("strategy"), "solve"),
+            Some("winprob")
+        );
+        assert_eq!(
+            config.file_value(option("strategy"), "race"),
+            Some("entropy")
+        );
+        // Neither file nor flag: the command's own default applies
+        assert_eq!(config.file_value(option("endgame"), "solve"), None);
+        assert!(!applied(&config, &["solve"]).contains(&"--endgame".to_string()));
+    }
+
+    #[test]
+    fn options_of_other_commands_are_not_applied() {
+        let config = config("minimal = true\nthreshold = 0.1\nlength = 6\n");
+        assert_eq!(applied(&config, &["rank"]), ["rank"]);
+        assert_eq!(
+            applied(&config, &["analyze"]),
+            ["analyze", "--threshold", "0.1"]
+        );
+        // A length for `wordlist info` doesn't stop the 5-letter games
+        assert_eq!(applied(&config, &["solve"]), ["solve", "--minimal"]);
+        assert_eq!(
+            applied(&config, &["wordlist", "info"]),
+            ["wordlist", "info", "--length", "6"]
+        );
+    }
+
+    #[test]
+    fn parse_errors_name_the_line() {
+        for (content, error) in [
+            (
+                "strategy = \"entropy\"\nthink-ms = fast\n",
+                "test.toml:2: unsupported value 'fast'",
+            ),
+            ("\n[solve\n", "test.toml:2: missing ']' in section header"),
+            (
+                "minimal = 1\n",
+                "test.toml:1: 'minimal' must be true or false",
+            ),
+            (
+                "think-ms = 5\nthink-ms = 6\n",
+                "test.toml:2: 'think-ms' is set twice",
+            ),
+            ("strategy = \"entropy\n", "test.toml:1: unterminated string"),
+            ("= 5\n", "test.toml:1: missing key before '='"),
+        ] {
+            let message = parse(content, "test.toml").unwrap_err().to_string();
+            assert!(message.starts_with(error), "{:?}: {}", content, message);
+        }
+    }
+
+    #[test]
+    fn unknown_keys_and_sections_only_warn() {
+        let config = config("colour = \"red\"\n[fly]\nstrategy = \"entropy\"\n[solve]\n");
+        assert_eq!(applied(&config, &["solve"]), ["solve"]);
+        assert_eq!(
+            config.sections[""].get("colour").map(String::as_str),
+            Some("red")
+        );
+    }
+}
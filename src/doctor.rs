@@ -0,0 +1,404 @@
+use crate::analysis::LetterStats;
+use crate::util;
+use crate::wordlist::Wordlist;
+use anyhow::Result;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Pass,
+    Warn,
+    Fail,
+}
+
+pub struct Check {
+    pub name: &'static str,
+    pub status: Status,
+    pub message: String,
+    pub fix: Option<String>,
+}
+
+impl Check {
+    fn pass(name: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: Status::Pass,
+            message: message.into(),
+            fix: None,
+        }
+    }
+
+    fn warn(name: &'static str, message: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: Status::Warn,
+            message: message.into(),
+            fix: Some(fix.into()),
+        }
+    }
+
+    fn fail(name: &'static str, message: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: Status::Fail,
+            message: message.into(),
+            fix: Some(fix.into()),
+        }
+    }
+}
+
+/// Runs every check, prints the report and returns whether any check failed.
+pub fn run() -> Result<bool> {
+    let wordlist = Wordlist::load();
+    let stats_json = util::read_letter_stats()?;
+
+    let mut checks = Vec::new();
+    match &wordlist {
+        Ok(wordlist) => {
+            checks.push(check_wordlist(wordlist));
+            checks.push(check_stats_match_wordlist(&stats_json, &wordlist.guesses));
+            checks.push(check_used_answers(
+                wordlist,
+                util::used_answers_path().as_deref(),
+            ));
+        }
+        Err(e) => checks.push(Check::fail(
+            "wordlist",
+            e.to_string(),
+            "Rebuild wordle-bot with a valid wordlist.txt",
+        )),
+    }
+    checks.push(check_local_stats_file(
+        Path::new("letter_stats.json"),
+        &stats_json,
+    ));
+    checks.push(check_solver_config(util::solver_config_path().as_deref()));
+
+    println!("=== wordle-bot doctor ===");
+    for check in &checks {
+        let label = match check.status {
+            Status::Pass => "PASS",
+            Status::Warn => "WARN",
+            Status::Fail => "FAIL",
+        };
+        println!("[{}] {}: {}", label, check.name, check.message);
+        if let Some(fix) = &check.fix {
+            println!("       fix: {}", fix);
+        }
+    }
+
+    let failures = checks.iter().filter(|c| c.status == Status::Fail).count();
+    let warnings = checks.iter().filter(|c| c.status == Status::Warn).count();
+    println!("=========================");
+    println!("{} failure(s), {} warning(s)", failures, warnings);
+
+    Ok(failures > 0)
+}
+
+pub fn check_wordlist(wordlist: &Wordlist) -> Check {
+    if wordlist.answers.is_empty() {
+        return Check::fail(
+            "wordlist",
+            "no answer words available",
+            "Make sure wordlist.txt ends with the answer list",
+        );
+    }
+
+    Check::pass(
+        "wordlist",
+        format!(
+            "{} guesses, {} answers",
+            wordlist.guesses.len(),
+            wordlist.answers.len()
+        ),
+    )
+}
+
+/// The stats must be parseable, be for words of the wordlist's length, cover its
+/// alphabet, and have been counted over as many words as the wordlist holds.
+pub fn check_stats_match_wordlist(stats_json: &str, words: &[String]) -> Check {
+    let checked = LetterStats::parse(stats_json)
+        .and_then(|stats| stats.check_wordlist(words).map(|stale| (stats, stale)));
+    let stats = match checked {
+        Ok((_, Some(reason))) => {
+            return Check::warn(
+                "letter stats",
+                reason,
+                "Run `wordle-bot analyze --incremental <diff>` with the added and removed words, or `wordle-bot analyze` to recount",
+            )
+        }
+        Ok((stats, None)) => stats,
+        Err(mismatch) => {
+            return Check::fail(
+                "letter stats",
+                mismatch.to_string(),
+                "Run `wordle-bot analyze` to regenerate letter_stats.json",
+            )
+        }
+    };
+
+    let counted = stats.word_count();
+    if counted != words.len() {
+        return Check::warn(
+            "letter stats",
+            format!(
+                "stats were built from {} words, the wordlist has {}",
+                counted,
+                words.len()
+            ),
+            "Run `wordle-bot analyze` to regenerate letter_stats.json",
+        );
+    }
+
+    Check::pass(
+        "letter stats",
+        format!("built from the same {} words", counted),
+    )
+}
+
+/// `analyze` writes to the working directory, but the binary uses the stats it was
+/// built with; flag a local file that differs from them.
+pub fn check_local_stats_file(path: &Path, embedded_json: &str) -> Check {
+    let Ok(content) = fs::read_to_string(path) else {
+        return Check::pass("local stats file", "no letter_stats.json in this directory");
+    };
+
+    let local: Result<LetterStats, _> = serde_json::from_str(&content);
+    let embedded: Result<LetterStats, _> = serde_json::from_str(embedded_json);
+    match (local, embedded) {
+        (Err(e), _) => Check::fail(
+            "local stats file",
+            format!("{} is corrupt: {}", path.display(), e),
+            "Run `wordle-bot analyze` to rewrite it",
+        ),
+        (Ok(local), Ok(embedded)) if local.counts != embedded.counts => Check::warn(
+            "local stats file",
+            format!(
+                "{} differs from the stats built into this binary",
+                path.display()
+            ),
+            "Rebuild wordle-bot to pick up the new stats",
+        ),
+        _ => Check::pass(
+            "local stats file",
+            format!("{} matches the built-in stats", path.display()),
+        ),
+    }
+}
+
+pub fn check_solver_config(path: Option<&Path>) -> Check {
+    let Some(path) = path else {
+        return Check::warn(
+            "solver config",
+            "no config directory available, using built-in weights",
+            "Set HOME (or the platform equivalent) so a config dir can be found",
+        );
+    };
+    let Ok(content) = fs::read_to_string(path) else {
+        return Check::pass(
+            "solver config",
+            "no solver_config.json yet, the default will be written on first use",
+        );
+    };
+
+    let config = match util::parse_solver_config(&content) {
+        Ok(config) => config,
+        Err(e) => {
+            return Check::fail(
+                "solver config",
+                format!(
+                    "{}: {} (the built-in default is used instead)",
+                    path.display(),
+                    e
+                ),
+                "Fix the file or delete it to restore the default",
+            )
+        }
+    };
+
+    let weights = &config.weights;
+    let invalid = weights
+        .iter()
+        .position(|&(a, b, c)| [a, b, c].iter().any(|w| !w.is_finite() || *w < 0.0));
+    if let Some(attempt) = invalid {
+        return Check::warn(
+            "solver config",
+            format!(
+                "weight set {} contains a negative or non-finite weight",
+                attempt + 1
+            ),
+            "Use non-negative weights in solver_config.json",
+        );
+    }
+
+    let positions = if config.positions == util::UNIFORM_POSITIONS {
+        String::new()
+    } else {
+        format!(", position multipliers {:?}", config.positions)
+    };
+    Check::pass(
+        "solver config",
+        format!(
+            "{} weight set(s){} in {}",
+            weights.len(),
+            positions,
+            path.display()
+        ),
+    )
+}
+
+pub fn check_used_answers(wordlist: &Wordlist, path: Option<&Path>) -> Check {
+    let read = path.map_or_else(|| Ok(Default::default()), util::read_used_answers_from);
+    let used = match read {
+        Ok(used) => used,
+        Err(e) => {
+            return Check::fail(
+                "used answers",
+                e.to_string(),
+                "Fix or delete used_answers.txt in the data dir",
+            )
+        }
+    };
+
+    let unknown = used
+        .iter()
+        .filter(|w| !wordlist.answers.contains(w))
+        .count();
+    if unknown > 0 {
+        return Check::warn(
+            "used answers",
+            format!(
+                "{} of {} used answers are not in the answer list",
+                unknown,
+                used.len()
+            ),
+            "Remove typos from used_answers.txt in the data dir",
+        );
+    }
+
+    Check::pass("used answers", format!("{} recorded", used.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::Solver;
+    use std::collections::HashSet;
+    use tempfile::TempDir;
+
+    fn fixture_wordlist() -> Wordlist {
+        let solver = Solver::fixture();
+        Wordlist {
+            guesses: solver.all_words.clone(),
+            answers: solver.all_words,
+            excluded_answers: HashSet::new(),
+        }
+    }
+
+    fn assert_status(check: &Check, status: Status, needle: &str) {
+        assert_eq!(check.status, status, "{}: {}", check.name, check.message);
+        assert!(check.message.contains(needle), "{}", check.message);
+        assert_eq!(check.fix.is_some(), status != Status::Pass);
+    }
+
+    #[test]
+    fn wordlist_without_answers_fails() {
+        let mut wordlist = fixture_wordlist();
+        assert_status(&check_wordlist(&wordlist), Status::Pass, "193 answers");
+        wordlist.answers.clear();
+        assert_status(&check_wordlist(&wordlist), Status::Fail, "no answer words");
+    }
+
+    #[test]
+    fn stats_of_another_alphabet_fail_and_of_fewer_words_warn() {
+        let words = fixture_wordlist().guesses;
+        let refs: Vec<&str> = words.iter().map(|w| w.as_str()).collect();
+        let json = |words: &[&str]| LetterStats::from_words(words).to_pretty_json().unwrap();
+
+        let check = check_stats_match_wordlist(&json(&refs), &words);
+        assert_status(&check, Status::Pass, "193 words");
+        let check = check_stats_match_wordlist(&json(&refs[..150]), &words);
+        assert_status(&check, Status::Warn, "");
+        let check = check_stats_match_wordlist("{\"counts\": 3}", &words);
+        assert_status(&check, Status::Fail, "");
+    }
+
+    #[test]
+    fn corrupt_local_stats_file_fails() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("letter_stats.json");
+        let embedded = util::read_letter_stats().unwrap();
+        assert_status(
+            &check_local_stats_file(&path, &embedded),
+            Status::Pass,
+            "no letter_stats.json",
+        );
+        fs::write(&path, &embedded).unwrap();
+        assert_status(
+            &check_local_stats_file(&path, &embedded),
+            Status::Pass,
+            "matches",
+        );
+        fs::write(&path, &embedded[..embedded.len() / 2]).unwrap();
+        assert_status(
+            &check_local_stats_file(&path, &embedded),
+            Status::Fail,
+            "is corrupt",
+        );
+    }
+
+    #[test]
+    fn bad_or_missing_solver_config_is_reported() {
+        assert_status(
+            &check_solver_config(None),
+            Status::Warn,
+            "no config directory",
+        );
+
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("solver_config.json");
+        let check = check_solver_config(Some(&path));
+        assert_status(&check, Status::Pass, "no solver_config.json yet");
+        fs::write(&path, "[[0.3, 0.4, 0.3]]").unwrap();
+        assert_status(
+            &check_solver_config(Some(&path)),
+            Status::Pass,
+            "1 weight set",
+        );
+        fs::write(&path, "[[0.3, -0.4, 0.3]]").unwrap();
+        assert_status(
+            &check_solver_config(Some(&path)),
+            Status::Warn,
+            "weight set 1",
+        );
+        fs::write(&path, "[[0.3, 0.4").unwrap();
+        assert_status(
+            &check_solver_config(Some(&path)),
+            Status::Fail,
+            "the built-in default is used instead",
+        );
+    }
+
+    #[test]
+    fn unreadable_or_unknown_used_answers_are_reported() {
+        let wordlist = fixture_wordlist();
+        assert_status(
+            &check_used_answers(&wordlist, None),
+            Status::Pass,
+            "0 recorded",
+        );
+
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("used_answers.txt");
+        fs::write(&path, "crier\nAROSE\n").unwrap();
+        let check = check_used_answers(&wordlist, Some(&path));
+        assert_status(&check, Status::Pass, "2 recorded");
+        fs::write(&path, "crier\nxyzzy\n").unwrap();
+        let check = check_used_answers(&wordlist, Some(&path));
+        assert_status(&check, Status::Warn, "1 of 2");
+        fs::write(&path, b"crier\n\xff\xfe\n").unwrap();
+        let check = check_used_answers(&wordlist, Some(&path));
+        assert_status(&check, Status::Fail, "Failed to read");
+    }
+}
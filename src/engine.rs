@@ -0,0 +1,336 @@
+use crate::game::parse_pattern;
+use crate::solver::Solver;
+use crate::strategy::MAX_GUESSES;
+use crate::util;
+use anyhow::Result;
+use std::io::{self, BufRead, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Set by SIGHUP; the engine reloads before handling its next line.
+static HANGUP: AtomicBool = AtomicBool::new(false);
+
+/// Plays against an external game over stdin/stdout with a line protocol:
+///
+/// - engine: `guess <word>`, `solved <word>`, `lost`, `reloaded <words>`,
+///   `error <message>`
+/// - game: `feedback <pattern>`, `newgame [length]`, `reload`, `quit`
+///
+/// A game starts right away and after every `newgame`. Bad input is answered with
+/// an `error` line and otherwise ignored.
+///
+/// `reload` (or SIGHUP on unix) builds a new solver with `reload`, which reads the
+/// wordlist again. A game in progress finishes on the words it started with; the
+/// next one uses the new solver. When the reload fails, the engine answers with an
+/// `error` and keeps the old solver.
+pub fn run(solver: &mut Solver, reload: &dyn Fn() -> Result<Solver>) -> Result<()> {
+    let weights = util::read_solver_config()?;
+    let mut engine = Engine::new(solver, weights, io::stdout());
+    watch_hangup();
+
+    engine.new_game();
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+        if HANGUP.swap(false, Ordering::Relaxed) {
+            engine.reload(reload);
+        }
+        if !engine.handle(&line, reload) {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+struct Engine<'a, W: Write> {
+    solver: &'a mut Solver,
+    stats_json: String,
+    weights: Vec<(f64, f64, f64)>,
+    /// Guess awaiting feedback; `None` between games.
+    last_guess: Option<String>,
+    /// Solver of a reload during a game, taking over at the next `newgame`.
+    reloaded: Option<Solver>,
+    /// Where the engine's lines go: stdout, or a buffer in tests.
+    out: W,
+}
+
+impl<'a, W: Write> Engine<'a, W> {
+    fn new(solver: &'a mut Solver, weights: Vec<(f64, f64, f64)>, out: W) -> Self {
+        let stats_json = solver.stats_json.clone();
+        Self {
+            solver,
+            stats_json,
+            weights,
+            last_guess: None,
+            reloaded: None,
+            out,
+        }
+    }
+
+    /// Answers one line of the game. Returns `false` on `quit`.
+    fn handle(&mut self, line: &str, reload: &dyn Fn() -> Result<Solver>) -> bool {
+        let mut parts = line.split_whitespace();
+        match (parts.next(), parts.next(), parts.next()) {
+            (None, _, _) => {}
+            (Some("quit"), None, _) => return false,
+            (Some("reload"), None, _) => self.reload(reload),
+            (Some("newgame"), length, None) => match length.map(str::parse::<usize>) {
+                None | Some(Ok(5)) => self.new_game(),
+                Some(Ok(n)) => self.reply(&format!(
+                    "error only 5-letter games are supported, not {}",
+                    n
+                )),
+                Some(Err(_)) => self.reply("error newgame length must be a number"),
+            },
+            (Some("feedback"), Some(pattern), None) => self.feedback(pattern),
+            (Some("feedback"), _, _) => self.reply("error usage: feedback <pattern>"),
+            (Some(command), _, _) => self.reply(&format!("error unknown command '{}'", command)),
+        }
+        true
+    }
+
+    fn new_game(&mut self) {
+        if let Some(solver) = self.reloaded.take() {
+            self.use_solver(solver);
+        }
+        self.solver.game.reset();
+        self.solver.reset_candidates();
+        self.commit_guess();
+    }
+
+    fn feedback(&mut self, raw: &str) {
+        let Some(guess) = self.last_guess.take() else {
+            self.reply("error no game in progress, send 'newgame'");
+            return;
+        };
+        let Some(pattern) = parse_pattern(raw) else {
+            self.reply(&format!(
+                "error invalid pattern '{}': use 5 of w/m/c, b/y/g, 0/1/2 or colored squares",
+                raw
+            ));
+            self.last_guess = Some(guess);
+            return;
+        };
+
+        self.solver.game.add_line(&guess, &pattern);
+        if pattern == "ccccc" {
+            self.reply(&format!("solved {}", guess));
+        } else if self.solver.game.lines.len() >= MAX_GUESSES {
+            self.reply("lost");
+        } else {
+            self.commit_guess();
+        }
+    }
+
+    /// Builds a new solver with `reload` and switches to it, right away between games
+    /// and at the next `newgame` during one.
+    fn reload(&mut self, reload: &dyn Fn() -> Result<Solver>) {
+        let solver = match reload() {
+            Ok(solver) => solver,
+            Err(e) => {
+                self.reply(&format!(
+                    "error reload failed, keeping the old wordlist: {}",
+                    e
+                ));
+                return;
+            }
+        };
+        let words = solver.all_words.len();
+        if self.last_guess.is_some() {
+            self.reloaded = Some(solver);
+            self.reply(&format!(
+                "reloaded {} words; the game in progress keeps the previous wordlist",
+                words
+            ));
+        } else {
+            self.use_solver(solver);
+            self.reply(&format!("reloaded {} words", words));
+        }
+    }
+
+    /// Replaces the solver and the stats it ranks with.
+    fn use_solver(&mut self, solver: Solver) {
+        *self.solver = solver;
+        self.stats_json = self.solver.stats_json.clone();
+    }
+
+    /// Picks the next guess with the active strategy and sends it.
+    fn commit_guess(&mut self) {
+        self.solver.update_wordlist();
+        if self.solver.current_words.is_empty() {
+            self.reply("error no word matches the feedback so far");
+            self.reply("lost");
+            return;
+        }
+
+        match self.solver.next_guess(&self.stats_json, &self.weights) {
+            Ok(guess) => {
+                self.reply(&format!("guess {}", guess));
+                self.last_guess = Some(guess);
+            }
+            Err(e) => {
+                self.reply(&format!("error {}", e));
+                self.reply("lost");
+            }
+        }
+    }
+
+    fn reply(&mut self, line: &str) {
+        // A closed pipe means the game went away; there is nobody left to tell
+        let _ = writeln!(self.out, "{}", line);
+        let _ = self.out.flush();
+    }
+}
+
+/// Makes SIGHUP request a reload instead of ending the process.
+#[cfg(unix)]
+fn watch_hangup() {
+    extern "C" fn on_hangup(_: libc::c_int) {
+        HANGUP.store(true, Ordering::Relaxed);
+    }
+    // SAFETY: the handler only stores to an atomic, which is async-signal-safe, and
+    // the zeroed sigaction is a valid empty one before its fields are set.
+    // SA_RESTART keeps the blocking read of stdin going after the signal.
+    unsafe {
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = on_hangup as *const () as libc::sighandler_t;
+        action.sa_flags = libc::SA_RESTART;
+        libc::sigemptyset(&mut action.sa_mask);
+        libc::sigaction(libc::SIGHUP, &action, std::ptr::null_mut());
+    }
+}
+
+#[cfg(not(unix))]
+fn watch_hangup() {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::grade;
+    use anyhow::anyhow;
+
+    fn no_reload() -> Result<Solver> {
+        Err(anyhow!("no wordlist to reload"))
+    }
+
+    /// Lines the engine wrote since the last call.
+    fn replies(engine: &mut Engine<'_, Vec<u8>>) -> Vec<String> {
+        let text = String::from_utf8(std::mem::take(&mut engine.out)).unwrap();
+        text.lines().map(str::to_string).collect()
+    }
+
+    /// Plays one game against `target`, grading every guess with `evaluate_word` the
+    /// way an external game would. Returns the engine's last line.
+    fn play(engine: &mut Engine<'_, Vec<u8>>, target: &str) -> String {
+        engine.handle("newgame", &no_reload);
+        loop {
+            let last = replies(engine).pop().unwrap();
+            let Some(guess) = last.strip_prefix("guess ") else {
+                return last;
+            };
+            let pattern = grade(guess, target).to_string();
+            engine.handle(&format!("feedback {}", pattern), &no_reload);
+        }
+    }
+
+    #[test]
+    fn engine_solves_games_graded_by_evaluate_word() {
+        let mut solver = Solver::fixture();
+        let weights = solver.weights.clone();
+        let targets = solver.answer_words.clone();
+        let mut engine = Engine::new(&mut solver, weights, Vec::new());
+        for target in &targets {
+            assert_eq!(play(&mut engine, target), format!("solved {}", target));
+        }
+    }
+
+    #[test]
+    fn feedback_in_other_alphabets_is_accepted() {
+        let mut solver = Solver::fixture();
+        let weights = solver.weights.clone();
+        let mut engine = Engine::new(&mut solver, weights, Vec::new());
+        engine.new_game();
+        assert_eq!(replies(&mut engine), ["guess arose"]);
+        // ABUSE against AROSE, in digits
+        engine.handle("feedback 20022", &no_reload);
+        assert_eq!(replies(&mut engine), ["guess abuse"]);
+        engine.handle("feedback 🟩🟩🟩🟩🟩", &no_reload);
+        assert_eq!(replies(&mut engine), ["solved abuse"]);
+    }
+
+    #[test]
+    fn protocol_errors_are_answered() {
+        let mut solver = Solver::fixture();
+        let weights = solver.weights.clone();
+        let mut engine = Engine::new(&mut solver, weights, Vec::new());
+        engine.new_game();
+        replies(&mut engine);
+        for line in [
+            "feedback",
+            "feedback wwx",
+            "feedback ww ww",
+            "newgame 6",
+            "newgame five",
+            "hello",
+            "reload",
+        ] {
+            assert!(engine.handle(line, &no_reload));
+            let answer = replies(&mut engine);
+            assert_eq!(answer.len(), 1, "{}", line);
+            assert!(answer[0].starts_with("error "), "{}: {}", line, answer[0]);
+        }
+        // The guess still waits for its feedback
+        engine.handle("feedback ccccc", &no_reload);
+        assert_eq!(replies(&mut engine), ["solved arose"]);
+        engine.handle("feedback ccccc", &no_reload);
+        assert_eq!(
+            replies(&mut engine),
+            ["error no game in progress, send 'newgame'"]
+        );
+        assert!(!engine.handle("quit", &no_reload));
+    }
+
+    #[test]
+    fn a_reload_mid_game_leaves_the_game_on_the_old_list() {
+        let mut solver = Solver::fixture();
+        let weights = solver.weights.clone();
+        let small: Vec<String> = [
+            "bound", "found", "hound", "mound", "pound", "round", "sound", "wound",
+        ]
+        .map(String::from)
+        .to_vec();
+        let reload_small = || Solver::from_words(small.clone(), small.clone());
+        let mut engine = Engine::new(&mut solver, weights, Vec::new());
+
+        // CRIER is only on the fixture list
+        engine.new_game();
+        assert_eq!(replies(&mut engine), ["guess arose"]);
+        engine.handle("reload", &reload_small);
+        assert_eq!(
+            replies(&mut engine),
+            ["reloaded 8 words; the game in progress keeps the previous wordlist"]
+        );
+        let mut guess = "arose".to_string();
+        let last = loop {
+            let pattern = grade(&guess, "crier").to_string();
+            engine.handle(&format!("feedback {}", pattern), &no_reload);
+            let last = replies(&mut engine).pop().unwrap();
+            match last.strip_prefix("guess ") {
+                Some(next) => guess = next.to_string(),
+                None => break last,
+            }
+        };
+        assert_eq!(last, "solved crier");
+
+        // A failed reload keeps the waiting list, which the next game plays on
+        engine.handle("reload", &no_reload);
+        assert!(replies(&mut engine)[0].starts_with("error reload failed"));
+        assert_eq!(play(&mut engine, "hound"), "solved hound");
+        assert_eq!(engine.solver.all_words, small);
+        assert!(engine
+            .solver
+            .game
+            .lines
+            .iter()
+            .all(|line| small.contains(&line.word)));
+    }
+}
@@ -1,72 +1,225 @@
-use crate::game::GameData;
+use crate::game::{GameData, LetterConstraint};
+use crate::solver::Solver;
+use log::debug;
 use std::collections::HashSet;
 
 pub struct Filter<'a> {
-    game: &'a GameData,
     words: &'a [String],
+    constraints: Vec<(char, LetterConstraint)>,
+}
+
+/// Parts of a letter constraint, in the order the filter checks them.
+#[derive(Debug, Clone, Copy)]
+enum Check {
+    /// A green position holds another letter.
+    Confirmed,
+    /// The letter sits where a yellow or gray cell ruled it out.
+    RuledOut,
+    /// Fewer copies than the feedback showed.
+    TooFew,
+    /// More copies than a gray cell allowed.
+    TooMany,
 }
 
 impl<'a> Filter<'a> {
     pub fn new(game: &'a GameData, words: &'a [String]) -> Self {
-        Self { game, words }
+        Self {
+            words,
+            constraints: game.letter_constraints().into_iter().collect(),
+        }
     }
 
     pub fn filter_words(&self) -> Vec<String> {
-        self.words
+        // Words rejected per check, counted by the first one they fail
+        let mut rejected = [0usize; 4];
+
+        let kept: Vec<String> = self
+            .words
             .iter()
             .filter(|word| {
                 let chars: Vec<char> = word.chars().collect();
 
-                self.not_strictly_forbidden(&chars)
-                    && self.matches_correct_positions(&chars)
-                    && self.respects_misplaced_constraints(&chars)
-                    && self.contains_required_letters(&chars)
+                match self.first_failed_check(&chars) {
+                    Some(check) => {
+                        rejected[check as usize] += 1;
+                        false
+                    }
+                    None => true,
+                }
             })
             .cloned()
-            .collect()
+            .collect();
+
+        debug!(
+            "Filter kept {} of {} words (rejected by confirmed positions: {}, ruled-out positions: {}, too few copies: {}, too many copies: {})",
+            kept.len(),
+            self.words.len(),
+            rejected[0],
+            rejected[1],
+            rejected[2],
+            rejected[3]
+        );
+        kept
     }
 
-    fn not_strictly_forbidden(&self, chars: &[char]) -> bool {
-        for ch in chars {
-            if self.game.contains_not.contains(ch) && !self.game.must_contain.contains(ch) {
-                return false;
+    fn first_failed_check(&self, chars: &[char]) -> Option<Check> {
+        self.constraints.iter().find_map(|(letter, constraint)| {
+            let at: Vec<usize> = (0..chars.len()).filter(|&i| chars[i] == *letter).collect();
+            if !constraint.positions.iter().all(|p| at.contains(p)) {
+                Some(Check::Confirmed)
+            } else if at.iter().any(|p| constraint.forbidden.contains(p)) {
+                Some(Check::RuledOut)
+            } else if at.len() < constraint.min_count {
+                Some(Check::TooFew)
+            } else if constraint.max_count.is_some_and(|max| at.len() > max) {
+                Some(Check::TooMany)
+            } else {
+                None
             }
-        }
-        true
+        })
     }
+}
 
-    fn matches_correct_positions(&self, chars: &[char]) -> bool {
-        for (i, maybe_correct) in self.game.correct_positions.iter().enumerate() {
-            if let Some(expected) = maybe_correct {
-                if chars[i] != *expected {
-                    return false;
-                }
-            }
-        }
-        true
+/// Words dropped from `before` to `after` that, taken as the answer, would have produced
+/// every recorded pattern. Any such word is a filter bug. Costs one `evaluate_word` per
+/// eliminated word and guess, so it is only run on request (`--check-filter`).
+pub fn wrongly_eliminated(game: &GameData, before: &[String], after: &[String]) -> Vec<String> {
+    let kept: HashSet<&String> = after.iter().collect();
+    before
+        .iter()
+        .filter(|word| !kept.contains(word))
+        .filter(|word| {
+            game.lines.iter().all(|line| {
+                let replayed = Solver::evaluate_word(&line.word, word);
+                replayed.pattern() == line.pattern()
+            })
+        })
+        .cloned()
+        .collect()
+}
+
+/// Whether `word` fits a `peek` hypothesis. A spec with `*` (any letters) or `?` (one
+/// letter) is a glob over the whole word, e.g. `*ly` or `s?a*`; otherwise it lists
+/// letters the word must contain.
+pub fn matches_hypothesis(spec: &str, word: &str) -> bool {
+    if spec.contains(['*', '?']) {
+        glob_match(spec.as_bytes(), word.as_bytes())
+    } else {
+        spec.chars().all(|ch| word.contains(ch))
     }
+}
 
-    fn respects_misplaced_constraints(&self, chars: &[char]) -> bool {
-        for (pos, letters) in &self.game.misplaced_letters {
-            for &letter in letters {
-                if chars[*pos] == letter {
-                    return false;
-                }
-                if !chars.contains(&letter) {
-                    return false;
-                }
+/// Glob matching with `*` and `?`, backtracking to the last `*` on a mismatch.
+pub fn glob_match(pattern: &[u8], word: &[u8]) -> bool {
+    let (mut p, mut w) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while w < word.len() {
+        match pattern.get(p) {
+            Some(b'*') => {
+                star = Some((p, w));
+                p += 1;
             }
+            Some(&c) if c == b'?' || c == word[w] => {
+                p += 1;
+                w += 1;
+            }
+            _ => match star {
+                Some((star_p, star_w)) => {
+                    p = star_p + 1;
+                    w = star_w + 1;
+                    star = Some((star_p, star_w + 1));
+                }
+                None => return false,
+            },
         }
-        true
+    }
+    pattern[p..].iter().all(|&c| c == b'*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::grade;
+    use std::collections::BTreeMap;
+
+    fn words(list: &[&str]) -> Vec<String> {
+        list.iter().map(|w| w.to_string()).collect()
+    }
+
+    /// A game with one guess graded against `answer`.
+    fn game_against(guess: &str, answer: &str) -> GameData {
+        let mut game = GameData::new();
+        let pattern = grade(guess, answer).to_string();
+        game.add_line(guess, &pattern);
+        game
     }
 
-    fn contains_required_letters(&self, chars: &[char]) -> bool {
-        let present: HashSet<char> = chars.iter().copied().collect();
-        for &req in &self.game.must_contain {
-            if !present.contains(&req) {
-                return false;
+    #[test]
+    fn filter_keeps_every_word_consistent_with_the_feedback() {
+        let game = game_against("crane", "braid");
+        let before = words(&["brave", "braid", "trash", "crane", "grail"]);
+        let after = Filter::new(&game, &before).filter_words();
+        assert_eq!(after, words(&["braid", "trash", "grail"]));
+        assert!(wrongly_eliminated(&game, &before, &after).is_empty());
+    }
+
+    #[test]
+    fn dropped_consistent_word_is_a_filter_bug() {
+        let game = game_against("crane", "braid");
+        let before = words(&["braid", "grail", "crane"]);
+        let buggy = words(&["grail"]);
+        assert_eq!(
+            wrongly_eliminated(&game, &before, &buggy),
+            words(&["braid"])
+        );
+    }
+
+    /// Every 5-letter word over `letters`.
+    fn all_words(letters: &str) -> Vec<String> {
+        (0..5).fold(vec![String::new()], |words, _| {
+            words
+                .iter()
+                .flat_map(|word| letters.chars().map(move |ch| format!("{}{}", word, ch)))
+                .collect()
+        })
+    }
+
+    #[test]
+    fn same_letter_green_yellow_and_gray_across_guesses_filters_exactly() {
+        // E takes every mix of green, yellow and gray: the guesses hold zero to five
+        // copies, and the answers zero to five copies among two other letters.
+        let answers = all_words("exy");
+        let second_guesses = ["eeeex", "xeexe", "eexxe", "yeyey"];
+        for first in all_words("ex") {
+            for second in [None].into_iter().chain(second_guesses.map(Some)) {
+                let guesses: Vec<&str> = [Some(first.as_str()), second]
+                    .into_iter()
+                    .flatten()
+                    .collect();
+                // The answers by the feedback they give: each group is exactly what
+                // the filter must keep after that feedback
+                let mut groups: BTreeMap<Vec<String>, Vec<String>> = BTreeMap::new();
+                for answer in &answers {
+                    let patterns = guesses
+                        .iter()
+                        .map(|guess| grade(guess, answer).to_string())
+                        .collect();
+                    groups.entry(patterns).or_default().push(answer.clone());
+                }
+                for (patterns, expected) in groups {
+                    let mut game = GameData::new();
+                    for (guess, pattern) in guesses.iter().zip(&patterns) {
+                        game.add_line(guess, pattern);
+                    }
+                    assert_eq!(
+                        Filter::new(&game, &answers).filter_words(),
+                        expected,
+                        "{:?} graded {:?}",
+                        guesses,
+                        patterns
+                    );
+                }
             }
         }
-        true
     }
 }
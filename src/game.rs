@@ -1,5 +1,20 @@
+use crate::analysis::WORD_LENGTH;
+use crate::messages;
+use crate::output;
+use crate::wordlist::PUNCTUATION;
+use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt;
+
+/// Version written into every serialized `GameData`.
+/// 1: original format without a version field.
+/// 2: adds `version` and the letter `restriction`.
+/// 3: adds the `wordlist_hash` of the list the game was saved with.
+/// 4: adds the `banned` and `pinned` suggestion words.
+///
+/// Each version has a save in `tests/fixtures/game_v<n>.json` that must keep loading.
+pub const GAME_DATA_VERSION: u64 = 4;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CellData {
@@ -13,27 +28,201 @@ pub struct LineData {
     pub cells: [CellData; 5],
 }
 
+impl LineData {
+    /// The feedback as typed, e.g. `wmcww`.
+    pub fn pattern(&self) -> String {
+        self.cells.iter().map(|cell| cell.state).collect()
+    }
+}
+
+/// Feedback on one letter of a guess.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CellState {
+    /// Gray: `w`.
+    Absent,
+    /// Yellow: `m`.
+    Present,
+    /// Green: `c`.
+    Correct,
+}
+
+impl CellState {
+    /// The `wmc` letter used in `CellData::state` and typed patterns.
+    pub fn symbol(self) -> char {
+        match self {
+            CellState::Absent => 'w',
+            CellState::Present => 'm',
+            CellState::Correct => 'c',
+        }
+    }
+}
+
+/// Feedback on a whole guess, one state per position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pattern(pub [CellState; WORD_LENGTH]);
+
+impl Pattern {
+    /// The guess with this feedback, as a game line.
+    pub fn line(&self, guess: &str) -> LineData {
+        let mut letters = guess.chars();
+        LineData {
+            word: guess.to_string(),
+            cells: self.0.map(|state| CellData {
+                letter: letters.next().unwrap_or(' '),
+                state: state.symbol(),
+            }),
+        }
+    }
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0
+            .iter()
+            .try_for_each(|state| write!(f, "{}", state.symbol()))
+    }
+}
+
+/// The feedback `guess` gets against `target`. Exact matches are green first; the
+/// other letters are then yellow from left to right while the target has copies of
+/// them left over, and gray after that. So a letter is never green or yellow more
+/// often than `target` contains it.
+pub fn grade(guess: &str, target: &str) -> Pattern {
+    let guess: Vec<char> = guess.chars().collect();
+    let target: Vec<char> = target.chars().collect();
+    let mut states = [CellState::Absent; WORD_LENGTH];
+    let mut remaining: HashMap<char, usize> = HashMap::new();
+    for (i, &t) in target.iter().enumerate() {
+        if guess.get(i) == Some(&t) {
+            states[i] = CellState::Correct;
+        } else {
+            *remaining.entry(t).or_default() += 1;
+        }
+    }
+    for (i, g) in guess.iter().enumerate().take(WORD_LENGTH) {
+        if states[i] == CellState::Correct {
+            continue;
+        }
+        if let Some(count) = remaining.get_mut(g).filter(|count| **count > 0) {
+            *count -= 1;
+            states[i] = CellState::Present;
+        }
+    }
+    Pattern(states)
+}
+
+/// Letters banned from or required in every word of the pool, e.g. for themed variants.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LetterRestriction {
+    pub excluded: HashSet<char>,
+    pub required: HashSet<char>,
+}
+
+impl LetterRestriction {
+    pub fn new(excluded: &str, required: &str) -> Self {
+        Self {
+            excluded: excluded
+                .to_lowercase()
+                .chars()
+                .filter(|c| c.is_ascii_lowercase())
+                .collect(),
+            required: required
+                .to_lowercase()
+                .chars()
+                .filter(|c| c.is_ascii_lowercase())
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.excluded.is_empty() && self.required.is_empty()
+    }
+}
+
+/// Everything the feedback so far says about one letter of the answer.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LetterConstraint {
+    /// The answer has at least this many: the most green and yellow cells of the
+    /// letter in any one guess.
+    pub min_count: usize,
+    /// The answer has at most this many, known once a guess showed the letter gray
+    /// next to its green and yellow cells (or gray alone: none).
+    pub max_count: Option<usize>,
+    /// Positions confirmed green.
+    pub positions: BTreeSet<usize>,
+    /// Positions where a yellow or gray cell showed it is not.
+    pub forbidden: BTreeSet<usize>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GameData {
+    pub version: u64,
     pub lines: Vec<LineData>,
     pub contains_not: HashSet<char>,
     pub correct_positions: [Option<char>; 5],
     pub misplaced_letters: HashMap<usize, HashSet<char>>,
     pub must_contain: HashSet<char>,
+    pub restriction: LetterRestriction,
+    /// `wordlist::active_hash` of the wordlist when the game was saved; `None` in
+    /// saves from before version 3, which can't be checked.
+    #[serde(default)]
+    pub wordlist_hash: Option<String>,
+    /// Words left out of the suggestions shown (`ban <word>`), though still candidates.
+    #[serde(default)]
+    pub banned: Vec<String>,
+    /// Words shown with their rank every turn (`pin <word>`).
+    #[serde(default)]
+    pub pinned: Vec<String>,
+    /// Words of `lines`, for spotting repeated guesses. Rebuilt from `lines` on load.
+    #[serde(skip)]
+    pub guessed: HashSet<String>,
 }
 
 impl GameData {
     pub fn new() -> Self {
         Self {
+            version: GAME_DATA_VERSION,
             lines: Vec::new(),
             contains_not: HashSet::new(),
             correct_positions: [None, None, None, None, None],
             misplaced_letters: HashMap::new(),
             must_contain: HashSet::new(),
+            restriction: LetterRestriction::default(),
+            wordlist_hash: None,
+            banned: Vec::new(),
+            pinned: Vec::new(),
+            guessed: HashSet::new(),
         }
     }
 
+    /// Records a pool restriction as game constraints so filtering agrees with the
+    /// restricted wordlist. Survives `reset`.
+    pub fn set_restriction(&mut self, restriction: LetterRestriction) {
+        self.restriction = restriction;
+        self.apply_restriction();
+    }
+
+    fn apply_restriction(&mut self) {
+        self.contains_not
+            .extend(self.restriction.excluded.iter().copied());
+        self.must_contain
+            .extend(self.restriction.required.iter().copied());
+    }
+
+    /// Records a guess and its `wmc` feedback. Both are lowercased first; anything
+    /// else that isn't 5 letters with a 5-cell pattern is a caller bug.
     pub fn add_line(&mut self, word: &str, pattern: &str) {
+        let word = word.to_lowercase();
+        let pattern = pattern.to_lowercase();
+        assert!(
+            word.chars().count() == 5
+                && pattern.len() == 5
+                && pattern.chars().all(|c| "wmc".contains(c)),
+            "invalid guess line '{}' '{}'",
+            word,
+            pattern
+        );
+
         let mut cells = Vec::new();
 
         for (i, (ch, state)) in word.chars().zip(pattern.chars()).enumerate() {
@@ -48,47 +237,388 @@ impl GameData {
                     self.misplaced_letters.entry(i).or_default().insert(ch);
                     self.must_contain.insert(ch);
                 }
-                'w' => {
-                    if !self.must_contain.contains(&ch) {
-                        self.contains_not.insert(ch);
-                    }
+                'w' if !self.must_contain.contains(&ch) => {
+                    self.contains_not.insert(ch);
                 }
                 _ => {}
             }
         }
 
         let cells: [CellData; 5] = cells.try_into().expect("must be 5 letters");
-        self.lines.push(LineData {
-            word: word.to_string(),
-            cells,
-        });
+        self.guessed.insert(word.clone());
+        self.lines.push(LineData { word, cells });
+    }
+
+    /// Replays `lines` into a fresh state, skipping the guess at index `skip` if given.
+    /// The restriction is kept.
+    pub fn replay(&mut self, lines: &[LineData], skip: Option<usize>) {
+        self.reset();
+        for (i, line) in lines.iter().enumerate() {
+            if Some(i) != skip {
+                let pattern: String = line.cells.iter().map(|cell| cell.state).collect();
+                self.add_line(&line.word, &pattern);
+            }
+        }
+    }
+
+    /// Keeps the first `len` guesses and rebuilds the derived state from them.
+    pub fn truncate(&mut self, len: usize) {
+        let lines = self.lines.clone();
+        self.replay(&lines[..len.min(lines.len())], None);
+    }
+
+    /// The feedback consolidated per letter. Counts are taken per guess, so a letter
+    /// yellow twice in one guess needs two copies, and a green copy doesn't satisfy
+    /// a yellow of the same letter elsewhere in that guess. The restriction adds
+    /// excluded letters as `max_count: Some(0)` and required ones as `min_count: 1`.
+    pub fn letter_constraints(&self) -> BTreeMap<char, LetterConstraint> {
+        let mut constraints: BTreeMap<char, LetterConstraint> = BTreeMap::new();
+
+        for line in &self.lines {
+            let mut found: BTreeMap<char, usize> = BTreeMap::new();
+            let mut capped: BTreeSet<char> = BTreeSet::new();
+            for (i, cell) in line.cells.iter().enumerate() {
+                let constraint = constraints.entry(cell.letter).or_default();
+                match cell.state {
+                    'c' => {
+                        constraint.positions.insert(i);
+                        *found.entry(cell.letter).or_default() += 1;
+                    }
+                    'm' => {
+                        constraint.forbidden.insert(i);
+                        *found.entry(cell.letter).or_default() += 1;
+                    }
+                    _ => {
+                        constraint.forbidden.insert(i);
+                        capped.insert(cell.letter);
+                    }
+                }
+            }
+            for cell in &line.cells {
+                let count = found.get(&cell.letter).copied().unwrap_or(0);
+                let constraint = constraints.entry(cell.letter).or_default();
+                constraint.min_count = constraint.min_count.max(count);
+                if capped.contains(&cell.letter) {
+                    constraint.max_count =
+                        Some(constraint.max_count.map_or(count, |m| m.min(count)));
+                }
+            }
+        }
+
+        for &ch in &self.restriction.excluded {
+            constraints.entry(ch).or_default().max_count = Some(0);
+        }
+        for &ch in &self.restriction.required {
+            let constraint = constraints.entry(ch).or_default();
+            constraint.min_count = constraint.min_count.max(1);
+        }
+        constraints
+    }
+
+    pub fn has_guessed(&self, word: &str) -> bool {
+        self.guessed.contains(word)
+    }
+
+    /// Letters known not to be at each position, from `letter_constraints`: the
+    /// positions a yellow or gray cell ruled out for a letter, and every position for
+    /// a letter the answer has none of. Confirmed slots stay empty.
+    pub fn position_exclusions(&self) -> [BTreeSet<char>; 5] {
+        let constraints = self.letter_constraints();
+        let mut exclusions: [BTreeSet<char>; 5] = Default::default();
+        for (&letter, constraint) in &constraints {
+            for (i, set) in exclusions.iter_mut().enumerate() {
+                if constraint.max_count == Some(0) || constraint.forbidden.contains(&i) {
+                    set.insert(letter);
+                }
+            }
+        }
+
+        let confirmed = constraints.values().flat_map(|c| c.positions.iter());
+        for &i in confirmed {
+            exclusions[i].clear();
+        }
+
+        exclusions
+    }
+
+    /// Parses saved game state, upgrading older formats through `migrate`.
+    pub fn from_json(json: &str) -> Result<Self> {
+        let value: serde_json::Value = serde_json::from_str(json)?;
+        migrate(value)
     }
 
     pub fn print_summary(&self) {
-        println!("\n=== Current Game State ===");
-        println!("Guesses: {}", self.lines.len());
+        println!("\n{}", messages::text("summary.header"));
+        println!(
+            "{}",
+            messages::fill("summary.guesses", &[&self.lines.len()])
+        );
 
-        println!("Not in word: {}", fmt_hashset(&self.contains_not));
         println!(
-            "Correct positions: {}",
-            fmt_correct_positions(&self.correct_positions)
+            "{}",
+            messages::fill("summary.not_in_word", &[&fmt_hashset(&self.contains_not)])
         );
         println!(
-            "Misplaced letters: {}",
-            fmt_misplaced_letters(&self.misplaced_letters)
+            "{}",
+            messages::fill(
+                "summary.correct",
+                &[&fmt_correct_positions(&self.correct_positions)]
+            )
+        );
+        println!(
+            "{}",
+            messages::fill(
+                "summary.misplaced",
+                &[&fmt_misplaced_letters(&self.misplaced_letters)]
+            )
+        );
+        println!(
+            "{}",
+            messages::fill("summary.must_contain", &[&fmt_hashset(&self.must_contain)])
+        );
+        print!(
+            "{}",
+            fmt_position_exclusions(
+                &self.position_exclusions(),
+                &self.correct_positions,
+                output::width()
+            )
         );
-        println!("Must contain: {}", fmt_hashset(&self.must_contain));
 
         println!("==========================\n");
     }
 
     pub fn reset(&mut self) {
         self.lines.clear();
+        self.guessed.clear();
         self.contains_not.clear();
         self.correct_positions = [None, None, None, None, None];
         self.misplaced_letters.clear();
         self.must_contain.clear();
+        self.apply_restriction();
+    }
+}
+
+/// Converts feedback in any of the common alphabets to `wmc`: `w/m/c`, `b/y/g`
+/// (also `x` or `-` for absent), `0/1/2`, or ⬛/⬜, 🟨 and 🟩 squares (🟦 and 🟧 in
+/// high contrast). Case, whitespace and emoji variation selectors are ignored.
+pub fn parse_pattern(raw: &str) -> Option<String> {
+    let pattern: Option<String> = raw
+        .chars()
+        .filter(|c| !skipped_in_pattern(*c))
+        .map(pattern_cell)
+        .collect();
+    pattern.filter(|p| p.len() == 5)
+}
+
+fn skipped_in_pattern(c: char) -> bool {
+    c.is_whitespace() || c == '\u{fe0f}'
+}
+
+/// The `wmc` state of one character of a pattern, see `parse_pattern`.
+fn pattern_cell(c: char) -> Option<char> {
+    match c.to_ascii_lowercase() {
+        'w' | 'b' | 'x' | '-' | '0' | '⬛' | '⬜' => Some('w'),
+        'm' | 'y' | '1' | '🟨' | '🟦' => Some('m'),
+        'c' | 'g' | '2' | '🟩' | '🟧' => Some('c'),
+        _ => None,
+    }
+}
+
+/// A guess entered together with its feedback on one line.
+#[derive(Debug, PartialEq)]
+pub struct Template {
+    pub word: String,
+    /// In `wmc`, as from `parse_pattern`.
+    pub pattern: String,
+}
+
+/// Why a one-line guess was rejected. Indices count characters of the line as typed
+/// from 1, leading spaces included.
+#[derive(Debug, PartialEq)]
+pub enum TemplateError {
+    /// The annotated guess doesn't have 5 letters.
+    WordLength(usize),
+    /// The pattern after the guess doesn't have 5 cells.
+    PatternLength(usize),
+    NotALetter(usize, char),
+    NotAPatternCell(usize, char),
+    /// A `*` not followed by a letter.
+    DanglingMarker(usize),
+    /// `Crane` could be a green C or a capitalized word.
+    Capitalized,
+}
+
+impl TemplateError {
+    pub fn describe(&self) -> String {
+        match self {
+            TemplateError::WordLength(found) => {
+                messages::fill("solve.template_word_length", &[found])
+            }
+            TemplateError::PatternLength(found) => {
+                messages::fill("solve.template_pattern_length", &[found])
+            }
+            TemplateError::NotALetter(index, c) => {
+                messages::fill("solve.template_not_letter", &[index, c])
+            }
+            TemplateError::NotAPatternCell(index, c) => {
+                messages::fill("solve.template_not_cell", &[index, c])
+            }
+            TemplateError::DanglingMarker(index) => {
+                messages::fill("solve.template_marker", &[index])
+            }
+            TemplateError::Capitalized => messages::text("solve.template_capitalized").to_string(),
+        }
+    }
+}
+
+/// Parses a guess typed with its feedback, in one of two forms:
+///
+/// - `crane wwmcw`: the word, then its pattern in any form `parse_pattern` takes. The
+///   word may contain the apostrophes and hyphens of a `--punctuation keep` list.
+/// - `cR*ANE`: one annotated word, a lowercase letter gray, an uppercase one green
+///   and a letter after `*` yellow (in either case).
+///
+/// `Ok(None)` is a plain guess (or anything else) left to the caller: one word in a
+/// single case without `*`, or a first word that isn't 5 letters. A capitalized word
+/// like `Crane` is rejected, since it could mean a green first letter or nothing.
+pub fn parse_template(line: &str) -> Result<Option<Template>, TemplateError> {
+    let input = line.trim();
+    // Characters before `input` in the line, so indices point into what was typed
+    let offset = line.chars().count() - line.trim_start().chars().count();
+    if let Some((word, _)) = input.split_once(char::is_whitespace) {
+        // Apostrophes and hyphens belong to words a wordlist keeps them in
+        if word.chars().count() != 5
+            || !word
+                .chars()
+                .all(|c| c.is_alphabetic() || PUNCTUATION.contains(&c))
+        {
+            return Ok(None);
+        }
+        let mut pattern = String::new();
+        for (index, c) in input.chars().enumerate().skip(5) {
+            if skipped_in_pattern(c) {
+                continue;
+            }
+            let cell =
+                pattern_cell(c).ok_or(TemplateError::NotAPatternCell(offset + index + 1, c))?;
+            pattern.push(cell);
+        }
+        if pattern.chars().count() != 5 {
+            return Err(TemplateError::PatternLength(pattern.chars().count()));
+        }
+        return Ok(Some(Template {
+            word: word.to_lowercase(),
+            pattern,
+        }));
+    }
+
+    let marked = input.contains('*');
+    let upper = input.chars().filter(|c| c.is_uppercase()).count();
+    let lower = input.chars().filter(|c| c.is_lowercase()).count();
+    if !marked && (upper == 0 || lower == 0) {
+        return Ok(None);
+    }
+    if !marked && upper == 1 && input.starts_with(char::is_uppercase) {
+        return Err(TemplateError::Capitalized);
+    }
+
+    let mut word = String::new();
+    let mut pattern = String::new();
+    let mut marker = None;
+    for (index, c) in input.chars().enumerate() {
+        if c == '*' {
+            if let Some(previous) = marker {
+                return Err(TemplateError::DanglingMarker(previous));
+            }
+            marker = Some(offset + index + 1);
+            continue;
+        }
+        if !c.is_alphabetic() {
+            return Err(TemplateError::NotALetter(offset + index + 1, c));
+        }
+        pattern.push(match (marker.take(), c.is_uppercase()) {
+            (Some(_), _) => 'm',
+            (None, true) => 'c',
+            (None, false) => 'w',
+        });
+        word.extend(c.to_lowercase());
+    }
+    if let Some(index) = marker {
+        return Err(TemplateError::DanglingMarker(index));
+    }
+    if word.chars().count() != 5 {
+        return Err(TemplateError::WordLength(word.chars().count()));
+    }
+    Ok(Some(Template { word, pattern }))
+}
+
+/// Lowercases every object key, so hand-edited saves like `"Lines"` still load.
+fn lowercase_keys(value: serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(map) => map
+            .into_iter()
+            .map(|(key, value)| (key.to_lowercase(), lowercase_keys(value)))
+            .collect(),
+        serde_json::Value::Array(items) => items.into_iter().map(lowercase_keys).collect(),
+        other => other,
+    }
+}
+
+/// Upgrades serialized game state from any known older version to the current one.
+pub fn migrate(value: serde_json::Value) -> Result<GameData> {
+    let mut value = lowercase_keys(value);
+    let obj = value
+        .as_object_mut()
+        .ok_or_else(|| anyhow!("Saved game state is not a JSON object"))?;
+
+    let version = match obj.get("version") {
+        None => 1,
+        Some(v) => v
+            .as_u64()
+            .ok_or_else(|| anyhow!("Saved game state has an invalid version: {}", v))?,
+    };
+
+    if version > GAME_DATA_VERSION {
+        return Err(anyhow!(
+            "Saved game state has version {}, but this build only supports up to version {}. Update wordle-bot to load it.",
+            version,
+            GAME_DATA_VERSION
+        ));
+    }
+
+    if version < 2 {
+        obj.insert(
+            "restriction".to_string(),
+            serde_json::to_value(LetterRestriction::default())?,
+        );
+    }
+
+    if version < 3 {
+        obj.insert("wordlist_hash".to_string(), serde_json::Value::Null);
+    }
+
+    if version < 4 {
+        obj.insert("banned".to_string(), serde_json::json!([]));
+        obj.insert("pinned".to_string(), serde_json::json!([]));
+    }
+
+    obj.insert("version".to_string(), GAME_DATA_VERSION.into());
+
+    let mut game: GameData = serde_json::from_value(value)
+        .map_err(|e| anyhow!("Failed to load saved game state: {}", e))?;
+    for line in &mut game.lines {
+        line.word = line.word.trim().to_lowercase();
+        for cell in &mut line.cells {
+            cell.letter = cell.letter.to_ascii_lowercase();
+            cell.state = cell.state.to_ascii_lowercase();
+        }
     }
+    for word in game.banned.iter_mut().chain(game.pinned.iter_mut()) {
+        *word = word.trim().to_lowercase();
+    }
+    game.guessed = game.lines.iter().map(|line| line.word.clone()).collect();
+    Ok(game)
 }
 
 // Formatting helper functions
@@ -111,6 +641,56 @@ fn fmt_correct_positions(positions: &[Option<char>; 5]) -> String {
         .join(" ")
 }
 
+const EXCLUSION_COLUMN_WIDTH: usize = 14;
+const EXCLUSION_LETTER_CAP: usize = 6;
+
+/// The per-position table of `print_summary`, its five columns narrowed (and long
+/// cells truncated) to fit in `width`.
+fn fmt_position_exclusions(
+    exclusions: &[BTreeSet<char>; 5],
+    positions: &[Option<char>; 5],
+    width: usize,
+) -> String {
+    let column = EXCLUSION_COLUMN_WIDTH.min(width.saturating_sub(2) / 5);
+    let header: String = (1..=5)
+        .map(|i| format!("{:<width$}", i, width = column))
+        .collect();
+
+    let cells: String = exclusions
+        .iter()
+        .zip(positions)
+        .map(|(set, confirmed)| {
+            let cell = match confirmed {
+                Some(c) => format!("={}", c.to_uppercase()),
+                None if set.is_empty() => "?".to_string(),
+                None => {
+                    let shown: String = set
+                        .iter()
+                        .take(EXCLUSION_LETTER_CAP)
+                        .map(|c| c.to_uppercase().to_string())
+                        .collect();
+                    if set.len() > EXCLUSION_LETTER_CAP {
+                        format!("-{} +{} more", shown, set.len() - EXCLUSION_LETTER_CAP)
+                    } else {
+                        format!("-{}", shown)
+                    }
+                }
+            };
+            format!(
+                "{:<width$}",
+                output::truncate(&cell, column - 1),
+                width = column
+            )
+        })
+        .collect();
+
+    format!(
+        "Per position:\n  {}\n  {}\n",
+        header.trim_end(),
+        cells.trim_end()
+    )
+}
+
 fn fmt_misplaced_letters(map: &HashMap<usize, HashSet<char>>) -> String {
     let mut parts = Vec::new();
 
@@ -130,3 +710,355 @@ fn fmt_misplaced_letters(map: &HashMap<usize, HashSet<char>>) -> String {
 
     parts.join(", ")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wordlist::{normalize_word, LoadReport, PunctuationPolicy};
+
+    /// Everything a game's filtering depends on, in an order-stable form.
+    fn canonical(game: &GameData) -> String {
+        let lines: Vec<String> = game
+            .lines
+            .iter()
+            .map(|line| {
+                let cells: String = line
+                    .cells
+                    .iter()
+                    .map(|cell| format!("{}{}", cell.letter, cell.state))
+                    .collect();
+                format!("{}:{}", line.word, cells)
+            })
+            .collect();
+        format!(
+            "{:?} {:?} {:?}",
+            lines,
+            game.correct_positions,
+            game.letter_constraints()
+        )
+    }
+
+    /// A game of the guesses read the way `solve` reads them: the word as a
+    /// wordlist entry would be, the feedback through `parse_pattern`.
+    fn game_of(guesses: &[(&str, &str)]) -> GameData {
+        let mut game = GameData::new();
+        for (word, pattern) in guesses {
+            let word = normalize_word(
+                word,
+                PunctuationPolicy::Reject,
+                WORD_LENGTH,
+                &mut LoadReport::default(),
+            )
+            .unwrap_or_else(|| panic!("{:?} was not normalized", word));
+            let pattern =
+                parse_pattern(pattern).unwrap_or_else(|| panic!("{:?} was rejected", pattern));
+            game.add_line(&word, &pattern);
+        }
+        game
+    }
+
+    #[test]
+    fn messy_input_gives_the_canonical_game() {
+        let expected = canonical(&game_of(&[("crane", "wmcwc"), ("stale", "wwcmc")]));
+        let messy: [[(&str, &str); 2]; 7] = [
+            [("CRANE", "WMCWC"), ("Stale", "wwCMc")],
+            [("crane\r", "wmcwc\r"), ("stale\r", "wwcmc\r")],
+            [("\tcrane\t", "\tw m c w c\t"), ("stale\t", "w\tw\tc\tm\tc")],
+            [("  crane", "  wmcwc"), ("   stale  ", " wwcmc ")],
+            [("crane", "⬛🟨🟩⬛🟩"), ("stale", "⬛⬛🟩🟨🟩")],
+            [
+                ("crane", "⬛\u{fe0f}🟨🟩⬛\u{fe0f}🟩"),
+                ("stale", "⬜️⬜️🟩🟨🟩"),
+            ],
+            [("CrAnE\r", "BYGBG\r"), ("sTaLe ", "0 0 2 1 2")],
+        ];
+        for guesses in &messy {
+            assert_eq!(canonical(&game_of(guesses)), expected, "{:?}", guesses);
+        }
+    }
+
+    #[test]
+    fn saved_game_with_messy_keys_and_letters_loads_canonically() {
+        let game = game_of(&[("crane", "wmcwc"), ("stale", "wwcmc")]);
+        let mut value = serde_json::to_value(&game).unwrap();
+        let lines = value["lines"].take();
+        let mut lines: Vec<serde_json::Value> = serde_json::from_value(lines).unwrap();
+        for line in &mut lines {
+            let word = line["word"].as_str().unwrap().to_uppercase();
+            line["word"] = format!(" {}\r", word).into();
+            for cell in line["cells"].as_array_mut().unwrap() {
+                let letter = cell["letter"].as_str().unwrap().to_uppercase();
+                let state = cell["state"].as_str().unwrap().to_uppercase();
+                *cell = serde_json::json!({ "Letter": letter, "STATE": state });
+            }
+        }
+        let object = value.as_object_mut().unwrap();
+        object.remove("lines");
+        object.insert("Lines".to_string(), lines.into());
+
+        let loaded = GameData::from_json(&value.to_string()).unwrap();
+        assert_eq!(canonical(&loaded), canonical(&game));
+        assert!(loaded.has_guessed("crane") && loaded.has_guessed("stale"));
+    }
+
+    #[test]
+    fn saves_of_every_version_load_as_the_current_one() {
+        let game = game_of(&[("arose", "wcwwm"), ("brief", "wcccw")]);
+        let fixtures = [
+            include_str!("../tests/fixtures/game_v1.json"),
+            include_str!("../tests/fixtures/game_v2.json"),
+            include_str!("../tests/fixtures/game_v3.json"),
+            include_str!("../tests/fixtures/game_v4.json"),
+        ];
+        for (version, json) in (1..).zip(fixtures) {
+            let loaded = GameData::from_json(json)
+                .unwrap_or_else(|e| panic!("game_v{}.json: {}", version, e));
+            assert_eq!(loaded.version, GAME_DATA_VERSION);
+            assert_eq!(canonical(&loaded), canonical(&game), "v{}", version);
+            assert!(loaded.has_guessed("arose") && loaded.has_guessed("brief"));
+            assert!(loaded.restriction.excluded.is_empty());
+            assert_eq!(loaded.wordlist_hash.is_some(), version >= 3, "v{}", version);
+            let (banned, pinned) = match version {
+                4 => (vec!["crier".to_string()], vec!["brier".to_string()]),
+                _ => (Vec::new(), Vec::new()),
+            };
+            assert_eq!((loaded.banned, loaded.pinned), (banned, pinned));
+        }
+    }
+
+    #[test]
+    fn position_table_fits_each_width() {
+        let game = game_of(&[("crane", "wwwww"), ("doubt", "wwwwm"), ("sight", "mmwwc")]);
+        let exclusions = game.position_exclusions();
+        let rendered: Vec<String> = [40, 80, 120]
+            .iter()
+            .map(|&width| fmt_position_exclusions(&exclusions, &game.correct_positions, width))
+            .collect();
+        assert_eq!(
+            rendered[0],
+            "Per position:\n  1      2      3      4      5\n  -ABCD… -ABCD… -ABCD… -ABCD… =T\n"
+        );
+        assert_eq!(
+            rendered[1],
+            "Per position:\n  1             2             3             4             5\n  \
+             -ABCDEG +6 m… -ABCDEG +6 m… -ABCDEG +5 m… -ABCDEG +5 m… =T\n"
+        );
+        // Columns stop growing at EXCLUSION_COLUMN_WIDTH
+        assert_eq!(rendered[2], rendered[1]);
+        for (width, text) in [40, 80, 120].iter().zip(&rendered) {
+            assert!(text.lines().all(|line| line.chars().count() <= *width));
+        }
+    }
+
+    #[test]
+    fn position_exclusions_follow_the_letter_constraints() {
+        let mut game = game_of(&[("geese", "wmcww"), ("sheep", "wwcmw")]);
+        game.set_restriction(LetterRestriction::new("z", ""));
+        let exclusions = game.position_exclusions();
+        let letters = |i: usize| exclusions[i].iter().collect::<String>();
+
+        // The gray E caps it at two copies, so it is only ruled out where it was
+        // yellow or gray; G, S, H, P and the restricted Z are ruled out everywhere
+        assert_eq!(letters(0), "ghpsz");
+        assert_eq!(letters(1), "eghpsz");
+        assert_eq!(letters(2), "");
+        assert_eq!(letters(3), "eghpsz");
+        assert_eq!(letters(4), "eghpsz");
+    }
+
+    #[test]
+    fn letter_constraints_count_copies_per_guess() {
+        let constraint = |guess: &str, answer: &str, letter: char| {
+            let mut game = GameData::new();
+            game.add_line(guess, &grade(guess, answer).to_string());
+            game.letter_constraints().remove(&letter).unwrap()
+        };
+
+        // The green E doesn't satisfy the yellow one: FEVER has two
+        let e = constraint("geese", "fever", 'e');
+        assert_eq!((e.min_count, e.max_count), (2, Some(2)));
+        assert_eq!(e.positions, BTreeSet::from([1]));
+        assert_eq!(e.forbidden, BTreeSet::from([2, 4]));
+
+        // Two green and one yellow without a gray: at least three, no upper bound
+        let e = constraint("eerie", "geese", 'e');
+        assert_eq!((e.min_count, e.max_count), (3, None));
+        assert_eq!(e.positions, BTreeSet::from([1, 4]));
+        assert_eq!(e.forbidden, BTreeSet::from([0]));
+
+        // Gray alone rules the letter out
+        let a = constraint("crane", "doubt", 'a');
+        assert_eq!((a.min_count, a.max_count), (0, Some(0)));
+    }
+
+    #[test]
+    fn templates_parse_or_point_at_the_offending_character() {
+        use TemplateError::*;
+        let template = |word: &str, pattern: &str| {
+            Ok(Some(Template {
+                word: word.to_string(),
+                pattern: pattern.to_string(),
+            }))
+        };
+        let cases: &[(&str, Result<Option<Template>, TemplateError>)] = &[
+            // Word and pattern, in any pattern form
+            ("crane wwmcw", template("crane", "wwmcw")),
+            ("CRANE bbygb", template("crane", "wwmcw")),
+            ("crane 0 0 1 2 0", template("crane", "wwmcw")),
+            ("crane ⬛⬛🟨🟩⬛", template("crane", "wwmcw")),
+            // Punctuation a kept wordlist puts in its words
+            ("X-RAY wmwww", template("x-ray", "wmwww")),
+            ("ne'er ccwww", template("ne'er", "ccwww")),
+            // Annotated: lowercase gray, uppercase green, `*` yellow
+            ("cR*ANE", template("crane", "wcmcc")),
+            ("  cR*ANE  ", template("crane", "wcmcc")),
+            ("*crane", template("crane", "mwwww")),
+            ("*Crane", template("crane", "mwwww")),
+            ("CRANe", template("crane", "ccccw")),
+            // Plain guesses and commands are left to the caller
+            ("crane", Ok(None)),
+            ("CRANE", Ok(None)),
+            ("exit", Ok(None)),
+            ("ban arose", Ok(None)),
+            ("peek s?o*", Ok(None)),
+            // Ambiguous: a green C or just a capital letter
+            ("Crane", Err(Capitalized)),
+            // Indices count from 1 in the line as typed
+            ("crane wwmc", Err(PatternLength(4))),
+            ("crane wwmcwc", Err(PatternLength(6))),
+            ("crane wwqcw", Err(NotAPatternCell(9, 'q'))),
+            ("  crane wwqcw", Err(NotAPatternCell(11, 'q'))),
+            ("cR1ANE", Err(NotALetter(3, '1'))),
+            (" cR1ANE", Err(NotALetter(4, '1'))),
+            ("cR**ANE", Err(DanglingMarker(3))),
+            ("cRANE*", Err(DanglingMarker(6))),
+            ("\tcRANE*", Err(DanglingMarker(7))),
+            ("cR*AN", Err(WordLength(4))),
+            ("cR*ANES", Err(WordLength(6))),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(&parse_template(input), expected, "{:?}", input);
+        }
+    }
+
+    /// Grading spelled out cell by cell: a cell is yellow while the target has more
+    /// copies of its letter than the guess has greens of it plus yellows to its left.
+    fn reference_grade(guess: &[u8], target: &[u8]) -> String {
+        let count = |word: &[u8], letter: u8| word.iter().filter(|&&c| c == letter).count();
+        let greens = |letter: u8| {
+            (0..WORD_LENGTH)
+                .filter(|&i| guess[i] == letter && target[i] == letter)
+                .count()
+        };
+        let mut pattern = String::new();
+        for i in 0..WORD_LENGTH {
+            let letter = guess[i];
+            let yellows_before = (0..i)
+                .filter(|&j| guess[j] == letter && target[j] != letter)
+                .filter(|&j| pattern.as_bytes()[j] == b'm')
+                .count();
+            pattern.push(if target[i] == letter {
+                'c'
+            } else if count(target, letter) > greens(letter) + yellows_before {
+                'm'
+            } else {
+                'w'
+            });
+        }
+        pattern
+    }
+
+    #[test]
+    fn grade_matches_the_reference_on_random_pairs() {
+        use rand::rngs::StdRng;
+        use rand::{Rng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(196);
+        for i in 0..10_000 {
+            // Most pairs draw from a few letters so repeats are common
+            let letters = if i % 4 == 0 { 26 } else { 4 };
+            let mut word = || -> Vec<u8> {
+                (0..WORD_LENGTH)
+                    .map(|_| b'a' + rng.random_range(0..letters))
+                    .collect()
+            };
+            let (guess, target) = (word(), word());
+            let (g, t) = (
+                std::str::from_utf8(&guess).unwrap(),
+                std::str::from_utf8(&target).unwrap(),
+            );
+            assert_eq!(
+                grade(g, t).to_string(),
+                reference_grade(&guess, &target),
+                "{g} against {t}"
+            );
+        }
+    }
+
+    mod grade_properties {
+        use super::*;
+        use proptest::prelude::*;
+
+        /// Green or yellow cells per letter.
+        fn marked(guess: &str, pattern: &Pattern) -> HashMap<char, usize> {
+            let mut marked = HashMap::new();
+            for (letter, state) in guess.chars().zip(pattern.0) {
+                if state != CellState::Absent {
+                    *marked.entry(letter).or_default() += 1;
+                }
+            }
+            marked
+        }
+
+        fn yellows(guess: &str, pattern: &Pattern) -> HashMap<char, usize> {
+            let mut yellows = HashMap::new();
+            for (letter, state) in guess.chars().zip(pattern.0) {
+                if state == CellState::Present {
+                    *yellows.entry(letter).or_default() += 1;
+                }
+            }
+            yellows
+        }
+
+        fn permuted(word: &str, order: &[usize]) -> String {
+            let letters: Vec<char> = word.chars().collect();
+            order.iter().map(|&i| letters[i]).collect()
+        }
+
+        proptest! {
+            #[test]
+            fn a_letter_is_never_marked_more_often_than_the_target_has_it(
+                guess in "[a-e]{5}",
+                target in "[a-e]{5}",
+            ) {
+                for (letter, marked) in marked(&guess, &grade(&guess, &target)) {
+                    prop_assert!(marked <= target.matches(letter).count());
+                }
+            }
+
+            #[test]
+            fn a_word_graded_against_itself_is_all_green(word in "[a-z]{5}") {
+                prop_assert_eq!(grade(&word, &word).to_string(), "ccccc");
+            }
+
+            #[test]
+            fn moving_the_letters_of_both_words_alike_moves_only_the_greens(
+                guess in "[a-e]{5}",
+                target in "[a-e]{5}",
+                order in Just((0..WORD_LENGTH).collect::<Vec<_>>()).prop_shuffle(),
+            ) {
+                let pattern = grade(&guess, &target);
+                let moved_guess = permuted(&guess, &order);
+                let moved = grade(&moved_guess, &permuted(&target, &order));
+                for (to, &from) in order.iter().enumerate() {
+                    prop_assert_eq!(
+                        moved.0[to] == CellState::Correct,
+                        pattern.0[from] == CellState::Correct
+                    );
+                }
+                // Which copy of a letter turns yellow may change, but not how many do
+                prop_assert_eq!(yellows(&moved_guess, &moved), yellows(&guess, &pattern));
+            }
+        }
+    }
+}
@@ -0,0 +1,454 @@
+use crate::clock::{Clock, SystemClock};
+use crate::output::{CellFormat, Table};
+use crate::play::commitment::Commitment;
+use crate::report;
+use crate::util::{self, DEFAULT_PROFILE};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// One finished game in history.jsonl.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameRecord {
+    /// Seconds since the Unix epoch when the game ended.
+    pub timestamp: u64,
+    /// Records written before profiles existed belong to the default profile.
+    #[serde(default = "default_profile")]
+    pub profile: String,
+    /// `normal`, `easy`, `practice` or `solve`; missing in records written before
+    /// modes existed.
+    #[serde(default = "default_mode")]
+    pub mode: String,
+    /// `None` for a lost `solve` game whose answer never became known; records
+    /// written before then kept an empty string, which loads as `None`.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "non_empty"
+    )]
+    pub answer: Option<String>,
+    pub guesses: usize,
+    pub won: bool,
+    /// Candidates left before each guess, then after the last one, when the game
+    /// was played in `solve`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub candidates: Vec<usize>,
+    /// Hints taken in a `solve --assist-on-demand` game; `None` for other games.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hints: Option<usize>,
+    /// Hash and salt of a `play --committed` game, to verify its answer later.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commitment: Option<Commitment>,
+    /// Seconds taken by the guesses of a `play --timed` game.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seconds: Option<f64>,
+    /// Score of a `play --timed` game, see `play::timer::score`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score: Option<usize>,
+    /// Where the answer ranked by frequency score after each guess of a `solve` game
+    /// whose answer became known, whatever strategy played it; see
+    /// `Solver::answer_trajectory`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub answer_ranks: Vec<AnswerStep>,
+    /// Seconds spent at the prompt before each guess of an interactive game, see
+    /// `ThinkTimes`. Empty for games recorded before timing or loaded mid-game.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub think_seconds: Vec<f64>,
+}
+
+/// The answer's place among the frequency-ranked candidates left by one guess.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnswerStep {
+    /// 1 for the top suggestion.
+    pub rank: usize,
+    pub score: f64,
+}
+
+/// How long the player thought about each guess of the game so far: the time the
+/// game waited at its prompt, not the time spent ranking or printing.
+#[derive(Clone)]
+pub struct ThinkTimes {
+    turns: Vec<Duration>,
+    /// Waited so far for the guess being entered, over every prompt since the last.
+    pending: Duration,
+    /// When the prompt being waited at appeared.
+    asked: Option<Instant>,
+    clock: Arc<dyn Clock>,
+}
+
+impl Default for ThinkTimes {
+    fn default() -> Self {
+        Self::with_clock(Arc::new(SystemClock))
+    }
+}
+
+impl ThinkTimes {
+    /// Times the prompts by `clock`.
+    pub fn with_clock(clock: Arc<dyn Clock>) -> Self {
+        Self {
+            turns: Vec::new(),
+            pending: Duration::ZERO,
+            asked: None,
+            clock,
+        }
+    }
+
+    /// Starts the wait at a prompt.
+    pub fn asking(&mut self) {
+        self.asked = Some(self.clock.now());
+    }
+
+    /// Ends the wait at the prompt, adding it to the guess being entered.
+    pub fn answered(&mut self) {
+        if let Some(asked) = self.asked.take() {
+            self.pending += self.clock.now().saturating_duration_since(asked);
+        }
+    }
+
+    /// Ends the timing of the guess just added to the game.
+    pub fn guessed(&mut self) {
+        self.turns.push(std::mem::take(&mut self.pending));
+    }
+
+    /// Keeps the timings of the first `turns` guesses, dropping those of undone ones
+    /// and the wait for the next.
+    pub fn truncate(&mut self, turns: usize) {
+        self.turns.truncate(turns);
+        self.pending = Duration::ZERO;
+        self.asked = None;
+    }
+
+    /// Seconds per guess for a game of `guesses` guesses; empty when some of them
+    /// weren't timed, e.g. after `load`.
+    pub fn seconds(&self, guesses: usize) -> Vec<f64> {
+        if self.turns.len() != guesses {
+            return Vec::new();
+        }
+        self.turns.iter().map(Duration::as_secs_f64).collect()
+    }
+}
+
+fn default_profile() -> String {
+    DEFAULT_PROFILE.to_string()
+}
+
+fn default_mode() -> String {
+    "normal".to_string()
+}
+
+fn non_empty<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<String>, D::Error> {
+    let answer = Option::<String>::deserialize(deserializer)?;
+    Ok(answer.filter(|answer| !answer.is_empty()))
+}
+
+impl GameRecord {
+    /// A record for a game that just ended in the active profile.
+    pub fn new(mode: &str, answer: Option<&str>, guesses: usize, won: bool) -> Self {
+        Self {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs()),
+            profile: util::profile().to_string(),
+            mode: mode.to_string(),
+            answer: answer.map(str::to_string),
+            guesses,
+            won,
+            candidates: Vec::new(),
+            hints: None,
+            commitment: None,
+            seconds: None,
+            score: None,
+            answer_ranks: Vec::new(),
+            think_seconds: Vec::new(),
+        }
+    }
+
+    /// Whether the solver helped: a `solve` game that showed its suggestions
+    /// throughout or took at least one hint.
+    pub fn assisted(&self) -> bool {
+        match self.hints {
+            Some(hints) => hints > 0,
+            None => self.mode == "solve",
+        }
+    }
+}
+
+/// history.jsonl inside a profile's data dir.
+pub fn history_path(profile: &str) -> Option<PathBuf> {
+    util::profile_dir(profile).map(|dir| dir.join("history.jsonl"))
+}
+
+/// Appends `record` to its profile's history, creating the file if needed.
+pub fn append(record: &GameRecord) -> Result<PathBuf> {
+    let path =
+        history_path(&record.profile).ok_or_else(|| anyhow!("No data directory available"))?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+    writeln!(file, "{}", serde_json::to_string(record)?)?;
+    Ok(path)
+}
+
+/// The records of one profile in the order they were played. A missing file means no
+/// games yet.
+pub fn load(profile: &str) -> Result<Vec<GameRecord>> {
+    let Some(path) = history_path(profile).filter(|p| p.exists()) else {
+        return Ok(Vec::new());
+    };
+
+    let content = fs::read_to_string(&path)
+        .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(number, line)| {
+            serde_json::from_str(line)
+                .map_err(|e| anyhow!("{}:{}: {}", path.display(), number + 1, e))
+        })
+        .collect()
+}
+
+/// Distinct answers of a profile's games (lost `solve` games may have none) and of
+/// used_answers.txt, for learning which words tend to be answers. A word in both
+/// counts once.
+pub fn past_answers(profile: &str) -> Result<Vec<String>> {
+    let mut answers: BTreeSet<String> = load(profile)?
+        .into_iter()
+        .filter_map(|r| r.answer)
+        .collect();
+    answers.extend(util::read_used_answers()?);
+    Ok(answers.into_iter().collect())
+}
+
+/// Names of every profile with a history file, the default profile first.
+pub fn profiles() -> Vec<String> {
+    let mut names = vec![DEFAULT_PROFILE.to_string()];
+    let Some(dir) = util::data_dir().map(|d| d.join("profiles")) else {
+        return names;
+    };
+    let mut others: Vec<String> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.path().join("history.jsonl").exists())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| name != DEFAULT_PROFILE)
+        .collect();
+    others.sort();
+    names.extend(others);
+    names
+}
+
+/// Most recent timed games whose think times `stats show` takes the trend over.
+pub const THINK_TREND_GAMES: usize = 30;
+
+/// Totals for the games of one profile and mode.
+#[derive(Debug, Default)]
+pub struct Summary {
+    pub games: usize,
+    pub wins: usize,
+    /// Guesses over all won games.
+    pub winning_guesses: usize,
+    /// Wins in a row at the end of the history.
+    pub current_streak: usize,
+    pub best_streak: usize,
+    /// Games and wins where the solver helped, see `GameRecord::assisted`.
+    pub assisted_games: usize,
+    pub assisted_wins: usize,
+    /// Fastest won timed game, in seconds, and the best timed score.
+    pub best_time: Option<f64>,
+    pub best_score: Option<usize>,
+    /// Guesses with a think time, and the seconds they took together.
+    pub think_turns: usize,
+    pub think_total: f64,
+    /// Average think time per guess of each of the last `THINK_TREND_GAMES` games
+    /// with think times, oldest first.
+    pub recent_think: VecDeque<f64>,
+}
+
+impl Summary {
+    fn record(&mut self, record: &GameRecord) {
+        self.games += 1;
+        if record.assisted() {
+            self.assisted_games += 1;
+            self.assisted_wins += usize::from(record.won);
+        }
+        if let Some(score) = record.score {
+            self.best_score = self.best_score.max(Some(score));
+        }
+        if !record.think_seconds.is_empty() {
+            let total: f64 = record.think_seconds.iter().sum();
+            self.think_turns += record.think_seconds.len();
+            self.think_total += total;
+            self.recent_think
+                .push_back(total / record.think_seconds.len() as f64);
+            if self.recent_think.len() > THINK_TREND_GAMES {
+                self.recent_think.pop_front();
+            }
+        }
+        if let (true, Some(seconds)) = (record.won, record.seconds) {
+            self.best_time = Some(self.best_time.map_or(seconds, |best| best.min(seconds)));
+        }
+        if record.won {
+            self.wins += 1;
+            self.winning_guesses += record.guesses;
+            self.current_streak += 1;
+            self.best_streak = self.best_streak.max(self.current_streak);
+        } else {
+            self.current_streak = 0;
+        }
+    }
+
+    /// Average seconds of thought per guess, over every timed guess.
+    pub fn average_think(&self) -> Option<f64> {
+        (self.think_turns > 0).then(|| self.think_total / self.think_turns as f64)
+    }
+
+    /// How the average think time per guess moved over the last `THINK_TREND_GAMES`
+    /// timed games: the newer half's average minus the older half's, negative when
+    /// guesses got quicker. `None` with fewer than two such games.
+    pub fn think_trend(&self) -> Option<f64> {
+        let games = self.recent_think.len();
+        if games < 2 {
+            return None;
+        }
+        let recent: Vec<f64> = self.recent_think.iter().copied().collect();
+        let (older, newer) = recent.split_at(games / 2);
+        let mean = |part: &[f64]| part.iter().sum::<f64>() / part.len() as f64;
+        Some(mean(newer) - mean(older))
+    }
+}
+
+/// Groups `records` by profile and mode, keeping streaks separate for each group.
+pub fn summarize(records: &[GameRecord]) -> BTreeMap<(String, String), Summary> {
+    let mut summaries: BTreeMap<(String, String), Summary> = BTreeMap::new();
+    for record in records {
+        summaries
+            .entry((record.profile.clone(), record.mode.clone()))
+            .or_default()
+            .record(record);
+    }
+    summaries
+}
+
+/// Table with one row per profile and mode.
+pub fn summary_table(summaries: &BTreeMap<(String, String), Summary>) -> Table {
+    let mut table = Table::new()
+        .column("profile", "Profile", CellFormat::Text)
+        .column("mode", "Mode", CellFormat::Text)
+        .column("games", "Games", CellFormat::Integer)
+        .column("wins", "Wins", CellFormat::Integer)
+        .column("win_rate", "Win %", CellFormat::Percent(1))
+        .column("unassisted_win_rate", "Solo %", CellFormat::Percent(1))
+        .column("assisted_win_rate", "Assisted %", CellFormat::Percent(1))
+        .column("avg_guesses", "Avg", CellFormat::Fixed(2))
+        .column("streak", "Streak", CellFormat::Integer)
+        .column("best_streak", "Best", CellFormat::Integer)
+        .column("best_time", "Best time", CellFormat::Fixed(1))
+        .column("best_score", "Best score", CellFormat::Integer)
+        .column("avg_think", "Think s", CellFormat::Fixed(1))
+        .column("think_trend", "Think trend", CellFormat::Fixed(1));
+    for ((profile, mode), summary) in summaries {
+        let average =
+            (summary.wins > 0).then(|| summary.winning_guesses as f64 / summary.wins as f64);
+        table.push_row(vec![
+            profile.as_str().into(),
+            mode.as_str().into(),
+            summary.games.into(),
+            summary.wins.into(),
+            (summary.wins as f64 / summary.games.max(1) as f64).into(),
+            rate(
+                summary.wins - summary.assisted_wins,
+                summary.games - summary.assisted_games,
+            )
+            .into(),
+            rate(summary.assisted_wins, summary.assisted_games).into(),
+            average.into(),
+            summary.current_streak.into(),
+            summary.best_streak.into(),
+            summary.best_time.into(),
+            summary.best_score.into(),
+            summary.average_think().into(),
+            summary.think_trend().into(),
+        ]);
+    }
+    table
+}
+
+/// Table with one row per game that recorded where its answer ranked by frequency,
+/// oldest first.
+pub fn trajectory_table(records: &[GameRecord]) -> Table {
+    let mut table = Table::new()
+        .column("date", "Date", CellFormat::Text)
+        .column("profile", "Profile", CellFormat::Text)
+        .column("answer", "Answer", CellFormat::Text)
+        .column("guesses", "Guesses", CellFormat::Integer)
+        .column("first_rank", "After 1", CellFormat::Integer)
+        .column("trajectory", "Frequency ranks", CellFormat::Text);
+    for record in records.iter().filter(|r| !r.answer_ranks.is_empty()) {
+        let ranks: Vec<usize> = record.answer_ranks.iter().map(|step| step.rank).collect();
+        table.push_row(vec![
+            util::date((record.timestamp / 86_400) as i64).into(),
+            record.profile.as_str().into(),
+            record.answer.as_deref().map(str::to_uppercase).into(),
+            record.guesses.into(),
+            ranks[0].into(),
+            report::format_trajectory(&ranks, record.won).into(),
+        ]);
+    }
+    table
+}
+
+/// `wins` over `games`, or `None` without games.
+fn rate(wins: usize, games: usize) -> Option<f64> {
+    (games > 0).then(|| wins as f64 / games as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::OutputFormat;
+
+    #[test]
+    fn lost_game_without_an_answer_has_no_answer_field() {
+        let record = GameRecord::new("solve", None, 6, false);
+        let line = serde_json::to_string(&record).unwrap();
+        assert!(!line.contains("\"answer\""), "{}", line);
+        let loaded: GameRecord = serde_json::from_str(&line).unwrap();
+        assert_eq!(loaded.answer, None);
+    }
+
+    #[test]
+    fn empty_answer_of_an_older_record_loads_as_none() {
+        let line = r#"{"timestamp":0,"mode":"solve","answer":"","guesses":6,"won":false}"#;
+        let record: GameRecord = serde_json::from_str(line).unwrap();
+        assert_eq!(record.answer, None);
+
+        let line = r#"{"timestamp":0,"answer":"crier","guesses":3,"won":true}"#;
+        let record: GameRecord = serde_json::from_str(line).unwrap();
+        assert_eq!(record.answer.as_deref(), Some("crier"));
+    }
+
+    #[test]
+    fn trajectory_of_a_game_without_an_answer_leaves_the_answer_empty() {
+        let mut record = GameRecord::new("solve", None, 6, false);
+        record.answer_ranks = vec![AnswerStep {
+            rank: 2,
+            score: 1.0,
+        }];
+        let json = trajectory_table(&[record]).render(OutputFormat::Json);
+        let rows: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(rows[0]["answer"], serde_json::Value::Null);
+        assert_eq!(rows[0]["first_rank"], 2);
+    }
+}
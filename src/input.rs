@@ -0,0 +1,215 @@
+use std::io::{self, IsTerminal, Write};
+
+/// Commands the interactive solver accepts besides guesses, offered by tab completion.
+#[cfg(feature = "readline")]
+const SOLVER_COMMANDS: &[&str] = &[
+    "exit",
+    "-r",
+    "new",
+    "answer ",
+    "save ",
+    "load ",
+    "what-if ",
+    "groups",
+    "probes",
+    "letters",
+    "reconstruct",
+    "report",
+    "peek ",
+    "rollback ",
+    "hint",
+    "ban ",
+    "pin ",
+    "set weight ",
+    "set strategy ",
+    "show config",
+    "save config ",
+];
+
+/// Maximum number of entries kept in the history file.
+#[cfg(feature = "readline")]
+const HISTORY_SIZE: usize = 500;
+
+/// Reads solver input. On a terminal (with the `readline` feature) it offers line
+/// editing, a persistent history and tab completion; piped stdin is read line by line.
+pub struct LineReader {
+    #[cfg(feature = "readline")]
+    editor: Option<readline::SolverEditor>,
+}
+
+impl LineReader {
+    /// `words` are completed at the guess prompt.
+    pub fn new(words: &[String]) -> Self {
+        let interactive = io::stdin().is_terminal();
+
+        #[cfg(feature = "readline")]
+        {
+            Self {
+                editor: if interactive {
+                    readline::SolverEditor::new(words)
+                } else {
+                    None
+                },
+            }
+        }
+        #[cfg(not(feature = "readline"))]
+        {
+            let _ = (words, interactive);
+            Self {}
+        }
+    }
+
+    /// Reads a guess or command as typed, without the line break, so errors can point
+    /// into the line. Returns `None` at the end of input.
+    pub fn read_command(&mut self, prompt: &str) -> io::Result<Option<String>> {
+        #[cfg(feature = "readline")]
+        if let Some(editor) = self.editor.as_mut() {
+            return editor.read(prompt, true);
+        }
+        read_plain(prompt)
+    }
+
+    /// Reads a feedback pattern, trimmed. Patterns are neither completed nor kept in
+    /// the history.
+    pub fn read_pattern(&mut self, prompt: &str) -> io::Result<Option<String>> {
+        #[cfg(feature = "readline")]
+        if let Some(editor) = self.editor.as_mut() {
+            return Ok(editor
+                .read(prompt, false)?
+                .map(|line| line.trim().to_string()));
+        }
+        Ok(read_plain(prompt)?.map(|line| line.trim().to_string()))
+    }
+}
+
+fn read_plain(prompt: &str) -> io::Result<Option<String>> {
+    if !prompt.is_empty() {
+        print!("{}", prompt);
+        io::stdout().flush()?;
+    }
+    let mut line = String::new();
+    if io::stdin().read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    Ok(Some(line.trim_end_matches(['\n', '\r']).to_string()))
+}
+
+#[cfg(feature = "readline")]
+mod readline {
+    use super::{HISTORY_SIZE, SOLVER_COMMANDS};
+    use crate::util;
+    use rustyline::completion::Completer;
+    use rustyline::error::ReadlineError;
+    use rustyline::highlight::Highlighter;
+    use rustyline::hint::Hinter;
+    use rustyline::history::DefaultHistory;
+    use rustyline::validate::Validator;
+    use rustyline::{CompletionType, Config, Context, Editor, Helper};
+    use std::fs;
+    use std::io;
+    use std::path::PathBuf;
+
+    /// Completes guesses from the wordlist and solver commands by the typed prefix.
+    struct SolverHelper {
+        words: Vec<String>,
+        /// Off while a pattern is being entered.
+        enabled: bool,
+    }
+
+    impl Completer for SolverHelper {
+        type Candidate = String;
+
+        fn complete(
+            &self,
+            line: &str,
+            pos: usize,
+            _ctx: &Context<'_>,
+        ) -> rustyline::Result<(usize, Vec<String>)> {
+            let prefix = line[..pos].to_lowercase();
+            if !self.enabled || prefix.contains(' ') {
+                return Ok((0, Vec::new()));
+            }
+
+            let commands = SOLVER_COMMANDS.iter().map(|c| c.to_string());
+            let words = self.words.iter().cloned();
+            let matches = commands
+                .chain(words)
+                .filter(|candidate| candidate.starts_with(&prefix))
+                .collect();
+            Ok((0, matches))
+        }
+    }
+
+    impl Hinter for SolverHelper {
+        type Hint = String;
+    }
+
+    impl Highlighter for SolverHelper {}
+
+    impl Validator for SolverHelper {}
+
+    impl Helper for SolverHelper {}
+
+    pub struct SolverEditor {
+        editor: Editor<SolverHelper, DefaultHistory>,
+        history_path: Option<PathBuf>,
+    }
+
+    impl SolverEditor {
+        /// Returns `None` when the terminal can't be set up, so the caller falls back
+        /// to plain reads.
+        pub fn new(words: &[String]) -> Option<Self> {
+            let config = Config::builder()
+                .max_history_size(HISTORY_SIZE)
+                .ok()?
+                .auto_add_history(false)
+                .completion_type(CompletionType::List)
+                .build();
+            let mut editor = Editor::with_config(config).ok()?;
+            editor.set_helper(Some(SolverHelper {
+                words: words.to_vec(),
+                enabled: true,
+            }));
+
+            let history_path = util::data_dir().map(|dir| dir.join(".solver_history"));
+            if let Some(path) = history_path.as_ref().filter(|p| p.exists()) {
+                let _ = editor.load_history(path);
+            }
+
+            Some(Self {
+                editor,
+                history_path,
+            })
+        }
+
+        pub fn read(&mut self, prompt: &str, command: bool) -> io::Result<Option<String>> {
+            if let Some(helper) = self.editor.helper_mut() {
+                helper.enabled = command;
+            }
+
+            match self.editor.readline(prompt) {
+                Ok(line) => {
+                    if command && !line.trim().is_empty() {
+                        let _ = self.editor.add_history_entry(line.trim());
+                    }
+                    Ok(Some(line))
+                }
+                Err(ReadlineError::Eof | ReadlineError::Interrupted) => Ok(None),
+                Err(ReadlineError::Io(e)) => Err(e),
+                Err(e) => Err(io::Error::other(e)),
+            }
+        }
+    }
+
+    impl Drop for SolverEditor {
+        fn drop(&mut self) {
+            let Some(path) = &self.history_path else {
+                return;
+            };
+            if let Some(dir) = path.parent() {
+                let _ = fs::create_dir_all(dir);
+            }
+            let _ = self.editor.save_history(path);
+        }
+    }
+}
@@ -0,0 +1,149 @@
+use anyhow::{anyhow, Result};
+use std::collections::{BTreeSet, HashMap};
+
+/// Key arrangement of the keyboard shown under the game in `play`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Layout {
+    #[default]
+    Qwerty,
+    Qwertz,
+    Azerty,
+    Alphabetical,
+}
+
+impl Layout {
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name {
+            "qwerty" => Ok(Layout::Qwerty),
+            "qwertz" => Ok(Layout::Qwertz),
+            "azerty" => Ok(Layout::Azerty),
+            "abc" => Ok(Layout::Alphabetical),
+            _ => Err(anyhow!(
+                "Unknown layout '{}'. Use 'qwerty', 'qwertz', 'azerty' or 'abc'.",
+                name
+            )),
+        }
+    }
+
+    /// Rows from top to bottom with the indent of each, in lowercase letters.
+    fn rows(self) -> &'static [(usize, &'static str)] {
+        match self {
+            Layout::Qwerty => &[(0, "qwertyuiop"), (1, "asdfghjkl"), (2, "zxcvbnm")],
+            Layout::Qwertz => &[(0, "qwertzuiop"), (1, "asdfghjkl"), (2, "yxcvbnm")],
+            Layout::Azerty => &[(0, "azertyuiop"), (1, "qsdfghjklm"), (2, "wxcvbn")],
+            Layout::Alphabetical => &[(0, "abcdefghij"), (0, "klmnopqrst"), (0, "uvwxyz")],
+        }
+    }
+}
+
+/// Keys to draw: the layout's rows, then one more row with the letters of `alphabet`
+/// the layout lacks (e.g. `ñ`), when there are any.
+pub fn key_rows(layout: Layout, alphabet: &BTreeSet<char>) -> Vec<(usize, Vec<char>)> {
+    let mut rows: Vec<(usize, Vec<char>)> = layout
+        .rows()
+        .iter()
+        .map(|(indent, keys)| (*indent, keys.chars().collect()))
+        .collect();
+    let extra: Vec<char> = alphabet
+        .iter()
+        .copied()
+        .filter(|letter| !rows.iter().any(|(_, keys)| keys.contains(letter)))
+        .collect();
+    if !extra.is_empty() {
+        rows.push((0, extra));
+    }
+    rows
+}
+
+/// Draws `rows` with each key colored by its `wmc` state in `states`; keys without a
+/// state are left plain.
+pub fn render(rows: &[(usize, Vec<char>)], states: &HashMap<char, char>) -> String {
+    let mut output = String::new();
+    for (indent, keys) in rows {
+        output.push_str(&" ".repeat(*indent));
+        for &letter in keys {
+            let color = match states.get(&letter) {
+                Some('c') => "\x1b[42m\x1b[30m",  // green bg, black text
+                Some('m') => "\x1b[43m\x1b[30m",  // yellow bg, black text
+                Some('w') => "\x1b[100m\x1b[37m", // gray bg, white text
+                _ => "\x1b[0m",                   // default
+            };
+            let upper: String = letter.to_uppercase().collect();
+            output.push_str(&format!("{} {} \x1b[0m", color, upper));
+        }
+        output.push('\n');
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use regex::Regex;
+
+    /// `render` output with each key's colors shown as brackets: `[C]` green, `(R)`
+    /// yellow, `-A-` gray and ` B ` plain.
+    fn readable(rendered: &str) -> String {
+        let key = Regex::new(r"\x1b\[(\d+)m(?:\x1b\[\d+m)? (.) \x1b\[0m").unwrap();
+        key.replace_all(rendered, |caps: &regex::Captures| {
+            let letter = &caps[2];
+            match &caps[1] {
+                "42" => format!("[{}]", letter),
+                "43" => format!("({})", letter),
+                "100" => format!("-{}-", letter),
+                _ => format!(" {} ", letter),
+            }
+        })
+        .into_owned()
+    }
+
+    /// CRANE with a green C and E, a yellow R, and a yellow Ñ from an earlier guess.
+    fn game_states() -> HashMap<char, char> {
+        [
+            ('c', 'c'),
+            ('r', 'm'),
+            ('a', 'w'),
+            ('n', 'w'),
+            ('e', 'c'),
+            ('ñ', 'm'),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    #[test]
+    fn every_layout_renders_as_pinned() {
+        let alphabet: BTreeSet<char> = ('a'..='z').chain(['ñ']).collect();
+        let mut rendered = String::new();
+        for name in ["qwerty", "qwertz", "azerty", "abc"] {
+            let rows = key_rows(Layout::from_name(name).unwrap(), &alphabet);
+            rendered.push_str(&format!(
+                "{}\n{}",
+                name,
+                readable(&render(&rows, &game_states()))
+            ));
+        }
+        assert_eq!(
+            rendered,
+            include_str!("../tests/fixtures/keyboard_layouts.txt")
+        );
+    }
+
+    #[test]
+    fn keys_are_colored_with_ansi_backgrounds() {
+        let rows = vec![(1, vec!['c', 'b'])];
+        assert_eq!(
+            render(&rows, &game_states()),
+            " \x1b[42m\x1b[30m C \x1b[0m\x1b[0m B \x1b[0m\n"
+        );
+    }
+
+    #[test]
+    fn letters_outside_the_layout_get_their_own_row() {
+        let alphabet: BTreeSet<char> = ['a', 'ñ', 'ü'].into_iter().collect();
+        let rows = key_rows(Layout::Qwerty, &alphabet);
+        assert_eq!(rows.len(), 4);
+        assert_eq!(rows[3], (0, vec!['ñ', 'ü']));
+        assert_eq!(key_rows(Layout::Qwerty, &BTreeSet::new()).len(), 3);
+    }
+}
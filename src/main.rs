@@ -1,43 +1,536 @@
 mod analysis;
+mod audit;
+mod batch;
+mod candidates;
+mod clock;
+mod config;
+mod doctor;
+mod engine;
 mod filter;
 mod game;
+mod history;
+mod input;
+mod keyboard;
+mod logging;
+mod messages;
+mod openers;
+mod output;
+mod perf;
 mod play;
+mod plot;
+mod practice;
+mod prefetch;
+mod probe;
 mod ranking;
+mod reconstruct;
+mod report;
+mod sanity;
+mod share;
 mod simulate;
 mod solver;
 mod stats;
+mod strategy;
+mod stratify;
+#[cfg(any(test, feature = "test-util"))]
+mod testing;
 mod trap;
+mod tune;
 mod util;
+mod warmstart;
+mod whatif;
+mod wordlist;
 
-use analysis::LetterStats;
-use anyhow::Result;
-use play::Play;
+use analysis::{LetterStats, RareLetterLimits};
+use anyhow::{anyhow, Result};
+use audit::AuditOptions;
+use config::Config;
+use game::LetterRestriction;
+use keyboard::Layout;
+use openers::OpenersOptions;
+use output::{CellFormat, OutputFormat, Table, Verbosity};
+use play::{timer, Play};
+use practice::Practice;
+use ranking::Stability;
+use report::ReportMode;
+use simulate::{RaceOptions, SimulationOptions};
 use solver::Solver;
+use stats::{HistoryTrends, SimulationResults};
 use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use strategy::Strategy;
+use stratify::Stratification;
+use tune::{TuneMethod, TuneOptions};
+use whatif::WhatIf;
+use wordlist::{PoolOptions, PunctuationPolicy};
+
+#[cfg(feature = "dhat")]
+#[global_allocator]
+static ALLOC: dhat::Alloc = dhat::Alloc;
 
 fn main() -> Result<()> {
-    let args: Vec<String> = std::env::args().collect();
+    let mut args: Vec<String> = std::env::args().collect();
+    let config = Config::load(&mut args)?;
 
     if args.len() < 2 {
-        eprintln!("Usage: wordle-bot <solve|play|simulate|analyze|rank>");
+        // Commands and flags of a feature left out of the build aren't offered
+        #[cfg(feature = "commit")]
+        let (verify_command, committed_flag) = ("|verify-commitment", " [--committed]");
+        #[cfg(not(feature = "commit"))]
+        let (verify_command, committed_flag) = ("", "");
+        eprintln!(
+            "Usage: wordle-bot <solve|play|simulate|tune|openers|engine|analyze|rank|score|batch-rank|race|stats|review|prior|wordlist|import-share|export-share{}|doctor|config>",
+            verify_command
+        );
+        eprintln!(
+            "       wordle-bot solve [--minimal|--json] [--strategy <name>] [--endgame <name>] [--what-if turn=<n> rank=<n>] [--no-cache] [--report [--spoiler-free]] [--history-prior] [--no-prognosis] [--assist-on-demand] [--no-sanity-checks] [--near-misses] [--stable [--stable-epsilon <e>] [--score-precision <n>]] [--prefetch [--prefetch-patterns <n>]] [--rare-letters [--rare-letter-share <f>] [--rare-letter-frequency <f>]] [--lang en|de]"
+        );
+        eprintln!(
+            "       wordle-bot simulate <num_runs> [--strategy <name>] [--endgame <name>] [--error-rate <0..1>] [--check-filter] [--replay-game <n>] [--output <games.csv>] [--perf] [--recent-window <n>] [--human-opener <word>]"
+        );
+        eprintln!(
+            "       wordle-bot simulate <num_runs> --stratified <games.csv> [--buckets <b1,b2,..>] [--per-bucket <n[,n..]>]"
+        );
+        eprintln!("       wordle-bot analyze|rank [--format text|json|csv] [--output <file>|-]");
+        eprintln!("       wordle-bot analyze --incremental <diff>   (update stats with +word/-word lines)");
+        eprintln!(
+            "       wordle-bot analyze --dry-run [--threshold <0..1>]   (show what would change)"
+        );
+        eprintln!(
+            "       wordle-bot play [--easy] [--seed <n>] [--layout qwerty|qwertz|azerty|abc] [--practice <name>|--practice-glob <pattern>]{} [--timed [--guess-seconds <n>]] [--lang en|de]",
+            committed_flag
+        );
+        eprintln!("       wordle-bot score <word> [--guess <word>:<pattern>]...");
+        eprintln!("       wordle-bot batch-rank [--top <k>] [--strategy <name>] < states.txt");
+        eprintln!(
+            "       wordle-bot race --a <strategy> --b <strategy> [--sample <n>] [--seed <n>] [--format text|json] [--output <file>]"
+        );
+        eprintln!("       wordle-bot stats show [--all-profiles]");
+        eprintln!("       wordle-bot stats plot <results.json>... [--html] [--output <file>]");
+        eprintln!(
+            "       wordle-bot review --history|--answer-trajectory [--since <YYYY-MM-DD>] [--all-profiles] [--format text|json|csv] [--output <file>]"
+        );
+        eprintln!("       wordle-bot import-share --answer <word> [--guess <word>]... < share.txt");
+        eprintln!(
+            "       wordle-bot export-share <saved game> [--near-misses [--answer <word>]]   (from the solver's 'save <file>')"
+        );
+        #[cfg(feature = "commit")]
+        eprintln!(
+            "       wordle-bot verify-commitment <hash> <salt> <word>   (check a play --committed game)"
+        );
+        eprintln!("       wordle-bot wordlist info [path] [--length <n>]");
+        eprintln!(
+            "       wordle-bot wordlist audit [--max-cluster-size <n>] [--format csv] [--output <path>]"
+        );
+        eprintln!("       wordle-bot wordlist sample --size <n> [--stratify-by letter-frequency] [--seed <n>] [--output <path>]");
+        eprintln!("       wordle-bot prior show   (answer likelihoods for solve --history-prior)");
+        #[cfg(feature = "test-util")]
+        eprintln!(
+            "       wordle-bot golden --update   (regenerates the pinned games of every strategy)"
+        );
+        eprintln!("Reproducible randomness (play, simulate, tune): --seed <n>");
+        eprintln!("Diagnostics on stderr (any command): -v/--verbose (debug), -vv (trace)");
+        eprintln!("Strategies: frequency, entropy, winprob, minimax");
+        eprintln!("Time budget per suggestion (solve, simulate): --think-ms <ms>");
+        eprintln!("       wordle-bot tune [--method halving|genetic] [--configs <n>] [--population <n>] [--generations <n>] [--tune-positions] [--sample <games>] [--budget <games>] [--seed <n>]");
+        eprintln!(
+            "       wordle-bot openers [--auto-select] [--candidates <n>] [--sample <games>] [--seed <n>]"
+        );
+        eprintln!("Pool restriction (analyze, solve, simulate): --exclude-letters <abc> --require-letters <abc>");
+        eprintln!("Past answers (solve, simulate): --exclude-used");
+        eprintln!("Probe words (solve, simulate): probe_words.txt, --probe-threshold <0..1>");
+        eprintln!(
+            "Wordlist punctuation (analyze, solve, simulate): --punctuation reject|strip|keep"
+        );
+        eprintln!("Wrap word lists at a width (any command): --width <columns>");
+        eprintln!(
+            "Worker threads (tune, openers, batch-rank): --threads <n>; --background for lower priority (also simulate)"
+        );
+        eprintln!("Separate history per profile (any command): --profile <name>");
+        eprintln!("Defaults from a file (any command): --config <file>, see 'wordle-bot config show [command]'");
+        eprintln!("Compare two solver configs: wordle-bot config diff <a.json> <b.json>");
         std::process::exit(1);
     }
 
+    if args[1] == "config" {
+        if args.get(2).map(String::as_str) == Some("diff") {
+            let (Some(a), Some(b)) = (args.get(3), args.get(4)) else {
+                eprintln!("Usage: wordle-bot config diff <a.json> <b.json>");
+                std::process::exit(1);
+            };
+            output::init_colors(&args);
+            print!(
+                "{}",
+                config::diff_solver_configs(
+                    (a, &config::load_solver_config(a)?),
+                    (b, &config::load_solver_config(b)?),
+                    output::colors_enabled(),
+                )
+            );
+            return Ok(());
+        }
+        if args.get(2).map(String::as_str) != Some("show") {
+            eprintln!("Usage: wordle-bot config show [command] [flags]");
+            eprintln!("       wordle-bot config diff <a.json> <b.json>");
+            std::process::exit(1);
+        }
+        let command = args.get(3).filter(|a| !a.starts_with('-'));
+        let flags_start = if command.is_some() { 4 } else { 3 };
+        return config.show(command.map(String::as_str), &args[flags_start..]);
+    }
+    let command = args[1].clone();
+    config.apply(&command, &mut args);
+
+    output::init_colors(&args);
+    output::init_width(&args)?;
+    logging::init(&args);
+    if let Some(profile) = flag_value(&args[2..], "--profile") {
+        util::set_profile(profile)?;
+    }
+    messages::init(&args[2..])?;
+    let threads = match flag_value(&args[2..], "--threads") {
+        Some(value) => match value.parse::<usize>() {
+            Ok(threads) if threads > 0 => Some(threads),
+            _ => return Err(anyhow!("--threads must be a positive number.")),
+        },
+        None => None,
+    };
+    util::set_threads(threads, args[2..].iter().any(|a| a == "--background"));
+    if let Some(path) = flag_value(&args[2..], "--wordlist") {
+        let reads_stdin = matches!(
+            args[1].as_str(),
+            "solve" | "play" | "engine" | "batch-rank" | "import-share"
+        );
+        if path == "-" && reads_stdin {
+            return Err(anyhow!(
+                "{} reads its input from stdin, so --wordlist - can't be used with it.",
+                args[1]
+            ));
+        }
+        let source = if path == "-" { "stdin" } else { path };
+        util::set_wordlist(source, wordlist::read_source(path)?);
+    }
+
     match args[1].as_str() {
-        "analyze" => analyze()?,
-        "rank" => rank()?,
-        "solve" => solve()?,
-        "play" => play()?,
-        "simulate" => {
-            if args.len() != 3 {
-                eprintln!("Usage: wordle-bot simulate <num_runs>");
+        "doctor" => {
+            if doctor::run()? {
+                std::process::exit(1);
+            }
+        }
+        "analyze" => analyze(
+            pool_from_args(&args[2..])?,
+            format_from_args(&args[2..])?,
+            flag_value(&args[2..], "--output"),
+            flag_value(&args[2..], "--incremental"),
+            args[2..]
+                .iter()
+                .any(|a| a == "--dry-run")
+                .then(|| threshold_from_args(&args[2..]))
+                .transpose()?,
+        )?,
+        "rank" => rank(
+            format_from_args(&args[2..])?,
+            flag_value(&args[2..], "--output"),
+        )?,
+        "solve" => {
+            let mut solver = Solver::with_pool(pool_from_args(&args[2..])?)?;
+            solver.verbosity = Verbosity::from_args(&args[2..]);
+            solver.warm_start = !args[2..].iter().any(|a| a == "--no-cache");
+            solver.what_if = what_if_from_args(&args[2..])?;
+            solver.report = report_from_args(&args[2..]);
+            solver.strategy = strategy_from_args(&args[2..])?;
+            solver.endgame = endgame_from_args(&args[2..])?;
+            solver.think_limit = think_limit_from_args(&args[2..]);
+            solver.prognosis = !args[2..].iter().any(|a| a == "--no-prognosis");
+            solver.assist_on_demand = args[2..].iter().any(|a| a == "--assist-on-demand");
+            solver.sanity_checks = !args[2..].iter().any(|a| a == "--no-sanity-checks");
+            solver.near_misses = args[2..].iter().any(|a| a == "--near-misses");
+            solver.stability = stability_from_args(&args[2..]);
+            solver.prefetch = prefetch_from_args(&args[2..]);
+            solver.rare_letters = rare_letters_from_args(&args[2..])?;
+            if args[2..].iter().any(|a| a == "--history-prior") {
+                solver.prior = Some(history_prior(&solver.answer_words)?);
+            }
+            solver.run()?;
+        }
+        "engine" => {
+            let build = || -> Result<Solver> {
+                let mut solver = Solver::with_pool(pool_from_args(&args[2..])?)?;
+                solver.strategy = strategy_from_args(&args[2..])?;
+                solver.endgame = endgame_from_args(&args[2..])?;
+                solver.think_limit = think_limit_from_args(&args[2..]);
+                Ok(solver)
+            };
+            let mut solver = build()?;
+            engine::run(&mut solver, &|| reload_wordlist(&args[2..], &build))?;
+        }
+        "score" => {
+            let Some(word) = args.get(2).filter(|a| !a.starts_with('-')) else {
+                eprintln!("Usage: wordle-bot score <word> [--guess <word>:<pattern>]...");
+                std::process::exit(1);
+            };
+            score(
+                word,
+                &guesses_from_args(&args[3..])?,
+                pool_from_args(&args[3..])?,
+            )?
+        }
+        "batch-rank" => {
+            let mut solver = Solver::with_pool(pool_from_args(&args[2..])?)?;
+            solver.endgame = endgame_from_args(&args[2..])?;
+            solver.think_limit = think_limit_from_args(&args[2..]);
+            batch_rank(
+                &solver,
+                strategy_from_args(&args[2..])?,
+                count_flag(&args[2..], "--top", 5),
+            )?
+        }
+        "stats" => match args.get(2).map(String::as_str) {
+            Some("show") => stats_show(&args[3..])?,
+            Some("plot") => stats_plot(&args[3..])?,
+            _ => {
+                eprintln!("Usage: wordle-bot stats show [--profile <name>|--all-profiles]");
+                eprintln!(
+                    "       wordle-bot stats plot <results.json>... [--html] [--output <file>]"
+                );
+                std::process::exit(1);
+            }
+        },
+        "wordlist" => {
+            match args.get(2).map(String::as_str) {
+                Some("info") => {
+                    let path = args.get(3).filter(|a| !a.starts_with('-'));
+                    wordlist_info(path.map(String::as_str), &args[3..])?
+                }
+                Some("audit") => {
+                    let table = audit::run_audit(AuditOptions {
+                        pool: pool_from_args(&args[3..])?,
+                        max_cluster_size: count_flag(
+                            &args[3..],
+                            "--max-cluster-size",
+                            audit::DEFAULT_MAX_CLUSTER_SIZE,
+                        ),
+                    })?;
+                    // A clean audit only writes its (empty) report when asked to
+                    if !table.is_empty() || flag_value(&args[3..], "--output").is_some() {
+                        write_table(table, &args[3..])?
+                    }
+                }
+                Some("sample") => wordlist_sample(&args[3..])?,
+                _ => {
+                    eprintln!("Usage: wordle-bot wordlist info [path] [--length <n>]");
+                    eprintln!("       wordle-bot wordlist audit [--max-cluster-size <n>] [--format csv] [--output <path>]");
+                    eprintln!("       wordle-bot wordlist sample --size <n> [--stratify-by letter-frequency] [--seed <n>] [--output <path>]");
+                    std::process::exit(1);
+                }
+            }
+        }
+        #[cfg(feature = "test-util")]
+        "golden" => {
+            if !args[2..].iter().any(|a| a == "--update") {
+                eprintln!("Usage: wordle-bot golden --update");
+                eprintln!("`cargo test` checks the golden games; --update prints them anew.");
                 std::process::exit(1);
             }
-            let num_runs: usize = args[2].parse().unwrap_or_else(|_| {
-                eprintln!("Please provide a valid number for <num_runs>.");
+            print!("{}", testing::golden_file()?);
+        }
+        #[cfg(feature = "commit")]
+        "verify-commitment" => {
+            let [hash, salt, word] = match &args[2..] {
+                [hash, salt, word, ..] => [hash, salt, word],
+                _ => {
+                    eprintln!("Usage: wordle-bot verify-commitment <hash> <salt> <word>");
+                    std::process::exit(1);
+                }
+            };
+            if play::commitment::verify(hash, salt, word)? {
+                println!(
+                    "Commitment verified: the hidden word was {}.",
+                    word.to_uppercase()
+                );
+            } else {
+                println!(
+                    "Commitment does NOT match: {} with that salt doesn't hash to {}.",
+                    word.to_uppercase(),
+                    hash
+                );
+                std::process::exit(1);
+            }
+        }
+        "review" => {
+            if args[2..].iter().any(|a| a == "--answer-trajectory") {
+                review_trajectories(&args[2..])?
+            } else if args[2..].iter().any(|a| a == "--history") {
+                review_history(&args[2..])?
+            } else {
+                eprintln!("Usage: wordle-bot review --history|--answer-trajectory [--since <YYYY-MM-DD>] [--all-profiles] [--format text|json|csv] [--output <file>]");
+                std::process::exit(1);
+            }
+        }
+        "import-share" => {
+            let Some(answer) = flag_value(&args[2..], "--answer") else {
+                eprintln!("Usage: wordle-bot import-share --answer <word> [--guess <word>]... < share.txt");
                 std::process::exit(1);
+            };
+            let mut solver = Solver::with_pool(pool_from_args(&args[2..])?)?;
+            solver.strategy = strategy_from_args(&args[2..])?;
+            let guesses: Vec<String> = flag_values(&args[2..], "--guess")
+                .into_iter()
+                .map(String::from)
+                .collect();
+            let text = std::io::read_to_string(std::io::stdin())?;
+            share::import(&mut solver, &text, answer, &guesses)?
+        }
+        "export-share" => {
+            let Some(path) = args.get(2).filter(|a| !a.starts_with('-')) else {
+                eprintln!(
+                    "Usage: wordle-bot export-share <saved game> [--near-misses [--answer <word>]]"
+                );
+                std::process::exit(1);
+            };
+            print!("{}", share::export(path)?);
+            if args[3..].iter().any(|a| a == "--near-misses") {
+                let answer = flag_value(&args[3..], "--answer");
+                print!("\n{}", share::export_near_misses(path, answer)?);
+            }
+        }
+        "prior" => {
+            if args.get(2).map(String::as_str) != Some("show") {
+                eprintln!("Usage: wordle-bot prior show [--profile <name>]");
+                std::process::exit(1);
+            }
+            prior_show(&args[3..])?
+        }
+        "play" => {
+            let mut play = new_play(
+                seed_from_args(&args[2..]),
+                args[2..].iter().any(|a| a == "--easy"),
+                flag_value(&args[2..], "--layout")
+                    .map(Layout::from_name)
+                    .unwrap_or(Ok(Layout::default()))?,
+                practice_from_args(&args[2..])?,
+            )?;
+            #[cfg(feature = "commit")]
+            if args[2..].iter().any(|a| a == "--committed") {
+                play.commit()?;
+            }
+            if let Some(limit) = timed_from_args(&args[2..]) {
+                play.time(limit);
+            }
+            play.run()?;
+        }
+        "simulate" => {
+            if args.len() < 3 {
+                eprintln!("Usage: wordle-bot simulate <num_runs> [--yes] [--confirm-above <n>]");
+                std::process::exit(1);
+            }
+            let num_runs: usize = match args[2].parse() {
+                Ok(0) => {
+                    eprintln!("<num_runs> must be at least 1.");
+                    std::process::exit(1);
+                }
+                Ok(num_runs) => num_runs,
+                Err(_) => {
+                    eprintln!("Please provide a valid number for <num_runs>.");
+                    std::process::exit(1);
+                }
+            };
+            let error_rate = match flag_value(&args[3..], "--error-rate") {
+                Some(value) => match value.parse::<f64>() {
+                    Ok(rate) if (0.0..=1.0).contains(&rate) => rate,
+                    _ => {
+                        eprintln!("--error-rate must be a number between 0 and 1.");
+                        std::process::exit(1);
+                    }
+                },
+                None => 0.0,
+            };
+            let replay_game = flag_value(&args[3..], "--replay-game").map(|_| {
+                match count_flag(&args[3..], "--replay-game", 0) {
+                    game if (1..=num_runs).contains(&game) => game,
+                    _ => {
+                        eprintln!("--replay-game must be between 1 and {}.", num_runs);
+                        std::process::exit(1);
+                    }
+                }
             });
-            simulate(num_runs)?;
+            if replay_game.is_some() && seed_from_args(&args[3..]).is_none() {
+                eprintln!("--replay-game needs the --seed of the run to replay.");
+                std::process::exit(1);
+            }
+            simulate(
+                num_runs,
+                SimulationOptions {
+                    pool: pool_from_args(&args[3..])?,
+                    strategy: strategy_from_args(&args[3..])?,
+                    endgame: endgame_from_args(&args[3..])?,
+                    error_rate,
+                    seed: util::resolve_seed(seed_from_args(&args[3..])),
+                    check_filter: args[3..].iter().any(|a| a == "--check-filter"),
+                    think_limit: think_limit_from_args(&args[3..]),
+                    replay_game,
+                    output: flag_value(&args[3..], "--output").map(PathBuf::from),
+                    perf: args[3..].iter().any(|a| a == "--perf"),
+                    stratified: stratification_from_args(&args[3..])?,
+                    confirm_above: (!args[3..].iter().any(|a| a == "--yes")).then(|| {
+                        count_flag(
+                            &args[3..],
+                            "--confirm-above",
+                            simulate::DEFAULT_CONFIRM_ABOVE,
+                        )
+                    }),
+                    recent_window: count_flag(&args[3..], "--recent-window", 0),
+                    human_opener: flag_value(&args[3..], "--human-opener")
+                        .map(|word| word.trim().to_lowercase()),
+                },
+            )?;
+        }
+        "race" => race(
+            RaceOptions {
+                pool: pool_from_args(&args[2..])?,
+                a: side_from_args(&args[2..], "--a")?,
+                b: side_from_args(&args[2..], "--b")?,
+                sample: count_flag(&args[2..], "--sample", 300),
+                seed: util::resolve_seed(seed_from_args(&args[2..])),
+                think_limit: think_limit_from_args(&args[2..]),
+            },
+            format_from_args(&args[2..])?,
+            flag_value(&args[2..], "--output"),
+        )?,
+        "tune" => {
+            let table = tune::run_tune(TuneOptions {
+                pool: pool_from_args(&args[2..])?,
+                method: TuneMethod::from_name(
+                    flag_value(&args[2..], "--method").unwrap_or("halving"),
+                    count_flag(&args[2..], "--population", 24),
+                    count_flag(&args[2..], "--generations", 15),
+                )?,
+                configs: count_flag(&args[2..], "--configs", 16),
+                initial_sample: count_flag(&args[2..], "--sample", 32),
+                budget: flag_value(&args[2..], "--budget")
+                    .map(|_| count_flag(&args[2..], "--budget", 0)),
+                seed: util::resolve_seed(seed_from_args(&args[2..])),
+                tune_positions: args[2..].iter().any(|a| a == "--tune-positions"),
+            })?;
+            if flag_value(&args[2..], "--output").is_none_or(|path| path == "-") {
+                println!();
+            }
+            write_table(table, &args[2..])?
+        }
+        "openers" => {
+            let table = openers::run_openers(OpenersOptions {
+                pool: pool_from_args(&args[2..])?,
+                strategy: strategy_from_args(&args[2..])?,
+                candidates: count_flag(&args[2..], "--candidates", 10),
+                sample: count_flag(&args[2..], "--sample", 300),
+                seed: seed_from_args(&args[2..]),
+                auto_select: args[2..].iter().any(|a| a == "--auto-select"),
+            })?;
+            if flag_value(&args[2..], "--output").is_none_or(|path| path == "-") {
+                println!();
+            }
+            write_table(table, &args[2..])?
         }
         _ => {
             eprintln!("Unknown command: {}", args[1]);
@@ -48,59 +541,844 @@ fn main() -> Result<()> {
     Ok(())
 }
 
-fn simulate(num_runs: usize) -> Result<()> {
+/// Every value of a flag that may be given more than once.
+fn flag_values<'a>(args: &'a [String], name: &str) -> Vec<&'a str> {
+    args.windows(2)
+        .filter(|pair| pair[0] == name)
+        .map(|pair| pair[1].as_str())
+        .collect()
+}
+
+/// Game state from `--guess <word>:<pattern>` flags, in the order given.
+fn guesses_from_args(args: &[String]) -> Result<Vec<(String, String)>> {
+    flag_values(args, "--guess")
+        .into_iter()
+        .map(parse_guess)
+        .collect()
+}
+
+/// Parses `<word>:<pattern>`, with the pattern in any alphabet `engine` accepts.
+fn parse_guess(value: &str) -> Result<(String, String)> {
+    let (word, pattern) = value
+        .split_once(':')
+        .ok_or_else(|| anyhow!("Expected <word>:<pattern>, got '{}'", value))?;
+    let pattern =
+        game::parse_pattern(pattern).ok_or_else(|| anyhow!("Invalid pattern in '{}'", value))?;
+    if word.chars().count() != 5 {
+        return Err(anyhow!("Guess '{}' must be 5 letters", word));
+    }
+    Ok((word.to_lowercase(), pattern))
+}
+
+fn flag_value<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|a| a == name)
+        .and_then(|i| args.get(i + 1))
+        .map(|s| s.as_str())
+}
+
+/// Parses a non-negative count flag, exiting with a message when it isn't a number.
+fn count_flag(args: &[String], name: &str, default: usize) -> usize {
+    match flag_value(args, name) {
+        Some(value) => value.parse().unwrap_or_else(|_| {
+            eprintln!("{} must be a non-negative number.", name);
+            std::process::exit(1);
+        }),
+        None => default,
+    }
+}
+
+/// Time per guess of `play --timed`, from `--guess-seconds`; `None` without `--timed`.
+fn timed_from_args(args: &[String]) -> Option<Duration> {
+    if !args.iter().any(|a| a == "--timed") {
+        return None;
+    }
+    let seconds = count_flag(
+        args,
+        "--guess-seconds",
+        timer::DEFAULT_GUESS_SECONDS as usize,
+    );
+    if seconds == 0 {
+        eprintln!("--guess-seconds must be at least 1.");
+        std::process::exit(1);
+    }
+    Some(Duration::from_secs(seconds as u64))
+}
+
+/// Seed from `--seed`, exiting with a message when it isn't a number.
+fn seed_from_args(args: &[String]) -> Option<u64> {
+    flag_value(args, "--seed").map(|value| {
+        value.parse().unwrap_or_else(|_| {
+            eprintln!("--seed must be a non-negative number.");
+            std::process::exit(1);
+        })
+    })
+}
+
+fn restriction_from_args(args: &[String]) -> LetterRestriction {
+    LetterRestriction::new(
+        flag_value(args, "--exclude-letters").unwrap_or(""),
+        flag_value(args, "--require-letters").unwrap_or(""),
+    )
+}
+
+fn pool_from_args(args: &[String]) -> Result<PoolOptions> {
+    let length = length_from_args(args)?;
+    if length != analysis::WORD_LENGTH {
+        return Err(anyhow!(
+            "--length {}: only {}-letter games are supported. `wordle-bot wordlist info --length {}` shows the {}-letter words of a list.",
+            length,
+            analysis::WORD_LENGTH,
+            length,
+            length
+        ));
+    }
+    Ok(PoolOptions {
+        restriction: restriction_from_args(args),
+        exclude_used: args.iter().any(|a| a == "--exclude-used"),
+        punctuation: flag_value(args, "--punctuation")
+            .map(PunctuationPolicy::from_name)
+            .unwrap_or(Ok(PunctuationPolicy::Reject))?,
+        probe_threshold: flag_value(args, "--probe-threshold")
+            .map(|value| match value.parse::<f64>() {
+                Ok(share) if (0.0..=1.0).contains(&share) => Ok(share),
+                _ => Err(anyhow!(
+                    "--probe-threshold must be a number between 0 and 1."
+                )),
+            })
+            .transpose()?,
+    })
+}
+
+/// Word length from `--length <n>`, 5 by default.
+fn length_from_args(args: &[String]) -> Result<usize> {
+    match flag_value(args, "--length") {
+        Some(value) => match value.parse::<usize>() {
+            Ok(length) if length > 0 => Ok(length),
+            _ => Err(anyhow!("--length must be a positive number.")),
+        },
+        None => Ok(analysis::WORD_LENGTH),
+    }
+}
+
+fn strategy_from_args(args: &[String]) -> Result<Strategy> {
+    flag_value(args, "--strategy")
+        .map(Strategy::from_name)
+        .unwrap_or(Ok(Strategy::Frequency))
+}
+
+/// Strategy of one side of a race, from `--a` or `--b`.
+fn side_from_args(args: &[String], name: &str) -> Result<Strategy> {
+    let value = flag_value(args, name)
+        .ok_or_else(|| anyhow!("race needs {} <strategy> for both sides", name))?;
+    Strategy::from_name(value)
+}
+
+fn format_from_args(args: &[String]) -> Result<Option<OutputFormat>> {
+    flag_value(args, "--format")
+        .map(OutputFormat::from_name)
+        .transpose()
+}
+
+/// Strategy for the last two guesses, from `--endgame`.
+fn endgame_from_args(args: &[String]) -> Result<Option<Strategy>> {
+    flag_value(args, "--endgame")
+        .map(Strategy::from_name)
+        .transpose()
+}
+
+/// Report printed when `solve` finishes, from `--report` and `--spoiler-free`.
+fn report_from_args(args: &[String]) -> Option<ReportMode> {
+    if !args.iter().any(|a| a == "--report") {
+        None
+    } else if args.iter().any(|a| a == "--spoiler-free") {
+        Some(ReportMode::SpoilerFree)
+    } else {
+        Some(ReportMode::Full)
+    }
+}
+
+/// Counterfactual from `--what-if turn=<n> rank=<n>`, as one argument or several.
+fn what_if_from_args(args: &[String]) -> Result<Option<WhatIf>> {
+    let Some(start) = args.iter().position(|a| a == "--what-if") else {
+        return Ok(None);
+    };
+    let spec: Vec<&str> = args[start + 1..]
+        .iter()
+        .take_while(|a| a.contains('='))
+        .map(|a| a.as_str())
+        .collect();
+    WhatIf::parse(&spec.join(" ")).map(Some)
+}
+
+/// `simulate --stratified <games.csv> [--buckets <b1,b2,..>] [--per-bucket <n[,n..]>]`.
+fn stratification_from_args(args: &[String]) -> Result<Option<Stratification>> {
+    let Some(path) = flag_value(args, "--stratified") else {
+        return Ok(None);
+    };
+    Ok(Some(Stratification {
+        difficulty: PathBuf::from(path),
+        boundaries: stratify::parse_boundaries(
+            flag_value(args, "--buckets").unwrap_or(stratify::DEFAULT_BOUNDARIES),
+        )?,
+        per_bucket: flag_value(args, "--per-bucket")
+            .map(stratify::parse_per_bucket)
+            .transpose()?
+            .unwrap_or_default(),
+    }))
+}
+
+/// Suggestion order of `solve --stable`, with `--stable-epsilon` and
+/// `--score-precision`; `None` without `--stable`. Exits on an invalid value.
+fn stability_from_args(args: &[String]) -> Option<Stability> {
+    if !args.iter().any(|a| a == "--stable") {
+        return None;
+    }
+    let epsilon = match flag_value(args, "--stable-epsilon") {
+        Some(value) => match value.parse::<f64>() {
+            Ok(epsilon) if epsilon.is_finite() && epsilon >= 0.0 => epsilon,
+            _ => {
+                eprintln!("--stable-epsilon must be a non-negative number, e.g. 0.0001.");
+                std::process::exit(1);
+            }
+        },
+        None => ranking::DEFAULT_STABLE_EPSILON,
+    };
+    let precision = count_flag(args, "--score-precision", ranking::DEFAULT_SCORE_PRECISION);
+    if precision > ranking::MAX_SCORE_PRECISION {
+        eprintln!(
+            "--score-precision must be at most {}.",
+            ranking::MAX_SCORE_PRECISION
+        );
+        std::process::exit(1);
+    }
+    Some(Stability { epsilon, precision })
+}
+
+/// Patterns ranked ahead per guess with `--prefetch` (`--prefetch-patterns`), 0
+/// without it.
+fn prefetch_from_args(args: &[String]) -> usize {
+    if !args.iter().any(|a| a == "--prefetch") {
+        return 0;
+    }
+    count_flag(
+        args,
+        "--prefetch-patterns",
+        prefetch::DEFAULT_PREFETCH_PATTERNS,
+    )
+}
+
+/// Limits of the rare-letter notes of `solve` (`--rare-letter-share`,
+/// `--rare-letter-frequency`); `None` unless one of them or `--rare-letters` is given.
+fn rare_letters_from_args(args: &[String]) -> Result<Option<RareLetterLimits>> {
+    let flags = [
+        "--rare-letters",
+        "--rare-letter-share",
+        "--rare-letter-frequency",
+    ];
+    if !args.iter().any(|a| flags.contains(&a.as_str())) {
+        return Ok(None);
+    }
+    let fraction = |flag: &str, default: f64| match flag_value(args, flag) {
+        Some(value) => value
+            .parse::<f64>()
+            .ok()
+            .filter(|f| (0.0..=1.0).contains(f))
+            .ok_or_else(|| anyhow!("{} must be a number between 0 and 1.", flag)),
+        None => Ok(default),
+    };
+    Ok(Some(RareLetterLimits {
+        share: fraction("--rare-letter-share", analysis::RARE_LETTER_SHARE)?,
+        frequency: fraction("--rare-letter-frequency", analysis::RARE_LETTER_FREQUENCY)?,
+    }))
+}
+
+/// Reads the `--wordlist` file again and builds a solver on it with `build`, which
+/// also checks the letter stats against it. On failure the previous wordlist stays
+/// active. Without `--wordlist` the built-in list is reloaded unchanged.
+fn reload_wordlist(args: &[String], build: &dyn Fn() -> Result<Solver>) -> Result<Solver> {
+    let Some(path) = flag_value(args, "--wordlist") else {
+        return build();
+    };
+    let previous = util::set_wordlist(path, wordlist::read_source(path)?);
+    build().inspect_err(|_| {
+        if let Some((source, content)) = previous {
+            util::set_wordlist(&source, content);
+        }
+    })
+}
+
+/// Per-suggestion time budget from `--think-ms`.
+fn think_limit_from_args(args: &[String]) -> Option<Duration> {
+    flag_value(args, "--think-ms")
+        .map(|_| Duration::from_millis(count_flag(args, "--think-ms", 0) as u64))
+}
+
+fn simulate(num_runs: usize, options: SimulationOptions) -> Result<()> {
     // Delegate the core logic to the new simulate module
-    simulate::run_simulation(num_runs)
+    simulate::run_simulation(num_runs, options)
+}
+
+/// A new game of `play`, printing the seed to replay it with when none was given.
+fn new_play(
+    seed: Option<u64>,
+    easy: bool,
+    layout: Layout,
+    practice: Option<Practice>,
+) -> Result<Play> {
+    let resolved = util::resolve_seed(seed);
+    if seed.is_none() {
+        println!(
+            "Seed: {} (replay this game with --seed {})",
+            resolved, resolved
+        );
+    }
+    Play::new(resolved, easy, layout, practice)
+}
+
+/// Prints win rates and streaks per profile and mode, for the active profile or, with
+/// `--all-profiles`, for every profile that has a history.
+fn stats_show(args: &[String]) -> Result<()> {
+    let profiles = if args.iter().any(|a| a == "--all-profiles") {
+        history::profiles()
+    } else {
+        vec![util::profile().to_string()]
+    };
+
+    let mut records = Vec::new();
+    for profile in &profiles {
+        records.extend(history::load(profile)?);
+    }
+    if records.is_empty() {
+        println!("No games recorded for profile {} yet.", profiles.join(", "));
+        return Ok(());
+    }
+
+    write_table(history::summary_table(&history::summarize(&records)), args)
+}
+
+/// Grades every recorded game with candidate counts since `--since` and prints the
+/// weekly skill and blunder trends. CSV and JSON hold only the weekly table.
+fn review_history(args: &[String]) -> Result<()> {
+    let (profiles, records) = review_records(args)?;
+    let mut trends = HistoryTrends::new();
+    for record in &records {
+        trends.record_game(
+            record.timestamp,
+            record.answer.as_deref(),
+            &record.candidates,
+        );
+    }
+    let format = format_from_args(args)?.unwrap_or(OutputFormat::Text);
+    if format != OutputFormat::Text {
+        return write_table(trends.week_table(), args);
+    }
+    let mut text = if trends.graded_games() > 0 {
+        trends.week_table().to_text() + "\n"
+    } else {
+        format!(
+            "No gradable games for profile {}; only games played in solve record candidate counts.\n",
+            profiles.join(", ")
+        )
+    };
+    text.push_str(&trends.summary());
+    output::write_output(flag_value(args, "--output").unwrap_or("-"), &text)
+}
+
+/// The profiles `review` looks at (`--all-profiles` or the active one) and their
+/// games from `--since` on.
+fn review_records(args: &[String]) -> Result<(Vec<String>, Vec<history::GameRecord>)> {
+    let since = flag_value(args, "--since")
+        .map(util::parse_date)
+        .transpose()?;
+    let profiles = if args.iter().any(|a| a == "--all-profiles") {
+        history::profiles()
+    } else {
+        vec![util::profile().to_string()]
+    };
+    let mut records = Vec::new();
+    for profile in &profiles {
+        records.extend(
+            history::load(profile)?
+                .into_iter()
+                .filter(|r| since.is_none_or(|day| ((r.timestamp / 86_400) as i64) >= day)),
+        );
+    }
+    Ok((profiles, records))
+}
+
+/// `review --answer-trajectory`: where each game's answer ranked after every guess.
+fn review_trajectories(args: &[String]) -> Result<()> {
+    let (profiles, records) = review_records(args)?;
+    let table = history::trajectory_table(&records);
+    if table.is_empty() && format_from_args(args)?.is_none() {
+        println!(
+            "No answer trajectories for profile {}; solve records them once a game's answer is known.",
+            profiles.join(", ")
+        );
+        return Ok(());
+    }
+    write_table(table, args)
+}
+
+/// `--practice <name>` or `--practice-glob <pattern>` of `play`.
+fn practice_from_args(args: &[String]) -> Result<Option<Practice>> {
+    match (
+        flag_value(args, "--practice"),
+        flag_value(args, "--practice-glob"),
+    ) {
+        (Some(_), Some(_)) => Err(anyhow!("--practice and --practice-glob can't be combined.")),
+        (Some(name), None) => Practice::named(name).map(Some),
+        (None, Some(pattern)) => Ok(Some(Practice::glob(pattern))),
+        (None, None) => Ok(None),
+    }
+}
+
+/// Plots the guess distributions of `simulate --output` summaries as SVG, or as an
+/// HTML page with `--html`.
+fn stats_plot(args: &[String]) -> Result<()> {
+    let mut paths = Vec::new();
+    let mut rest = args.iter();
+    while let Some(arg) = rest.next() {
+        if config::takes_value(arg) {
+            rest.next();
+        } else if !arg.starts_with('-') {
+            paths.push(arg.as_str());
+        }
+    }
+    if paths.is_empty() {
+        return Err(anyhow!(
+            "stats plot needs at least one results file, e.g. the <name>.summary.json of 'simulate --output'."
+        ));
+    }
+
+    let mut series = Vec::new();
+    for path in &paths {
+        let content =
+            fs::read_to_string(path).map_err(|e| anyhow!("Failed to read {}: {}", path, e))?;
+        let value: serde_json::Value = serde_json::from_str(&content)
+            .map_err(|e| anyhow!("{} is not valid JSON: {}", path, e))?;
+        let results =
+            SimulationResults::from_json(&value).map_err(|e| anyhow!("{}: {}", path, e))?;
+        let name = Path::new(path)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or(path);
+        let label = name
+            .strip_suffix(".summary.json")
+            .or_else(|| name.strip_suffix(".json"))
+            .unwrap_or(name)
+            .to_string();
+        series.push((label, results));
+    }
+    if let Some((first, first_results)) = series.first() {
+        let limit = first_results.max_guesses();
+        if let Some((label, results)) = series.iter().find(|(_, r)| r.max_guesses() != limit) {
+            return Err(anyhow!(
+                "{} allows {} guesses but {} allows {}; results with different guess limits can't be plotted together.",
+                first,
+                limit,
+                label,
+                results.max_guesses()
+            ));
+        }
+    }
+    let series: Vec<(&str, &SimulationResults)> = series
+        .iter()
+        .map(|(label, results)| (label.as_str(), results))
+        .collect();
+    let content = if args.iter().any(|a| a == "--html") {
+        plot::distribution_html(&series)
+    } else {
+        plot::distribution_svg(&series)
+    };
+    output::write_output(flag_value(args, "--output").unwrap_or("-"), &content)
+}
+
+/// Prints the `wordlist info` summary of the file at `path`, or of the built-in
+/// wordlist.
+fn wordlist_info(path: Option<&str>, args: &[String]) -> Result<()> {
+    let content = match path {
+        Some(path) => wordlist::read_source(path)?,
+        None => util::read_wordlist()?,
+    };
+    let policy = flag_value(args, "--punctuation")
+        .map(PunctuationPolicy::from_name)
+        .unwrap_or(Ok(PunctuationPolicy::Reject))?;
+    let info = wordlist::WordlistInfo::from_text(&content, policy, length_from_args(args)?);
+    print!("{}", info.to_text());
+    Ok(())
+}
+
+/// Writes a sample of the loaded wordlist, one word per line (`wordlist sample`),
+/// and reports how closely its letter distribution follows the full list's.
+fn wordlist_sample(args: &[String]) -> Result<()> {
+    let size = match count_flag(args, "--size", 0) {
+        0 => {
+            eprintln!("wordlist sample needs --size <n> of at least 1.");
+            std::process::exit(1);
+        }
+        size => size,
+    };
+    let sampling = flag_value(args, "--stratify-by")
+        .map(wordlist::Sampling::from_name)
+        .unwrap_or(Ok(wordlist::Sampling::Uniform))?;
+    let policy = flag_value(args, "--punctuation")
+        .map(PunctuationPolicy::from_name)
+        .unwrap_or(Ok(PunctuationPolicy::Reject))?;
+    let seed = util::resolve_seed(seed_from_args(args));
+    let words = wordlist::Wordlist::load_normalized(policy)?.guesses;
+
+    let sample = wordlist::sample(&words, size, sampling, seed);
+    let stats = |words: &[String]| {
+        let refs: Vec<&str> = words.iter().map(String::as_str).collect();
+        LetterStats::from_words(&refs)
+    };
+    let full = stats(&words);
+    let uniform = wordlist::sample(&words, size, wordlist::Sampling::Uniform, seed);
+    // Stdout may be the sample itself, so the summary goes to stderr
+    eprintln!(
+        "Sampled {} of {} words ({}, seed {}). Letter divergence from the full list: {:.4} (a uniform sample: {:.4}).",
+        sample.len(),
+        words.len(),
+        sampling.name(),
+        seed,
+        wordlist::letter_divergence(&stats(&sample), &full),
+        wordlist::letter_divergence(&stats(&uniform), &full)
+    );
+    let mut content = sample.join("\n");
+    content.push('\n');
+    output::write_output(flag_value(args, "--output").unwrap_or("-"), &content)
 }
 
-fn play() -> Result<()> {
-    let mut play = Play::new()?;
-    play.run()?;
+/// Answer likelihoods learned from the active profile's past answers.
+fn history_prior(pool: &[String]) -> Result<analysis::HistoryPrior> {
+    let answers = history::past_answers(util::profile())?;
+    Ok(analysis::history_prior(&answers, pool))
+}
+
+fn prior_show(args: &[String]) -> Result<()> {
+    let wordlist = wordlist::Wordlist::load()?;
+    let prior = history_prior(&wordlist.answers)?;
+    if format_from_args(args)?.unwrap_or(OutputFormat::Text) == OutputFormat::Text {
+        println!(
+            "Learned from {} past answer(s) in profile {}, shrunk toward the answer pool with {} pseudo-answers.",
+            prior.answers,
+            util::profile(),
+            analysis::PRIOR_STRENGTH
+        );
+    }
+
+    let mut table = Table::new()
+        .column("feature", "Feature", CellFormat::Text)
+        .column("pool_rate", "Pool %", CellFormat::Percent(1))
+        .column("seen", "Seen", CellFormat::Integer)
+        .column("shrunk_rate", "Shrunk %", CellFormat::Percent(1))
+        .column("with", "With", CellFormat::Fixed(3))
+        .column("without", "Without", CellFormat::Fixed(3));
+    for adjustment in &prior.adjustments {
+        table.push_row(vec![
+            adjustment.feature.into(),
+            adjustment.base_rate.into(),
+            adjustment.hits.into(),
+            adjustment.estimate.into(),
+            adjustment.multiplier(true).into(),
+            adjustment.multiplier(false).into(),
+        ]);
+    }
+    write_table(table, args)
+}
+
+/// Writes `table` sorted by `--sort-by <column>`, in `--format text|json|csv`, to
+/// `--output <file>` or stdout.
+fn write_table(mut table: Table, args: &[String]) -> Result<()> {
+    if let Some(key) = flag_value(args, "--sort-by") {
+        table.sort_by(key)?;
+    }
+    let format = format_from_args(args)?.unwrap_or(OutputFormat::Text);
+    output::write_output(
+        flag_value(args, "--output").unwrap_or("-"),
+        &table.render(format),
+    )
+}
+
+fn race(options: RaceOptions, format: Option<OutputFormat>, output: Option<&str>) -> Result<()> {
+    let results = simulate::run_race(options)?;
+    let content = match format.unwrap_or(OutputFormat::Text) {
+        OutputFormat::Text => results.to_text(),
+        OutputFormat::Json => serde_json::to_string_pretty(&results.to_json())? + "\n",
+        OutputFormat::Csv => return Err(anyhow!("race supports --format text or json")),
+    };
+    output::write_output(output.unwrap_or("-"), &content)
+}
+
+/// `dry_run` holds the `--threshold` for listing count changes; with it nothing is
+/// written, only the differences to the previous stats are printed.
+fn analyze(
+    pool: PoolOptions,
+    format: Option<OutputFormat>,
+    output: Option<&str>,
+    incremental: Option<&str>,
+    dry_run: Option<f64>,
+) -> Result<()> {
+    let wordlist = wordlist::Wordlist::load_with(&pool)?;
+
+    let stats = match incremental {
+        Some(diff_path) => incremental_stats(&wordlist.guesses, diff_path, output)?,
+        None => {
+            let words: Vec<&str> = wordlist.guesses.iter().map(|s| s.as_str()).collect();
+            LetterStats::from_words(&words)
+        }
+    };
+    let format = format.unwrap_or(OutputFormat::Json);
+    let content = match format {
+        OutputFormat::Json => stats.to_pretty_json()? + "\n",
+        OutputFormat::Csv => stats.to_csv(),
+        OutputFormat::Text => stats.to_text(),
+    };
+
+    // A restricted pool only exists for this run, so never overwrite the main stats file
+    let restricted = !pool.restriction.is_empty() || pool.punctuation != PunctuationPolicy::Reject;
+    let path = match output {
+        Some(path) => path,
+        None if restricted || format != OutputFormat::Json => "-",
+        None => "letter_stats.json",
+    };
+
+    if let Some(threshold) = dry_run {
+        return analyze_dry_run(&wordlist, &stats, &content, output, threshold);
+    }
+    output::write_output(path, &content)?;
+    if path != "-" {
+        eprintln!("Saved letter stats to {}", path);
+    }
 
     Ok(())
 }
 
-fn solve() -> Result<()> {
-    let solver = Solver::new();
-    solver?.run()?;
+/// Prints how `stats`, counted over `wordlist`, differ from the previous stats (the
+/// output file, else the built-in ones) and which saved data the change would
+/// invalidate, without writing.
+fn analyze_dry_run(
+    wordlist: &wordlist::Wordlist,
+    stats: &LetterStats,
+    content: &str,
+    output: Option<&str>,
+    threshold: f64,
+) -> Result<()> {
+    let previous_path = output.filter(|p| *p != "-").unwrap_or("letter_stats.json");
+    let (previous_json, source) = match fs::read_to_string(previous_path) {
+        Ok(content) => (content, previous_path.to_string()),
+        Err(_) => (util::read_letter_stats()?, "the built-in stats".to_string()),
+    };
+    let previous = LetterStats::parse(&previous_json)?;
+    let diff = previous.diff(stats, threshold);
+
+    println!(
+        "Dry run: comparing with {} (counts changed by {:.0}% or more). Nothing is written.\n",
+        source,
+        threshold * 100.0
+    );
+    print!("{}", diff.to_text());
 
+    println!("\nWould invalidate:");
+    if previous_json == content {
+        println!("  nothing: {} is unchanged", previous_path);
+        return Ok(());
+    }
+    println!("  {}: would be rewritten", previous_path);
+    let stale = warmstart::rankings_from(&previous_json);
+    if stale.is_empty() {
+        println!("  initial_ranking.json: no cached ranking uses these stats");
+    } else {
+        println!(
+            "  initial_ranking.json: cached first-guess ranking(s) for {} would be recomputed",
+            stale.join(", ")
+        );
+    }
+    if let Some(opener) = openers::load_opener() {
+        if opener.wordlist_hash != openers::lists_hash(&wordlist.guesses, &wordlist.answers) {
+            println!(
+                "  opener.json: the benchmarked opener '{}' was chosen for other word lists",
+                opener.word
+            );
+        }
+    }
     Ok(())
 }
 
-fn analyze() -> Result<()> {
-    let content = util::read_wordlist()?;
-    let words: Vec<&str> = content.lines().collect();
-    let stats = LetterStats::from_words(&words);
+/// Share of a count's previous value a change must reach to be listed by
+/// `analyze --dry-run` (`--threshold`, default 0.05).
+fn threshold_from_args(args: &[String]) -> Result<f64> {
+    match flag_value(args, "--threshold") {
+        Some(value) => value
+            .parse::<f64>()
+            .ok()
+            .filter(|t| *t >= 0.0)
+            .ok_or_else(|| anyhow!("--threshold must be a non-negative number, e.g. 0.05.")),
+        None => Ok(0.05),
+    }
+}
 
-    let mut json = serde_json::to_string_pretty(&stats)?;
+/// Updates the previous stats with the words added and removed in the diff at
+/// `diff_path`. The previous stats are read from the output file when it exists,
+/// otherwise the built-in ones are used.
+fn incremental_stats(
+    words: &[String],
+    diff_path: &str,
+    output: Option<&str>,
+) -> Result<LetterStats> {
+    let diff = fs::read_to_string(diff_path)
+        .map_err(|e| anyhow!("Failed to read diff {}: {}", diff_path, e))?;
+    let (added, removed) = analysis::parse_word_diff(&diff);
 
-    // This regex joins lines between '[' and ']'
-    let re = regex::Regex::new(r"\[\s*((?:\d+,\s*)*\d+)\s*\]").unwrap();
-    json = re
-        .replace_all(&json, |caps: &regex::Captures| {
-            let inner = caps[1].split_whitespace().collect::<Vec<_>>().join(" ");
-            format!("[{}]", inner.replace(", ", ", "))
-        })
-        .to_string();
+    let previous_path = output.filter(|p| *p != "-").unwrap_or("letter_stats.json");
+    let previous_json = match fs::read_to_string(previous_path) {
+        Ok(content) => content,
+        Err(_) => util::read_letter_stats()?,
+    };
+    let mut stats = LetterStats::parse(&previous_json)?;
+    stats.apply_diff(&added, &removed, words).map_err(|e| {
+        anyhow!(
+            "Cannot apply {}: {}. Run a full `wordle-bot analyze`.",
+            diff_path,
+            e
+        )
+    })?;
 
-    fs::write("letter_stats.json", json)?;
-    println!("Saved letter stats to letter_stats.json");
+    eprintln!(
+        "Updated letter stats with {} added and {} removed words",
+        added.len(),
+        removed.len()
+    );
+    Ok(stats)
+}
+
+/// Prints how the frequency ranking scores `word` after `guesses`, term by term, and
+/// where it ranks among the remaining candidates.
+fn score(word: &str, guesses: &[(String, String)], pool: PoolOptions) -> Result<()> {
+    let word = word.to_lowercase();
+    let mut solver = Solver::with_pool(pool)?;
+    for (guess, pattern) in guesses {
+        solver.game.add_line(guess, pattern);
+    }
+    solver.update_wordlist();
+
+    // Same weight selection as the solver's frequency ranking
+    let weights = util::read_solver_config()?;
+    let attempt = solver.game.lines.len().min(weights.len() - 1);
+    let scorer = ranking::Scorer::new(&solver.stats_json)?;
+    let breakdown = scorer
+        .score_word_detailed(&word, weights[attempt], solver.position_weights)
+        .ok_or_else(|| {
+            anyhow!(
+                "'{}' can't be scored: it needs 5 letters that appear in the letter stats",
+                word
+            )
+        })?;
+
+    println!(
+        "Score of {} after {} guess(es), using weight set {}:\n",
+        word.to_uppercase(),
+        solver.game.lines.len(),
+        attempt + 1
+    );
+    print!("{}", breakdown.to_text());
+
+    let word_refs: Vec<&str> = solver.current_words.iter().map(|s| s.as_str()).collect();
+    let ranked = ranking::weighted_rank(
+        &word_refs,
+        &solver.stats_json,
+        weights[attempt],
+        solver.position_weights,
+    )?;
+    match ranked.position_of(&word) {
+        Some(index) => println!(
+            "\nRank {} of {} remaining candidates",
+            index + 1,
+            ranked.len()
+        ),
+        None => println!("\nNot among the {} remaining candidates", ranked.len()),
+    }
+    if !solver.all_words.contains(&word) {
+        println!("Note: '{}' is not in the wordlist", word);
+    }
 
     Ok(())
 }
 
-fn rank() -> Result<()> {
-    use ranking::rank_words;
-    let content = util::read_wordlist()?;
-    let words: Vec<&str> = content.lines().collect();
+/// Ranks one game state per stdin line (`<word>:<pattern>` entries separated by
+/// spaces, an empty line for the opening) and prints one JSON object per state.
+fn batch_rank(solver: &Solver, strategy: Strategy, top_k: usize) -> Result<()> {
+    let weights = util::read_solver_config()?;
 
-    let stats_json = util::read_letter_stats()?;
-    let results = rank_words(&words, &stats_json)?;
+    let mut states = Vec::new();
+    for (number, line) in std::io::stdin().lines().enumerate() {
+        let mut state = game::GameData::new();
+        state.set_restriction(solver.game.restriction.clone());
+        for entry in line?.split_whitespace() {
+            let (word, pattern) =
+                parse_guess(entry).map_err(|e| anyhow!("Line {}: {}", number + 1, e))?;
+            state.add_line(&word, &pattern);
+        }
+        states.push(state);
+    }
 
-    println!("Top 10 words by letter position frequency:");
-    for (word, score) in results.iter().take(10) {
-        println!("{word:<10} {score:.5}");
+    let ranked = batch::batch_rank(&states, solver, strategy, &weights, top_k)?;
+    let mut stdout = std::io::stdout().lock();
+    for state in ranked {
+        let top: Vec<serde_json::Value> = state
+            .top
+            .iter()
+            .map(|&(guess, score)| {
+                serde_json::json!({ "word": solver.all_words[guess as usize], "score": score })
+            })
+            .collect();
+        let record = serde_json::json!({ "candidates": state.candidates, "top": top });
+        writeln!(stdout, "{}", record)?;
     }
 
     Ok(())
 }
+
+fn rank(format: Option<OutputFormat>, output: Option<&str>) -> Result<()> {
+    use ranking::rank_words;
+    let results = if util::wordlist_source().is_some() {
+        // A --wordlist override is validated and gets its own letter stats
+        let wordlist = wordlist::Wordlist::load()?;
+        let words: Vec<&str> = wordlist.guesses.iter().map(|s| s.as_str()).collect();
+        let stats_json = serde_json::to_string(&LetterStats::from_words(&words))?;
+        rank_words(&words, &stats_json)?
+    } else {
+        let content = util::read_wordlist()?;
+        let words: Vec<&str> = content.lines().collect();
+        rank_words(&words, &util::read_letter_stats()?)?
+    };
+    let path = output.unwrap_or("-");
+
+    let content = match format.unwrap_or(OutputFormat::Text) {
+        OutputFormat::Text => {
+            if path != "-" {
+                output::disable_colors();
+            }
+            eprintln!("Top 10 words by letter position frequency:");
+            output::format_ranked(results.top(10), &game::GameData::new())
+        }
+        OutputFormat::Csv => {
+            let mut csv = String::from("rank,word,score\n");
+            for (i, (word, score)) in results.iter().enumerate() {
+                csv.push_str(&format!("{},{},{}\n", i + 1, word, score));
+            }
+            csv
+        }
+        OutputFormat::Json => {
+            let entries: Vec<serde_json::Value> = results
+                .iter()
+                .map(|(word, score)| serde_json::json!({ "word": word, "score": score }))
+                .collect();
+            serde_json::to_string_pretty(&entries)? + "\n"
+        }
+    };
+
+    output::write_output(path, &content)
+}
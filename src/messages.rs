@@ -0,0 +1,1123 @@
+use anyhow::{anyhow, Result};
+use std::fmt;
+use std::sync::OnceLock;
+
+/// Language of the prompts and summaries of `solve` and `play`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lang {
+    En,
+    De,
+}
+
+impl Lang {
+    pub const ALL: [Lang; 2] = [Lang::En, Lang::De];
+
+    pub fn code(self) -> &'static str {
+        match self {
+            Lang::En => "en",
+            Lang::De => "de",
+        }
+    }
+
+    /// A language by its code, or the language part of a locale like `de_DE.UTF-8`.
+    pub fn parse(value: &str) -> Option<Lang> {
+        let code = value
+            .split(['_', '.', '-'])
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        Lang::ALL.into_iter().find(|lang| lang.code() == code)
+    }
+}
+
+static LANG: OnceLock<Lang> = OnceLock::new();
+
+/// Selects the language for this run: `--lang`, else the `LANG` environment
+/// variable, else English. An unknown `--lang` is an error; an unknown `LANG` falls
+/// back to English.
+pub fn init(args: &[String]) -> Result<()> {
+    let lang = match args.iter().position(|a| a == "--lang") {
+        Some(i) => {
+            let value = args.get(i + 1).map(String::as_str).unwrap_or_default();
+            Lang::parse(value).ok_or_else(|| {
+                let codes: Vec<&str> = Lang::ALL.iter().map(|lang| lang.code()).collect();
+                anyhow!(
+                    "Unknown language '{}'. Available: {}.",
+                    value,
+                    codes.join(", ")
+                )
+            })?
+        }
+        None => std::env::var("LANG")
+            .ok()
+            .and_then(|value| Lang::parse(&value))
+            .unwrap_or(Lang::En),
+    };
+    let _ = LANG.set(lang);
+    Ok(())
+}
+
+pub fn lang() -> Lang {
+    LANG.get().copied().unwrap_or(Lang::En)
+}
+
+/// Every message by key, with its text in each language that has one. English is
+/// the fallback and must always be there. `{}` marks an argument of `fill`.
+static CATALOG: &[(&str, &[(Lang, &str)])] = &[
+    (
+        "solve.hidden",
+        &[
+            (Lang::En, "Suggestions are hidden: enter 'hint' for the solver's top pick."),
+            (Lang::De, "Vorschläge sind ausgeblendet: 'hint' zeigt den besten Tipp des Solvers."),
+        ],
+    ),
+    (
+        "solve.guess_prompt",
+        &[
+            (Lang::En, "Enter your 5-letter guess (or 'exit'): "),
+            (Lang::De, "Gib dein Wort mit 5 Buchstaben ein (oder 'exit'): "),
+        ],
+    ),
+    (
+        "solve.exiting",
+        &[
+            (Lang::En, "Exiting solver."),
+            (Lang::De, "Solver wird beendet."),
+        ],
+    ),
+    (
+        "solve.reset",
+        &[
+            (Lang::En, "Solver has been reset."),
+            (Lang::De, "Der Solver wurde zurückgesetzt."),
+        ],
+    ),
+    (
+        "solve.rollback_usage",
+        &[
+            (Lang::En, "Use 'rollback <n>' to remove the last n guesses."),
+            (Lang::De, "'rollback <n>' entfernt die letzten n Versuche."),
+        ],
+    ),
+    (
+        "solve.answer_length",
+        &[
+            (Lang::En, "Please enter a 5-letter answer."),
+            (Lang::De, "Bitte gib eine Lösung mit 5 Buchstaben ein."),
+        ],
+    ),
+    (
+        "solve.answer_recorded",
+        &[
+            (Lang::En, "Recorded '{}' as a used answer in {}."),
+            (Lang::De, "'{}' ist jetzt als verwendete Lösung in {} gespeichert."),
+        ],
+    ),
+    (
+        "solve.answer_failed",
+        &[
+            (Lang::En, "Could not record answer: {}"),
+            (Lang::De, "Die Lösung konnte nicht gespeichert werden: {}"),
+        ],
+    ),
+    (
+        "solve.what_if_answer",
+        &[
+            (Lang::En, "What-if replays need the answer: enter 'answer <word>' first."),
+            (Lang::De, "What-if braucht die Lösung: gib zuerst 'answer <wort>' ein."),
+        ],
+    ),
+    (
+        "solve.report_usage",
+        &[
+            (Lang::En, "Use 'report', 'report spoiler-free' or 'report near-misses'."),
+            (Lang::De, "Verwende 'report', 'report spoiler-free' oder 'report near-misses'."),
+        ],
+    ),
+    (
+        "solve.near_misses_answer",
+        &[
+            (Lang::En, "Near misses need the answer: solve the puzzle or enter 'answer <word>' first."),
+            (Lang::De, "Knappe Kandidaten brauchen die Lösung: löse das Rätsel oder gib zuerst 'answer <wort>' ein."),
+        ],
+    ),
+    (
+        "solve.hint_unneeded",
+        &[
+            (Lang::En, "Suggestions are already shown after every guess; 'hint' is for --assist-on-demand."),
+            (Lang::De, "Vorschläge erscheinen schon nach jedem Versuch; 'hint' ist für --assist-on-demand."),
+        ],
+    ),
+    (
+        "solve.saved",
+        &[
+            (Lang::En, "Game saved to {}."),
+            (Lang::De, "Spiel in {} gespeichert."),
+        ],
+    ),
+    (
+        "solve.save_failed",
+        &[
+            (Lang::En, "Could not save game: {}"),
+            (Lang::De, "Das Spiel konnte nicht gespeichert werden: {}"),
+        ],
+    ),
+    (
+        "solve.loaded",
+        &[
+            (Lang::En, "Game loaded from {}."),
+            (Lang::De, "Spiel aus {} geladen."),
+        ],
+    ),
+    (
+        "solve.load_failed",
+        &[
+            (Lang::En, "Could not load game: {}"),
+            (Lang::De, "Das Spiel konnte nicht geladen werden: {}"),
+        ],
+    ),
+    (
+        "solve.word_length",
+        &[
+            (Lang::En, "Please enter a 5-letter word."),
+            (Lang::De, "Bitte gib ein Wort mit 5 Buchstaben ein."),
+        ],
+    ),
+    (
+        "solve.not_in_wordlist",
+        &[
+            (Lang::En, "'{}' is not in the wordlist."),
+            (Lang::De, "'{}' steht nicht in der Wortliste."),
+        ],
+    ),
+    (
+        "solve.already_guessed",
+        &[
+            (Lang::En, "'{}' was already guessed and would waste a turn. Guess it again? (y/n): "),
+            (Lang::De, "'{}' wurde schon geraten und würde einen Versuch verschwenden. Trotzdem nehmen? (y/n): "),
+        ],
+    ),
+    (
+        "solve.pattern_prompt",
+        &[
+            (Lang::En, "Enter pattern (w = wrong, m = misplaced, c = correct): "),
+            (Lang::De, "Muster eingeben (w = falsch, m = falsche Stelle, c = richtig): "),
+        ],
+    ),
+    (
+        "solve.invalid_pattern",
+        &[
+            (Lang::En, "Invalid pattern. Use w, m, c (b/y/g, 0/1/2 and colored squares work too)."),
+            (Lang::De, "Ungültiges Muster. Verwende w, m, c (b/y/g, 0/1/2 und farbige Quadrate gehen auch)."),
+        ],
+    ),
+    (
+        "solve.template_word_length",
+        &[
+            (Lang::En, "The annotated guess has {} letters, not 5."),
+            (Lang::De, "Das markierte Wort hat {} Buchstaben statt 5."),
+        ],
+    ),
+    (
+        "solve.template_pattern_length",
+        &[
+            (Lang::En, "The pattern after the guess has {} cells, not 5."),
+            (Lang::De, "Das Muster nach dem Wort hat {} Felder statt 5."),
+        ],
+    ),
+    (
+        "solve.template_not_letter",
+        &[
+            (Lang::En, "Character {} ('{}') is neither a letter nor '*'."),
+            (Lang::De, "Zeichen {} ('{}') ist weder ein Buchstabe noch '*'."),
+        ],
+    ),
+    (
+        "solve.template_not_cell",
+        &[
+            (Lang::En, "Character {} ('{}') is not a pattern cell. Use w, m, c (b/y/g, 0/1/2 and colored squares work too)."),
+            (Lang::De, "Zeichen {} ('{}') ist kein Musterfeld. Verwende w, m, c (b/y/g, 0/1/2 und farbige Quadrate gehen auch)."),
+        ],
+    ),
+    (
+        "solve.template_marker",
+        &[
+            (Lang::En, "The '*' at character {} must be followed by a letter."),
+            (Lang::De, "Auf das '*' an Zeichen {} muss ein Buchstabe folgen."),
+        ],
+    ),
+    (
+        "solve.template_capitalized",
+        &[
+            (Lang::En, "A capitalized word is ambiguous: type it in lowercase, or as 'word pattern' if the first letter is green."),
+            (Lang::De, "Ein großgeschriebenes Wort ist mehrdeutig: tippe es klein, oder als 'Wort Muster', wenn der erste Buchstabe grün ist."),
+        ],
+    ),
+    (
+        "solve.solved",
+        &[
+            (Lang::En, "Congratulations! You've solved the puzzle! The word is '{}'."),
+            (Lang::De, "Glückwunsch! Rätsel gelöst! Das Wort ist '{}'."),
+        ],
+    ),
+    (
+        "solve.unusual",
+        &[
+            (Lang::En, "This feedback is unusual: {}. Double-check the pattern? (keep/undo): "),
+            (Lang::De, "Dieses Muster ist ungewöhnlich: {}. Bitte prüfen! (keep/undo): "),
+        ],
+    ),
+    (
+        "solve.unusual_eliminated",
+        &[
+            (Lang::En, "it leaves {} of {} candidates ({}% eliminated in one guess)"),
+            (Lang::De, "es lässt {} von {} Kandidaten übrig ({}% mit einem Versuch ausgeschlossen)"),
+        ],
+    ),
+    (
+        "solve.unusual_letter",
+        &[
+            (Lang::En, "it rules out '{}', which {}% of the candidates contained"),
+            (Lang::De, "es schließt '{}' aus, das {}% der Kandidaten enthielten"),
+        ],
+    ),
+    (
+        "solve.undone",
+        &[
+            (Lang::En, "Removed {} {}; enter the guess again."),
+            (Lang::De, "{} {} entfernt; gib den Versuch noch einmal ein."),
+        ],
+    ),
+    (
+        "solve.determined",
+        &[
+            (Lang::En, "Every position is known: the answer is {}. Type it as your next guess."),
+            (Lang::De, "Alle Positionen sind bekannt: die Lösung ist {}. Gib sie als nächsten Versuch ein."),
+        ],
+    ),
+    (
+        "solve.determined_unknown",
+        &[
+            (Lang::En, "Warning: {} is not in the wordlist; check the patterns entered so far."),
+            (Lang::De, "Warnung: {} steht nicht in der Wortliste; prüfe die bisher eingegebenen Muster."),
+        ],
+    ),
+    (
+        "solve.top_initial",
+        &[
+            (Lang::En, "Top 10 words by {}:"),
+            (Lang::De, "Die 10 besten Wörter nach {}:"),
+        ],
+    ),
+    (
+        "solve.top_suggestions",
+        &[
+            (Lang::En, "Top suggested words ({}):"),
+            (Lang::De, "Beste Vorschläge ({}):"),
+        ],
+    ),
+    (
+        "solve.top_normal",
+        &[
+            (Lang::En, "Top suggested words (Normal Mode):"),
+            (Lang::De, "Beste Vorschläge (normaler Modus):"),
+        ],
+    ),
+    (
+        "solve.banned",
+        &[
+            (Lang::En, "Banned {}: it stays a candidate but won't be suggested."),
+            (Lang::De, "{} gesperrt: es bleibt ein Kandidat, wird aber nicht vorgeschlagen."),
+        ],
+    ),
+    (
+        "solve.pinned_added",
+        &[
+            (Lang::En, "Pinned {}: its rank is shown with every suggestion list."),
+            (Lang::De, "{} angeheftet: sein Rang wird bei jeder Vorschlagsliste angezeigt."),
+        ],
+    ),
+    (
+        "solve.set_usage",
+        &[
+            (Lang::En, "Use 'set weight <attempt> <position> <overall> <unique>' or 'set strategy <name>'."),
+            (Lang::De, "'set weight <Versuch> <Position> <Gesamt> <Einzigartig>' oder 'set strategy <Name>' verwenden."),
+        ],
+    ),
+    (
+        "solve.weight_attempt",
+        &[
+            (Lang::En, "The attempt must be a number from 1 to {}."),
+            (Lang::De, "Der Versuch muss eine Zahl von 1 bis {} sein."),
+        ],
+    ),
+    (
+        "solve.weight_value",
+        &[
+            (Lang::En, "'{}' is not a weight: each weight must be a finite number."),
+            (Lang::De, "'{}' ist kein Gewicht: jedes Gewicht muss eine endliche Zahl sein."),
+        ],
+    ),
+    (
+        "solve.weight_set",
+        &[
+            (Lang::En, "Attempt {} now weighs letter position {}, overall frequency {} and unique letters {} (this session only)."),
+            (Lang::De, "Versuch {} gewichtet jetzt Buchstabenposition {}, Gesamthäufigkeit {} und einzigartige Buchstaben {} (nur in dieser Sitzung)."),
+        ],
+    ),
+    (
+        "solve.strategy_set",
+        &[
+            (Lang::En, "Strategy is now {} (this session only)."),
+            (Lang::De, "Die Strategie ist jetzt {} (nur in dieser Sitzung)."),
+        ],
+    ),
+    (
+        "solve.config_strategy",
+        &[
+            (Lang::En, "Strategy: {}, endgame: {}"),
+            (Lang::De, "Strategie: {}, Endspiel: {}"),
+        ],
+    ),
+    (
+        "solve.config_weights",
+        &[
+            (Lang::En, "Weights per attempt (letter position, overall frequency, unique letters):"),
+            (Lang::De, "Gewichte pro Versuch (Buchstabenposition, Gesamthäufigkeit, einzigartige Buchstaben):"),
+        ],
+    ),
+    (
+        "solve.config_last_set",
+        &[
+            (Lang::En, "Later attempts use the weights of attempt {}."),
+            (Lang::De, "Spätere Versuche verwenden die Gewichte von Versuch {}."),
+        ],
+    ),
+    (
+        "solve.config_positions",
+        &[
+            (Lang::En, "Position multipliers: {}"),
+            (Lang::De, "Positionsfaktoren: {}"),
+        ],
+    ),
+    (
+        "solve.config_edited",
+        &[
+            (Lang::En, "* changed in this session and not saved; 'save config <path>' writes them to a file."),
+            (Lang::De, "* in dieser Sitzung geändert und nicht gespeichert; 'save config <Pfad>' schreibt sie in eine Datei."),
+        ],
+    ),
+    (
+        "solve.config_saved",
+        &[
+            (Lang::En, "Saved the session's solver config to {}."),
+            (Lang::De, "Solver-Konfiguration der Sitzung in {} gespeichert."),
+        ],
+    ),
+    (
+        "solve.config_save_failed",
+        &[
+            (Lang::En, "Could not save the solver config: {}"),
+            (Lang::De, "Die Solver-Konfiguration konnte nicht gespeichert werden: {}"),
+        ],
+    ),
+    (
+        "solve.pinned",
+        &[
+            (Lang::En, "Pinned {}: rank {}, score {}"),
+            (Lang::De, "Angeheftet {}: Rang {}, Wert {}"),
+        ],
+    ),
+    (
+        "solve.pinned_unranked",
+        &[
+            (Lang::En, "Pinned {}: not ranked this turn."),
+            (Lang::De, "Angeheftet {}: in dieser Runde nicht bewertet."),
+        ],
+    ),
+    (
+        "solve.most_likely",
+        &[
+            (Lang::En, "Most likely answer: {} ({}%)"),
+            (Lang::De, "Wahrscheinlichste Lösung: {} ({}%)"),
+        ],
+    ),
+    (
+        "solve.most_likely_tied",
+        &[
+            (Lang::En, "Most likely answer: {} ({}%, as likely as {} others)"),
+            (Lang::De, "Wahrscheinlichste Lösung: {} ({}%, so wahrscheinlich wie {} weitere)"),
+        ],
+    ),
+    (
+        "solve.rare_letter",
+        &[
+            (Lang::En, "{}% of remaining words contain '{}' — consider probing it."),
+            (
+                Lang::De,
+                "{}% der verbleibenden Wörter enthalten '{}' — es lohnt sich, den Buchstaben zu testen.",
+            ),
+        ],
+    ),
+    (
+        "solve.possible_answers",
+        &[
+            (Lang::En, "{} of the top {} are possible answers (marked {})."),
+            (Lang::De, "{} der besten {} sind mögliche Lösungen (markiert mit {})."),
+        ],
+    ),
+    (
+        "solve.words_left",
+        &[
+            (Lang::En, "Total Words Left: {}"),
+            (Lang::De, "Verbleibende Wörter: {}"),
+        ],
+    ),
+    (
+        "solve.stale_stats",
+        &[
+            (Lang::En, "Warning: letter stats are out of date ({}). Update them with `wordle-bot analyze --incremental <diff>`."),
+            (Lang::De, "Warnung: die Buchstabenstatistik ist veraltet ({}). `wordle-bot analyze --incremental <diff>` aktualisiert sie."),
+        ],
+    ),
+    (
+        "solve.rollback_none",
+        &[
+            (Lang::En, "Nothing rolled back: 'rollback 0' keeps every guess."),
+            (Lang::De, "Nichts zurückgenommen: 'rollback 0' behält alle Versuche."),
+        ],
+    ),
+    (
+        "solve.rollback_empty",
+        &[
+            (Lang::En, "There are no guesses to roll back."),
+            (Lang::De, "Es gibt keine Versuche zum Zurücknehmen."),
+        ],
+    ),
+    (
+        "solve.rollback_all",
+        &[
+            (Lang::En, "Warning: only {} guess(es) to roll back; removing all of them."),
+            (Lang::De, "Warnung: nur {} Versuch(e) zum Zurücknehmen; alle werden entfernt."),
+        ],
+    ),
+    (
+        "solve.rolled_back",
+        &[
+            (Lang::En, "Rolled back to {} guess(es)."),
+            (Lang::De, "Zurückgesetzt auf {} Versuch(e)."),
+        ],
+    ),
+    (
+        "solve.hint_again",
+        &[
+            (Lang::En, "Hint: try {}."),
+            (Lang::De, "Tipp: versuch es mit {}."),
+        ],
+    ),
+    (
+        "solve.hint",
+        &[
+            (Lang::En, "Hint: try {} ({} hint(s) this game)."),
+            (Lang::De, "Tipp: versuch es mit {} ({} Tipp(s) in diesem Spiel)."),
+        ],
+    ),
+    (
+        "solve.hint_none",
+        &[
+            (Lang::En, "No hint: no word fits the feedback."),
+            (Lang::De, "Kein Tipp: kein Wort passt zu den Rückmeldungen."),
+        ],
+    ),
+    (
+        "solve.prognosis",
+        &[
+            (Lang::En, "Prognosis: with {} guess(es) left, no play is sure to find the answer among {} possible answers. The best chance is {}%."),
+            (Lang::De, "Prognose: mit {} verbleibenden Versuch(en) findet kein Zug sicher die Lösung unter {} möglichen Lösungen. Die beste Chance ist {}%."),
+        ],
+    ),
+    (
+        "solve.contradiction",
+        &[
+            (Lang::En, "No word fits all the feedback; a pattern was probably entered wrong."),
+            (Lang::De, "Kein Wort passt zu allen Rückmeldungen; vermutlich wurde ein Muster falsch eingegeben."),
+        ],
+    ),
+    (
+        "solve.contradiction_without",
+        &[
+            (Lang::En, "Without guess {} ('{}'), {} word(s) would fit: 'rollback {}' removes it and the guesses after it."),
+            (Lang::De, "Ohne Versuch {} ('{}') würden {} Wort/Wörter passen: 'rollback {}' entfernt ihn und die Versuche danach."),
+        ],
+    ),
+    (
+        "solve.contradiction_reconstruct",
+        &[
+            (Lang::En, "If the answer is missing from the wordlist, 'reconstruct' lists strings that fit the feedback."),
+            (Lang::De, "Fehlt die Lösung in der Wortliste, listet 'reconstruct' Zeichenfolgen, die zu den Rückmeldungen passen."),
+        ],
+    ),
+    (
+        "solve.replay_failed",
+        &[
+            (Lang::En, "Could not replay the game: {}"),
+            (Lang::De, "Das Spiel konnte nicht nachgespielt werden: {}"),
+        ],
+    ),
+    (
+        "solve.one_match",
+        &[
+            (Lang::En, "Only one word matches: {}"),
+            (Lang::De, "Nur ein Wort passt: {}"),
+        ],
+    ),
+    (
+        "solve.two_matches",
+        &[
+            (Lang::En, "Two words match: {} and {}."),
+            (Lang::De, "Zwei Wörter passen: {} und {}."),
+        ],
+    ),
+    (
+        "solve.two_matches_either",
+        &[
+            (Lang::En, "Guessing either one solves the game in at most two more tries."),
+            (Lang::De, "Mit einem der beiden ist das Spiel in höchstens zwei weiteren Versuchen gelöst."),
+        ],
+    ),
+    (
+        "solve.time_budget",
+        &[
+            (Lang::En, "Time budget reached: evaluated {} of {} guesses ({}%)."),
+            (Lang::De, "Zeitbudget erreicht: {} von {} Wörtern bewertet ({}%)."),
+        ],
+    ),
+    (
+        "solve.trap",
+        &[
+            (Lang::En, "!!! TRAP DETECTED !!!"),
+            (Lang::De, "!!! FALLE ERKANNT !!!"),
+        ],
+    ),
+    (
+        "solve.trap_positions",
+        &[
+            (Lang::En, "Varying positions: {}"),
+            (Lang::De, "Wechselnde Positionen: {}"),
+        ],
+    ),
+    (
+        "solve.trap_letters",
+        &[
+            (Lang::En, "Distinguishing letters: {}"),
+            (Lang::De, "Unterscheidende Buchstaben: {}"),
+        ],
+    ),
+    (
+        "solve.trap_elimination",
+        &[
+            (Lang::En, "ELIMINATION MODE: Recommended guess is '{}' (score: {})"),
+            (Lang::De, "AUSSCHLUSSMODUS: Empfohlener Versuch ist '{}' (Punkte: {})"),
+        ],
+    ),
+    (
+        "solve.trap_remaining",
+        &[
+            (Lang::En, "Remaining valid words:"),
+            (Lang::De, "Verbleibende gültige Wörter:"),
+        ],
+    ),
+    (
+        "solve.probe_restriction",
+        &[
+            (Lang::En, "Probe pool restricted to probe_words.txt: '{}' ({}) instead of '{}' ({}), {}% lower score."),
+            (Lang::De, "Erkundungswörter auf probe_words.txt beschränkt: '{}' ({}) statt '{}' ({}), {}% weniger Punkte."),
+        ],
+    ),
+    (
+        "solve.load_other_wordlist",
+        &[
+            (Lang::En, "{} was saved with a different wordlist (hash {}, now {}), so its guesses may not fit this one. Use 'load --force {}' to load it anyway."),
+            (Lang::De, "{} wurde mit einer anderen Wortliste gespeichert (Hash {}, jetzt {}), daher passen die Versuche eventuell nicht zu dieser. Mit 'load --force {}' wird es trotzdem geladen."),
+        ],
+    ),
+    (
+        "solve.load_forced",
+        &[
+            (Lang::En, "Warning: {} was saved with a different wordlist; loading it anyway."),
+            (Lang::De, "Warnung: {} wurde mit einer anderen Wortliste gespeichert; es wird trotzdem geladen."),
+        ],
+    ),
+    (
+        "solve.opener",
+        &[
+            (Lang::En, "Benchmarked opener: {} ({} average guesses over {} games)"),
+            (Lang::De, "Getestetes Startwort: {} ({} Versuche im Schnitt über {} Spiele)"),
+        ],
+    ),
+    (
+        "solve.opener_stale",
+        &[
+            (Lang::En, "Warning: the benchmarked opener '{}' was chosen for different word lists. Run `wordle-bot openers --auto-select` to benchmark again."),
+            (Lang::De, "Warnung: das getestete Startwort '{}' wurde für andere Wortlisten gewählt. `wordle-bot openers --auto-select` testet es neu."),
+        ],
+    ),
+    (
+        "solve.elimination",
+        &[
+            (Lang::En, "Candidate elimination:"),
+            (Lang::De, "Ausschluss der Kandidaten:"),
+        ],
+    ),
+    (
+        "solve.column_word",
+        &[
+            (Lang::En, "Word"),
+            (Lang::De, "Wort"),
+        ],
+    ),
+    (
+        "solve.column_before",
+        &[
+            (Lang::En, "Before"),
+            (Lang::De, "Vorher"),
+        ],
+    ),
+    (
+        "solve.column_after",
+        &[
+            (Lang::En, "After"),
+            (Lang::De, "Nachher"),
+        ],
+    ),
+    (
+        "solve.column_eliminated",
+        &[
+            (Lang::En, "Eliminated"),
+            (Lang::De, "Ausgeschlossen"),
+        ],
+    ),
+    (
+        "solve.column_bits",
+        &[
+            (Lang::En, "Bits"),
+            (Lang::De, "Bits"),
+        ],
+    ),
+    (
+        "solve.information",
+        &[
+            (Lang::En, "Information: {} bits needed for {} candidates; {} guess(es) could give up to {} bits ({}% used)."),
+            (Lang::De, "Information: {} Bits nötig für {} Kandidaten; {} Versuch(e) hätten bis zu {} Bits liefern können ({}% genutzt)."),
+        ],
+    ),
+    (
+        "solve.ranks_failed",
+        &[
+            (Lang::En, "Warning: could not rank the answer's trajectory: {}"),
+            (Lang::De, "Warnung: die Häufigkeitsränge der Lösung konnten nicht bestimmt werden: {}"),
+        ],
+    ),
+    (
+        "solve.history_failed",
+        &[
+            (Lang::En, "Warning: could not save the game to the history: {}"),
+            (Lang::De, "Warnung: das Spiel konnte nicht im Verlauf gespeichert werden: {}"),
+        ],
+    ),
+    (
+        "solve.groups",
+        &[
+            (Lang::En, "{} candidates in {} group(s):"),
+            (Lang::De, "{} Kandidaten in {} Gruppe(n):"),
+        ],
+    ),
+    (
+        "solve.no_candidates",
+        &[
+            (Lang::En, "No candidates left."),
+            (Lang::De, "Keine Kandidaten mehr übrig."),
+        ],
+    ),
+    (
+        "solve.letters",
+        &[
+            (Lang::En, "Most likely letters per position ({} candidates):"),
+            (Lang::De, "Wahrscheinlichste Buchstaben je Position ({} Kandidaten):"),
+        ],
+    ),
+    (
+        "solve.probes_none",
+        &[
+            (Lang::En, "Nothing left to probe: at most one candidate remains."),
+            (Lang::De, "Nichts mehr zu erkunden: höchstens ein Kandidat ist übrig."),
+        ],
+    ),
+    (
+        "solve.probes",
+        &[
+            (Lang::En, "Most informative guesses over {} candidates:"),
+            (Lang::De, "Aufschlussreichste Versuche über {} Kandidaten:"),
+        ],
+    ),
+    (
+        "solve.probe",
+        &[
+            (Lang::En, "{}. {}  {} bits, {} candidates expected to remain{}"),
+            (Lang::De, "{}. {}  {} Bits, voraussichtlich bleiben {} Kandidaten übrig{}"),
+        ],
+    ),
+    (
+        "solve.probe_candidate",
+        &[
+            (Lang::En, " (possible answer)"),
+            (Lang::De, " (mögliche Lösung)"),
+        ],
+    ),
+    (
+        "solve.probes_unchanged",
+        &[
+            (Lang::En, "(The game is unchanged; enter one as your guess to play it.)"),
+            (Lang::De, "(Das Spiel bleibt unverändert; gib eines als Versuch ein, um es zu spielen.)"),
+        ],
+    ),
+    (
+        "solve.reconstruct_unneeded",
+        &[
+            (Lang::En, "{} word(s) of the wordlist still fit the feedback; 'reconstruct' is for when none does."),
+            (Lang::De, "{} Wort/Wörter der Wortliste passen noch zu den Rückmeldungen; 'reconstruct' ist für den Fall, dass keines passt."),
+        ],
+    ),
+    (
+        "solve.stats_unreadable",
+        &[
+            (Lang::En, "Could not read the letter stats: {}"),
+            (Lang::De, "Die Buchstabenstatistik konnte nicht gelesen werden: {}"),
+        ],
+    ),
+    (
+        "solve.reconstruct_too_many",
+        &[
+            (Lang::En, "More than {} strings fit the feedback; another guess narrows them down."),
+            (Lang::De, "Mehr als {} Zeichenfolgen passen zu den Rückmeldungen; ein weiterer Versuch grenzt sie ein."),
+        ],
+    ),
+    (
+        "solve.reconstruct_none",
+        &[
+            (Lang::En, "No string fits the feedback either, so a pattern was probably entered wrong."),
+            (Lang::De, "Auch keine Zeichenfolge passt zu den Rückmeldungen, vermutlich wurde ein Muster falsch eingegeben."),
+        ],
+    ),
+    (
+        "solve.reconstructed",
+        &[
+            (Lang::En, "{} string(s) over the wordlist's letters fit the feedback. Most likely by letter position:"),
+            (Lang::De, "{} Zeichenfolge(n) aus den Buchstaben der Wortliste passen zu den Rückmeldungen. Am wahrscheinlichsten nach Buchstabenposition:"),
+        ],
+    ),
+    (
+        "solve.reconstruction",
+        &[
+            (Lang::En, "{}. {}  (not in the wordlist)"),
+            (Lang::De, "{}. {}  (nicht in der Wortliste)"),
+        ],
+    ),
+    (
+        "solve.reconstruct_play",
+        &[
+            (Lang::En, "(Enter one as your guess to play it.)"),
+            (Lang::De, "(Gib eine als Versuch ein, um sie zu spielen.)"),
+        ],
+    ),
+    (
+        "solve.peek",
+        &[
+            (Lang::En, "Hypothetical ({}): {} of {} candidates match."),
+            (Lang::De, "Angenommen ({}): {} von {} Kandidaten passen."),
+        ],
+    ),
+    (
+        "solve.peek_none",
+        &[
+            (Lang::En, "No candidate matches, so there is nothing to rank."),
+            (Lang::De, "Kein Kandidat passt, daher gibt es nichts zu bewerten."),
+        ],
+    ),
+    (
+        "solve.peek_top",
+        &[
+            (Lang::En, "Top suggested words if it holds:"),
+            (Lang::De, "Beste Vorschläge, falls es zutrifft:"),
+        ],
+    ),
+    (
+        "solve.peek_unchanged",
+        &[
+            (Lang::En, "(Hypothetical only: the game and its candidates are unchanged.)"),
+            (Lang::De, "(Nur angenommen: das Spiel und seine Kandidaten bleiben unverändert.)"),
+        ],
+    ),
+    (
+        "summary.header",
+        &[
+            (Lang::En, "=== Current Game State ==="),
+            (Lang::De, "=== Aktueller Spielstand ==="),
+        ],
+    ),
+    (
+        "summary.guesses",
+        &[(Lang::En, "Guesses: {}"), (Lang::De, "Versuche: {}")],
+    ),
+    (
+        "summary.not_in_word",
+        &[
+            (Lang::En, "Not in word: {}"),
+            (Lang::De, "Nicht im Wort: {}"),
+        ],
+    ),
+    (
+        "summary.correct",
+        &[
+            (Lang::En, "Correct positions: {}"),
+            (Lang::De, "Richtige Positionen: {}"),
+        ],
+    ),
+    (
+        "summary.misplaced",
+        &[
+            (Lang::En, "Misplaced letters: {}"),
+            (Lang::De, "Falsch platzierte Buchstaben: {}"),
+        ],
+    ),
+    (
+        "summary.must_contain",
+        &[
+            (Lang::En, "Must contain: {}"),
+            (Lang::De, "Muss enthalten: {}"),
+        ],
+    ),
+    (
+        "play.header_easy",
+        &[
+            (Lang::En, "=== Current Game State (easy mode, {} guesses) ==="),
+            (Lang::De, "=== Aktueller Spielstand (leichter Modus, {} Versuche) ==="),
+        ],
+    ),
+    (
+        "play.columns",
+        &[(Lang::En, "Nr.  Word"), (Lang::De, "Nr.  Wort")],
+    ),
+    (
+        "play.prompt",
+        &[
+            (Lang::En, "Enter your guess:"),
+            (Lang::De, "Gib dein Wort ein:"),
+        ],
+    ),
+    (
+        "play.prompt_hint",
+        &[
+            (Lang::En, "Enter your guess (? for a hint):"),
+            (Lang::De, "Gib dein Wort ein (? für einen Tipp):"),
+        ],
+    ),
+    (
+        "play.read_failed",
+        &[
+            (Lang::En, "Failed to read input. Try again."),
+            (Lang::De, "Die Eingabe konnte nicht gelesen werden. Versuch es noch einmal."),
+        ],
+    ),
+    (
+        "play.word_length",
+        &[
+            (Lang::En, "Word must be 5 letters long!"),
+            (Lang::De, "Das Wort muss 5 Buchstaben lang sein!"),
+        ],
+    ),
+    (
+        "play.letters_only",
+        &[
+            (Lang::En, "Use letters only!"),
+            (Lang::De, "Nur Buchstaben verwenden!"),
+        ],
+    ),
+    (
+        "play.not_in_wordlist",
+        &[
+            (Lang::En, "Word not in the allowed word list!"),
+            (Lang::De, "Das Wort steht nicht in der Wortliste!"),
+        ],
+    ),
+    (
+        "play.hint",
+        &[
+            (Lang::En, "Hint: try {}"),
+            (Lang::De, "Tipp: versuch es mit {}"),
+        ],
+    ),
+    (
+        "play.no_hint",
+        &[
+            (Lang::En, "No hint available."),
+            (Lang::De, "Kein Tipp verfügbar."),
+        ],
+    ),
+    (
+        "play.easy_mode",
+        &[(Lang::En, " (easy mode)"), (Lang::De, " (leichter Modus)")],
+    ),
+    (
+        "play.won",
+        &[
+            (Lang::En, "Congratulations! You've guessed the word: {}{}"),
+            (Lang::De, "Glückwunsch! Du hast das Wort erraten: {}{}"),
+        ],
+    ),
+    (
+        "play.lost",
+        &[
+            (Lang::En, "Game Over! The correct word was: {}{}"),
+            (Lang::De, "Spiel vorbei! Das gesuchte Wort war: {}{}"),
+        ],
+    ),
+    (
+        "play.practice",
+        &[
+            (Lang::En, "Practice: {}, one of {} matching answers."),
+            (Lang::De, "Übung: {}, eine von {} passenden Lösungen."),
+        ],
+    ),
+    (
+        "play.committed",
+        &[
+            (Lang::En, "Commitment to the hidden word (SHA-256 of salt + word): {}"),
+            (Lang::De, "Festlegung auf das gesuchte Wort (SHA-256 von Salz + Wort): {}"),
+        ],
+    ),
+    (
+        "play.revealed",
+        &[
+            (Lang::En, "Commitment: {}, salt: {}"),
+            (Lang::De, "Festlegung: {}, Salz: {}"),
+        ],
+    ),
+    (
+        "play.verify_with",
+        &[
+            (Lang::En, "Verify with: wordle-bot verify-commitment {} {} {}"),
+            (Lang::De, "Prüfen mit: wordle-bot verify-commitment {} {} {}"),
+        ],
+    ),
+    (
+        "play.untimed",
+        &[
+            (Lang::En, "Warning: input is not a terminal, so the game is not timed."),
+            (Lang::De, "Warnung: die Eingabe ist kein Terminal, daher läuft das Spiel ohne Zeitlimit."),
+        ],
+    ),
+    (
+        "play.timed",
+        &[
+            (Lang::En, "Timed game: {} seconds per guess. Running out of time loses the game."),
+            (Lang::De, "Spiel auf Zeit: {} Sekunden pro Versuch. Wer die Zeit überschreitet, verliert."),
+        ],
+    ),
+    (
+        "play.time_left",
+        &[
+            (Lang::En, "{} seconds left for this guess."),
+            (Lang::De, "Noch {} Sekunden für diesen Versuch."),
+        ],
+    ),
+    (
+        "play.input_closed",
+        &[
+            (Lang::En, "Input ended before the game did, so it isn't recorded."),
+            (Lang::De, "Die Eingabe endete vor dem Spiel, daher wird es nicht gespeichert."),
+        ],
+    ),
+    (
+        "play.time_up",
+        &[
+            (Lang::En, "Time is up! The game is forfeited."),
+            (Lang::De, "Die Zeit ist um! Das Spiel ist verloren."),
+        ],
+    ),
+    (
+        "play.timed_score",
+        &[
+            (Lang::En, "Time: {} seconds, score: {}"),
+            (Lang::De, "Zeit: {} Sekunden, Punkte: {}"),
+        ],
+    ),
+    (
+        "play.history_failed",
+        &[
+            (Lang::En, "Warning: could not save the game to the history: {}"),
+            (Lang::De, "Warnung: das Spiel konnte nicht im Verlauf gespeichert werden: {}"),
+        ],
+    ),
+];
+
+/// The text of `key` in `lang`, if the catalog has one.
+pub fn lookup(lang: Lang, key: &str) -> Option<&'static str> {
+    CATALOG
+        .iter()
+        .find(|(k, _)| *k == key)
+        .and_then(|(_, texts)| texts.iter().find(|(l, _)| *l == lang))
+        .map(|(_, text)| *text)
+}
+
+/// The text of `key` in the run's language, falling back to English. An unknown key
+/// is returned as is, so a typo shows up in the output instead of panicking.
+pub fn text(key: &'static str) -> &'static str {
+    lookup(lang(), key)
+        .or_else(|| lookup(Lang::En, key))
+        .unwrap_or(key)
+}
+
+/// `text(key)` with each `{}` replaced by the next of `args`.
+pub fn fill(key: &'static str, args: &[&dyn fmt::Display]) -> String {
+    let mut parts = text(key).split("{}");
+    let mut filled = parts.next().unwrap_or_default().to_string();
+    for (i, part) in parts.enumerate() {
+        if let Some(arg) = args.get(i) {
+            filled.push_str(&arg.to_string());
+        }
+        filled.push_str(part);
+    }
+    filled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_message_has_every_language_with_the_english_arguments() {
+        let mut wrong = Vec::new();
+        for (key, _) in CATALOG {
+            let english =
+                lookup(Lang::En, key).unwrap_or_else(|| panic!("{} has no English text", key));
+            for lang in Lang::ALL {
+                match lookup(lang, key) {
+                    Some(text) if text.matches("{}").count() == english.matches("{}").count() => {}
+                    Some(_) => wrong.push(format!("{}:{} takes other arguments", lang.code(), key)),
+                    None => wrong.push(format!("{}:{} is missing", lang.code(), key)),
+                }
+            }
+        }
+        assert!(wrong.is_empty(), "{}", wrong.join("\n"));
+    }
+
+    #[test]
+    fn keys_are_unique() {
+        let mut keys: Vec<&str> = CATALOG.iter().map(|(key, _)| *key).collect();
+        keys.sort();
+        let count = keys.len();
+        keys.dedup();
+        assert_eq!(keys.len(), count);
+    }
+}
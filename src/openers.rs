@@ -0,0 +1,259 @@
+use crate::analysis;
+use crate::output::{CellFormat, Table};
+use crate::simulate;
+use crate::solver::Solver;
+use crate::strategy::{Strategy, MAX_GUESSES};
+use crate::util;
+use crate::wordlist::PoolOptions;
+use anyhow::{anyhow, Result};
+use rand::prelude::*;
+use rand::rngs::StdRng;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+
+pub struct OpenersOptions {
+    pub pool: PoolOptions,
+    pub strategy: Strategy,
+    /// Number of top-ranked first guesses to benchmark.
+    pub candidates: usize,
+    /// Games played per opener, on the same targets for every opener.
+    pub sample: usize,
+    /// `None` resumes an interrupted benchmark with its seed, or picks a fresh one.
+    pub seed: Option<u64>,
+    /// Save the winner as the profile's benchmarked opener.
+    pub auto_select: bool,
+}
+
+/// Benchmark result of one opener.
+#[derive(Serialize, Deserialize, Clone)]
+struct OpenerResult {
+    word: String,
+    games: usize,
+    score: f64,
+    win_rate: f64,
+    average_guesses: Option<f64>,
+}
+
+/// Results so far of a benchmark, saved after every batch of openers so an
+/// interrupted run picks up where it stopped.
+#[derive(Serialize, Deserialize)]
+struct Progress {
+    strategy: String,
+    wordlist_hash: String,
+    sample: usize,
+    seed: u64,
+    results: Vec<OpenerResult>,
+}
+
+/// The opener `openers --auto-select` chose for a profile, pinned above the first
+/// suggestions of `solve`.
+#[derive(Serialize, Deserialize)]
+pub struct StoredOpener {
+    pub word: String,
+    pub strategy: String,
+    /// Hash of the guess and answer lists the opener was benchmarked on.
+    pub wordlist_hash: String,
+    pub score: f64,
+    pub games: usize,
+}
+
+/// opener.json in the active profile's data dir.
+fn opener_path() -> Option<PathBuf> {
+    util::profile_dir(util::profile()).map(|dir| dir.join("opener.json"))
+}
+
+/// opener_benchmark.json in the active profile's data dir, present while a
+/// benchmark is unfinished.
+fn progress_path() -> Option<PathBuf> {
+    util::profile_dir(util::profile()).map(|dir| dir.join("opener_benchmark.json"))
+}
+
+/// Identifies the lists an opener was benchmarked on.
+pub fn lists_hash(guesses: &[String], answers: &[String]) -> String {
+    analysis::content_hash(&[
+        analysis::wordlist_hash(guesses),
+        analysis::wordlist_hash(answers),
+    ])
+}
+
+/// The active profile's benchmarked opener. A missing or unreadable file means none.
+pub fn load_opener() -> Option<StoredOpener> {
+    opener_path()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+}
+
+pub fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    util::write_atomic(path, serde_json::to_string_pretty(value)?.as_bytes())
+        .map_err(|e| anyhow!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Plays the same sample of answers with each of the best-ranked first guesses and
+/// returns their results, best first. Progress is saved after every batch, so
+/// running the same benchmark again after an interruption skips the finished
+/// openers.
+pub fn run_openers(options: OpenersOptions) -> Result<Table> {
+    let mut solver = Solver::with_pool(options.pool)?;
+    solver.strategy = options.strategy;
+    let wordlist_hash = lists_hash(&solver.all_words, &solver.answer_words);
+    let sample = options.sample.max(1).min(solver.answer_words.len());
+
+    let candidates: Vec<String> = solver
+        .initial_ranking()?
+        .into_iter()
+        .take(options.candidates.max(1))
+        .map(|(word, _)| word)
+        .collect();
+
+    let progress_path = progress_path();
+    let saved = progress_path
+        .as_ref()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str::<Progress>(&content).ok());
+    if saved
+        .as_ref()
+        .is_some_and(|progress| progress.wordlist_hash != wordlist_hash)
+    {
+        println!("The unfinished benchmark was run on different word lists; starting over.");
+    }
+    let resumed = saved.filter(|progress| {
+        progress.strategy == options.strategy.name()
+            && progress.wordlist_hash == wordlist_hash
+            && progress.sample == sample
+            && options.seed.is_none_or(|seed| seed == progress.seed)
+    });
+    let mut progress = match resumed {
+        Some(mut progress) => {
+            progress
+                .results
+                .retain(|result| candidates.contains(&result.word));
+            println!(
+                "Resuming the benchmark: {} of {} openers already played (seed {}).",
+                progress.results.len(),
+                candidates.len(),
+                progress.seed
+            );
+            progress
+        }
+        None => Progress {
+            strategy: options.strategy.name().to_string(),
+            wordlist_hash: wordlist_hash.clone(),
+            sample,
+            seed: util::resolve_seed(options.seed),
+            results: Vec::new(),
+        },
+    };
+
+    let mut targets = solver.answer_words.clone();
+    targets.shuffle(&mut StdRng::seed_from_u64(progress.seed));
+    targets.truncate(sample);
+
+    let remaining: Vec<String> = candidates
+        .iter()
+        .filter(|word| !progress.results.iter().any(|r| &r.word == *word))
+        .cloned()
+        .collect();
+    let weights = util::read_solver_config()?;
+
+    let threads = util::threads().min(remaining.len().max(1));
+    let mut workers: Vec<Solver> = (1..threads).map(|_| solver.fork()).collect();
+    workers.push(solver);
+
+    println!(
+        "Benchmarking {} openers on {} games each ({} threads, seed {})...",
+        remaining.len(),
+        sample,
+        workers.len(),
+        progress.seed
+    );
+
+    for batch in remaining.chunks(workers.len()) {
+        let results: Vec<Result<OpenerResult>> = thread::scope(|scope| {
+            let handles: Vec<_> = workers
+                .iter_mut()
+                .zip(batch)
+                .map(|(solver, opener)| {
+                    let (targets, weights) = (&targets, &weights);
+                    scope.spawn(move || {
+                        let results = simulate::simulate_targets(solver, opener, targets, weights)?;
+                        Ok(OpenerResult {
+                            word: opener.clone(),
+                            games: results.games(),
+                            score: results.score(),
+                            win_rate: results.win_rate() / 100.0,
+                            average_guesses: results.average_guesses(),
+                        })
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|h| h.join().expect("opener benchmark worker panicked"))
+                .collect()
+        });
+
+        for result in results {
+            let result = result?;
+            println!("  {}: {:.3}", result.word, result.score);
+            progress.results.push(result);
+        }
+        if let Some(path) = &progress_path {
+            write_json(path, &progress)?;
+        }
+    }
+
+    let mut results = progress.results;
+    results.sort_by(|a, b| a.score.total_cmp(&b.score).then(a.word.cmp(&b.word)));
+    if let Some(path) = &progress_path {
+        let _ = fs::remove_file(path);
+    }
+
+    let best = results
+        .first()
+        .ok_or_else(|| anyhow!("No opener to benchmark."))?;
+    println!(
+        "\nBest opener: {} ({:.3} average guesses, a loss counts as {}).",
+        best.word,
+        best.score,
+        MAX_GUESSES + 1
+    );
+    if options.auto_select {
+        let path = opener_path().ok_or_else(|| anyhow!("No data directory available."))?;
+        write_json(
+            &path,
+            &StoredOpener {
+                word: best.word.clone(),
+                strategy: options.strategy.name().to_string(),
+                wordlist_hash,
+                score: best.score,
+                games: best.games,
+            },
+        )?;
+        println!(
+            "Saved it as the benchmarked opener of profile '{}'.",
+            util::profile()
+        );
+    }
+
+    let mut table = Table::new()
+        .column("opener", "Opener", CellFormat::Text)
+        .column("games", "Games", CellFormat::Integer)
+        .column("score", "Score", CellFormat::Fixed(3))
+        .column("win_rate", "Win %", CellFormat::Percent(1))
+        .column("avg_guesses", "Avg", CellFormat::Fixed(3));
+    for result in results {
+        table.push_row(vec![
+            result.word.into(),
+            result.games.into(),
+            result.score.into(),
+            result.win_rate.into(),
+            result.average_guesses.into(),
+        ]);
+    }
+    Ok(table)
+}
@@ -0,0 +1,704 @@
+use crate::game::GameData;
+use crate::util;
+use anyhow::{anyhow, Result};
+use std::collections::HashSet;
+use std::io::{self, IsTerminal, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+static COLORS_ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// Sets the global color policy: off with `--no-color`, the `NO_COLOR` env var, or
+/// when stdout isn't a terminal.
+pub fn init_colors(args: &[String]) {
+    let enabled = !args.iter().any(|a| a == "--no-color")
+        && std::env::var_os("NO_COLOR").is_none()
+        && io::stdout().is_terminal();
+    COLORS_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Turns colors off, e.g. when text output goes to a file.
+pub fn disable_colors() {
+    COLORS_ENABLED.store(false, Ordering::Relaxed);
+}
+
+pub fn colors_enabled() -> bool {
+    COLORS_ENABLED.load(Ordering::Relaxed)
+}
+
+/// Width assumed when stdout isn't a terminal.
+const DEFAULT_WIDTH: usize = 100;
+/// Narrowest width honored, so every layout keeps at least a word per line.
+const MIN_WIDTH: usize = 20;
+
+static WIDTH: AtomicUsize = AtomicUsize::new(DEFAULT_WIDTH);
+
+/// Sets the width word lists wrap at: `--width <n>`, else the terminal's width, else
+/// 100 columns.
+pub fn init_width(args: &[String]) -> Result<()> {
+    let width = match args.iter().position(|a| a == "--width") {
+        Some(i) => args
+            .get(i + 1)
+            .and_then(|value| value.parse::<usize>().ok())
+            .ok_or_else(|| anyhow!("--width must be a number of columns."))?,
+        None if io::stdout().is_terminal() => terminal_width().unwrap_or(DEFAULT_WIDTH),
+        None => DEFAULT_WIDTH,
+    };
+    WIDTH.store(width.max(MIN_WIDTH), Ordering::Relaxed);
+    Ok(())
+}
+
+#[cfg(feature = "terminal")]
+fn terminal_width() -> Option<usize> {
+    terminal_size::terminal_size().map(|(terminal_size::Width(w), _)| w as usize)
+}
+
+/// Without the `terminal` feature the width comes from `COLUMNS`, which most shells
+/// set for interactive sessions.
+#[cfg(not(feature = "terminal"))]
+fn terminal_width() -> Option<usize> {
+    std::env::var("COLUMNS").ok()?.parse().ok()
+}
+
+pub fn width() -> usize {
+    WIDTH.load(Ordering::Relaxed)
+}
+
+/// `text` cut to `width` characters, ending in `…` when something was cut.
+pub fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    let kept: String = text.chars().take(width.saturating_sub(1)).collect();
+    format!("{}…", kept)
+}
+
+/// `words` in as many columns as fit in `width`, filled top to bottom like `ls`.
+/// Words wider than the line are truncated.
+pub fn columns(words: &[String], width: usize) -> String {
+    let longest = words.iter().map(|w| w.chars().count()).max().unwrap_or(0);
+    let cell = (longest + 2).min(width);
+    let count = (width / cell).max(1);
+    let rows = words.len().div_ceil(count);
+
+    let mut text = String::new();
+    for row in 0..rows {
+        let line: String = words
+            .iter()
+            .skip(row)
+            .step_by(rows.max(1))
+            .map(|word| format!("{:<cell$}", truncate(word, cell)))
+            .collect();
+        text.push_str(line.trim_end());
+        text.push('\n');
+    }
+    text
+}
+
+/// `prefix` followed by `items` joined with `", "`, wrapped before `width` with the
+/// continuation lines indented past the prefix. Items wider than a line are
+/// truncated.
+pub fn wrap_list(prefix: &str, items: &[String], width: usize) -> String {
+    let indent = prefix.chars().count().min(width / 2);
+    let room = width - indent;
+    let mut text = prefix.to_string();
+    let mut line_len = indent;
+    for (i, item) in items.iter().enumerate() {
+        let separator = if i + 1 < items.len() { "," } else { "" };
+        let item = format!(
+            "{}{}",
+            truncate(item, room.saturating_sub(separator.len())),
+            separator
+        );
+        let item_len = item.chars().count();
+        if i > 0 && line_len + 1 + item_len > width {
+            text.push('\n');
+            text.push_str(&" ".repeat(indent));
+            line_len = indent;
+        } else if i > 0 {
+            text.push(' ');
+            line_len += 1;
+        }
+        text.push_str(&item);
+        line_len += item_len;
+    }
+    text.push('\n');
+    text
+}
+
+/// Output format of `analyze` and `rank`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+    Csv,
+}
+
+impl OutputFormat {
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            "csv" => Ok(OutputFormat::Csv),
+            _ => Err(anyhow!(
+                "Unknown format '{}'. Use 'text', 'json' or 'csv'.",
+                name
+            )),
+        }
+    }
+}
+
+/// Writes `content` to the file at `path`, or to stdout when `path` is `-`.
+pub fn write_output(path: &str, content: &str) -> Result<()> {
+    if path == "-" {
+        let mut stdout = io::stdout().lock();
+        stdout.write_all(content.as_bytes())?;
+        stdout.flush()?;
+    } else {
+        util::write_atomic(Path::new(path), content.as_bytes())
+            .map_err(|e| anyhow!("Failed to write {}: {}", path, e))?;
+    }
+    Ok(())
+}
+
+/// Controls how much the interactive solver prints between inputs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verbosity {
+    /// Full state summary and top-10 list after every guess (default).
+    Verbose,
+    /// Only a compact prompt carrying the guess count, candidates left and best word.
+    Minimal,
+    /// One JSON object per turn on stdout, no prompts. Overrides the other two.
+    Json,
+}
+
+impl Verbosity {
+    pub fn from_args(args: &[String]) -> Self {
+        if args.iter().any(|a| a == "--json") {
+            Verbosity::Json
+        } else if args.iter().any(|a| a == "--minimal") {
+            Verbosity::Minimal
+        } else {
+            Verbosity::Verbose
+        }
+    }
+
+    pub fn is_verbose(&self) -> bool {
+        *self == Verbosity::Verbose
+    }
+
+    /// Picks the input prompt. Minimal mode uses the short form, JSON mode shows
+    /// nothing so stdout stays machine-readable.
+    pub fn prompt_text<'a>(&self, verbose: &'a str, minimal: &'a str) -> &'a str {
+        match self {
+            Verbosity::Verbose => verbose,
+            Verbosity::Minimal => minimal,
+            Verbosity::Json => "",
+        }
+    }
+
+    /// Prints a user-facing notice (validation errors, resets). In JSON mode it goes
+    /// to stderr instead.
+    pub fn notice(&self, text: &str) {
+        match self {
+            Verbosity::Json => eprintln!("{}", text),
+            _ => println!("{}", text),
+        }
+    }
+}
+
+/// Builds the compact status shown in minimal mode, e.g. `[3 guesses, 14 left] best: POISE >`.
+pub fn minimal_status(guesses: usize, remaining: usize, best: Option<&str>) -> String {
+    let best = best
+        .map(|w| w.to_uppercase())
+        .unwrap_or_else(|| "-".to_string());
+    format!(
+        "[{} guesses, {} left] best: {} > ",
+        guesses, remaining, best
+    )
+}
+
+/// Marks each letter of a suggestion by what it tells us given `game`: confirmed
+/// position (green / `[s]`), known letter at a new position (yellow / `(a)`) or
+/// unknown letter (dim / plain).
+pub fn annotate_suggestion(word: &str, game: &GameData) -> String {
+    word.chars()
+        .enumerate()
+        .map(|(i, ch)| {
+            let upper = ch.to_ascii_uppercase();
+            let confirmed = game.correct_positions.get(i) == Some(&Some(ch));
+            let known = game.must_contain.contains(&ch);
+
+            match (confirmed, known, colors_enabled()) {
+                (true, _, true) => format!("\x1b[42m\x1b[30m{}\x1b[0m", upper),
+                (false, true, true) => format!("\x1b[43m\x1b[30m{}\x1b[0m", upper),
+                (false, false, true) => format!("\x1b[2m{}\x1b[0m", ch),
+                (true, _, false) => format!("[{}]", ch),
+                (false, true, false) => format!("({})", ch),
+                (false, false, false) => ch.to_string(),
+            }
+        })
+        .collect()
+}
+
+/// Prints a ranked suggestion list with annotated words.
+pub fn print_ranked(ranked_words: &[(String, f64)], game: &GameData) {
+    print!("{}", format_ranked(ranked_words, game));
+}
+
+/// One line per word: the annotated word, padded, then its score.
+pub fn format_ranked(ranked_words: &[(String, f64)], game: &GameData) -> String {
+    ranked_words
+        .iter()
+        .map(|(word, score)| ranked_line(&annotate_suggestion(word, game), *score) + "\n")
+        .collect()
+}
+
+/// Mark after a suggestion that is a possible answer, when the answer list is
+/// separate from the guess list.
+pub const ANSWER_MARK: char = '†';
+
+/// `annotate_suggestion` followed by `ANSWER_MARK` for the words in `marked`.
+fn marked_suggestion(word: &str, game: &GameData, marked: &HashSet<String>) -> String {
+    let mut annotated = annotate_suggestion(word, game);
+    if marked.contains(word) {
+        annotated.push(ANSWER_MARK);
+    }
+    annotated
+}
+
+/// `format_ranked` for one representative per group of equivalent guesses, with the
+/// others listed after its score, e.g. `(+2 equivalent: SLATE, STALE)`. The words in
+/// `marked` get `ANSWER_MARK`.
+pub fn format_grouped(
+    groups: &[((String, f64), Vec<String>)],
+    game: &GameData,
+    count: usize,
+    marked: &HashSet<String>,
+) -> String {
+    let mut text = String::new();
+    for ((word, score), equivalent) in groups.iter().take(count) {
+        text.push_str(&ranked_line(&marked_suggestion(word, game, marked), *score));
+        if !equivalent.is_empty() {
+            let words: Vec<String> = equivalent.iter().map(|w| w.to_uppercase()).collect();
+            text.push_str(&format!(
+                "  (+{} equivalent: {})",
+                words.len(),
+                words.join(", ")
+            ));
+        }
+        text.push('\n');
+    }
+    text
+}
+
+/// `format_grouped` with the scores rounded to `precision` decimals. Neighbouring
+/// suggestions that show the same score are a tie and share one line in braces,
+/// e.g. `{crane, slate}  0.258`.
+pub fn format_grouped_rounded(
+    groups: &[((String, f64), Vec<String>)],
+    game: &GameData,
+    count: usize,
+    precision: usize,
+    marked: &HashSet<String>,
+) -> String {
+    let shown = &groups[..groups.len().min(count)];
+    let rounded = |score: f64| format!("{:.*}", precision, score);
+    let mut text = String::new();
+    for tie in shown.chunk_by(|a, b| rounded(a.0 .1) == rounded(b.0 .1)) {
+        let words: Vec<String> = tie
+            .iter()
+            .map(|((word, _), _)| marked_suggestion(word, game, marked))
+            .collect();
+        let label = match words.len() {
+            1 => words[0].clone(),
+            _ => format!("{{{}}}", words.join(", ")),
+        };
+        let padding = " ".repeat(10usize.saturating_sub(visible_len(&label)) + 1);
+        text.push_str(&format!("{}{}{}", label, padding, rounded(tie[0].0 .1)));
+        let equivalent: Vec<String> = tie
+            .iter()
+            .flat_map(|(_, equivalent)| equivalent.iter().map(|w| w.to_uppercase()))
+            .collect();
+        if !equivalent.is_empty() {
+            text.push_str(&format!(
+                "  (+{} equivalent: {})",
+                equivalent.len(),
+                equivalent.join(", ")
+            ));
+        }
+        text.push('\n');
+    }
+    text
+}
+
+/// A suggestion as already annotated, padded to line its score up with the others.
+fn ranked_line(annotated: &str, score: f64) -> String {
+    let padding = " ".repeat(10usize.saturating_sub(visible_len(annotated)) + 1);
+    format!("{}{}{:.5}", annotated, padding, score)
+}
+
+/// Length of a string as shown on screen, ignoring ANSI escape sequences.
+pub fn visible_len(text: &str) -> usize {
+    let mut len = 0;
+    let mut in_escape = false;
+    for ch in text.chars() {
+        match (in_escape, ch) {
+            (false, '\x1b') => in_escape = true,
+            (true, 'm') => in_escape = false,
+            (true, _) => {}
+            (false, _) => len += 1,
+        }
+    }
+    len
+}
+
+/// How a table column shows its numbers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CellFormat {
+    Text,
+    Integer,
+    /// Fixed number of decimals.
+    Fixed(usize),
+    /// A fraction shown as a percentage with this many decimals.
+    Percent(usize),
+}
+
+/// One value of a table row. `Empty` shows as `-` (empty in CSV).
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cell {
+    Text(String),
+    Number(f64),
+    Empty,
+}
+
+impl From<&str> for Cell {
+    fn from(text: &str) -> Self {
+        Cell::Text(text.to_string())
+    }
+}
+
+impl From<String> for Cell {
+    fn from(text: String) -> Self {
+        Cell::Text(text)
+    }
+}
+
+impl From<usize> for Cell {
+    fn from(value: usize) -> Self {
+        Cell::Number(value as f64)
+    }
+}
+
+impl From<f64> for Cell {
+    fn from(value: f64) -> Self {
+        Cell::Number(value)
+    }
+}
+
+impl<T: Into<Cell>> From<Option<T>> for Cell {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Cell::Empty, Into::into)
+    }
+}
+
+struct Column {
+    /// Name for `--sort-by` and the CSV and JSON keys.
+    key: &'static str,
+    /// Header of the text table.
+    title: &'static str,
+    format: CellFormat,
+}
+
+/// Rows of values under named columns, printed as an aligned text table (text left,
+/// numbers right), CSV or JSON.
+pub struct Table {
+    columns: Vec<Column>,
+    rows: Vec<Vec<Cell>>,
+}
+
+impl Table {
+    pub fn new() -> Self {
+        Self {
+            columns: Vec::new(),
+            rows: Vec::new(),
+        }
+    }
+
+    /// Adds a column; rows list their cells in the order columns were added.
+    pub fn column(mut self, key: &'static str, title: &'static str, format: CellFormat) -> Self {
+        self.columns.push(Column { key, title, format });
+        self
+    }
+
+    pub fn push_row(&mut self, cells: Vec<Cell>) {
+        debug_assert_eq!(cells.len(), self.columns.len());
+        self.rows.push(cells);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Sorts the rows by the column named `key`, ascending, or descending with a
+    /// leading `-` (`-wins`). Empty cells go last; equal rows keep their order.
+    pub fn sort_by(&mut self, key: &str) -> Result<()> {
+        let (descending, name) = match key.strip_prefix('-') {
+            Some(name) => (true, name),
+            None => (false, key),
+        };
+        let index = self
+            .columns
+            .iter()
+            .position(|c| c.key == name)
+            .ok_or_else(|| {
+                let keys: Vec<&str> = self.columns.iter().map(|c| c.key).collect();
+                anyhow!(
+                    "Unknown column '{}' for --sort-by. Use one of: {}",
+                    name,
+                    keys.join(", ")
+                )
+            })?;
+        self.rows.sort_by(|a, b| {
+            let order = match (&a[index], &b[index]) {
+                (Cell::Empty, Cell::Empty) => return std::cmp::Ordering::Equal,
+                (Cell::Empty, _) => return std::cmp::Ordering::Greater,
+                (_, Cell::Empty) => return std::cmp::Ordering::Less,
+                (Cell::Number(x), Cell::Number(y)) => x.total_cmp(y),
+                (x, y) => x.text(CellFormat::Text).cmp(&y.text(CellFormat::Text)),
+            };
+            if descending {
+                order.reverse()
+            } else {
+                order
+            }
+        });
+        Ok(())
+    }
+
+    pub fn render(&self, format: OutputFormat) -> String {
+        match format {
+            OutputFormat::Text => self.to_text(),
+            OutputFormat::Csv => self.to_csv(),
+            OutputFormat::Json => {
+                let rows: Vec<serde_json::Value> = self
+                    .rows
+                    .iter()
+                    .map(|row| {
+                        let fields = self.columns.iter().zip(row).map(|(column, cell)| {
+                            let value = match cell {
+                                Cell::Text(text) => serde_json::json!(text),
+                                Cell::Number(value) if column.format == CellFormat::Integer => {
+                                    serde_json::json!(*value as i64)
+                                }
+                                Cell::Number(value) => serde_json::json!(value),
+                                Cell::Empty => serde_json::Value::Null,
+                            };
+                            (column.key.to_string(), value)
+                        });
+                        serde_json::Value::Object(fields.collect())
+                    })
+                    .collect();
+                serde_json::to_string_pretty(&rows).unwrap_or_default() + "\n"
+            }
+        }
+    }
+
+    /// Columns padded to their widest cell and separated by two spaces.
+    pub fn to_text(&self) -> String {
+        let cells: Vec<Vec<String>> = self
+            .rows
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .zip(&self.columns)
+                    .map(|(cell, column)| cell.text(column.format))
+                    .collect()
+            })
+            .collect();
+        let widths: Vec<usize> = self
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(i, column)| {
+                cells
+                    .iter()
+                    .map(|row| row[i].chars().count())
+                    .chain([column.title.chars().count()])
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+
+        let line = |values: Vec<&str>| {
+            let padded: Vec<String> = values
+                .iter()
+                .zip(&self.columns)
+                .zip(&widths)
+                .map(|((value, column), &width)| match column.format {
+                    CellFormat::Text => format!("{:<width$}", value),
+                    _ => format!("{:>width$}", value),
+                })
+                .collect();
+            padded.join("  ").trim_end().to_string() + "\n"
+        };
+
+        let mut text = line(self.columns.iter().map(|c| c.title).collect());
+        for row in &cells {
+            text.push_str(&line(row.iter().map(String::as_str).collect()));
+        }
+        text
+    }
+
+    /// A header row of column keys, then one row per table row. Numbers keep the
+    /// column's decimals without a `%` sign; text with commas or quotes is quoted.
+    pub fn to_csv(&self) -> String {
+        let field = |text: String| {
+            if text.contains([',', '"', '\n']) {
+                format!("\"{}\"", text.replace('"', "\"\""))
+            } else {
+                text
+            }
+        };
+        let keys: Vec<&str> = self.columns.iter().map(|c| c.key).collect();
+        let mut csv = keys.join(",") + "\n";
+        for row in &self.rows {
+            let fields: Vec<String> = row
+                .iter()
+                .zip(&self.columns)
+                .map(|(cell, column)| match cell {
+                    Cell::Empty => String::new(),
+                    Cell::Number(value) => match column.format {
+                        CellFormat::Percent(decimals) => format!("{:.*}", decimals, value * 100.0),
+                        format => cell.text(format),
+                    },
+                    Cell::Text(text) => field(text.clone()),
+                })
+                .collect();
+            csv.push_str(&fields.join(","));
+            csv.push('\n');
+        }
+        csv
+    }
+}
+
+impl Cell {
+    fn text(&self, format: CellFormat) -> String {
+        match (self, format) {
+            (Cell::Text(text), _) => text.clone(),
+            (Cell::Empty, _) => "-".to_string(),
+            (Cell::Number(value), CellFormat::Integer) => format!("{:.0}", value),
+            (Cell::Number(value), CellFormat::Fixed(decimals)) => format!("{:.*}", decimals, value),
+            (Cell::Number(value), CellFormat::Percent(decimals)) => {
+                format!("{:.*}%", decimals, value * 100.0)
+            }
+            (Cell::Number(value), CellFormat::Text) => value.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture_table() -> Table {
+        let mut table = Table::new()
+            .column("opener", "Opener", CellFormat::Text)
+            .column("games", "Games", CellFormat::Integer)
+            .column("avg_guesses", "Avg", CellFormat::Fixed(3))
+            .column("win_rate", "Win %", CellFormat::Percent(1));
+        table.push_row(vec![
+            "arose".into(),
+            193.into(),
+            3.6010.into(),
+            0.9845.into(),
+        ]);
+        table.push_row(vec!["crane".into(), 12.into(), 3.25.into(), Cell::Empty]);
+        table.push_row(vec!["\"q, u\"".into(), 7.into(), 4.0.into(), 1.0.into()]);
+        table
+    }
+
+    #[test]
+    fn text_table_is_aligned_per_column() {
+        assert_eq!(
+            fixture_table().to_text(),
+            "Opener  Games    Avg   Win %\n\
+             arose     193  3.601   98.5%\n\
+             crane      12  3.250       -\n\
+             \"q, u\"      7  4.000  100.0%\n"
+        );
+    }
+
+    #[test]
+    fn csv_export_parses_back() {
+        let csv = fixture_table().to_csv();
+        let mut reader = csv::Reader::from_reader(csv.as_bytes());
+        assert_eq!(
+            reader.headers().unwrap(),
+            vec!["opener", "games", "avg_guesses", "win_rate"]
+        );
+        let rows: Vec<Vec<String>> = reader
+            .records()
+            .map(|record| record.unwrap().iter().map(str::to_string).collect())
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                ["arose", "193", "3.601", "98.5"],
+                ["crane", "12", "3.250", ""],
+                ["\"q, u\"", "7", "4.000", "100.0"],
+            ]
+        );
+    }
+
+    #[test]
+    fn sorting_puts_empty_cells_last() {
+        let mut table = fixture_table();
+        table.sort_by("-win_rate").unwrap();
+        let openers: Vec<String> = table
+            .rows
+            .iter()
+            .map(|row| row[0].text(CellFormat::Text))
+            .collect();
+        assert_eq!(openers, ["\"q, u\"", "arose", "crane"]);
+        table.sort_by("avg_guesses").unwrap();
+        assert_eq!(table.rows[0][0], Cell::from("crane"));
+        assert!(table.sort_by("wins").is_err());
+    }
+
+    fn fixture_words(count: usize) -> Vec<String> {
+        include_str!("../fixtures/golden_wordlist.txt")
+            .lines()
+            .take(count)
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// Fixture words in columns, then listed after a prefix with one item too long
+    /// for a narrow line.
+    fn rendered_at(width: usize) -> String {
+        let mut items = fixture_words(23);
+        items.push("a very long entry that cannot fit in forty columns".to_string());
+        format!(
+            "width {}\n{}{}",
+            width,
+            columns(&fixture_words(40), width),
+            wrap_list("Candidates: ", &items, width)
+        )
+    }
+
+    #[test]
+    fn word_lists_render_as_pinned_at_each_width() {
+        let rendered: String = [40, 80, 120].map(rendered_at).concat();
+        assert_eq!(rendered, include_str!("../tests/fixtures/word_lists.txt"));
+    }
+
+    #[test]
+    fn word_lists_stay_within_the_width() {
+        for width in [40, 80, 120] {
+            for line in rendered_at(width).lines().skip(1) {
+                assert!(line.chars().count() <= width, "{}: {:?}", width, line);
+            }
+        }
+    }
+}
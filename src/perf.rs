@@ -0,0 +1,212 @@
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// Steps of a simulated turn timed by `simulate --perf`.
+#[derive(Debug, Clone, Copy)]
+pub enum Phase {
+    /// Narrowing the candidates to the feedback so far.
+    Filter,
+    /// Choosing the next guess.
+    Rank,
+    /// Scoring a guess against the target.
+    Evaluate,
+}
+
+const PHASES: [Phase; 3] = [Phase::Filter, Phase::Rank, Phase::Evaluate];
+
+impl Phase {
+    fn name(self) -> &'static str {
+        match self {
+            Phase::Filter => "filter",
+            Phase::Rank => "rank",
+            Phase::Evaluate => "evaluate",
+        }
+    }
+}
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    // Per thread, so worker threads never wait on each other to record a timing
+    static COUNTERS: RefCell<Counters> = RefCell::new(Counters::default());
+}
+
+/// Time per phase and the largest candidate set seen on one thread.
+#[derive(Debug, Clone, Default)]
+pub struct Counters {
+    phase_time: [Duration; 3],
+    peak_candidates: usize,
+}
+
+/// Turns on collection for the rest of the run. Off, `time` only runs its closure.
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Runs `f`, adding its duration to `phase` on this thread when collection is on.
+pub fn time<T>(phase: Phase, f: impl FnOnce() -> T) -> T {
+    if !enabled() {
+        return f();
+    }
+    let started = Instant::now();
+    let value = f();
+    let elapsed = started.elapsed();
+    COUNTERS.with(|counters| counters.borrow_mut().phase_time[phase as usize] += elapsed);
+    value
+}
+
+/// Notes a candidate set size, keeping the largest.
+pub fn record_candidates(count: usize) {
+    if enabled() {
+        COUNTERS.with(|counters| {
+            let mut counters = counters.borrow_mut();
+            counters.peak_candidates = counters.peak_candidates.max(count);
+        });
+    }
+}
+
+/// This thread's counters, leaving them empty.
+pub fn take() -> Counters {
+    COUNTERS.with(|counters| counters.take())
+}
+
+/// Heap allocations made while playing the games, counted with the `dhat` feature.
+#[derive(Debug, Clone, Copy)]
+pub struct Allocations {
+    pub blocks: u64,
+    pub bytes: u64,
+}
+
+impl Allocations {
+    fn to_json(self) -> serde_json::Value {
+        serde_json::json!({ "blocks": self.blocks, "bytes": self.bytes })
+    }
+}
+
+/// Throughput of a simulation run. The setup (loading the lists and ranking the
+/// opener) is the warm-up; the steady state is the second half of the games, once
+/// the pattern cache is filled.
+#[derive(Debug, Clone)]
+pub struct PerfReport {
+    pub games: usize,
+    pub setup: Duration,
+    /// Time spent playing all games.
+    pub wall: Duration,
+    /// Time spent playing the second half of the games.
+    pub steady: Duration,
+    pub counters: Counters,
+    /// Allocations made during the setup and while playing, with the `dhat` feature.
+    pub setup_allocations: Option<Allocations>,
+    pub allocations: Option<Allocations>,
+}
+
+impl PerfReport {
+    fn games_per_second(games: usize, time: Duration) -> f64 {
+        if time.is_zero() {
+            0.0
+        } else {
+            games as f64 / time.as_secs_f64()
+        }
+    }
+
+    pub fn print(&self) {
+        let steady_games = self.games - self.games / 2;
+        println!("=== Performance ===");
+        println!("Setup (warm-up): {:.3}s", self.setup.as_secs_f64());
+        println!(
+            "Games: {:.3}s, {:.1} games/s overall, {:.1} games/s steady state",
+            self.wall.as_secs_f64(),
+            Self::games_per_second(self.games, self.wall),
+            Self::games_per_second(steady_games, self.steady)
+        );
+        let phases = PHASES
+            .iter()
+            .map(|&phase| (phase.name(), self.counters.phase_time[phase as usize]))
+            .chain([("other", self.other())]);
+        for (name, time) in phases {
+            println!(
+                "  {:<9}{:>9.3}s {:>6.1}%",
+                name,
+                time.as_secs_f64(),
+                share(time, self.wall) * 100.0
+            );
+        }
+        println!("Peak candidates: {}", self.counters.peak_candidates);
+        if let Some(allocations) = self.setup_allocations {
+            println!(
+                "Setup allocations: {} ({} bytes)",
+                allocations.blocks, allocations.bytes
+            );
+        }
+        if let Some(allocations) = self.allocations {
+            println!(
+                "Allocations: {} ({} bytes, {:.1} per game)",
+                allocations.blocks,
+                allocations.bytes,
+                allocations.blocks as f64 / self.games.max(1) as f64
+            );
+        }
+        println!("===================\n");
+    }
+
+    /// Time playing the games outside the timed phases: bookkeeping, counting the
+    /// candidates left and analysing losses.
+    fn other(&self) -> Duration {
+        let timed: Duration = self.counters.phase_time.iter().sum();
+        self.wall.saturating_sub(timed)
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        let phases: serde_json::Map<String, serde_json::Value> = PHASES
+            .iter()
+            .map(|&phase| {
+                (
+                    phase.name().to_string(),
+                    self.counters.phase_time[phase as usize]
+                        .as_secs_f64()
+                        .into(),
+                )
+            })
+            .chain([("other".to_string(), self.other().as_secs_f64().into())])
+            .collect();
+        serde_json::json!({
+            "setup_seconds": self.setup.as_secs_f64(),
+            "wall_seconds": self.wall.as_secs_f64(),
+            "games_per_second": Self::games_per_second(self.games, self.wall),
+            "steady_games_per_second":
+                Self::games_per_second(self.games - self.games / 2, self.steady),
+            "phase_seconds": phases,
+            "peak_candidates": self.counters.peak_candidates,
+            "setup_allocations": self.setup_allocations.map(Allocations::to_json),
+            "allocations": self.allocations.map(Allocations::to_json),
+        })
+    }
+}
+
+fn share(part: Duration, whole: Duration) -> f64 {
+    if whole.is_zero() {
+        0.0
+    } else {
+        part.as_secs_f64() / whole.as_secs_f64()
+    }
+}
+
+/// Heap allocations so far, when built with the `dhat` feature and a profiler runs.
+#[cfg(feature = "dhat")]
+pub fn allocations() -> Option<Allocations> {
+    let stats = dhat::HeapStats::get();
+    Some(Allocations {
+        blocks: stats.total_blocks,
+        bytes: stats.total_bytes,
+    })
+}
+
+#[cfg(not(feature = "dhat"))]
+pub fn allocations() -> Option<Allocations> {
+    None
+}
@@ -1,10 +1,23 @@
-use crate::game::{CellData, GameData, LineData};
-use crate::util::read_wordlist;
-use anyhow::Result;
-use rand::prelude::IndexedRandom;
-use rand::rngs::ThreadRng;
-use std::collections::HashMap;
-use std::io;
+use crate::filter::Filter;
+use crate::game::{self, GameData, LineData};
+use crate::history::{self, GameRecord, ThinkTimes};
+use crate::keyboard::{self, Layout};
+use crate::messages;
+use crate::practice::Practice;
+use crate::util;
+use crate::wordlist::Wordlist;
+use anyhow::{anyhow, Result};
+use commitment::Commitment;
+use rand::prelude::*;
+use rand::rngs::StdRng;
+use std::borrow::Cow;
+use std::collections::{BTreeSet, HashMap};
+use std::io::{self, IsTerminal};
+use std::time::Duration;
+use timer::{Timer, Wait};
+
+pub mod commitment;
+pub mod timer;
 
 pub struct Play {
     word: String,
@@ -12,8 +25,34 @@ pub struct Play {
     game_data: GameData,
     wordlist: Vec<String>,
     result: GameResult,
+    /// Easy mode: common hidden words, more guesses, any letters accepted and `?` hints.
+    easy: bool,
+    /// Words `?` may suggest in easy mode.
+    common_words: Vec<String>,
+    /// Rows of the keyboard view, from `--layout` and the wordlist's alphabet.
+    keys: Vec<(usize, Vec<char>)>,
+    /// What a `--practice` game practiced, and how many words it could have drawn.
+    practice: Option<(String, usize)>,
+    /// The `--committed` commitment to the hidden word, revealed when the game ends.
+    commitment: Option<Commitment>,
+    /// The clock of a `--timed` game.
+    timer: Option<Timer>,
+    /// Time spent at the prompt for each guess, recorded with the game.
+    think_times: ThinkTimes,
 }
 
+/// How waiting for a guess ended.
+enum Entry {
+    Added,
+    /// The guess of a timed game ran out of time.
+    TimedOut,
+    /// Stdin ended before a guess was entered.
+    Closed,
+}
+
+/// Guesses allowed in easy mode.
+const EASY_GUESSES: usize = 8;
+
 #[derive(PartialEq, Debug)]
 pub enum GameResult {
     Win,
@@ -22,111 +61,107 @@ pub enum GameResult {
 }
 
 impl Play {
-    pub fn new() -> Result<Self> {
-        let content = read_wordlist()?;
-
-        let words: Vec<String> = content
+    /// Starts a game with the hidden word drawn from `seed`, so the same seed
+    /// replays the same game. `easy` draws it from common_words.txt instead, and
+    /// `practice` only from the words it allows.
+    pub fn new(seed: u64, easy: bool, layout: Layout, practice: Option<Practice>) -> Result<Self> {
+        let wordlist = Wordlist::load()?;
+        let alphabet: BTreeSet<char> = wordlist.guesses.iter().flat_map(|w| w.chars()).collect();
+        let common_words: Vec<String> = util::read_common_words()?
             .lines()
             .map(|w| w.trim().to_lowercase())
-            .filter(|w| w.len() == 5)
+            .filter(|w| wordlist.answers.contains(w))
             .collect();
 
-        let allowed_words = &words[10657..];
-
-        let mut rng = ThreadRng::default();
-        let random_word = allowed_words
-            .choose(&mut rng)
-            .expect("No words available")
-            .clone();
+        let mut rng = StdRng::seed_from_u64(seed);
+        let pool = if easy && !common_words.is_empty() {
+            &common_words
+        } else {
+            &wordlist.answers
+        };
+        let (pool, practice) = match practice {
+            Some(practice) => {
+                let selected = practice.select(pool);
+                if selected.is_empty() {
+                    return Err(anyhow!(
+                        "No answer matches the practice {}.",
+                        practice.label
+                    ));
+                }
+                let count = selected.len();
+                (Cow::Owned(selected), Some((practice.label, count)))
+            }
+            None => (Cow::Borrowed(pool), None),
+        };
+        let random_word = pool.choose(&mut rng).expect("No words available").clone();
 
         Ok(Self {
             word: random_word,
-            num_guesses: 6,
+            num_guesses: if easy { EASY_GUESSES } else { 6 },
             game_data: GameData::new(),
-            wordlist: words,
+            wordlist: wordlist.guesses,
             result: GameResult::Ongoing,
+            easy,
+            common_words,
+            keys: keyboard::key_rows(layout, &alphabet),
+            practice,
+            commitment: None,
+            timer: None,
+            think_times: ThinkTimes::default(),
         })
     }
 
-    pub fn evaluate_word(&mut self, guessed_word: &str) -> LineData {
-        let guessed_chars: Vec<char> = guessed_word.chars().collect();
-        let target_chars: Vec<char> = self.word.chars().collect();
-
-        let mut result_cells: [CellData; 5] = [
-            CellData {
-                letter: ' ',
-                state: 'w',
-            },
-            CellData {
-                letter: ' ',
-                state: 'w',
-            },
-            CellData {
-                letter: ' ',
-                state: 'w',
-            },
-            CellData {
-                letter: ' ',
-                state: 'w',
-            },
-            CellData {
-                letter: ' ',
-                state: 'w',
-            },
-        ];
-
-        // Count remaining letters in target for handling duplicates
-        let mut remaining_counts: HashMap<char, usize> = HashMap::new();
-        for &c in &target_chars {
-            *remaining_counts.entry(c).or_insert(0) += 1;
-        }
-
-        // First pass: correct positions
-        for i in 0..5 {
-            let g = guessed_chars[i];
-            let t = target_chars[i];
-
-            if g == t {
-                result_cells[i] = CellData {
-                    letter: g,
-                    state: 'c',
-                };
-                *remaining_counts.get_mut(&g).unwrap() -= 1;
-            } else {
-                result_cells[i].letter = g;
-            }
+    /// Gives every guess `limit` to be entered (`--timed`); running out forfeits the
+    /// game. Input that isn't a terminal can't be timed, so the game stays untimed
+    /// with a warning.
+    pub fn time(&mut self, limit: Duration) {
+        if !io::stdin().is_terminal() {
+            eprintln!("{}", messages::text("play.untimed"));
+            return;
         }
+        let timer = Timer::new(limit);
+        self.think_times = ThinkTimes::with_clock(timer.clock());
+        self.timer = Some(timer);
+    }
 
-        // Second pass: misplaced or wrong
-        for i in 0..5 {
-            if result_cells[i].state == 'c' {
-                continue;
-            }
-            let g = guessed_chars[i];
-            if let Some(count) = remaining_counts.get_mut(&g) {
-                if *count > 0 {
-                    result_cells[i].state = 'm';
-                    *count -= 1;
-                } else {
-                    result_cells[i].state = 'w';
-                }
-            } else {
-                result_cells[i].state = 'w';
-            }
-        }
+    /// Commits to the hidden word (`--committed`): its hash is printed before the
+    /// first guess and the salt to check it with after the last.
+    #[cfg(feature = "commit")]
+    pub fn commit(&mut self) -> Result<()> {
+        self.commitment = Some(commitment::commit(&self.word)?);
+        Ok(())
+    }
 
-        LineData {
-            word: guessed_word.to_string(),
-            cells: result_cells,
-        }
+    pub fn evaluate_word(&mut self, guessed_word: &str) -> LineData {
+        game::grade(guessed_word, &self.word).line(guessed_word)
     }
 
     pub fn run(&mut self) -> Result<()> {
         let mut attempts = 0;
+        if let Some(commitment) = &self.commitment {
+            println!("{}", messages::fill("play.committed", &[&commitment.hash]));
+        }
+        if let Some(timer) = &self.timer {
+            println!(
+                "{}",
+                messages::fill("play.timed", &[&timer.limit.as_secs()])
+            );
+        }
 
         while attempts < self.num_guesses && self.result == GameResult::Ongoing {
             self.print_summary();
-            self.add_line();
+            match self.add_line() {
+                Entry::Added => {}
+                Entry::TimedOut => {
+                    println!("{}", messages::text("play.time_up"));
+                    self.result = GameResult::Lose;
+                    break;
+                }
+                Entry::Closed => {
+                    println!("{}", messages::text("play.input_closed"));
+                    break;
+                }
+            }
 
             if let Some(last_line) = self.game_data.lines.last() {
                 if last_line.word == self.word {
@@ -145,29 +180,142 @@ impl Play {
 
         self.print_summary();
 
+        let mode = if self.easy {
+            messages::text("play.easy_mode")
+        } else {
+            ""
+        };
         match self.result {
             GameResult::Win => {
-                println!("Congratulations! You've guessed the word: {}", self.word);
+                println!("{}", messages::fill("play.won", &[&self.word, &mode]));
             }
             GameResult::Lose => {
-                println!("Game Over! The correct word was: {}", self.word);
+                println!("{}", messages::fill("play.lost", &[&self.word, &mode]));
             }
             GameResult::Ongoing => {}
         }
+        if let Some((label, count)) = &self.practice {
+            println!("{}", messages::fill("play.practice", &[label, count]));
+        }
+        if let Some(timer) = self
+            .timer
+            .as_ref()
+            .filter(|_| self.result != GameResult::Ongoing)
+        {
+            println!(
+                "{}",
+                messages::fill(
+                    "play.timed_score",
+                    &[
+                        &format!("{:.1}", timer.elapsed.as_secs_f64()),
+                        &self.score()
+                    ]
+                )
+            );
+        }
+        if let Some(commitment) = &self.commitment {
+            println!(
+                "{}",
+                messages::fill("play.revealed", &[&commitment.hash, &commitment.salt])
+            );
+            println!(
+                "{}",
+                messages::fill(
+                    "play.verify_with",
+                    &[&commitment.hash, &commitment.salt, &self.word]
+                )
+            );
+        }
+        if self.result != GameResult::Ongoing {
+            self.record_history();
+        }
 
         Ok(())
     }
 
-    fn add_line(&mut self) {
-        loop {
-            println!("Enter your guess:");
+    /// Score of a finished timed game, see `timer::score`.
+    fn score(&self) -> usize {
+        self.timer.as_ref().map_or(0, |timer| {
+            timer::score(
+                self.game_data.lines.len(),
+                self.num_guesses,
+                timer.limit,
+                timer.elapsed,
+                self.result == GameResult::Win,
+            )
+        })
+    }
 
-            let mut input = String::new();
-            if io::stdin().read_line(&mut input).is_err() {
-                println!("Failed to read input. Try again.");
-                continue;
+    /// Appends the finished game to the active profile's history. A failed write only
+    /// warns, since the game itself is over.
+    fn record_history(&self) {
+        let mut record = GameRecord::new(
+            if self.practice.is_some() {
+                "practice"
+            } else if self.timer.is_some() {
+                "timed"
+            } else if self.easy {
+                "easy"
+            } else {
+                "normal"
+            },
+            Some(&self.word),
+            self.game_data.lines.len(),
+            self.result == GameResult::Win,
+        );
+        record.commitment = self.commitment.clone();
+        record.think_seconds = self.think_times.seconds(self.game_data.lines.len());
+        if let Some(timer) = &self.timer {
+            record.seconds = Some(timer.elapsed.as_secs_f64());
+            record.score = Some(self.score());
+        }
+        if let Err(e) = history::append(&record) {
+            eprintln!("{}", messages::fill("play.history_failed", &[&e]));
+        }
+    }
+
+    /// Reads guesses until a valid one is entered and adds it. Nothing is added when
+    /// the guess of a timed game runs out of time or stdin ends.
+    fn add_line(&mut self) -> Entry {
+        let started = self.timer.as_ref().map(Timer::start);
+        loop {
+            if let (Some(timer), Some(started)) = (&self.timer, started) {
+                let left = timer.left(started);
+                println!(
+                    "{}",
+                    messages::fill(
+                        "play.time_left",
+                        &[&(left.as_millis() as u64).div_ceil(1000)]
+                    )
+                );
+            }
+            if self.easy {
+                println!("{}", messages::text("play.prompt_hint"));
+            } else {
+                println!("{}", messages::text("play.prompt"));
             }
 
+            self.think_times.asking();
+            let input = match (&mut self.timer, started) {
+                (Some(timer), Some(started)) => match timer.read_line(started) {
+                    Wait::Line(line) => line,
+                    Wait::TimedOut => return Entry::TimedOut,
+                    Wait::Closed => return Entry::Closed,
+                },
+                _ => {
+                    let mut input = String::new();
+                    match io::stdin().read_line(&mut input) {
+                        Ok(0) => return Entry::Closed,
+                        Ok(_) => input,
+                        Err(_) => {
+                            println!("{}", messages::text("play.read_failed"));
+                            continue;
+                        }
+                    }
+                }
+            };
+            self.think_times.answered();
+
             // Remove all whitespace and lowercase
             let cleaned: String = input
                 .chars()
@@ -175,32 +323,66 @@ impl Play {
                 .collect::<String>()
                 .to_lowercase();
 
+            if self.easy && cleaned == "?" {
+                self.print_hint();
+                continue;
+            }
+
             // Take only the first 5 letters
             let word: String = cleaned.chars().take(5).collect();
 
             if word.len() < 5 {
-                println!("Word must be 5 letters long!");
+                println!("{}", messages::text("play.word_length"));
                 continue;
             }
 
-            // Check against the wordlist in the struct
-            if !self.wordlist.contains(&word) {
-                println!("Word not in the allowed word list!");
+            if self.easy {
+                // Any letters will do, the word doesn't have to be in the dictionary
+                if !word.chars().all(|c| c.is_ascii_lowercase()) {
+                    println!("{}", messages::text("play.letters_only"));
+                    continue;
+                }
+            } else if !self.wordlist.contains(&word) {
+                // Check against the wordlist in the struct
+                println!("{}", messages::text("play.not_in_wordlist"));
                 continue;
             }
 
             // Valid word, process it
             let line = self.evaluate_word(&word);
-            let pattern = self.get_pattern(&line);
+            let pattern = line.pattern();
 
             self.game_data.add_line(&word, &pattern);
-            break;
+            self.think_times.guessed();
+            if let (Some(timer), Some(started)) = (&mut self.timer, started) {
+                timer.guessed(started);
+            }
+            return Entry::Added;
+        }
+    }
+
+    /// Suggests a common word that fits every clue so far and hasn't been guessed yet.
+    fn print_hint(&self) {
+        let fitting = Filter::new(&self.game_data, &self.common_words).filter_words();
+        let hint = fitting
+            .iter()
+            .find(|w| !self.game_data.lines.iter().any(|line| &line.word == *w));
+        match hint {
+            Some(word) => println!("{}", messages::fill("play.hint", &[&word.to_uppercase()])),
+            None => println!("{}", messages::text("play.no_hint")),
         }
     }
 
     fn print_summary(&self) {
-        println!("\n=== Current Game State ===");
-        println!("Nr.  Word");
+        if self.easy {
+            println!(
+                "\n{}",
+                messages::fill("play.header_easy", &[&self.num_guesses])
+            );
+        } else {
+            println!("\n{}", messages::text("summary.header"));
+        }
+        println!("{}", messages::text("play.columns"));
 
         for (number, line) in self.game_data.lines.iter().enumerate() {
             print!("{}.   ", number + 1);
@@ -224,56 +406,32 @@ impl Play {
     }
 
     fn generate_keyboard(&self) -> String {
-        let row1 = "QWERTYUIOP".chars().collect::<Vec<_>>();
-        let row2 = "ASDFGHJKL".chars().collect::<Vec<_>>();
-        let row3 = "ZXCVBNM".chars().collect::<Vec<_>>();
-
+        // Derive key colors from the same per-position knowledge the solver filters on:
+        // green if confirmed somewhere, yellow if required, gray if excluded at every
+        // slot that isn't confirmed.
+        let exclusions = self.game_data.position_exclusions();
         let mut letter_states: HashMap<char, char> = HashMap::new();
 
         for line in &self.game_data.lines {
             for cell in &line.cells {
-                let upper = cell.letter.to_ascii_uppercase();
-                let state = letter_states.entry(upper).or_insert(cell.state);
-
-                if *state != 'c' && cell.state == 'c' {
-                    *state = 'c';
-                } else if *state == 'w' && cell.state == 'm' {
-                    *state = 'm';
-                }
-            }
-        }
-
-        let colorize = |c: char| -> &'static str {
-            match letter_states.get(&c) {
-                Some('c') => "\x1b[42m\x1b[30m",  // green bg, black text
-                Some('m') => "\x1b[43m\x1b[30m",  // yellow bg, black text
-                Some('w') => "\x1b[100m\x1b[37m", // gray bg, white text
-                _ => "\x1b[0m",                   // default
-            }
-        };
-
-        let mut output = String::new();
-
-        for row in &[row1, row2, row3] {
-            let indent = match row.len() {
-                10 => 0,
-                9 => 1,
-                7 => 2,
-                _ => 0,
-            };
-
-            output.push_str(&" ".repeat(indent));
-
-            for &letter in row {
-                output.push_str(&format!("{} {} \x1b[0m", colorize(letter), letter));
+                let letter = cell.letter;
+                let state = if self.game_data.correct_positions.contains(&Some(letter)) {
+                    'c'
+                } else if self.game_data.must_contain.contains(&letter) {
+                    'm'
+                } else if exclusions
+                    .iter()
+                    .zip(&self.game_data.correct_positions)
+                    .all(|(set, confirmed)| confirmed.is_some() || set.contains(&letter))
+                {
+                    'w'
+                } else {
+                    continue;
+                };
+                letter_states.insert(letter, state);
             }
-            output.push('\n');
         }
 
-        output
-    }
-
-    fn get_pattern(&self, line: &LineData) -> String {
-        line.cells.iter().map(|cell| cell.state).collect()
+        keyboard::render(&self.keys, &letter_states)
     }
 }
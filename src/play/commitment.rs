@@ -0,0 +1,77 @@
+#[cfg(feature = "commit")]
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// Random bytes in a salt, so the hidden word can't be found by hashing every
+/// answer.
+#[cfg(feature = "commit")]
+const SALT_BYTES: usize = 16;
+
+/// A SHA-256 commitment to the hidden word of `play --committed`. The hash is shown
+/// before the first guess and the salt once the game ends, so the player can check
+/// the word wasn't changed in between.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Commitment {
+    /// Hex SHA-256 of the salt followed by the word.
+    pub hash: String,
+    /// Hex salt, kept secret until the game ends.
+    pub salt: String,
+}
+
+/// Hex SHA-256 of `salt || word`, with the word lowercased.
+#[cfg(feature = "commit")]
+pub fn hash(salt: &str, word: &str) -> Result<String> {
+    use sha2::{Digest, Sha256};
+    let mut hasher = Sha256::new();
+    hasher.update(salt.as_bytes());
+    hasher.update(word.to_lowercase().as_bytes());
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
+
+/// Commits to `word` with a fresh salt from the OS, never from the game's seed, so
+/// knowing the seed doesn't reveal the salt.
+#[cfg(feature = "commit")]
+pub fn commit(word: &str) -> Result<Commitment> {
+    use rand::RngCore;
+    let mut bytes = [0u8; SALT_BYTES];
+    rand::rng().fill_bytes(&mut bytes);
+    let salt: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    Ok(Commitment {
+        hash: hash(&salt, word)?,
+        salt,
+    })
+}
+
+/// Whether `word` with `salt` gives the committed `hash`, in either hex case.
+#[cfg(feature = "commit")]
+pub fn verify(hash: &str, salt: &str, word: &str) -> Result<bool> {
+    Ok(self::hash(salt.trim(), word.trim())?.eq_ignore_ascii_case(hash.trim()))
+}
+
+#[cfg(all(test, feature = "commit"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hash_is_sha256_of_salt_then_word() {
+        // SHA-256("abc") from FIPS 180-2
+        let abc = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+        assert_eq!(hash("a", "bc").unwrap(), abc);
+        assert_eq!(hash("ab", "C").unwrap(), abc);
+    }
+
+    #[test]
+    fn commitment_verifies_only_the_committed_word() {
+        let commitment = commit("crane").unwrap();
+        assert_eq!(commitment.salt.len(), SALT_BYTES * 2);
+        assert!(verify(&commitment.hash, &commitment.salt, "crane").unwrap());
+        assert!(verify(&commitment.hash.to_uppercase(), &commitment.salt, " CRANE ").unwrap());
+        assert!(!verify(&commitment.hash, &commitment.salt, "crate").unwrap());
+        assert!(!verify(&commitment.hash, "00", "crane").unwrap());
+        assert_ne!(commit("crane").unwrap().salt, commitment.salt);
+    }
+}
@@ -0,0 +1,194 @@
+use crate::clock::{Clock, SystemClock};
+use std::io::{self, BufRead};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Seconds per guess of `play --timed` without `--guess-seconds`.
+pub const DEFAULT_GUESS_SECONDS: u64 = 60;
+
+/// Points for each guess left over at the end of a won timed game, counting the
+/// winning one. A second saved is worth one point.
+pub const POINTS_PER_GUESS: usize = 100;
+
+/// Score of a timed game: `POINTS_PER_GUESS` for each guess of `allowed` not needed,
+/// plus the whole seconds left of the time the guesses used were given. A lost or
+/// forfeited game scores 0.
+pub fn score(
+    guesses: usize,
+    allowed: usize,
+    limit: Duration,
+    elapsed: Duration,
+    won: bool,
+) -> usize {
+    if !won {
+        return 0;
+    }
+    let budget = limit * guesses as u32;
+    let saved = budget.saturating_sub(elapsed).as_secs() as usize;
+    POINTS_PER_GUESS * (allowed + 1).saturating_sub(guesses) + saved
+}
+
+/// What waiting for a line of input ended with.
+pub enum Wait {
+    Line(String),
+    TimedOut,
+    /// Stdin was closed or failed.
+    Closed,
+}
+
+/// Stdin read line by line on a thread of its own, so the game can stop waiting
+/// for a guess when its time is up. The thread stays blocked on the last read until
+/// the process exits.
+pub struct TimedInput {
+    lines: Receiver<String>,
+}
+
+impl TimedInput {
+    pub fn new() -> Self {
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in io::stdin().lock().lines() {
+                let Ok(line) = line else { break };
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        Self { lines }
+    }
+
+    /// The next line typed before `deadline`, given the time is `now`.
+    pub fn read_until(&self, deadline: Instant, now: Instant) -> Wait {
+        let wait = deadline.saturating_duration_since(now);
+        match self.lines.recv_timeout(wait) {
+            Ok(line) => Wait::Line(line),
+            Err(RecvTimeoutError::Timeout) => Wait::TimedOut,
+            Err(RecvTimeoutError::Disconnected) => Wait::Closed,
+        }
+    }
+}
+
+/// Time limit and time used of a `--timed` game.
+pub struct Timer {
+    /// Time allowed for each guess.
+    pub limit: Duration,
+    /// Time taken by the guesses so far, a forfeited one counting its whole limit.
+    pub elapsed: Duration,
+    input: TimedInput,
+    clock: Arc<dyn Clock>,
+}
+
+impl Timer {
+    /// Times guesses typed on stdin by the system clock.
+    pub fn new(limit: Duration) -> Self {
+        Self::with(limit, TimedInput::new(), Arc::new(SystemClock))
+    }
+
+    fn with(limit: Duration, input: TimedInput, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            limit,
+            elapsed: Duration::ZERO,
+            input,
+            clock,
+        }
+    }
+
+    /// The clock the guesses are timed by.
+    pub fn clock(&self) -> Arc<dyn Clock> {
+        Arc::clone(&self.clock)
+    }
+
+    /// When the guess being entered now started.
+    pub fn start(&self) -> Instant {
+        self.clock.now()
+    }
+
+    /// Time left for the guess started at `started`.
+    pub fn left(&self, started: Instant) -> Duration {
+        self.limit
+            .saturating_sub(self.clock.now().saturating_duration_since(started))
+    }
+
+    /// The next line typed for the guess started at `started`. Running out of time
+    /// uses up the guess's whole limit.
+    pub fn read_line(&mut self, started: Instant) -> Wait {
+        let wait = self
+            .input
+            .read_until(started + self.limit, self.clock.now());
+        if let Wait::TimedOut = wait {
+            self.elapsed += self.limit;
+        }
+        wait
+    }
+
+    /// Counts the time the guess started at `started` took, now that it is in.
+    pub fn guessed(&mut self, started: Instant) {
+        self.elapsed += self
+            .clock
+            .now()
+            .saturating_duration_since(started)
+            .min(self.limit);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use std::sync::mpsc::Sender;
+
+    /// A timer of `limit` seconds reading lines sent on the returned sender.
+    fn timer(limit: u64) -> (Timer, Sender<String>, Arc<ManualClock>) {
+        let (sender, lines) = mpsc::channel();
+        let clock = Arc::new(ManualClock::new());
+        let timer = Timer::with(
+            Duration::from_secs(limit),
+            TimedInput { lines },
+            clock.clone(),
+        );
+        (timer, sender, clock)
+    }
+
+    #[test]
+    fn guesses_in_time_count_the_time_they_took() {
+        let (mut timer, sender, clock) = timer(60);
+        let started = timer.start();
+        clock.advance(Duration::from_secs(12));
+        assert_eq!(timer.left(started), Duration::from_secs(48));
+        sender.send("crane".to_string()).unwrap();
+        assert!(matches!(timer.read_line(started), Wait::Line(line) if line == "crane"));
+        timer.guessed(started);
+        assert_eq!(timer.elapsed, Duration::from_secs(12));
+    }
+
+    #[test]
+    fn running_out_of_time_uses_the_whole_limit() {
+        let (mut timer, _sender, clock) = timer(30);
+        let started = timer.start();
+        clock.advance(Duration::from_secs(31));
+        assert_eq!(timer.left(started), Duration::ZERO);
+        assert!(matches!(timer.read_line(started), Wait::TimedOut));
+        assert_eq!(timer.elapsed, Duration::from_secs(30));
+    }
+
+    #[test]
+    fn closed_input_takes_no_time() {
+        let (mut timer, sender, _clock) = timer(30);
+        drop(sender);
+        let started = timer.start();
+        assert!(matches!(timer.read_line(started), Wait::Closed));
+        assert_eq!(timer.elapsed, Duration::ZERO);
+    }
+
+    #[test]
+    fn score_rewards_guesses_and_seconds_left_of_a_win() {
+        let limit = Duration::from_secs(60);
+        // Two of six guesses: five left over, counting the winning one, and 20 of
+        // 120 seconds used
+        assert_eq!(score(2, 6, limit, Duration::from_secs(20), true), 600);
+        assert_eq!(score(6, 6, limit, Duration::from_secs(360), true), 100);
+        assert_eq!(score(3, 6, limit, Duration::from_secs(20), false), 0);
+    }
+}
@@ -0,0 +1,156 @@
+use crate::stats::SimulationResults;
+use crate::strategy::MAX_GUESSES;
+
+const WIDTH: f64 = 640.0;
+const HEIGHT: f64 = 360.0;
+const MARGIN_LEFT: f64 = 48.0;
+const MARGIN_RIGHT: f64 = 16.0;
+const MARGIN_TOP: f64 = 48.0;
+const MARGIN_BOTTOM: f64 = 40.0;
+
+/// Bar colors of the series, repeating for more series.
+const SERIES_COLORS: &[&str] = &["#6aaa64", "#4a7fc1", "#c9b458", "#8e6bbf", "#3fa7a0"];
+
+/// Loss bar colors of the series, shades of red so losses stand out.
+const LOSS_COLORS: &[&str] = &["#c0392b", "#e57373", "#8e2a20", "#f1948a", "#a93226"];
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Self-contained SVG bar chart of the guess distribution of each labelled result,
+/// one group of bars per guess count and the losses last in red. Bar heights are
+/// proportional to the game counts, the tallest filling the plot. The series are
+/// expected to share one guess limit, which sets the buckets.
+pub fn distribution_svg(series: &[(&str, &SimulationResults)]) -> String {
+    let distributions: Vec<Vec<usize>> = series.iter().map(|(_, r)| r.distribution()).collect();
+    let max_guesses = series.first().map_or(MAX_GUESSES, |(_, r)| r.max_guesses());
+    // Guess counts along the x axis, then the losses
+    let buckets: Vec<String> = (1..=max_guesses)
+        .map(|i| i.to_string())
+        .chain(std::iter::once("X".to_string()))
+        .collect();
+    let max = distributions
+        .iter()
+        .flat_map(|d| d.iter().copied())
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let plot_width = WIDTH - MARGIN_LEFT - MARGIN_RIGHT;
+    let plot_height = HEIGHT - MARGIN_TOP - MARGIN_BOTTOM;
+    let baseline = MARGIN_TOP + plot_height;
+    let group_width = plot_width / buckets.len() as f64;
+    let bar_width = group_width * 0.8 / series.len().max(1) as f64;
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\" font-family=\"sans-serif\" font-size=\"12\">\n",
+        w = WIDTH,
+        h = HEIGHT
+    );
+    svg.push_str(&format!(
+        "<rect width=\"{}\" height=\"{}\" fill=\"#ffffff\"/>\n",
+        WIDTH, HEIGHT
+    ));
+    svg.push_str(&format!(
+        "<text x=\"{}\" y=\"20\" text-anchor=\"middle\" font-size=\"15\">Guess distribution</text>\n",
+        WIDTH / 2.0
+    ));
+
+    // Legend
+    for (i, (label, results)) in series.iter().enumerate() {
+        let x = MARGIN_LEFT + i as f64 * 150.0;
+        svg.push_str(&format!(
+            "<rect x=\"{:.1}\" y=\"28\" width=\"10\" height=\"10\" fill=\"{}\"/>\n",
+            x,
+            SERIES_COLORS[i % SERIES_COLORS.len()]
+        ));
+        svg.push_str(&format!(
+            "<text x=\"{:.1}\" y=\"37\">{} ({} games)</text>\n",
+            x + 14.0,
+            escape(label),
+            results.games()
+        ));
+    }
+
+    svg.push_str(&format!(
+        "<line x1=\"{l}\" y1=\"{b}\" x2=\"{r}\" y2=\"{b}\" stroke=\"#333333\"/>\n",
+        l = MARGIN_LEFT,
+        r = WIDTH - MARGIN_RIGHT,
+        b = baseline
+    ));
+    svg.push_str(&format!(
+        "<text x=\"14\" y=\"{:.1}\" text-anchor=\"middle\" transform=\"rotate(-90 14 {:.1})\">Games</text>\n",
+        MARGIN_TOP + plot_height / 2.0,
+        MARGIN_TOP + plot_height / 2.0
+    ));
+
+    for (bucket, name) in buckets.iter().enumerate() {
+        let group_x = MARGIN_LEFT + bucket as f64 * group_width + group_width * 0.1;
+        for (i, distribution) in distributions.iter().enumerate() {
+            let count = distribution.get(bucket).copied().unwrap_or(0);
+            let height = count as f64 / max as f64 * plot_height;
+            let x = group_x + i as f64 * bar_width;
+            let colors = if bucket == buckets.len() - 1 {
+                LOSS_COLORS
+            } else {
+                SERIES_COLORS
+            };
+            svg.push_str(&format!(
+                "<rect class=\"bar\" data-series=\"{}\" data-bucket=\"{}\" data-count=\"{}\" x=\"{:.2}\" y=\"{:.2}\" width=\"{:.2}\" height=\"{:.2}\" fill=\"{}\"/>\n",
+                i,
+                name,
+                count,
+                x,
+                baseline - height,
+                bar_width,
+                height,
+                colors[i % colors.len()]
+            ));
+            svg.push_str(&format!(
+                "<text x=\"{:.2}\" y=\"{:.2}\" text-anchor=\"middle\" font-size=\"10\">{}</text>\n",
+                x + bar_width / 2.0,
+                baseline - height - 3.0,
+                count
+            ));
+        }
+        svg.push_str(&format!(
+            "<text x=\"{:.2}\" y=\"{:.1}\" text-anchor=\"middle\">{}</text>\n",
+            MARGIN_LEFT + (bucket as f64 + 0.5) * group_width,
+            baseline + 18.0,
+            name
+        ));
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Standalone HTML page with the distributions as grouped bars and a summary table
+/// per result.
+pub fn distribution_html(series: &[(&str, &SimulationResults)]) -> String {
+    let mut html = String::from(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Guess distribution</title>\n\
+         <style>body { font-family: sans-serif; } td, th { padding: 2px 10px; text-align: right; }</style>\n\
+         </head>\n<body>\n",
+    );
+    html.push_str(&distribution_svg(series));
+    html.push_str(
+        "<table>\n<tr><th>Result</th><th>Games</th><th>Win %</th><th>Avg</th><th>Score</th></tr>\n",
+    );
+    for (label, results) in series {
+        html.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td>{:.1}</td><td>{}</td><td>{:.3}</td></tr>\n",
+            escape(label),
+            results.games(),
+            results.win_rate(),
+            results
+                .average_guesses()
+                .map_or("-".to_string(), |avg| format!("{:.3}", avg)),
+            results.score()
+        ));
+    }
+    html.push_str("</table>\n</body>\n</html>\n");
+    html
+}
@@ -0,0 +1,188 @@
+use crate::analysis;
+use crate::filter;
+use anyhow::{anyhow, Result};
+
+/// Letters of a word that has none of the most common ones.
+const COMMON_LETTERS: &str = "etaoin";
+
+/// Letters that make a word a rare-letter puzzle.
+const RARE_LETTERS: &str = "qxzj";
+
+/// Tests a word, given the argument of a parameterized predicate (empty otherwise).
+type WordTest = fn(&str, &str) -> bool;
+
+/// A named situation to practice with `play --practice <name>`. A name ending in `-`
+/// takes an argument appended to it, e.g. `ends-in-y`.
+pub struct Predicate {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// An example argument for a parameterized predicate, empty for the others.
+    pub example: &'static str,
+    test: WordTest,
+}
+
+/// Every practice predicate. New ones only need an entry here.
+pub const PREDICATES: &[Predicate] = &[
+    Predicate {
+        name: "double-letter",
+        description: "contains a letter twice",
+        example: "",
+        test: |word, _| {
+            let bytes = word.as_bytes();
+            bytes
+                .iter()
+                .enumerate()
+                .any(|(i, b)| bytes[i + 1..].contains(b))
+        },
+    },
+    Predicate {
+        name: "no-etaoin",
+        description: "has none of the common letters E, T, A, O, I, N",
+        example: "",
+        test: |word, _| !word.contains(|c| COMMON_LETTERS.contains(c)),
+    },
+    Predicate {
+        name: "rare-letter",
+        description: "contains Q, X, Z or J",
+        example: "",
+        test: |word, _| word.contains(|c| RARE_LETTERS.contains(c)),
+    },
+    Predicate {
+        name: "not-like-",
+        description: "shares at most 3 positions with",
+        example: "hills",
+        test: |word, other| !analysis::words_similar(word, other, analysis::SIMILAR_POSITIONS),
+    },
+    Predicate {
+        name: "ends-in-",
+        description: "ends in",
+        example: "y",
+        test: |word, suffix| word.ends_with(suffix),
+    },
+];
+
+/// The hidden-word restriction of a practice game.
+pub struct Practice {
+    /// What the game practiced, reported at its end.
+    pub label: String,
+    matches: Box<dyn Fn(&str) -> bool>,
+}
+
+impl Practice {
+    /// The practice of a registered predicate, e.g. `double-letter` or `ends-in-er`.
+    pub fn named(spec: &str) -> Result<Self> {
+        let spec = spec.trim().to_lowercase();
+        for predicate in PREDICATES {
+            let argument = if predicate.name.ends_with('-') {
+                match spec.strip_prefix(predicate.name) {
+                    Some(argument) if !argument.is_empty() => argument.to_string(),
+                    _ => continue,
+                }
+            } else if spec == predicate.name {
+                String::new()
+            } else {
+                continue;
+            };
+            let test = predicate.test;
+            let label = match argument.as_str() {
+                "" => format!("{} ({})", predicate.name, predicate.description),
+                _ => format!("{} ({} '{}')", spec, predicate.description, argument),
+            };
+            return Ok(Self {
+                label,
+                matches: Box::new(move |word| test(word, &argument)),
+            });
+        }
+        Err(anyhow!(
+            "Unknown practice '{}'. Available: {}.",
+            spec,
+            names().join(", ")
+        ))
+    }
+
+    /// The practice of a glob over the whole word (`--practice-glob "?a??y"`).
+    pub fn glob(pattern: &str) -> Self {
+        let pattern = pattern.trim().to_lowercase();
+        Self {
+            label: format!("glob {}", pattern),
+            matches: Box::new(move |word| filter::glob_match(pattern.as_bytes(), word.as_bytes())),
+        }
+    }
+
+    /// The words of `pool` the practice allows.
+    pub fn select(&self, pool: &[String]) -> Vec<String> {
+        pool.iter().filter(|w| (self.matches)(w)).cloned().collect()
+    }
+}
+
+/// The predicate names as typed, with an example argument where one is needed.
+pub fn names() -> Vec<String> {
+    PREDICATES
+        .iter()
+        .map(|p| format!("{}{}", p.name, p.example))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::Solver;
+
+    #[test]
+    fn every_predicate_selects_some_fixture_answers() {
+        let answers = Solver::fixture().answer_words;
+        for name in names() {
+            let selected = Practice::named(&name).unwrap().select(&answers);
+            assert!(!selected.is_empty(), "{} selects no answer", name);
+        }
+    }
+
+    #[test]
+    fn predicates_match_their_description() {
+        let cases = [
+            ("double-letter", "sissy", true),
+            ("double-letter", "crane", false),
+            ("no-etaoin", "lumpy", true),
+            ("no-etaoin", "crumb", true),
+            ("no-etaoin", "shrub", true),
+            ("no-etaoin", "crane", false),
+            ("rare-letter", "jazzy", true),
+            ("rare-letter", "equip", true),
+            ("rare-letter", "crane", false),
+            ("ends-in-y", "lumpy", true),
+            ("ends-in-er", "crier", true),
+            ("ends-in-er", "crane", false),
+            ("not-like-hills", "fills", false),
+            ("not-like-hills", "hilly", false),
+            ("not-like-hills", "hello", true),
+        ];
+        for (name, word, expected) in cases {
+            let practice = Practice::named(name).unwrap();
+            assert_eq!(
+                practice.select(&[word.to_string()]).len() == 1,
+                expected,
+                "{} {}",
+                name,
+                word
+            );
+        }
+    }
+
+    #[test]
+    fn unknown_or_incomplete_names_are_rejected() {
+        for name in ["triple-letter", "ends-in-", "not-like-"] {
+            let error = Practice::named(name).err().unwrap().to_string();
+            assert!(error.starts_with("Unknown practice"), "{}", error);
+        }
+    }
+
+    #[test]
+    fn glob_matches_the_whole_word() {
+        let words = ["fairy", "lumpy", "party", "happy"].map(String::from);
+        assert_eq!(
+            Practice::glob("?A??Y").select(&words),
+            ["fairy", "party", "happy"]
+        );
+        assert_eq!(Practice::glob("*py").select(&words), ["lumpy", "happy"]);
+    }
+}
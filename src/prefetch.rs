@@ -0,0 +1,98 @@
+use crate::game::{self, parse_pattern};
+use crate::solver::Solver;
+use crate::strategy::{Ranking, Strategy};
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::thread;
+
+/// Patterns ranked ahead by `solve --prefetch` without `--prefetch-patterns`.
+pub const DEFAULT_PREFETCH_PATTERNS: usize = 8;
+
+/// The feedback patterns `guess` can get from `candidates`, most candidates first
+/// (ties by pattern), at most `limit`. Patterns that win or leave two candidates or
+/// fewer are left out, since the solver doesn't rank those.
+pub fn likely_patterns(guess: &str, candidates: &[String], limit: usize) -> Vec<String> {
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for candidate in candidates {
+        *counts
+            .entry(game::grade(guess, candidate).to_string())
+            .or_default() += 1;
+    }
+    let mut patterns: Vec<(String, usize)> = counts
+        .into_iter()
+        .filter(|(pattern, count)| *count > 2 && pattern != "ccccc")
+        .collect();
+    patterns.sort_by_key(|(_, count)| Reverse(*count));
+    patterns
+        .into_iter()
+        .take(limit)
+        .map(|(pattern, _)| pattern)
+        .collect()
+}
+
+/// The next-turn ranking of a guess and pattern, computed while the pattern was
+/// typed.
+pub struct Prefetched {
+    pub guess: String,
+    pub pattern: String,
+    /// Guesses in the game before `guess`.
+    pub turn: usize,
+    pub ranking: Ranking,
+}
+
+/// Calls `read` for the pattern of `guess` while a worker thread ranks the game after
+/// each of `patterns` in turn. The worker is a fork of the solver with the same game
+/// and a shared pattern cache, so a prefetched ranking is the one the solver would
+/// compute itself. Once `read` returns, the worker finishes the entered pattern if it
+/// was planned and is cancelled otherwise; it is stopped and joined before this
+/// returns. Returns the line read, and the ranking of the entered pattern when it was
+/// planned and its turn is ranked by a pattern strategy.
+pub fn while_reading<E>(
+    mut worker: Solver,
+    guess: &str,
+    patterns: &[String],
+    read: impl FnOnce() -> Result<Option<String>, E>,
+) -> Result<(Option<String>, Option<Prefetched>), E> {
+    let turn = worker.game.lines.len();
+    let cancel = Arc::new(AtomicBool::new(false));
+    worker.cancel = Some(Arc::clone(&cancel));
+    thread::scope(|scope| {
+        let (sender, results) = mpsc::channel();
+        scope.spawn(move || {
+            for pattern in patterns {
+                if worker.is_cancelled() {
+                    break;
+                }
+                worker.game.add_line(guess, pattern);
+                let ranking = (worker.active_strategy() != Strategy::Frequency)
+                    .then(|| worker.pattern_ranking());
+                worker.game.truncate(turn);
+                if sender.send((pattern, ranking)).is_err() {
+                    break;
+                }
+            }
+        });
+        let line = read();
+        let entered = match &line {
+            Ok(Some(line)) => parse_pattern(line).filter(|pattern| patterns.contains(pattern)),
+            _ => None,
+        };
+        let ranking = entered.as_ref().and_then(|entered| {
+            results
+                .iter()
+                .find(|(pattern, _)| *pattern == entered)
+                .and_then(|(_, ranking)| ranking)
+        });
+        cancel.store(true, Ordering::Relaxed);
+        let prefetched = entered.zip(ranking).map(|(pattern, ranking)| Prefetched {
+            guess: guess.to_string(),
+            pattern,
+            turn,
+            ranking,
+        });
+        line.map(|line| (line, prefetched))
+    })
+}
@@ -0,0 +1,161 @@
+use crate::messages;
+use crate::util;
+use anyhow::Result;
+use std::collections::HashSet;
+
+/// Share of the unrestricted best score a listed probe must reach when
+/// `--probe-threshold` is not given.
+pub const DEFAULT_THRESHOLD: f64 = 0.9;
+
+/// Probe guesses the player would actually play, from probe_words.txt. Only guesses
+/// that can't be the answer are restricted; candidates are always allowed.
+#[derive(Clone)]
+pub struct ProbeWords {
+    words: HashSet<String>,
+    /// Below this share of the unrestricted best score, the full pool is used instead.
+    pub threshold: f64,
+}
+
+/// A probe swapped for a listed one, shown with the suggestions.
+#[derive(Debug, Clone)]
+pub struct ProbeRestriction {
+    pub unrestricted: (String, f64),
+    pub listed: (String, f64),
+}
+
+impl ProbeRestriction {
+    /// Score given up by the listed probe, as a share of the unrestricted best.
+    pub fn sacrificed(&self) -> f64 {
+        if self.unrestricted.1 > 0.0 {
+            1.0 - self.listed.1 / self.unrestricted.1
+        } else {
+            0.0
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        messages::fill(
+            "solve.probe_restriction",
+            &[
+                &self.listed.0,
+                &format!("{:.5}", self.listed.1),
+                &self.unrestricted.0,
+                &format!("{:.5}", self.unrestricted.1),
+                &format!("{:.1}", self.sacrificed() * 100.0),
+            ],
+        )
+    }
+}
+
+impl ProbeWords {
+    /// Loads probe_words.txt from the active profile; `None` when there is none.
+    pub fn load(threshold: Option<f64>) -> Result<Option<Self>> {
+        let Some(content) = util::read_probe_words()? else {
+            return Ok(None);
+        };
+        let words: HashSet<String> = content
+            .lines()
+            .map(|line| line.trim().to_lowercase())
+            .filter(|word| !word.is_empty() && !word.starts_with('#'))
+            .collect();
+        Ok(Some(Self {
+            words,
+            threshold: threshold.unwrap_or(DEFAULT_THRESHOLD),
+        }))
+    }
+
+    pub fn contains(&self, word: &str) -> bool {
+        self.words.contains(word)
+    }
+
+    /// Looks at `ranked` (best first, scores higher is better) and, when its best
+    /// guess is a probe not on the list, finds the best guess that is either a
+    /// candidate or a listed probe. Returns that guess's index and the restriction
+    /// when it scores at least `threshold` of the best; `None` keeps the full pool.
+    pub fn restrict(
+        &self,
+        ranked: &[(String, f64)],
+        is_candidate: impl Fn(usize) -> bool,
+    ) -> Option<(usize, ProbeRestriction)> {
+        let (best, best_score) = ranked.first()?;
+        let allowed = |i: usize| is_candidate(i) || self.contains(&ranked[i].0);
+        if allowed(0) {
+            return None;
+        }
+
+        let index = (1..ranked.len()).find(|&i| allowed(i))?;
+        let (listed, listed_score) = &ranked[index];
+        if *listed_score < self.threshold * best_score {
+            return None;
+        }
+        Some((
+            index,
+            ProbeRestriction {
+                unrestricted: (best.clone(), *best_score),
+                listed: (listed.clone(), *listed_score),
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn probes(words: &[&str], threshold: f64) -> ProbeWords {
+        ProbeWords {
+            words: words.iter().map(|w| w.to_string()).collect(),
+            threshold,
+        }
+    }
+
+    fn ranked(scores: &[(&str, f64)]) -> Vec<(String, f64)> {
+        scores.iter().map(|&(w, s)| (w.to_string(), s)).collect()
+    }
+
+    #[test]
+    fn listed_probe_at_the_threshold_is_taken() {
+        let ranked = ranked(&[("xylyl", 2.0), ("oxbow", 1.9), ("slate", 1.8)]);
+        let (index, restriction) = probes(&["slate"], 0.9)
+            .restrict(&ranked, |_| false)
+            .unwrap();
+        assert_eq!(index, 2);
+        assert_eq!(restriction.unrestricted.0, "xylyl");
+        assert_eq!(restriction.listed.0, "slate");
+        assert!((restriction.sacrificed() - 0.1).abs() < 1e-12);
+    }
+
+    #[test]
+    fn listed_probe_below_the_threshold_keeps_the_full_pool() {
+        let ranked = ranked(&[("xylyl", 2.0), ("slate", 1.79)]);
+        assert!(probes(&["slate"], 0.9)
+            .restrict(&ranked, |_| false)
+            .is_none());
+        assert!(probes(&["slate"], 0.8)
+            .restrict(&ranked, |_| false)
+            .is_some());
+    }
+
+    #[test]
+    fn candidates_are_allowed_without_being_listed() {
+        let ranked = ranked(&[("xylyl", 2.0), ("crane", 1.9), ("slate", 1.95)]);
+        let (index, _) = probes(&["slate"], 0.5)
+            .restrict(&ranked, |i| i == 1)
+            .unwrap();
+        assert_eq!(index, 1);
+        // A best guess that is a candidate or listed needs no restriction
+        assert!(probes(&[], 0.5).restrict(&ranked, |i| i == 0).is_none());
+        assert!(probes(&["xylyl"], 0.5)
+            .restrict(&ranked, |_| false)
+            .is_none());
+    }
+
+    #[test]
+    fn no_allowed_guess_keeps_the_full_pool() {
+        let ranked = ranked(&[("xylyl", 2.0), ("oxbow", 1.9)]);
+        assert!(probes(&["slate"], 0.0)
+            .restrict(&ranked, |_| false)
+            .is_none());
+        assert!(probes(&["slate"], 0.9).restrict(&[], |_| false).is_none());
+    }
+}
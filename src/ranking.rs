@@ -1,59 +1,239 @@
 use crate::analysis::LetterStats;
+use crate::util::UNIFORM_POSITIONS;
 use anyhow::Result;
-use serde_json;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-pub fn rank_words<'a>(words: &[&'a str], stats_json: &str) -> Result<Vec<(String, f64)>> {
+pub fn rank_words(words: &[&str], stats_json: &str) -> Result<RankedList> {
     // === Adjustable weight parameters ===
     let w_pos = 0.2; // weight for positional frequency
     let w_overall = 0.1; // weight for overall frequency
     let w_unique = 0.7; // weight for letter uniqueness
 
-    weighted_rank(words, stats_json, (w_pos, w_overall, w_unique))
+    weighted_rank(
+        words,
+        stats_json,
+        (w_pos, w_overall, w_unique),
+        UNIFORM_POSITIONS,
+    )
 }
 
-pub fn weighted_rank<'a>(
-    words: &[&'a str],
+/// Ranks `words` by the blend of `weights`, with each position's letter frequency
+/// scaled by its multiplier in `positions`.
+pub fn weighted_rank(
+    words: &[&str],
     stats_json: &str,
     weights: (f64, f64, f64),
-) -> Result<Vec<(String, f64)>> {
-    let (w_pos, w_overall, w_unique) = weights;
-    let stats: LetterStats = serde_json::from_str(stats_json)?;
-
-    // === Compute positional totals ===
-    let mut totals = [0.0; 5];
-    for counts in stats.counts.values() {
-        for (i, &c) in counts.iter().enumerate() {
-            totals[i] += c as f64;
+    positions: [f64; 5],
+) -> Result<RankedList> {
+    let scorer = Scorer::new(stats_json)?;
+
+    // === Rank each word ===
+    let scores: Vec<(String, f64)> = words
+        .iter()
+        .filter_map(|word| scorer.score_word_detailed(word, weights, positions))
+        .map(|breakdown| {
+            let score = breakdown.score();
+            (breakdown.word, score)
+        })
+        .collect();
+
+    Ok(RankedList::new(scores))
+}
+
+/// Words with their scores, best first and equal scores alphabetically. A word ranked
+/// twice is listed once, so the order never depends on the order of the input. Only
+/// `in_order` lists keep their own order for equal scores.
+#[derive(Debug, Clone, Default)]
+pub struct RankedList(Vec<(String, f64)>);
+
+impl RankedList {
+    pub fn new(mut scores: Vec<(String, f64)>) -> Self {
+        scores.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        scores.dedup_by(|a, b| a.0 == b.0);
+        Self(scores)
+    }
+
+    /// `scores` as given, which must already be best first, keeping the first entry of
+    /// a word ranked twice. For rankings that order equal scores on purpose, such as
+    /// the pattern strategies trying likely answers first.
+    pub fn in_order(scores: Vec<(String, f64)>) -> Self {
+        debug_assert!(scores.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+        let mut seen = HashSet::new();
+        Self(
+            scores
+                .into_iter()
+                .filter(|(word, _)| seen.insert(word.clone()))
+                .collect(),
+        )
+    }
+
+    /// The words with each score replaced by `rescore(word, score)`, ranked again.
+    pub fn rescored(self, rescore: impl Fn(&str, f64) -> f64) -> Self {
+        Self::new(
+            self.0
+                .into_iter()
+                .map(|(word, score)| {
+                    let score = rescore(&word, score);
+                    (word, score)
+                })
+                .collect(),
+        )
+    }
+
+    /// The best `n` words, or all of them when there are fewer.
+    pub fn top(&self, n: usize) -> &[(String, f64)] {
+        &self.0[..n.min(self.0.len())]
+    }
+
+    /// Where `word` ranks, 0 for the best; `None` when it wasn't ranked.
+    pub fn position_of(&self, word: &str) -> Option<usize> {
+        self.0.iter().position(|(w, _)| w == word)
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, (String, f64)> {
+        self.0.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The words without their scores, best first.
+    pub fn words(self) -> Vec<String> {
+        self.0.into_iter().map(|(word, _)| word).collect()
+    }
+
+    pub fn into_vec(self) -> Vec<(String, f64)> {
+        self.0
+    }
+}
+
+impl IntoIterator for RankedList {
+    type Item = (String, f64);
+    type IntoIter = std::vec::IntoIter<(String, f64)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a RankedList {
+    type Item = &'a (String, f64);
+    type IntoIter = std::slice::Iter<'a, (String, f64)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+/// Score gap within which `solve --stable` orders suggestions alphabetically.
+pub const DEFAULT_STABLE_EPSILON: f64 = 1e-4;
+
+/// Decimals of the scores `solve --stable` shows.
+pub const DEFAULT_SCORE_PRECISION: usize = 3;
+
+/// Most decimals `--score-precision` accepts; f64 scores carry no more.
+pub const MAX_SCORE_PRECISION: usize = 12;
+
+/// Suggestion order that tiny score differences don't reshuffle (`solve --stable`),
+/// so a small wordlist edit doesn't reorder the whole top 10.
+#[derive(Debug, Clone, Copy)]
+pub struct Stability {
+    /// Words scoring within this of the first word of their group are one group,
+    /// ordered alphabetically.
+    pub epsilon: f64,
+    /// Decimals of the scores shown; neighbours showing the same score are a tie.
+    pub precision: usize,
+}
+
+impl Stability {
+    /// `ranked` with each run of words scoring within `epsilon` of the run's first
+    /// word sorted alphabetically. Runs keep their place, so the order of clearly
+    /// different scores is unchanged.
+    pub fn apply(&self, mut ranked: Vec<(String, f64)>) -> Vec<(String, f64)> {
+        let mut start = 0;
+        while start < ranked.len() {
+            let first = ranked[start].1;
+            let len = ranked[start..]
+                .iter()
+                .take_while(|(_, score)| (first - score).abs() <= self.epsilon)
+                .count();
+            ranked[start..start + len].sort_by(|a, b| a.0.cmp(&b.0));
+            start += len;
         }
+        ranked
     }
 
-    // === Compute overall totals ===
-    let mut overall_totals = HashMap::new();
-    let mut grand_total = 0.0;
-    for (ch, counts) in &stats.counts {
-        let sum: f64 = counts.iter().map(|&c| c as f64).sum();
-        overall_totals.insert(*ch, sum);
-        grand_total += sum;
+    /// `score` rounded to `precision` decimals.
+    pub fn round(&self, score: f64) -> f64 {
+        let scale = 10f64.powi(self.precision as i32);
+        (score * scale).round() / scale
     }
+}
 
-    // === Rank each word ===
-    let mut scores: Vec<(String, f64)> = Vec::new();
+/// Letter frequencies `weighted_rank` scores words with.
+pub struct Scorer {
+    stats: LetterStats,
+    /// Letters counted at each position.
+    totals: [f64; 5],
+    /// Occurrences of each letter over all positions.
+    overall_totals: HashMap<char, f64>,
+    grand_total: f64,
+}
 
-    for &word in words {
-        let chars: Vec<char> = word.chars().collect();
-        if chars.len() != 5 || !chars.iter().all(|c| c.is_ascii_lowercase()) {
-            continue;
+impl Scorer {
+    pub fn new(stats_json: &str) -> Result<Self> {
+        let stats = LetterStats::parse(stats_json)?;
+
+        // === Compute positional totals ===
+        let mut totals = [0.0; 5];
+        for counts in stats.counts.values() {
+            for (i, &c) in counts.iter().enumerate() {
+                totals[i] += c as f64;
+            }
+        }
+
+        // === Compute overall totals ===
+        let mut overall_totals = HashMap::new();
+        let mut grand_total = 0.0;
+        for (ch, counts) in &stats.counts {
+            let sum: f64 = counts.iter().map(|&c| c as f64).sum();
+            overall_totals.insert(*ch, sum);
+            grand_total += sum;
         }
 
-        let mut score_pos = 0.0;
-        let mut score_overall = 0.0;
+        Ok(Self {
+            stats,
+            totals,
+            overall_totals,
+            grand_total,
+        })
+    }
 
+    /// Every term of `word`'s score under `weights` and the position multipliers.
+    /// `None` for words that aren't 5 letters or use letters missing from the stats,
+    /// which are never ranked.
+    pub fn score_word_detailed(
+        &self,
+        word: &str,
+        weights: (f64, f64, f64),
+        positions: [f64; 5],
+    ) -> Option<ScoreBreakdown> {
+        let chars: Vec<char> = word.chars().collect();
+        if chars.len() != 5 || !chars.iter().all(|c| self.stats.counts.contains_key(c)) {
+            return None;
+        }
+
+        let mut letters = [LetterScore::default(); 5];
         for (i, &ch) in chars.iter().enumerate() {
             // Positional frequency score
-            let pos_score = if let Some(counts) = stats.counts.get(&ch) {
-                if totals[i] > 0.0 {
-                    counts[i] as f64 / totals[i]
+            let positional = if let Some(counts) = self.stats.counts.get(&ch) {
+                if self.totals[i] > 0.0 {
+                    counts[i] as f64 / self.totals[i]
                 } else {
                     0.0
                 }
@@ -62,32 +242,247 @@ pub fn weighted_rank<'a>(
             };
 
             // Overall frequency score
-            let overall_score = if let Some(&sum) = overall_totals.get(&ch) {
-                sum / grand_total
+            let overall = if let Some(&sum) = self.overall_totals.get(&ch) {
+                sum / self.grand_total
             } else {
                 0.0
             };
 
-            score_pos += pos_score;
-            score_overall += overall_score;
+            letters[i] = LetterScore {
+                letter: ch,
+                positional,
+                overall,
+            };
         }
 
+        // === Uniqueness score ===
+        let unique_letters = chars.iter().collect::<HashSet<_>>().len();
+
+        Some(ScoreBreakdown {
+            word: word.to_string(),
+            letters,
+            unique_letters,
+            weights,
+            positions,
+        })
+    }
+}
+
+/// One letter's share of all letters at its position and over all positions.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LetterScore {
+    pub letter: char,
+    pub positional: f64,
+    pub overall: f64,
+}
+
+/// The terms of a word's ranking score. `score` combines them exactly as the ranking
+/// does, so the breakdown always adds up to the ranked score.
+#[derive(Debug, Clone)]
+pub struct ScoreBreakdown {
+    pub word: String,
+    pub letters: [LetterScore; 5],
+    /// Distinct letters in the word; repeated letters lower the uniqueness term.
+    pub unique_letters: usize,
+    /// Weights for positional frequency, overall frequency and uniqueness.
+    pub weights: (f64, f64, f64),
+    /// Multiplier of each letter's positional share.
+    pub positions: [f64; 5],
+}
+
+impl ScoreBreakdown {
+    /// Mean positional share of the letters, each scaled by its position's multiplier.
+    pub fn positional(&self) -> f64 {
         // Normalize by length (so 5-letter words stay comparable)
-        score_pos /= 5.0;
-        score_overall /= 5.0;
+        self.letters
+            .iter()
+            .zip(self.positions)
+            .map(|(l, multiplier)| multiplier * l.positional)
+            .fold(0.0, |a, b| a + b)
+            / 5.0
+    }
 
-        // === Uniqueness score ===
-        let unique_letters = chars.iter().collect::<std::collections::HashSet<_>>();
-        let uniqueness = unique_letters.len() as f64 / 5.0; // 0.2–1.0 range
+    /// Mean overall share of the letters.
+    pub fn overall(&self) -> f64 {
+        self.letters
+            .iter()
+            .map(|l| l.overall)
+            .fold(0.0, |a, b| a + b)
+            / 5.0
+    }
+
+    /// Share of distinct letters, 0.2–1.0.
+    pub fn uniqueness(&self) -> f64 {
+        self.unique_letters as f64 / 5.0
+    }
 
+    pub fn score(&self) -> f64 {
         // === Weighted blend ===
-        let blended_score = w_pos * score_pos + w_overall * score_overall + w_unique * uniqueness;
+        let (w_pos, w_overall, w_unique) = self.weights;
+        w_pos * self.positional() + w_overall * self.overall() + w_unique * self.uniqueness()
+    }
+
+    /// Table of the per-letter shares and the weighted terms, ending in the score.
+    pub fn to_text(&self) -> String {
+        let (w_pos, w_overall, w_unique) = self.weights;
+        let mut text = format!(
+            "{:<8}{:>12}{:>12}{:>12}\n",
+            "Letter", "Positional", "Multiplier", "Overall"
+        );
+        for (i, letter) in self.letters.iter().enumerate() {
+            text.push_str(&format!(
+                "{:<8}{:>12.4}{:>12}{:>12.4}\n",
+                format!("{}. {}", i + 1, letter.letter.to_ascii_uppercase()),
+                letter.positional,
+                format!("x {}", self.positions[i]),
+                letter.overall
+            ));
+        }
+        text.push('\n');
+        text.push_str(&format!(
+            "{:<22}{:>8.4} x {:<6} = {:.4}\n",
+            "Positional frequency",
+            self.positional(),
+            w_pos,
+            w_pos * self.positional()
+        ));
+        text.push_str(&format!(
+            "{:<22}{:>8.4} x {:<6} = {:.4}\n",
+            "Overall frequency",
+            self.overall(),
+            w_overall,
+            w_overall * self.overall()
+        ));
+        text.push_str(&format!(
+            "{:<22}{:>8.4} x {:<6} = {:.4}  ({} distinct letters)\n",
+            "Uniqueness",
+            self.uniqueness(),
+            w_unique,
+            w_unique * self.uniqueness(),
+            self.unique_letters
+        ));
+        text.push_str(&format!("{:<41}{:.4}\n", "Score", self.score()));
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::Solver;
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+    use rand::SeedableRng;
+
+    fn stats_json(words: &[&str]) -> String {
+        serde_json::to_string(&LetterStats::from_words(words)).unwrap()
+    }
+
+    #[test]
+    fn breakdown_terms_are_computed_from_the_stats() {
+        let scorer = Scorer::new(&stats_json(&["abcde", "abcdf"])).unwrap();
+        let breakdown = scorer
+            .score_word_detailed("abcde", (0.2, 0.1, 0.7), UNIFORM_POSITIONS)
+            .unwrap();
+        // Four letters hold their position in both words, E in one of two
+        assert!((breakdown.positional() - 0.9).abs() < 1e-12);
+        // A to D are 2 of the 10 letters each, E is 1
+        assert!((breakdown.overall() - 0.18).abs() < 1e-12);
+        assert_eq!(breakdown.uniqueness(), 1.0);
+        assert!((breakdown.score() - (0.2 * 0.9 + 0.1 * 0.18 + 0.7)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn breakdown_adds_up_to_the_ranked_score() {
+        let solver = Solver::fixture();
+        let words: Vec<&str> = solver.all_words.iter().map(|w| w.as_str()).collect();
+        let scorer = Scorer::new(&solver.stats_json).unwrap();
+        for weights in [(0.2, 0.1, 0.7), (0.3, 0.4, 0.3), (1.0, 0.0, 0.0)] {
+            for positions in [UNIFORM_POSITIONS, [2.0, 1.0, 1.0, 1.0, 0.5]] {
+                let ranked = weighted_rank(&words, &solver.stats_json, weights, positions).unwrap();
+                assert_eq!(ranked.len(), words.len());
+                for (word, score) in &ranked {
+                    let breakdown = scorer
+                        .score_word_detailed(word, weights, positions)
+                        .unwrap();
+                    assert_eq!(breakdown.score(), *score, "{}", word);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn words_the_stats_cannot_score_are_not_broken_down() {
+        let scorer = Scorer::new(&stats_json(&["abcde"])).unwrap();
+        let weights = (0.2, 0.1, 0.7);
+        assert!(scorer
+            .score_word_detailed("abcdz", weights, UNIFORM_POSITIONS)
+            .is_some());
+        assert!(scorer
+            .score_word_detailed("abcd", weights, UNIFORM_POSITIONS)
+            .is_none());
+        assert!(scorer
+            .score_word_detailed("abcd1", weights, UNIFORM_POSITIONS)
+            .is_none());
+    }
+
+    #[test]
+    fn stable_order_ignores_a_one_in_a_million_score_difference() {
+        let stability = Stability {
+            epsilon: 1e-4,
+            precision: 3,
+        };
+        let ranked = |first: (&str, f64), second: (&str, f64)| {
+            let words = [first, second, ("pious", 0.4)].map(|(w, s)| (w.to_string(), s));
+            stability
+                .apply(words.to_vec())
+                .into_iter()
+                .map(|(word, _)| word)
+                .collect::<Vec<_>>()
+        };
+        let expected = ["crane", "slate", "pious"];
+        assert_eq!(ranked(("slate", 0.500001), ("crane", 0.5)), expected);
+        assert_eq!(ranked(("crane", 0.500001), ("slate", 0.5)), expected);
+        assert_eq!(stability.round(0.500001), stability.round(0.5));
 
-        scores.push((word.to_string(), blended_score));
+        // Without the epsilon the higher score still comes first
+        let exact = Stability {
+            epsilon: 0.0,
+            precision: 3,
+        };
+        let words = [("slate", 0.500001), ("crane", 0.5)].map(|(w, s)| (w.to_string(), s));
+        assert_eq!(exact.apply(words.to_vec())[0].0, "slate");
     }
 
-    // Sort descending by score
-    scores.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+    #[test]
+    fn shuffled_and_repeated_words_rank_the_same() {
+        let words = Solver::fixture().all_words;
+        let words: Vec<&str> = words.iter().map(String::as_str).collect();
+        let stats = stats_json(&words);
+        let expected = rank_words(&words, &stats).unwrap().into_vec();
 
-    Ok(scores)
+        let mut rng = StdRng::seed_from_u64(11);
+        for _ in 0..5 {
+            let mut shuffled: Vec<&str> = words.iter().chain(&words[..40]).copied().collect();
+            shuffled.shuffle(&mut rng);
+            let ranked = rank_words(&shuffled, &stats).unwrap().into_vec();
+            assert_eq!(ranked, expected);
+        }
+
+        let listed: HashSet<&str> = expected.iter().map(|(word, _)| word.as_str()).collect();
+        assert_eq!(listed.len(), expected.len());
+        assert_eq!(expected.len(), words.len());
+    }
+
+    #[test]
+    fn an_in_order_ranking_keeps_its_ties_and_drops_repeats() {
+        let scores = [
+            ("slate", 2.0),
+            ("crane", 1.0),
+            ("adieu", 1.0),
+            ("slate", 1.0),
+        ];
+        let ranked = RankedList::in_order(scores.map(|(w, s)| (w.to_string(), s)).to_vec());
+        assert_eq!(ranked.words(), ["slate", "crane", "adieu"]);
+    }
 }
@@ -0,0 +1,211 @@
+use crate::analysis::LetterStats;
+use crate::game::LetterConstraint;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Most strings `enumerate` builds before giving up; beyond this the feedback says
+/// too little for a list of guesses to help.
+pub const MAX_RECONSTRUCTIONS: usize = 5000;
+
+/// Strings listed by the `reconstruct` command.
+pub const RECONSTRUCT_LIST: usize = 10;
+
+/// Every 5-letter string over `alphabet` that fits `constraints` (see
+/// `GameData::letter_constraints`): green letters in place, no letter where a yellow
+/// or gray cell ruled it out, and each letter's count within its bounds. Sorted
+/// alphabetically; `None` when there are more than `limit`.
+pub fn enumerate(
+    constraints: &BTreeMap<char, LetterConstraint>,
+    alphabet: &BTreeSet<char>,
+    limit: usize,
+) -> Option<Vec<String>> {
+    let mut fixed = [None; 5];
+    for (&letter, constraint) in constraints {
+        for &position in &constraint.positions {
+            if let Some(slot) = fixed.get_mut(position) {
+                *slot = Some(letter);
+            }
+        }
+    }
+    let mut search = Search {
+        constraints,
+        alphabet: alphabet.iter().copied().collect(),
+        fixed,
+        limit,
+        word: Vec::with_capacity(5),
+        counts: BTreeMap::new(),
+        found: Vec::new(),
+    };
+    search.extend().then_some(search.found)
+}
+
+/// Depth-first search state of `enumerate`, one position at a time.
+struct Search<'a> {
+    constraints: &'a BTreeMap<char, LetterConstraint>,
+    alphabet: Vec<char>,
+    fixed: [Option<char>; 5],
+    limit: usize,
+    word: Vec<char>,
+    /// Letters of `word` so far.
+    counts: BTreeMap<char, usize>,
+    found: Vec<String>,
+}
+
+impl Search<'_> {
+    /// Letter occurrences the remaining positions still have to supply to reach every
+    /// letter's minimum.
+    fn missing(&self) -> usize {
+        self.constraints
+            .iter()
+            .map(|(letter, c)| {
+                c.min_count
+                    .saturating_sub(self.counts.get(letter).copied().unwrap_or(0))
+            })
+            .sum()
+    }
+
+    /// Whether `letter` may go at the next position. Copies a later green position
+    /// needs count against the letter's maximum already.
+    fn allows(&self, letter: char) -> bool {
+        let position = self.word.len();
+        if let Some(fixed) = self.fixed[position] {
+            return letter == fixed;
+        }
+        let Some(constraint) = self.constraints.get(&letter) else {
+            return true;
+        };
+        let reserved = self.fixed[position + 1..]
+            .iter()
+            .filter(|&&fixed| fixed == Some(letter))
+            .count();
+        let count = self.counts.get(&letter).copied().unwrap_or(0) + reserved;
+        !constraint.forbidden.contains(&position)
+            && constraint.max_count.is_none_or(|max| count < max)
+    }
+
+    /// Completes `word` every way the constraints allow. Returns false once more than
+    /// `limit` strings were found.
+    fn extend(&mut self) -> bool {
+        let position = self.word.len();
+        if self.missing() > 5 - position {
+            return true;
+        }
+        if position == 5 {
+            if self.found.len() == self.limit {
+                return false;
+            }
+            self.found.push(self.word.iter().collect());
+            return true;
+        }
+        for i in 0..self.alphabet.len() {
+            let letter = self.alphabet[i];
+            if !self.allows(letter) {
+                continue;
+            }
+            self.word.push(letter);
+            *self.counts.entry(letter).or_default() += 1;
+            let within = self.extend();
+            *self.counts.entry(letter).or_default() -= 1;
+            self.word.pop();
+            if !within {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// `words` with the log-likelihood of each under the positional letter frequencies
+/// of `stats` (add-one smoothed), most likely first, ties alphabetical.
+pub fn rank(words: &[String], stats: &LetterStats) -> Vec<(String, f64)> {
+    let total = stats.word_count() as f64 + stats.counts.len().max(1) as f64;
+    let mut ranked: Vec<(String, f64)> = words
+        .iter()
+        .map(|word| {
+            let score = word
+                .chars()
+                .enumerate()
+                .map(|(position, letter)| {
+                    let count = stats
+                        .counts
+                        .get(&letter)
+                        .and_then(|counts| counts.get(position))
+                        .copied()
+                        .unwrap_or(0);
+                    ((count as f64 + 1.0) / total).ln()
+                })
+                .sum();
+            (word.clone(), score)
+        })
+        .collect();
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+    ranked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{grade, GameData};
+
+    /// A game of `guesses` against `answer`, and the letters of both.
+    fn game(guesses: &[&str], answer: &str) -> (GameData, BTreeSet<char>) {
+        let mut game = GameData::new();
+        for guess in guesses {
+            game.add_line(guess, &grade(guess, answer).to_string());
+        }
+        let alphabet = guesses.iter().chain([&answer]).flat_map(|w| w.chars());
+        (game, alphabet.collect())
+    }
+
+    /// Every string over `alphabet` the game's feedback allows, by grading each.
+    fn brute_force(game: &GameData, alphabet: &BTreeSet<char>) -> Vec<String> {
+        let mut words = vec![String::new()];
+        for _ in 0..5 {
+            words = words
+                .iter()
+                .flat_map(|word| alphabet.iter().map(move |&c| format!("{}{}", word, c)))
+                .collect();
+        }
+        words.retain(|word| {
+            game.lines.iter().all(|line| {
+                let pattern: String = line.cells.iter().map(|cell| cell.state).collect();
+                grade(&line.word, word).to_string() == pattern
+            })
+        });
+        words
+    }
+
+    #[test]
+    fn enumeration_finds_the_held_out_answer_and_nothing_the_feedback_rules_out() {
+        for (guesses, answer) in [
+            // S, T, A and R pinned, the last letter open
+            (&["crane", "stale", "start"][..], "stark"),
+            // Three letters pinned and two copies of E required
+            (&["geese", "lever"], "fever"),
+            // Yellows only: every letter's place is still open
+            (&["ghost", "bumpy"], "tombs"),
+        ] {
+            let (game, alphabet) = game(guesses, answer);
+            let found = enumerate(&game.letter_constraints(), &alphabet, MAX_RECONSTRUCTIONS)
+                .expect("over the limit");
+            assert!(found.iter().any(|word| word == answer), "{}", answer);
+            assert_eq!(found, brute_force(&game, &alphabet), "{}", answer);
+        }
+    }
+
+    #[test]
+    fn enumeration_gives_up_above_the_limit() {
+        let alphabet: BTreeSet<char> = ('a'..='z').collect();
+        assert_eq!(enumerate(&BTreeMap::new(), &alphabet, 1000), None);
+        let (game, _) = game(&["crane", "stale", "start"], "stark");
+        let found = enumerate(&game.letter_constraints(), &alphabet, 1000).unwrap();
+        assert!(found.len() < 26 && found.contains(&"stark".to_string()));
+    }
+
+    #[test]
+    fn ranking_follows_the_positional_letter_counts() {
+        let stats = LetterStats::from_words(&["stack", "stark", "stalk", "shark"]);
+        let words = ["starx", "stark", "stack"].map(String::from);
+        let ranked: Vec<String> = rank(&words, &stats).into_iter().map(|(w, _)| w).collect();
+        assert_eq!(ranked, ["stark", "stack", "starx"]);
+    }
+}
@@ -0,0 +1,251 @@
+use crate::strategy::MAX_GUESSES;
+
+/// What a report reveals.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportMode {
+    Full,
+    /// No words or letters: feedback squares and candidate counts only.
+    SpoilerFree,
+}
+
+/// One guess as the report shows it.
+pub struct ReportTurn {
+    pub word: String,
+    /// Feedback in `w`/`m`/`c` form.
+    pub pattern: String,
+    /// Candidates left before and after the guess, when known.
+    pub candidates: Option<(usize, usize)>,
+    /// The solver's top suggestion for this turn, when known.
+    pub suggestion: Option<String>,
+    /// Where the answer ranked by frequency after the guess, when the answer is known.
+    pub answer_rank: Option<usize>,
+    /// Seconds the player spent on the guess, when it was timed.
+    pub think_seconds: Option<f64>,
+}
+
+/// Plain-text report of a game for pasting into a chat: the guesses with their
+/// feedback and candidate counts, how often they followed the solver, the answer
+/// with its frequency rank after each guess, the time spent on each guess, and the
+/// share grid. Every line stays under 80 columns. `hints` are the hints taken in a
+/// `--assist-on-demand` game, appended to the result as `(2 hints)`.
+pub fn format_report(
+    turns: &[ReportTurn],
+    answer: Option<&str>,
+    mode: ReportMode,
+    hints: Option<usize>,
+) -> String {
+    let solved = turns.last().is_some_and(|t| t.pattern == "ccccc");
+    let result = if solved {
+        format!("solved in {}/{}", turns.len(), MAX_GUESSES)
+    } else if turns.len() >= MAX_GUESSES {
+        format!("X/{}", MAX_GUESSES)
+    } else {
+        format!("in progress after {} guess(es)", turns.len())
+    };
+    let hints = match hints {
+        Some(1) => " (1 hint)".to_string(),
+        Some(hints) => format!(" ({} hints)", hints),
+        None => String::new(),
+    };
+    let mut text = format!("Wordle report: {}{}\n", result, hints);
+
+    for (i, turn) in turns.iter().enumerate() {
+        let counts = match turn.candidates {
+            Some((before, after)) => format!("{:>6} -> {}", before, after),
+            None => String::new(),
+        };
+        let line = match mode {
+            ReportMode::Full => format!(
+                "{:>2}. {}  {}  {}",
+                i + 1,
+                turn.word.to_uppercase(),
+                turn.pattern,
+                counts
+            ),
+            ReportMode::SpoilerFree => format!("{:>2}. {}", i + 1, counts),
+        };
+        text.push_str(line.trim_end());
+        text.push('\n');
+    }
+
+    let compared: Vec<(usize, &ReportTurn, &str)> = turns
+        .iter()
+        .enumerate()
+        .filter_map(|(i, t)| t.suggestion.as_deref().map(|s| (i, t, s)))
+        .collect();
+    if !compared.is_empty() {
+        let agreed = compared.iter().filter(|(_, t, s)| t.word == *s).count();
+        text.push_str(&format!(
+            "\nBot agreement: {} of {} guesses matched its top pick\n",
+            agreed,
+            compared.len()
+        ));
+        if mode == ReportMode::Full {
+            for (i, turn, suggestion) in &compared {
+                let note = if turn.word == *suggestion {
+                    "matches".to_string()
+                } else {
+                    format!("bot: {}", suggestion.to_uppercase())
+                };
+                text.push_str(&format!(
+                    "{:>2}. {}  {}\n",
+                    i + 1,
+                    turn.word.to_uppercase(),
+                    note
+                ));
+            }
+        }
+    }
+
+    if let Some(answer) = answer.filter(|_| mode == ReportMode::Full) {
+        text.push_str(&format!("Answer: {}\n", answer.to_uppercase()));
+    }
+    let ranks: Option<Vec<usize>> = turns.iter().map(|t| t.answer_rank).collect();
+    if let Some(ranks) = ranks.filter(|ranks| !ranks.is_empty()) {
+        text.push_str(&format!(
+            "Answer frequency rank: {}\n",
+            format_trajectory(&ranks, solved)
+        ));
+    }
+    let thinks: Option<Vec<f64>> = turns.iter().map(|t| t.think_seconds).collect();
+    if let Some(thinks) = thinks.filter(|thinks| !thinks.is_empty()) {
+        text.push_str(&format!(
+            "Think time: {} (avg {:.1}s)\n",
+            format_think_times(&thinks),
+            thinks.iter().sum::<f64>() / thinks.len() as f64
+        ));
+    }
+
+    if !turns.is_empty() {
+        text.push('\n');
+        for turn in turns {
+            text.push_str(&share_row(&turn.pattern));
+            text.push('\n');
+        }
+    }
+    text
+}
+
+/// The answer's rank after each guess, e.g. `#412 → #88 → #3 → guessed` for a game
+/// won with the fourth guess.
+pub fn format_trajectory(ranks: &[usize], won: bool) -> String {
+    let mut steps: Vec<String> = ranks.iter().map(|rank| format!("#{}", rank)).collect();
+    if won {
+        steps.pop();
+        steps.push("guessed".to_string());
+    }
+    steps.join(" → ")
+}
+
+/// Seconds per guess, e.g. `4.2s, 12.0s, 3.1s`.
+fn format_think_times(seconds: &[f64]) -> String {
+    seconds
+        .iter()
+        .map(|s| format!("{:.1}s", s))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// The share block of a game: `Wordle 4/6` (`X/6` when lost, `?/6` while unfinished),
+/// a blank line and one row of squares per guess.
+pub fn share_block(patterns: &[String]) -> String {
+    let solved = patterns.last().is_some_and(|p| p == "ccccc");
+    let score = if solved {
+        patterns.len().to_string()
+    } else if patterns.len() >= MAX_GUESSES {
+        "X".to_string()
+    } else {
+        "?".to_string()
+    };
+    let mut text = format!("Wordle {}/{}\n\n", score, MAX_GUESSES);
+    for pattern in patterns {
+        text.push_str(&share_row(pattern));
+        text.push('\n');
+    }
+    text
+}
+
+/// Feedback as share-grid squares.
+pub fn share_row(pattern: &str) -> String {
+    pattern
+        .chars()
+        .map(|state| match state {
+            'c' => '🟩',
+            'm' => '🟨',
+            _ => '⬛',
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn turn(word: &str, pattern: &str, candidates: (usize, usize), suggestion: &str) -> ReportTurn {
+        ReportTurn {
+            word: word.to_string(),
+            pattern: pattern.to_string(),
+            candidates: Some(candidates),
+            suggestion: Some(suggestion.to_string()),
+            answer_rank: None,
+            think_seconds: None,
+        }
+    }
+
+    /// The CRIER game of the fixture list, with a second guess off the bot's pick.
+    fn crier_game() -> Vec<ReportTurn> {
+        let mut turns = vec![
+            turn("arose", "wcwwm", (193, 3), "arose"),
+            turn("fiber", "wmwcc", (3, 1), "brief"),
+            turn("crier", "ccccc", (1, 1), "crier"),
+        ];
+        for (turn, (rank, seconds)) in turns.iter_mut().zip([(57, 4.2), (1, 12.0), (1, 3.1)]) {
+            turn.answer_rank = Some(rank);
+            turn.think_seconds = Some(seconds);
+        }
+        turns
+    }
+
+    fn assert_chat_width(report: &str) {
+        for line in report.lines() {
+            assert!(line.chars().count() < 80, "too wide: {:?}", line);
+        }
+    }
+
+    #[test]
+    fn full_report_matches_the_golden_file() {
+        let report = format_report(&crier_game(), Some("crier"), ReportMode::Full, None);
+        assert_eq!(report, include_str!("../tests/fixtures/report_full.txt"));
+        assert_chat_width(&report);
+    }
+
+    #[test]
+    fn spoiler_free_report_matches_the_golden_file() {
+        let report = format_report(
+            &crier_game(),
+            Some("crier"),
+            ReportMode::SpoilerFree,
+            Some(2),
+        );
+        assert_eq!(
+            report,
+            include_str!("../tests/fixtures/report_spoiler_free.txt")
+        );
+        assert_chat_width(&report);
+        for word in ["AROSE", "FIBER", "CRIER", "BRIEF"] {
+            assert!(!report.to_uppercase().contains(word), "{} revealed", word);
+        }
+    }
+
+    #[test]
+    fn unfinished_and_lost_games_are_labelled() {
+        let turns = vec![turn("arose", "wcwwm", (193, 3), "arose")];
+        assert!(format_report(&turns, None, ReportMode::Full, None)
+            .starts_with("Wordle report: in progress after 1 guess(es)\n"));
+        let lost: Vec<ReportTurn> = (0..MAX_GUESSES)
+            .map(|_| turn("arose", "wwwww", (1, 1), "arose"))
+            .collect();
+        assert!(format_report(&lost, None, ReportMode::Full, None)
+            .starts_with(&format!("Wordle report: X/{}\n", MAX_GUESSES)));
+    }
+}
@@ -0,0 +1,234 @@
+use crate::analysis;
+use crate::game::{self, GameData, LineData};
+use crate::ranking::rank_words;
+use crate::report::{self, ReportMode, ReportTurn};
+use crate::solver::Solver;
+use crate::strategy::MAX_GUESSES;
+use crate::util;
+use crate::wordlist::PoolOptions;
+use anyhow::{anyhow, Result};
+use std::fs;
+
+/// Squares of a share grid in every theme: absent in dark and light mode, present and
+/// correct in the normal and high-contrast colors.
+const SHARE_SQUARES: &[char] = &['⬛', '⬜', '🟨', '🟩', '🟦', '🟧'];
+
+/// Fitting words listed for a row whose guess can't be told from its feedback.
+const AMBIGUOUS_EXAMPLES: usize = 5;
+
+/// Feedback rows of a pasted share block in `wmc` form. The header and any other
+/// line without squares is skipped.
+pub fn parse_share(text: &str) -> Result<Vec<String>> {
+    let mut rows = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        if !line.chars().any(|c| SHARE_SQUARES.contains(&c)) {
+            continue;
+        }
+        let row = game::parse_pattern(line).ok_or_else(|| {
+            anyhow!(
+                "Line {} is not a row of five squares: '{}'",
+                number + 1,
+                line.trim()
+            )
+        })?;
+        rows.push(row);
+    }
+
+    if rows.is_empty() {
+        return Err(anyhow!(
+            "No share rows found. Paste the block with its rows of squares."
+        ));
+    }
+    if rows.len() > MAX_GUESSES {
+        return Err(anyhow!(
+            "The share block has {} rows, more than {} guesses.",
+            rows.len(),
+            MAX_GUESSES
+        ));
+    }
+    Ok(rows)
+}
+
+/// How the guess behind a row was found.
+enum Source {
+    /// Given with `--guess`.
+    Given,
+    /// The only word giving the row's feedback.
+    Unique,
+    /// The most plausible of the fitting words, ranked best first.
+    Assumed(Vec<String>),
+}
+
+/// Reconstructs the guesses behind a share block played against `answer`, reports
+/// rows whose guess is ambiguous, and prints the game's report with the solver's
+/// picks. `guesses` are the actual guesses of the first rows, if known.
+pub fn import(solver: &mut Solver, text: &str, answer: &str, guesses: &[String]) -> Result<()> {
+    let answer = answer.to_lowercase();
+    if !solver.all_words.contains(&answer) {
+        return Err(anyhow!("'{}' is not in the wordlist.", answer));
+    }
+    let rows = parse_share(text)?;
+    if guesses.len() > rows.len() {
+        return Err(anyhow!(
+            "{} guesses given for {} rows.",
+            guesses.len(),
+            rows.len()
+        ));
+    }
+
+    let mut turns: Vec<(String, String, Source)> = Vec::new();
+    for (i, row) in rows.iter().enumerate() {
+        let feedback = |word: &str| game::grade(word, &answer).to_string();
+        let (word, source) = if let Some(guess) = guesses.get(i) {
+            let guess = guess.to_lowercase();
+            if guess.chars().count() != 5 {
+                return Err(anyhow!("Guess {} '{}' must be 5 letters.", i + 1, guess));
+            }
+            if !solver.all_words.contains(&guess) {
+                return Err(anyhow!(
+                    "Guess {} '{}' is not in the wordlist.",
+                    i + 1,
+                    guess
+                ));
+            }
+            if feedback(&guess) != *row {
+                return Err(anyhow!(
+                    "Guess {} '{}' gives {} against '{}', but row {} is {}.",
+                    i + 1,
+                    guess,
+                    feedback(&guess),
+                    answer,
+                    i + 1,
+                    row
+                ));
+            }
+            (guess, Source::Given)
+        } else if row == "ccccc" {
+            (answer.clone(), Source::Unique)
+        } else {
+            let fitting: Vec<&str> = solver
+                .all_words
+                .iter()
+                .filter(|w| feedback(w) == *row)
+                .map(|w| w.as_str())
+                .collect();
+            match fitting.len() {
+                0 => {
+                    return Err(anyhow!(
+                        "No word gives row {} ({}) against '{}'.",
+                        i + 1,
+                        row,
+                        answer
+                    ))
+                }
+                1 => (fitting[0].to_string(), Source::Unique),
+                _ => {
+                    let ranked = rank_words(&fitting, &solver.stats_json)?.words();
+                    (ranked[0].clone(), Source::Assumed(ranked))
+                }
+            }
+        };
+        turns.push((word, row.clone(), source));
+    }
+
+    println!("Guesses against {}:", answer.to_uppercase());
+    for (i, (word, row, source)) in turns.iter().enumerate() {
+        let note = match source {
+            Source::Given => "given".to_string(),
+            Source::Unique => "the only word that fits".to_string(),
+            Source::Assumed(fitting) => format!(
+                "ambiguous: {} words fit, e.g. {}",
+                fitting.len(),
+                fitting[1..fitting.len().min(AMBIGUOUS_EXAMPLES + 1)].join(", ")
+            ),
+        };
+        println!(
+            "{:>2}. {}  {}  ({})",
+            i + 1,
+            report::share_row(row),
+            word.to_uppercase(),
+            note
+        );
+    }
+    let ambiguous = turns
+        .iter()
+        .filter(|(_, _, source)| matches!(source, Source::Assumed(_)))
+        .count();
+    if ambiguous > 0 {
+        println!(
+            "{} row(s) can't be told apart from their feedback; the most plausible word is assumed and not compared with the solver. Pass the real guesses with --guess to review them.",
+            ambiguous
+        );
+    }
+    println!();
+
+    let stats_json = solver.stats_json.clone();
+    let weights = util::read_solver_config()?;
+    solver.game.reset();
+    solver.reset_candidates();
+    let mut report_turns = Vec::new();
+    for (word, row, source) in turns {
+        let before = solver.current_words.len();
+        let suggestion = match source {
+            Source::Assumed(_) => None,
+            _ => Some(solver.next_guess(&stats_json, &weights)?),
+        };
+        solver.game.add_line(&word, &row);
+        let after = if row == "ccccc" {
+            1
+        } else {
+            solver.update_wordlist();
+            solver.current_words.len()
+        };
+        report_turns.push(ReportTurn {
+            word,
+            pattern: row,
+            candidates: Some((before, after)),
+            suggestion,
+            answer_rank: None,
+            think_seconds: None,
+        });
+    }
+
+    print!(
+        "{}",
+        report::format_report(&report_turns, Some(&answer), ReportMode::Full, None)
+    );
+    Ok(())
+}
+
+fn read_game(path: &str) -> Result<GameData> {
+    let json = fs::read_to_string(path).map_err(|e| anyhow!("Failed to read {}: {}", path, e))?;
+    GameData::from_json(&json)
+}
+
+/// The share block of a game saved with `save <file>`.
+pub fn export(path: &str) -> Result<String> {
+    let game = read_game(path)?;
+    let patterns: Vec<String> = game.lines.iter().map(LineData::pattern).collect();
+    Ok(report::share_block(&patterns))
+}
+
+/// How close the other candidates came to the answer in a game saved with
+/// `save <file>`, replayed on the default pool. `answer` is needed unless the saved
+/// game is solved.
+pub fn export_near_misses(path: &str, answer: Option<&str>) -> Result<String> {
+    let game = read_game(path)?;
+    let solved = game
+        .lines
+        .last()
+        .filter(|line| line.pattern() == "ccccc")
+        .map(|line| line.word.clone());
+    let answer = answer.map(str::to_lowercase).or(solved).ok_or_else(|| {
+        anyhow!(
+            "{} is not solved; pass its answer with --answer <word>.",
+            path
+        )
+    })?;
+    let mut solver = Solver::with_pool(PoolOptions::default())?;
+    solver.game = game;
+    Ok(analysis::near_miss_report(
+        &solver.candidate_trace(),
+        &answer,
+    ))
+}
@@ -1,28 +1,292 @@
-use crate::game::GameData;
+use crate::analysis::{self, LetterStats};
+use crate::candidates::{encode_pattern, CandidateSet};
+use crate::perf::{self, PerfReport, Phase};
+use crate::ranking::rank_words;
 use crate::solver::Solver;
-use crate::stats::SimulationResults;
-use crate::trap;
+use crate::stats::loss_taxonomy::{self, LossTrace};
+use crate::stats::{GameLog, PairedResults, SimulationResults};
+use crate::strategy::{self, Strategy, MAX_GUESSES};
+use crate::stratify::{self, Stratification};
 use crate::util;
-use anyhow::Result;
+use crate::wordlist::PoolOptions;
+use anyhow::{anyhow, Result};
+use log::{debug, trace, warn};
 use rand::prelude::*;
-use rand::rngs::ThreadRng;
+use rand::rngs::StdRng;
+use std::collections::VecDeque;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, Instant};
 
-pub fn run_simulation(num_runs: usize) -> Result<()> {
-    let solver = Solver::new()?;
-    let all_words = solver.all_words.clone();
+pub struct SimulationOptions {
+    pub pool: PoolOptions,
+    pub strategy: Strategy,
+    /// Strategy for the last two guesses, if different.
+    pub endgame: Option<Strategy>,
+    /// Probability per game that one feedback cell is entered wrong.
+    pub error_rate: f64,
+    /// Seed for target and corruption draws.
+    pub seed: u64,
+    /// Verify every filtering pass and fail when the filter drops a consistent word.
+    pub check_filter: bool,
+    /// Time budget per suggestion; unlimited unless `--think-ms` is given.
+    pub think_limit: Option<Duration>,
+    /// Play only this game (1-based) of the run, traced (`--replay-game`).
+    pub replay_game: Option<usize>,
+    /// CSV file receiving one row per game as it finishes (`--output`).
+    pub output: Option<PathBuf>,
+    /// Time the phases of each turn and report throughput (`--perf`).
+    pub perf: bool,
+    /// Sample the targets per difficulty bucket (`--stratified`).
+    pub stratified: Option<Stratification>,
+    /// Runs of more games show their estimated duration and ask before starting;
+    /// `None` with `--yes`.
+    pub confirm_above: Option<usize>,
+    /// Targets must not be like any of this many previous ones (`--recent-window`);
+    /// 0 turns the check off.
+    pub recent_window: usize,
+    /// Opening guess of every game instead of the bot's (`--human-opener`); the
+    /// games are then replayed with the bot's opener for comparison.
+    pub human_opener: Option<String>,
+}
+
+pub struct RaceOptions {
+    pub pool: PoolOptions,
+    pub a: Strategy,
+    pub b: Strategy,
+    /// Number of targets both strategies play.
+    pub sample: usize,
+    pub seed: u64,
+    pub think_limit: Option<Duration>,
+}
 
-    let stats_json = util::read_letter_stats()?;
+/// Default number of games above which `simulate` asks before starting.
+pub const DEFAULT_CONFIRM_ABOVE: usize = 100_000;
 
-    let mut results = SimulationResults::new();
-    let mut rng = ThreadRng::default();
+/// Games timed to estimate how long a run will take.
+pub const CALIBRATION_GAMES: usize = 50;
 
-    println!("Starting simulation of {} games...", num_runs);
+/// Random source of game `index` (0-based) of a run seeded with `seed`. Every game
+/// gets its own stream, hashed from the seed and its index, so any game can be
+/// replayed without playing the games before it.
+fn game_rng(seed: u64, index: usize) -> StdRng {
+    StdRng::seed_from_u64(splitmix64(seed ^ splitmix64(index as u64)))
+}
 
-    let target_words = if all_words.len() > 10657 {
-        &all_words[10657..]
+/// SplitMix64 finalizer, a cheap bijective hash of a 64-bit value.
+fn splitmix64(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9e3779b97f4a7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+/// Target and feedback corruption of game `index`, a pure function of its arguments.
+fn draw_game(
+    targets: &[String],
+    seed: u64,
+    index: usize,
+    error_rate: f64,
+) -> (&String, Option<Corruption>) {
+    let mut rng = game_rng(seed, index);
+    let target = targets.choose(&mut rng).expect("Target word list is empty");
+    let corruption = if rng.random_bool(error_rate.clamp(0.0, 1.0)) {
+        Some(draw_corruption(&mut rng))
     } else {
-        &all_words[..]
+        None
     };
+    (target, corruption)
+}
+
+/// Redraws of a game's target before `--recent-window` keeps one like a recent
+/// target, so a small answer list can't stall the run.
+pub const MAX_REDRAWS: usize = 50;
+
+/// The last targets of a `--recent-window` run. A new target that has
+/// `analysis::SIMILAR_POSITIONS` letters in place in common with one of them is
+/// drawn again, the way a daily puzzle avoids answers like the last few.
+struct RecentTargets {
+    size: usize,
+    recent: VecDeque<String>,
+    /// Draws rejected for being like a recent target.
+    rejected: usize,
+    /// Games that kept a target like a recent one after `MAX_REDRAWS` redraws.
+    fallbacks: usize,
+}
+
+impl RecentTargets {
+    fn new(size: usize) -> Self {
+        Self {
+            size,
+            recent: VecDeque::with_capacity(size),
+            rejected: 0,
+            fallbacks: 0,
+        }
+    }
+
+    fn is_recent(&self, word: &str) -> bool {
+        self.recent
+            .iter()
+            .any(|recent| analysis::words_similar(recent, word, analysis::SIMILAR_POSITIONS))
+    }
+
+    /// The target of game `index`: `first` unless it is like a recent target, else
+    /// the first redraw that isn't. Redraws come from a stream of their own, so the
+    /// game's feedback corruption stays the one drawn with `first`.
+    fn choose<'a>(
+        &mut self,
+        targets: &'a [String],
+        first: &'a String,
+        seed: u64,
+        index: usize,
+    ) -> &'a String {
+        if self.size == 0 {
+            return first;
+        }
+        let mut rng = game_rng(splitmix64(seed), index);
+        let mut target = first;
+        let mut redraws = 0;
+        while self.is_recent(target) {
+            self.rejected += 1;
+            if redraws == MAX_REDRAWS {
+                self.fallbacks += 1;
+                break;
+            }
+            target = targets.choose(&mut rng).expect("Target word list is empty");
+            redraws += 1;
+        }
+        if self.recent.len() == self.size {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(target.clone());
+        target
+    }
+}
+
+/// Plays game `game` (1-based) of the run alone, exactly as the full run would, with
+/// trace logging on, and prints each guess with its feedback and the candidates left.
+fn replay_game(
+    solver: &mut Solver,
+    opener: &str,
+    weights: &[(f64, f64, f64)],
+    seed: u64,
+    game: usize,
+    target: &str,
+    corruption: Option<Corruption>,
+) -> Result<()> {
+    println!(
+        "Replaying game {} (seed {}): target '{}'",
+        game, seed, target
+    );
+    if let Some(c) = corruption {
+        println!(
+            "Feedback of guess {} is entered wrong in cell {}",
+            c.turn + 1,
+            c.cell + 1
+        );
+    }
+
+    log::set_max_level(log::LevelFilter::Trace);
+    let stats_json = solver.stats_json.clone();
+    let outcome = simulate_game(solver, opener, target, &stats_json, weights, corruption)?;
+
+    for (i, line) in solver.game.lines.iter().enumerate() {
+        println!(
+            "{}. {}  {}  {} -> {} candidates",
+            i + 1,
+            line.word,
+            line.pattern(),
+            outcome.candidate_counts[i],
+            outcome.candidate_counts[i + 1]
+        );
+    }
+    if outcome.guesses <= MAX_GUESSES {
+        println!("{}. {}  ccccc", outcome.guesses, target);
+        println!("Solved in {} guesses.", outcome.guesses);
+    } else if outcome.repeated_guess {
+        println!("Lost: the strategy repeated a guess.");
+    } else if outcome.contradiction {
+        println!("Lost: the feedback left no candidate.");
+    } else {
+        println!("Lost after {} guesses.", MAX_GUESSES);
+    }
+    if corruption.is_some() && !outcome.corrupted {
+        println!("The game ended before the mistake, so all feedback was entered correctly.");
+    }
+    Ok(())
+}
+
+/// CSV row of game `number` for the `--output` log. A loss counts as `MAX_GUESSES + 1`
+/// guesses.
+fn game_row(number: usize, target: &str, outcome: &GameOutcome) -> String {
+    let candidates: Vec<String> = outcome
+        .candidate_counts
+        .iter()
+        .map(|c| c.to_string())
+        .collect();
+    format!(
+        "{},{},{},{},{},{},{},{}",
+        number,
+        target,
+        outcome.guesses.min(MAX_GUESSES + 1),
+        outcome.guesses <= MAX_GUESSES,
+        outcome.corrupted,
+        outcome.contradiction,
+        outcome.repeated_guess,
+        candidates.join(" ")
+    )
+}
+
+/// A single wrongly entered feedback cell, drawn before the game starts.
+#[derive(Clone, Copy)]
+struct Corruption {
+    turn: usize,
+    cell: usize,
+    /// Picks which of the two wrong states replaces the true one.
+    pick_second: bool,
+}
+
+struct GameOutcome {
+    guesses: usize,
+    /// The feedback left no candidate at some point, i.e. it was self-contradictory.
+    contradiction: bool,
+    /// Possible answers before each guess, followed by the count after the last one.
+    candidate_counts: Vec<usize>,
+    /// The strategy suggested a word it had already guessed, which ends the game as a
+    /// loss since it points at a strategy bug.
+    repeated_guess: bool,
+    /// The game lasted until the turn of its feedback mistake, which was entered.
+    corrupted: bool,
+}
+
+pub fn run_simulation(num_runs: usize, options: SimulationOptions) -> Result<()> {
+    let setup_started = Instant::now();
+    #[cfg(feature = "dhat")]
+    let _profiler = options
+        .perf
+        .then(|| dhat::Profiler::builder().testing().build());
+    if options.perf {
+        perf::enable();
+    }
+    let mut solver = Solver::with_pool(options.pool)?;
+    solver.strategy = options.strategy;
+    solver.endgame = options.endgame;
+    solver.check_filter = options.check_filter;
+    solver.think_limit = options.think_limit;
+    let stats_json = solver.stats_json.clone();
+
+    let mut results = SimulationResults::new(MAX_GUESSES);
+
+    let strategy_name = match solver.endgame {
+        Some(endgame) => format!(
+            "{} strategy, {} endgame",
+            solver.strategy.name(),
+            endgame.name()
+        ),
+        None => format!("{} strategy", solver.strategy.name()),
+    };
+    let target_words = solver.answer_words.clone();
 
     if target_words.is_empty() {
         return Err(anyhow::anyhow!(
@@ -30,78 +294,918 @@ pub fn run_simulation(num_runs: usize) -> Result<()> {
         ));
     }
 
+    if let Some(word) = options
+        .human_opener
+        .as_ref()
+        .filter(|word| !solver.all_words.contains(word))
+    {
+        return Err(anyhow!("--human-opener '{}' is not in the wordlist.", word));
+    }
+
     let weights = util::read_solver_config()?;
 
+    // Bucket of every game when the targets are stratified, played bucket by bucket
+    let strata = match &options.stratified {
+        Some(stratification) => stratify::build_strata(&target_words, stratification, num_runs)?,
+        None => Vec::new(),
+    };
+    let plan: Vec<usize> = strata
+        .iter()
+        .enumerate()
+        .flat_map(|(bucket, stratum)| std::iter::repeat_n(bucket, stratum.games))
+        .collect();
+    let num_runs = if strata.is_empty() {
+        num_runs
+    } else {
+        plan.len()
+    };
+    let targets_of = |game: usize| match plan.get(game) {
+        Some(&bucket) => &strata[bucket].words,
+        None => &target_words,
+    };
+
+    if options.replay_game.is_none() {
+        println!(
+            "Starting simulation of {} games ({}, seed {})...",
+            num_runs, strategy_name, options.seed
+        );
+        for stratum in &strata {
+            println!(
+                "  bucket {}: {} games from {} answers",
+                stratum.label,
+                stratum.games,
+                stratum.words.len()
+            );
+        }
+        if options.recent_window > 0 {
+            println!(
+                "  targets with {} letters in place in common with one of the last {} are drawn again",
+                analysis::SIMILAR_POSITIONS,
+                options.recent_window
+            );
+        }
+        if util::background() {
+            println!("Running in the background: lower priority, one thread.");
+        }
+    }
+    let mut recent = RecentTargets::new(options.recent_window);
+
+    // The opening guess only depends on the wordlist, so rank it once for all games
+    let bot_opener = solver.get_top_suggestion_silent(&stats_json, None)?;
+    let opener = options
+        .human_opener
+        .clone()
+        .unwrap_or_else(|| bot_opener.clone());
+    // Each game as played with the human opener, to replay with the bot's
+    let mut paired_games: Option<Vec<(&str, Option<Corruption>, usize)>> = options
+        .human_opener
+        .as_ref()
+        .filter(|word| **word != bot_opener)
+        .map(|_| Vec::new());
+    if options.replay_game.is_none() && options.human_opener.is_some() {
+        println!(
+            "  every game opens with {} instead of the bot's {}",
+            opener.to_uppercase(),
+            bot_opener.to_uppercase()
+        );
+    }
+    // Opener of the minimax replays used to classify losses, ranked on the first loss
+    let mut minimax_opener = None;
+
+    if let (None, Some(limit)) = (options.replay_game, options.confirm_above) {
+        if num_runs > limit {
+            let per_game =
+                time_per_game(&mut solver, &opener, &target_words, &weights, options.seed)?;
+            if !confirm_long_run(num_runs, limit, estimate_duration(per_game, num_runs, 1))? {
+                println!("Simulation cancelled.");
+                return Ok(());
+            }
+        }
+    }
+
+    if let Some(game) = options.replay_game {
+        if game > num_runs {
+            return Err(anyhow::anyhow!(
+                "--replay-game must be between 1 and {}.",
+                num_runs
+            ));
+        }
+        // The window depends on the targets before, which are drawn but not played
+        for i in 0..game - 1 {
+            let (first, _) = draw_game(targets_of(i), options.seed, i, options.error_rate);
+            recent.choose(targets_of(i), first, options.seed, i);
+        }
+        let targets = targets_of(game - 1);
+        let (first, corruption) = draw_game(targets, options.seed, game - 1, options.error_rate);
+        let target = recent.choose(targets, first, options.seed, game - 1);
+        return replay_game(
+            &mut solver,
+            &opener,
+            &weights,
+            options.seed,
+            game,
+            target,
+            corruption,
+        );
+    }
+
+    let mut log = match &options.output {
+        Some(path) => Some(GameLog::create(
+            path,
+            "game,target,guesses,won,corrupted,contradiction,repeated_guess,candidates",
+        )?),
+        None => None,
+    };
+
+    let mut bucket_results: Vec<SimulationResults> = strata
+        .iter()
+        .map(|_| SimulationResults::new(MAX_GUESSES))
+        .collect();
+
+    let setup = setup_started.elapsed();
+    let allocations_before = options.perf.then(perf::allocations).flatten();
+    let games_started = Instant::now();
+    let mut steady_started = games_started;
     for i in 0..num_runs {
-        let target_word = target_words
-            .choose(&mut rng)
-            .expect("Target word list is empty");
+        if i == num_runs / 2 {
+            steady_started = Instant::now();
+        }
+        util::background_yield(i);
+
+        let (target_word, corruption) =
+            draw_game(targets_of(i), options.seed, i, options.error_rate);
+        let target_word = recent.choose(targets_of(i), target_word, options.seed, i);
 
         // Use custom simulation loop here to inject trap catching logic
-        let num_guesses = simulate_game(&solver, target_word.to_string(), &stats_json, &weights)?;
+        let outcome = simulate_game(
+            &mut solver,
+            &opener,
+            target_word,
+            &stats_json,
+            &weights,
+            corruption,
+        )?;
+
+        debug!(
+            "Game {}: '{}' took {} guess(es), candidates {:?}",
+            i + 1,
+            target_word,
+            outcome.guesses,
+            outcome.candidate_counts
+        );
+
+        if outcome.repeated_guess {
+            results.record_repeated_guess();
+        }
+        if let Some(games) = &mut paired_games {
+            games.push((target_word, corruption, outcome.guesses));
+        }
+        if let Some(&bucket) = plan.get(i) {
+            bucket_results[bucket].record_game(outcome.guesses);
+        }
+        if let Some(log) = &mut log {
+            log.record(&game_row(i + 1, target_word, &outcome))?;
+        }
+
+        // A game won before the turn of its mistake was played with clean feedback
+        if outcome.corrupted {
+            // Replay the same target with clean feedback to see whether the mistake cost the game
+            let lost_to_corruption = if outcome.guesses > MAX_GUESSES {
+                let clean = simulate_game(
+                    &mut solver,
+                    &opener,
+                    target_word,
+                    &stats_json,
+                    &weights,
+                    None,
+                )?;
+                clean.guesses <= MAX_GUESSES
+            } else {
+                false
+            };
+            results.record_corrupted_game(
+                outcome.guesses,
+                lost_to_corruption,
+                outcome.contradiction,
+            );
+        } else {
+            results.record_game(outcome.guesses);
+            results.record_candidate_counts(&outcome.candidate_counts);
 
-        results.record_game(num_guesses);
+            if outcome.guesses > MAX_GUESSES && !outcome.repeated_guess {
+                let worse_turns = worse_bucket_turns(&mut solver);
+                let kind = loss_taxonomy::classify(&loss_trace(&mut solver, target_word)?);
+                let forced = minimax_replay_lost(
+                    &mut solver,
+                    &mut minimax_opener,
+                    target_word,
+                    &stats_json,
+                    &weights,
+                )?;
+                debug!(
+                    "Lost '{}': {} turn(s) with a larger worst case than necessary, {}, {:?}",
+                    target_word,
+                    worse_turns,
+                    if forced { "forced" } else { "strategy error" },
+                    kind
+                );
+                results.record_loss_analysis(forced, worse_turns, kind);
+            }
+        }
 
         if (i + 1) % 100 == 0 {
             println!("... {} games simulated ...", i + 1);
         }
     }
 
+    if options.perf {
+        let allocations = allocations_before
+            .zip(perf::allocations())
+            .map(|(before, after)| perf::Allocations {
+                blocks: after.blocks - before.blocks,
+                bytes: after.bytes - before.bytes,
+            });
+        results.set_perf(PerfReport {
+            games: num_runs,
+            setup,
+            wall: games_started.elapsed(),
+            steady: steady_started.elapsed(),
+            counters: perf::take(),
+            setup_allocations: allocations_before,
+            allocations,
+        });
+    }
+
     println!("\nSimulation finished.");
+    if options.recent_window > 0 {
+        println!(
+            "Recent-answer window of {}: {} draw(s) rejected, {} game(s) kept a target like a recent one after {} redraws.",
+            options.recent_window, recent.rejected, recent.fallbacks, MAX_REDRAWS
+        );
+    }
     results.print_summary();
+    if !strata.is_empty() {
+        stratify::print_strata(&strata, &bucket_results);
+    }
+    if let Some(games) = &paired_games {
+        let comparison = compare_openers(
+            &mut solver,
+            (&opener, &bot_opener),
+            games,
+            &weights,
+            options.seed,
+        )?;
+        print!("{}", comparison.to_text());
+        println!(
+            "{}",
+            opener_cost(&opener, &bot_opener, comparison.average_difference())
+        );
+    } else if options.human_opener.is_some() {
+        println!(
+            "{} is the bot's own opener, so there is nothing to compare.",
+            opener.to_uppercase()
+        );
+    }
+    if let (Some(log), Some(path)) = (log, &options.output) {
+        let summary = log.finish(&results)?;
+        println!(
+            "Wrote {} game(s) to {} and the summary to {}.",
+            num_runs,
+            path.display(),
+            summary.display()
+        );
+    }
+
+    if options.check_filter {
+        if solver.filter_bugs > 0 {
+            return Err(anyhow::anyhow!(
+                "Filter check found {} wrongly eliminated word(s).",
+                solver.filter_bugs
+            ));
+        }
+        println!("Filter check: no wrongly eliminated words.");
+    }
 
     Ok(())
 }
 
+/// Replays `games` (target, corruption, guesses with the human opener) with the bot's
+/// opener and pairs the results: A is the human opener, B the bot's.
+fn compare_openers(
+    solver: &mut Solver,
+    (human, bot): (&str, &str),
+    games: &[(&str, Option<Corruption>, usize)],
+    weights: &[(f64, f64, f64)],
+    seed: u64,
+) -> Result<PairedResults> {
+    println!(
+        "\nReplaying the {} games with the bot's opener {}...",
+        games.len(),
+        bot.to_uppercase()
+    );
+    let stats_json = solver.stats_json.clone();
+    let mut comparison = PairedResults::new(
+        &format!("{} (yours)", human.to_uppercase()),
+        &format!("{} (bot)", bot.to_uppercase()),
+        seed,
+        MAX_GUESSES,
+    )
+    .titled("Opener comparison");
+    for (i, &(target, corruption, guesses)) in games.iter().enumerate() {
+        util::background_yield(i);
+        let outcome = simulate_game(solver, bot, target, &stats_json, weights, corruption)?;
+        comparison.record_game(target, guesses, outcome.guesses);
+    }
+    Ok(comparison)
+}
+
+/// The headline of an opener comparison, e.g. `Your opener ADIEU costs +0.21
+/// guesses on average against the bot's TARES.`
+fn opener_cost(human: &str, bot: &str, difference: f64) -> String {
+    let (human, bot) = (human.to_uppercase(), bot.to_uppercase());
+    if difference.abs() < 0.005 {
+        format!(
+            "Your opener {} does as well as the bot's {} on average.",
+            human, bot
+        )
+    } else if difference > 0.0 {
+        format!(
+            "Your opener {} costs {:+.2} guesses on average against the bot's {}.",
+            human, difference, bot
+        )
+    } else {
+        format!(
+            "Your opener {} saves {:.2} guesses on average against the bot's {}.",
+            human, -difference, bot
+        )
+    }
+}
+
+/// Plays one clean game against each of `targets` in order and returns the results.
+/// Used by the tuner, which needs paired comparisons on the same targets.
+pub fn simulate_targets(
+    solver: &mut Solver,
+    opener: &str,
+    targets: &[String],
+    weights: &[(f64, f64, f64)],
+) -> Result<SimulationResults> {
+    let stats_json = solver.stats_json.clone();
+    let mut results = SimulationResults::new(MAX_GUESSES);
+
+    for (i, target) in targets.iter().enumerate() {
+        util::background_yield(i);
+        let outcome = simulate_game(solver, opener, target, &stats_json, weights, None)?;
+        results.record_game(outcome.guesses);
+        results.record_candidate_counts(&outcome.candidate_counts);
+        if outcome.repeated_guess {
+            results.record_repeated_guess();
+        }
+    }
+
+    Ok(results)
+}
+
+/// Plays one clean game against `target` and returns each guess with its feedback,
+/// ending with the winning guess when the game was won.
+#[cfg(any(test, feature = "test-util"))]
+pub fn play_game_lines(
+    solver: &mut Solver,
+    target: &str,
+    weights: &[(f64, f64, f64)],
+) -> Result<Vec<(String, String)>> {
+    let stats_json = solver.stats_json.clone();
+    let opener = solver.get_top_suggestion_silent(&stats_json, None)?;
+    let outcome = simulate_game(solver, &opener, target, &stats_json, weights, None)?;
+    let mut lines: Vec<(String, String)> = solver
+        .game
+        .lines
+        .iter()
+        .map(|line| (line.word.clone(), line.pattern()))
+        .collect();
+    if outcome.guesses <= MAX_GUESSES {
+        lines.push((target.to_string(), "ccccc".to_string()));
+    }
+    Ok(lines)
+}
+
+/// Plays `options.sample` targets drawn from `options.seed` with both strategies and
+/// pairs the guess counts per target. The two sides play on their own threads.
+pub fn run_race(options: RaceOptions) -> Result<PairedResults> {
+    let mut solver = Solver::with_pool(options.pool)?;
+    solver.think_limit = options.think_limit;
+    if solver.answer_words.is_empty() {
+        return Err(anyhow::anyhow!(
+            "No target words available for the race. Ensure 'wordlist.txt' is correct."
+        ));
+    }
+
+    let targets: Vec<String> = (0..options.sample)
+        .map(|i| {
+            draw_game(&solver.answer_words, options.seed, i, 0.0)
+                .0
+                .clone()
+        })
+        .collect();
+    let weights = util::read_solver_config()?;
+
+    eprintln!(
+        "Racing {} against {} on {} games (seed {})...",
+        options.a.name(),
+        options.b.name(),
+        targets.len(),
+        options.seed
+    );
+
+    let mut side_b = solver.fork();
+    solver.strategy = options.a;
+    side_b.strategy = options.b;
+    let (a_guesses, b_guesses) = thread::scope(|scope| {
+        let a = scope.spawn(|| play_targets(&mut solver, &targets, &weights));
+        let b = scope.spawn(|| play_targets(&mut side_b, &targets, &weights));
+        (
+            a.join().expect("race worker panicked"),
+            b.join().expect("race worker panicked"),
+        )
+    });
+
+    let mut results = PairedResults::new(
+        options.a.name(),
+        options.b.name(),
+        options.seed,
+        MAX_GUESSES,
+    );
+    for ((target, a), b) in targets.iter().zip(a_guesses?).zip(b_guesses?) {
+        debug!("Race on '{}': {} guesses vs {}", target, a, b);
+        results.record_game(target, a, b);
+    }
+    Ok(results)
+}
+
+/// Average time `solver` takes per game, measured on `CALIBRATION_GAMES` games
+/// against targets drawn with `seed`. The games aren't recorded anywhere.
+pub fn time_per_game(
+    solver: &mut Solver,
+    opener: &str,
+    targets: &[String],
+    weights: &[(f64, f64, f64)],
+    seed: u64,
+) -> Result<Duration> {
+    let stats_json = solver.stats_json.clone();
+    let filter_bugs = solver.filter_bugs;
+    let started = Instant::now();
+    for index in 0..CALIBRATION_GAMES {
+        let (target, _) = draw_game(targets, seed, index, 0.0);
+        simulate_game(solver, opener, target, &stats_json, weights, None)?;
+    }
+    solver.filter_bugs = filter_bugs;
+    Ok(started.elapsed() / CALIBRATION_GAMES as u32)
+}
+
+/// Wall time of `games` games taking `per_game` each, spread over `threads` threads.
+pub fn estimate_duration(per_game: Duration, games: usize, threads: usize) -> Duration {
+    per_game.mul_f64(games as f64 / threads.max(1) as f64)
+}
+
+/// A duration rounded for estimates, e.g. `40s`, `12 min` or `3.5 h`.
+pub fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs_f64();
+    if seconds < 90.0 {
+        format!("{:.0}s", seconds.max(1.0))
+    } else if seconds < 5400.0 {
+        format!("{:.0} min", seconds / 60.0)
+    } else {
+        format!("{:.1} h", seconds / 3600.0)
+    }
+}
+
+/// Asks whether to start a run of more than `limit` games, showing its estimated
+/// duration. Without a terminal to ask on, the run needs `--yes`.
+fn confirm_long_run(num_runs: usize, limit: usize, estimate: Duration) -> Result<bool> {
+    let message = format!(
+        "Simulating {} games will take about {} (timed on {} games).",
+        num_runs,
+        format_duration(estimate),
+        CALIBRATION_GAMES
+    );
+    if !io::stdin().is_terminal() {
+        return Err(anyhow!(
+            "{} Runs of more than {} games need --yes (or a higher --confirm-above).",
+            message,
+            limit
+        ));
+    }
+    print!("{} Continue? (y/n) ", message);
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Guess counts of clean games against `targets` in order, `MAX_GUESSES + 1` for a loss.
+pub fn play_targets(
+    solver: &mut Solver,
+    targets: &[String],
+    weights: &[(f64, f64, f64)],
+) -> Result<Vec<usize>> {
+    let stats_json = solver.stats_json.clone();
+    let opener = solver.get_top_suggestion_silent(&stats_json, None)?;
+    targets
+        .iter()
+        .map(|target| {
+            simulate_game(solver, &opener, target, &stats_json, weights, None)
+                .map(|outcome| outcome.guesses)
+        })
+        .collect()
+}
+
+/// The answers left before the last guess of the game just played, and the turn
+/// the target first ranked among the top `DISCOVERY_RANK` candidates by letter
+/// frequency. Each turn ranks by the letter stats of the answers still possible,
+/// not of the whole list.
+fn loss_trace(solver: &mut Solver, target: &str) -> Result<LossTrace> {
+    let lines = solver.game.lines.clone();
+    let mut candidates = CandidateSet::full(solver.answer_words.len());
+    let mut trace = LossTrace {
+        final_candidates: Vec::new(),
+        discovered_after: None,
+    };
+
+    for (turn, line) in lines.iter().enumerate() {
+        let words: Vec<&str> = candidates
+            .iter()
+            .map(|answer| solver.answer_words[answer].as_str())
+            .collect();
+        if trace.discovered_after.is_none() {
+            let stats = serde_json::to_string(&LetterStats::from_words(&words))?;
+            let ranked = rank_words(&words, &stats)?;
+            if ranked
+                .top(loss_taxonomy::DISCOVERY_RANK)
+                .iter()
+                .any(|(word, _)| word == target)
+            {
+                trace.discovered_after = Some(turn);
+            }
+        }
+        if turn + 1 == lines.len() {
+            trace.final_candidates = words.iter().map(|w| w.to_string()).collect();
+        }
+        let Some(guess) = solver.cache.guess_index(&line.word) else {
+            break;
+        };
+        candidates.intersect_with(
+            &solver
+                .cache
+                .survivors(guess, encode_pattern(&line.pattern())),
+        );
+    }
+    Ok(trace)
+}
+
+/// Counts the turns of the game just played where the chosen guess could leave more
+/// answers (its largest feedback bucket) than the best available guess could.
+fn worse_bucket_turns(solver: &mut Solver) -> usize {
+    let lines = solver.game.lines.clone();
+    let cache = &mut solver.cache;
+    let mut candidates = CandidateSet::full(solver.answer_words.len());
+    let mut worse = 0;
+
+    for line in &lines {
+        let Some(guess) = cache.guess_index(&line.word) else {
+            break;
+        };
+        let chosen = strategy::worst_bucket(&cache.buckets(guess, &candidates));
+        let best = (0..cache.guess_count())
+            .map(|g| strategy::worst_bucket(&cache.buckets(g, &candidates)))
+            .min()
+            .unwrap_or(chosen);
+        if chosen > best {
+            worse += 1;
+        }
+        candidates.intersect_with(&cache.survivors(guess, encode_pattern(&line.pattern())));
+    }
+
+    worse
+}
+
+/// Replays `target_word` with the minimax strategy and reports whether it was lost
+/// too, in which case the original loss counts as forced rather than a strategy error.
+fn minimax_replay_lost(
+    solver: &mut Solver,
+    minimax_opener: &mut Option<String>,
+    target_word: &str,
+    stats_json: &str,
+    weights: &[(f64, f64, f64)],
+) -> Result<bool> {
+    let (strategy, endgame) = (solver.strategy, solver.endgame);
+    solver.strategy = Strategy::Minimax;
+    solver.endgame = None;
+
+    let replay = (|| {
+        if minimax_opener.is_none() {
+            solver.game.reset();
+            solver.reset_candidates();
+            *minimax_opener = Some(solver.get_top_suggestion_silent(stats_json, None)?);
+        }
+        let opener = minimax_opener.as_deref().unwrap_or_default();
+        simulate_game(solver, opener, target_word, stats_json, weights, None)
+    })();
+
+    solver.strategy = strategy;
+    solver.endgame = endgame;
+    Ok(replay?.guesses > MAX_GUESSES)
+}
+
+fn draw_corruption(rng: &mut impl Rng) -> Corruption {
+    Corruption {
+        // The last turn's feedback is never used, so only corrupt turns that feed the solver
+        turn: rng.random_range(0..5),
+        cell: rng.random_range(0..5),
+        pick_second: rng.random_bool(0.5),
+    }
+}
+
+/// Picks the next guess of a simulated game from the solver's state.
+type PickGuess = fn(&mut Solver, &str, &[(f64, f64, f64)]) -> Result<String>;
+
+/// Plays one game against `target_word`, reusing `temp_solver` (and its pattern
+/// cache) across games.
 fn simulate_game(
-    solver: &Solver,
-    target_word: String,
+    temp_solver: &mut Solver,
+    opener: &str,
+    target_word: &str,
     stats_json: &str,
-    weights: &Vec<(f64, f64, f64)>,
-) -> Result<usize> {
-    let mut temp_solver = Solver {
-        game: GameData::new(),
-        current_words: solver.all_words.clone(),
-        all_words: solver.all_words.clone(),
-    };
+    weights: &[(f64, f64, f64)],
+    corruption: Option<Corruption>,
+) -> Result<GameOutcome> {
+    play_with(
+        temp_solver,
+        opener,
+        target_word,
+        stats_json,
+        weights,
+        corruption,
+        Solver::next_guess,
+    )
+}
+
+/// `simulate_game` with the guesses after the opener picked by `pick`.
+fn play_with(
+    temp_solver: &mut Solver,
+    opener: &str,
+    target_word: &str,
+    stats_json: &str,
+    weights: &[(f64, f64, f64)],
+    corruption: Option<Corruption>,
+    pick: PickGuess,
+) -> Result<GameOutcome> {
+    temp_solver.game.reset();
+    temp_solver.reset_candidates();
     let mut guesses = 0;
-    let max_guesses = 6;
+    let mut candidate_counts = Vec::new();
+    let mut corrupted = false;
+    let max_guesses = MAX_GUESSES;
 
     while guesses < max_guesses {
-        let attempt = temp_solver.game.lines.len().min(weights.len() - 1);
-        let weight_tuple = weights[attempt];
+        let candidates = perf::time(Phase::Filter, || {
+            temp_solver
+                .cache
+                .consistent_answers(&temp_solver.game)
+                .count()
+        });
+        perf::record_candidates(candidates);
+        candidate_counts.push(candidates);
 
         let guess_word = if guesses == 0 {
-            temp_solver.get_top_suggestion_silent(stats_json, None)?
+            opener.to_string()
         } else {
             // Update wordlist first
-            temp_solver.current_words = temp_solver.update_wordlist();
-
-            // TRAP CATCHING LOGIC
-            if let Some(trap) = trap::detect_trap(&temp_solver.current_words) {
-                if let Some((elim_word, _)) =
-                    trap::find_best_elimination(&temp_solver.all_words, &trap)
-                {
-                    elim_word
-                } else {
-                    temp_solver.get_top_suggestion_silent(stats_json, Some(weight_tuple))?
-                }
-            } else {
-                temp_solver.get_top_suggestion_silent(stats_json, Some(weight_tuple))?
+            perf::time(Phase::Filter, || temp_solver.update_wordlist());
+
+            // Wrong feedback can eliminate every word, which ends the game
+            if temp_solver.current_words.is_empty() {
+                return Ok(GameOutcome {
+                    guesses: max_guesses + 1,
+                    contradiction: true,
+                    candidate_counts,
+                    repeated_guess: false,
+                    corrupted,
+                });
+            }
+
+            let guess = perf::time(Phase::Rank, || pick(temp_solver, stats_json, weights))?;
+            if temp_solver.game.has_guessed(&guess) {
+                warn!(
+                    "Target '{}': strategy repeated the guess '{}' on turn {}",
+                    target_word,
+                    guess,
+                    guesses + 1
+                );
+                return Ok(GameOutcome {
+                    guesses: max_guesses + 1,
+                    contradiction: false,
+                    candidate_counts,
+                    repeated_guess: true,
+                    corrupted,
+                });
             }
+            guess
         };
 
         guesses += 1;
+        trace!(
+            "Target '{}', guess {}: '{}'",
+            target_word,
+            guesses,
+            guess_word
+        );
 
         if guess_word == target_word {
-            return Ok(guesses);
+            candidate_counts.push(1);
+            return Ok(GameOutcome {
+                guesses,
+                contradiction: false,
+                candidate_counts,
+                repeated_guess: false,
+                corrupted,
+            });
         }
 
         // Evaluate and update
-        let line = Solver::evaluate_word(&guess_word, &target_word);
-        let pattern = Solver::get_pattern(&line);
+        let mut pattern: Vec<char> = perf::time(Phase::Evaluate, || {
+            let line = Solver::evaluate_word(&guess_word, target_word);
+            line.pattern().chars().collect()
+        });
+
+        if let Some(c) = corruption.filter(|c| c.turn == guesses - 1) {
+            let wrong_states: Vec<char> = "wmc".chars().filter(|&s| s != pattern[c.cell]).collect();
+            pattern[c.cell] = wrong_states[c.pick_second as usize];
+            corrupted = true;
+        }
+
+        let pattern: String = pattern.into_iter().collect();
         temp_solver.game.add_line(&guess_word, &pattern);
     }
 
-    Ok(max_guesses + 1)
+    candidate_counts.push(
+        temp_solver
+            .cache
+            .consistent_answers(&temp_solver.game)
+            .count(),
+    );
+    Ok(GameOutcome {
+        guesses: max_guesses + 1,
+        contradiction: false,
+        candidate_counts,
+        repeated_guess: false,
+        corrupted,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture_game(target: &str, corruption: Corruption) -> GameOutcome {
+        let mut solver = Solver::fixture();
+        let stats_json = solver.stats_json.clone();
+        let weights = solver.weights.clone();
+        simulate_game(
+            &mut solver,
+            "arose",
+            target,
+            &stats_json,
+            &weights,
+            Some(corruption),
+        )
+        .unwrap()
+    }
+
+    fn words(list: &[&str]) -> Vec<String> {
+        list.iter().map(|w| w.to_string()).collect()
+    }
+
+    #[test]
+    fn target_like_a_recent_one_is_drawn_again() {
+        let targets = words(&["fills", "hills", "crane", "slate", "mound"]);
+        let mut recent = RecentTargets::new(2);
+        assert_eq!(recent.choose(&targets, &targets[0], 1, 0), "fills");
+        // HILLS has four letters in place in common with FILLS
+        let second = recent.choose(&targets, &targets[1], 1, 1).clone();
+        assert!(!analysis::words_similar(
+            &second,
+            "fills",
+            analysis::SIMILAR_POSITIONS
+        ));
+        assert!(recent.rejected >= 1);
+        assert_eq!(recent.fallbacks, 0);
+        assert_eq!(recent.recent, ["fills".to_string(), second]);
+
+        // The window keeps the last two targets: FILLS drops out with the third
+        let before = recent.rejected;
+        let third = targets.iter().find(|w| !recent.is_recent(w)).unwrap();
+        recent.choose(&targets, third, 1, 2);
+        assert_eq!(recent.rejected, before);
+        assert!(!recent.recent.contains(&"fills".to_string()));
+    }
+
+    #[test]
+    fn list_of_one_family_falls_back_instead_of_stalling() {
+        let targets = words(&["fills", "hills", "kills", "mills", "pills", "tills"]);
+        let mut recent = RecentTargets::new(3);
+        for index in 0..10 {
+            let (first, _) = draw_game(&targets, 7, index, 0.0);
+            recent.choose(&targets, first, 7, index);
+        }
+        assert_eq!(recent.fallbacks, 9);
+        assert_eq!(recent.rejected, 9 * (MAX_REDRAWS + 1));
+        assert_eq!(recent.recent.len(), 3);
+    }
+
+    #[test]
+    fn mistake_after_the_game_ended_is_not_counted() {
+        // ABUSE is solved with the second guess, so the fourth feedback never comes
+        let outcome = fixture_game(
+            "abuse",
+            Corruption {
+                turn: 3,
+                cell: 0,
+                pick_second: false,
+            },
+        );
+        assert_eq!(outcome.guesses, 2);
+        assert!(!outcome.corrupted);
+    }
+
+    #[test]
+    fn mistake_on_a_played_turn_is_counted() {
+        let outcome = fixture_game(
+            "abuse",
+            Corruption {
+                turn: 0,
+                cell: 1,
+                pick_second: false,
+            },
+        );
+        assert!(outcome.corrupted);
+        assert_ne!(outcome.guesses, 2);
+    }
+
+    #[test]
+    fn filter_never_drops_a_consistent_word() {
+        // Every fixture target with clean feedback and with a mistake, which makes the
+        // filter handle contradictory patterns too
+        let mut solver = Solver::fixture();
+        solver.check_filter = true;
+        let stats_json = solver.stats_json.clone();
+        let weights = solver.weights.clone();
+        let opener = solver.get_top_suggestion_silent(&stats_json, None).unwrap();
+        let targets = solver.all_words.clone();
+        for (i, target) in targets.iter().enumerate() {
+            let mistake = Corruption {
+                turn: i % 3,
+                cell: i % 5,
+                pick_second: i % 2 == 0,
+            };
+            for corruption in [None, Some(mistake)] {
+                simulate_game(
+                    &mut solver,
+                    &opener,
+                    target,
+                    &stats_json,
+                    &weights,
+                    corruption,
+                )
+                .unwrap();
+            }
+        }
+        assert_eq!(solver.filter_bugs, 0);
+    }
+
+    #[test]
+    fn strategy_repeating_a_guess_loses_the_game() {
+        let mut solver = Solver::fixture();
+        let stats_json = solver.stats_json.clone();
+        let weights = solver.weights.clone();
+        // A broken strategy that plays its first guess again
+        let repeat_first: PickGuess = |solver, _, _| Ok(solver.game.lines[0].word.clone());
+        let outcome = play_with(
+            &mut solver,
+            "arose",
+            "growl",
+            &stats_json,
+            &weights,
+            None,
+            repeat_first,
+        )
+        .unwrap();
+        assert!(outcome.repeated_guess);
+        assert_eq!(outcome.guesses, MAX_GUESSES + 1);
+
+        let mut results = SimulationResults::new(MAX_GUESSES);
+        results.record_game(outcome.guesses);
+        results.record_repeated_guess();
+        let summary = results.to_json();
+        assert_eq!(summary["wins"], 0);
+        assert_eq!(summary["repeated_guesses"], 1);
+    }
 }
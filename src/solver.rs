@@ -1,203 +1,915 @@
-use crate::filter::Filter;
-use crate::game::{CellData, GameData, LineData}; // CellData and LineData for simulation helpers
-use crate::ranking::{rank_words, weighted_rank};
-use crate::trap;
+use crate::analysis::{self, HistoryPrior, LetterStats, RareLetter, RareLetterLimits, TraceStep};
+use crate::candidates::{CandidateSet, PatternCache, DEFAULT_CACHE_ROWS};
+use crate::filter::{self, Filter};
+use crate::game::{self, parse_pattern, parse_template, GameData, LetterRestriction, LineData}; // LineData for simulation helpers
+use crate::history::{self, AnswerStep, GameRecord, ThinkTimes};
+use crate::input::LineReader;
+use crate::messages;
+use crate::openers;
+use crate::output::{self, CellFormat, Table, Verbosity};
+use crate::prefetch::{self, Prefetched};
+use crate::probe::{ProbeRestriction, ProbeWords};
+use crate::ranking::{rank_words, weighted_rank, RankedList, Stability};
+use crate::reconstruct::{self, MAX_RECONSTRUCTIONS, RECONSTRUCT_LIST};
+use crate::report::{self, ReportMode, ReportTurn};
+use crate::sanity;
+use crate::stats;
+use crate::strategy::{
+    self, Deadline, Ranking, Strategy, ENDGAME_TURNS, MAX_GUESSES, PROGNOSIS_LIMIT,
+};
+use crate::trap::{self, TrapInfo};
 use crate::util;
+use crate::warmstart::{self, CacheKey};
+use crate::whatif::{self, WhatIf};
+use crate::wordlist::{self, PoolOptions, PunctuationPolicy, Wordlist};
 use anyhow::anyhow;
-use anyhow::Result;
-use std::collections::HashMap;
-use std::io::{self, Write}; // Required for word evaluation in simulate
+use anyhow::{Context, Result};
+use log::{debug, trace};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Guesses listed by the `probes` command.
+const PROBE_LIST: usize = 5;
+
+/// Letters listed per position by the `letters` command.
+const PROBS_LETTERS: usize = 3;
+
+/// Candidates listed with their probability of being the answer in a `--json` turn.
+const ANSWER_PROBABILITIES: usize = 50;
+
+/// Ranked words checked for strategically equivalent guesses when suggestions are
+/// printed, which bounds the partitions computed per turn.
+const EQUIVALENCE_WINDOW: usize = 50;
 
 pub struct Solver {
     pub game: GameData,
     pub current_words: Vec<String>,
     pub all_words: Vec<String>, // Made public for use in simulation
+    pub answer_words: Vec<String>,
+    /// Words that may still be the answer before any guess: `all_words` minus
+    /// excluded past answers.
+    pub candidate_pool: Vec<String>,
+    pub stats_json: String,
+    pub verbosity: Verbosity,
+    pub strategy: Strategy,
+    /// Strategy for the last `ENDGAME_TURNS` guesses, replacing `strategy` there.
+    pub endgame: Option<Strategy>,
+    pub cache: PatternCache,
+    /// Cross-check every filtering pass against `evaluate_word` (`--check-filter`).
+    pub check_filter: bool,
+    /// Words the filter wrongly eliminated so far, found by the check.
+    pub filter_bugs: usize,
+    /// Guess indices in the order the anytime strategies search them.
+    pub search_order: Vec<usize>,
+    /// Time allowed per suggestion for the entropy and win-probability strategies.
+    pub think_limit: Option<Duration>,
+    /// Multipliers of each position's letter frequency in the frequency ranking, from
+    /// solver_config.json.
+    pub position_weights: [f64; 5],
+    /// Frequency-ranking weight set per attempt, from solver_config.json. `set weight`
+    /// changes them for the session only.
+    pub weights: Vec<(f64, f64, f64)>,
+    /// Attempts (from 0) whose weights were changed with `set weight`.
+    edited_weights: Vec<usize>,
+    /// Set once `set strategy` changed the strategy.
+    edited_strategy: bool,
+    /// Counterfactual shown when a game is solved (`--what-if`).
+    pub what_if: Option<WhatIf>,
+    /// Answer entered with `answer <word>`, which what-if replays need.
+    known_answer: Option<String>,
+    /// Reuse the first-guess ranking saved in the data dir (off with `--no-cache`).
+    pub warm_start: bool,
+    /// Probes the player would actually play, from probe_words.txt.
+    pub probe_words: Option<ProbeWords>,
+    /// Set when the last ranking swapped an unlisted probe for a listed one.
+    probe_restriction: Option<ProbeRestriction>,
+    /// Rank (from 1) and score of each pinned word in the last ranking, `None` for a
+    /// word it didn't rank.
+    pinned_ranks: Vec<(String, Option<(usize, f64)>)>,
+    /// Order near-equal suggestions alphabetically and round the scores shown
+    /// (`--stable`).
+    pub stability: Option<Stability>,
+    /// Strings outside the wordlist that fit the feedback, listed by `reconstruct`
+    /// and accepted as guesses.
+    reconstructed: Vec<String>,
+    /// Report printed when the puzzle is solved (`--report`).
+    pub report: Option<ReportMode>,
+    /// Show how close the other candidates came when the game ends (`--near-misses`).
+    pub near_misses: bool,
+    /// Candidates left and the top suggestion before each guess of `game`; `None`
+    /// for guesses restored with `load`.
+    turn_notes: Vec<(Option<usize>, Option<String>)>,
+    /// Answer likelihoods learned from past answers, weighting the frequency
+    /// strategy's scores (`--history-prior`).
+    pub prior: Option<HistoryPrior>,
+    /// Warn when no play guarantees a win any more (off with `--no-prognosis`).
+    pub prognosis: bool,
+    /// Only suggest when asked with `hint` (`--assist-on-demand`).
+    pub assist_on_demand: bool,
+    /// Ask to double-check feedback that looks mistyped (off with `--no-sanity-checks`).
+    pub sanity_checks: bool,
+    /// Hints taken in the current game.
+    hints: usize,
+    /// Set once the current game is in the history, so it is recorded only once.
+    recorded: bool,
+    /// Words of the answer list when it is separate from the guess list, for marking
+    /// the suggestions that may be the answer; empty otherwise.
+    answer_set: HashSet<String>,
+    /// Likely patterns of a guess ranked ahead while they are typed (`--prefetch`),
+    /// 0 for none.
+    pub prefetch: usize,
+    /// The ranking prefetched for the last guess and pattern, until it is used.
+    prefetched: Option<Prefetched>,
+    /// Ends this solver's pattern rankings early once set; only prefetch workers
+    /// have one.
+    pub cancel: Option<Arc<AtomicBool>>,
+    /// When rare letters most candidates contain are flagged and boosted in the
+    /// frequency ranking; `None` to leave the ranking alone.
+    pub rare_letters: Option<RareLetterLimits>,
+    /// Time spent at the guess prompt for each guess, recorded with the game.
+    think_times: ThinkTimes,
 }
 
 impl Solver {
-    pub fn new() -> Result<Self> {
-        let content =
-            util::read_wordlist().map_err(|e| anyhow!("Failed to read wordlist.txt: {}", e))?;
+    /// Loads the wordlist narrowed by `options`. A letter-restricted pool, one loaded
+    /// with a non-default punctuation policy or a `--wordlist` override gets its
+    /// letter stats regenerated in memory instead of using letter_stats.json.
+    pub fn with_pool(options: PoolOptions) -> Result<Self> {
+        let wordlist = Wordlist::load_with(&options)?;
+        let restriction = options.restriction;
+
+        let stats_json = if restriction.is_empty()
+            && options.punctuation == PunctuationPolicy::Reject
+            && util::wordlist_source().is_none()
+        {
+            let stats_json = util::read_letter_stats()?;
+            let stale = LetterStats::parse(&stats_json)
+                .and_then(|stats| stats.check_wordlist(&wordlist.guesses))
+                .context("Letter stats don't fit the wordlist. Run `wordle-bot analyze` to regenerate letter_stats.json")?;
+            if let Some(reason) = stale {
+                eprintln!("{}", messages::fill("solve.stale_stats", &[&reason]));
+            }
+            stats_json
+        } else {
+            let word_refs: Vec<&str> = wordlist.guesses.iter().map(|s| s.as_str()).collect();
+            serde_json::to_string(&LetterStats::from_words(&word_refs))?
+        };
+        let probe_words = ProbeWords::load(options.probe_threshold)?;
+
+        let mut solver = Self::from_parts(wordlist, restriction, stats_json, probe_words)?;
+        let config = util::read_full_solver_config()?;
+        solver.position_weights = config.positions;
+        solver.weights = config.weights;
+        Ok(solver)
+    }
+
+    /// A solver over the given words with letter stats counted from `guesses`,
+    /// independent of the data dir: no probe words, no past answers.
+    #[cfg(any(test, feature = "test-util"))]
+    pub fn from_words(guesses: Vec<String>, answers: Vec<String>) -> Result<Self> {
+        let word_refs: Vec<&str> = guesses.iter().map(|s| s.as_str()).collect();
+        let stats_json = serde_json::to_string(&LetterStats::from_words(&word_refs))?;
+        let wordlist = Wordlist {
+            guesses,
+            answers,
+            excluded_answers: Default::default(),
+        };
+        Self::from_parts(wordlist, Default::default(), stats_json, None)
+    }
 
-        let words: Vec<String> = content
+    /// A solver over fixtures/golden_wordlist.txt, for unit tests.
+    #[cfg(test)]
+    pub fn fixture() -> Self {
+        let words: Vec<String> = include_str!("../fixtures/golden_wordlist.txt")
             .lines()
-            .map(|w| w.trim().to_lowercase())
-            .filter(|w| w.len() == 5)
+            .map(|w| w.trim().to_string())
+            .filter(|w| !w.is_empty())
             .collect();
+        Self::from_words(words.clone(), words).unwrap()
+    }
 
-        if words.is_empty() {
-            return Err(anyhow!("wordlist.txt is empty or invalid"));
-        }
+    fn from_parts(
+        wordlist: Wordlist,
+        restriction: LetterRestriction,
+        stats_json: String,
+        probe_words: Option<ProbeWords>,
+    ) -> Result<Self> {
+        let mut game = GameData::new();
+        game.set_restriction(restriction);
+
+        let cache = PatternCache::new(&wordlist.guesses, &wordlist.answers, DEFAULT_CACHE_ROWS);
+        let candidate_pool: Vec<String> = wordlist
+            .guesses
+            .iter()
+            .filter(|w| !wordlist.excluded_answers.contains(*w))
+            .cloned()
+            .collect();
+        let search_order = strategy::frequency_order(&cache, &wordlist.guesses, &stats_json)?;
+        let answer_set = if wordlist.answers.len() < wordlist.guesses.len() {
+            wordlist.answers.iter().cloned().collect()
+        } else {
+            HashSet::new()
+        };
 
         Ok(Self {
-            game: GameData::new(),
-            current_words: words.clone(), // filtered, may shrink during filtering
-            all_words: words,             // full list stays available for checking
+            game,
+            current_words: candidate_pool.clone(), // filtered, may shrink during filtering
+            all_words: wordlist.guesses,           // full list stays available for checking
+            answer_words: wordlist.answers,
+            candidate_pool,
+            stats_json,
+            verbosity: Verbosity::Verbose,
+            strategy: Strategy::Frequency,
+            endgame: None,
+            cache,
+            check_filter: false,
+            filter_bugs: 0,
+            search_order,
+            think_limit: None,
+            position_weights: util::UNIFORM_POSITIONS,
+            weights: util::default_solver_config()?,
+            edited_weights: Vec::new(),
+            edited_strategy: false,
+            what_if: None,
+            known_answer: None,
+            warm_start: true,
+            probe_words,
+            probe_restriction: None,
+            pinned_ranks: Vec::new(),
+            stability: None,
+            reconstructed: Vec::new(),
+            report: None,
+            near_misses: false,
+            turn_notes: Vec::new(),
+            prior: None,
+            prognosis: true,
+            assist_on_demand: false,
+            sanity_checks: true,
+            hints: 0,
+            recorded: false,
+            answer_set,
+            prefetch: 0,
+            prefetched: None,
+            cancel: None,
+            rare_letters: None,
+            think_times: ThinkTimes::default(),
         })
     }
 
+    /// Copy of this solver sharing its pattern cache, for playing games on another thread.
+    pub fn fork(&self) -> Self {
+        Self {
+            game: self.game.clone(),
+            current_words: self.current_words.clone(),
+            all_words: self.all_words.clone(),
+            answer_words: self.answer_words.clone(),
+            candidate_pool: self.candidate_pool.clone(),
+            stats_json: self.stats_json.clone(),
+            verbosity: self.verbosity,
+            strategy: self.strategy,
+            endgame: self.endgame,
+            cache: self.cache.clone(),
+            check_filter: self.check_filter,
+            filter_bugs: 0,
+            search_order: self.search_order.clone(),
+            think_limit: self.think_limit,
+            position_weights: self.position_weights,
+            weights: self.weights.clone(),
+            edited_weights: self.edited_weights.clone(),
+            edited_strategy: self.edited_strategy,
+            what_if: self.what_if,
+            known_answer: self.known_answer.clone(),
+            warm_start: self.warm_start,
+            probe_words: self.probe_words.clone(),
+            probe_restriction: None,
+            pinned_ranks: Vec::new(),
+            stability: self.stability,
+            reconstructed: Vec::new(),
+            report: self.report,
+            near_misses: self.near_misses,
+            turn_notes: self.turn_notes.clone(),
+            prior: self.prior.clone(),
+            prognosis: self.prognosis,
+            assist_on_demand: self.assist_on_demand,
+            sanity_checks: self.sanity_checks,
+            hints: self.hints,
+            recorded: self.recorded,
+            answer_set: self.answer_set.clone(),
+            prefetch: 0,
+            prefetched: None,
+            cancel: None,
+            rare_letters: self.rare_letters,
+            think_times: self.think_times.clone(),
+        }
+    }
+
     pub fn run(&mut self) -> Result<()> {
-        self.print_initial_suggestions()?;
+        if self.assist_on_demand {
+            self.verbosity
+                .notice(&format!("{}\n", messages::text("solve.hidden")));
+        }
+        let mut best = self.first_suggestions()?;
+        let mut reader = LineReader::new(&self.all_words);
 
         loop {
+            let status = output::minimal_status(
+                self.game.lines.len(),
+                self.current_words.len(),
+                best.as_deref(),
+            );
+
             // Step 1: enter word
-            print!("Enter your 5-letter guess (or 'exit'): ");
-            io::stdout().flush()?;
-            let mut word = String::new();
-            io::stdin().read_line(&mut word)?;
-            let word = word.trim().to_lowercase();
+            let prompt = self
+                .verbosity
+                .prompt_text(messages::text("solve.guess_prompt"), &status);
+            self.think_times.asking();
+            let Some(line) = reader.read_command(prompt)? else {
+                break;
+            };
+            self.think_times.answered();
+            let input = line.trim();
+            let word = input.to_lowercase();
 
             if word == "exit" {
-                println!("Exiting solver.");
+                self.verbosity.notice(messages::text("solve.exiting"));
                 break;
             }
 
-            if word == "-r" {
-                self.reset()?;
-                println!("Solver has been reset.\n");
+            if word == "-r" || word == "new" {
+                self.game.reset();
+                self.game.banned.clear();
+                self.game.pinned.clear();
+                self.reset_candidates();
+                self.known_answer = None;
+                self.reconstructed.clear();
+                self.turn_notes.clear();
+                self.think_times.truncate(0);
+                self.hints = 0;
+                self.recorded = false;
+                self.verbosity
+                    .notice(&format!("{}\n", messages::text("solve.reset")));
+                best = self.first_suggestions()?;
                 continue; // skip the rest of the loop entirely
             }
 
-            if word.len() != 5 {
-                println!("Please enter a 5-letter word.\n");
+            if let Some(count) = word.strip_prefix("rollback") {
+                match count.trim().parse::<usize>() {
+                    Ok(count) => best = self.rollback(count, best)?,
+                    Err(_) => self
+                        .verbosity
+                        .notice(&format!("{}\n", messages::text("solve.rollback_usage"))),
+                }
                 continue;
             }
 
-            // Check if guess exists in wordlist (uses preloaded all_words)
-            if !self.all_words.contains(&word) {
-                println!("'{}' is not in the wordlist.\n", word);
+            if let Some(answer) = word.strip_prefix("answer ") {
+                let answer = answer.trim();
+                if answer.chars().count() != 5 {
+                    self.verbosity
+                        .notice(&format!("{}\n", messages::text("solve.answer_length")));
+                } else {
+                    self.known_answer = Some(answer.to_string());
+                    match util::append_used_answer(answer) {
+                        Ok(path) => self.verbosity.notice(&format!(
+                            "{}\n",
+                            messages::fill("solve.answer_recorded", &[&answer, &path.display()])
+                        )),
+                        Err(e) => self.verbosity.notice(&format!(
+                            "{}\n",
+                            messages::fill("solve.answer_failed", &[&e])
+                        )),
+                    }
+                }
                 continue;
             }
 
-            // Step 2: enter pattern
-            print!("Enter pattern (w = wrong, m = misplaced, c = correct): ");
-            io::stdout().flush()?;
-            let mut pattern = String::new();
-            io::stdin().read_line(&mut pattern)?;
-            let pattern = pattern.trim().to_lowercase();
-
-            if pattern.len() != 5 || !pattern.chars().all(|c| "wmc".contains(c)) {
-                println!("Invalid pattern. Use only w, m, c.\n");
+            if let Some(spec) = word.strip_prefix("what-if") {
+                match WhatIf::parse(spec) {
+                    Ok(what_if) => match self.known_answer.clone() {
+                        Some(target) => self.show_what_if(&target, what_if),
+                        None => self
+                            .verbosity
+                            .notice(&format!("{}\n", messages::text("solve.what_if_answer"))),
+                    },
+                    Err(e) => self.verbosity.notice(&format!("{}\n", e)),
+                }
+                continue;
+            }
+
+            if let Some(option) = word.strip_prefix("report") {
+                match option.trim() {
+                    "" => self.print_report(ReportMode::Full),
+                    "spoiler-free" => self.print_report(ReportMode::SpoilerFree),
+                    "near-misses" => {
+                        match self.get_solved_word().or_else(|| self.known_answer.clone()) {
+                            Some(answer) => {
+                                self.print_report(ReportMode::Full);
+                                self.print_near_misses(&answer);
+                            }
+                            None => self.verbosity.notice(&format!(
+                                "{}\n",
+                                messages::text("solve.near_misses_answer")
+                            )),
+                        }
+                    }
+                    _ => self
+                        .verbosity
+                        .notice(&format!("{}\n", messages::text("solve.report_usage"))),
+                }
+                continue;
+            }
+
+            if word == "hint" {
+                if self.assist_on_demand {
+                    best = self.hint(best)?;
+                } else {
+                    self.verbosity
+                        .notice(&format!("{}\n", messages::text("solve.hint_unneeded")));
+                }
+                continue;
+            }
+
+            if word == "groups" {
+                self.print_groups();
+                continue;
+            }
+
+            // Not `probe`: PROBE is a valid guess
+            if word == "probes" {
+                self.print_probes();
+                continue;
+            }
+
+            // Not `probs`: PROBS is a valid guess
+            if word == "letters" {
+                self.print_position_probabilities();
+                continue;
+            }
+
+            if word == "reconstruct" {
+                self.print_reconstruction();
+                continue;
+            }
+
+            if word == "show config" {
+                self.print_config();
+                continue;
+            }
+
+            if let Some(setting) = word.strip_prefix("set ") {
+                best = self.set_config(setting.trim(), best)?;
+                continue;
+            }
+
+            if let Some(path) = input.strip_prefix("save config ") {
+                match self.save_config(path.trim()) {
+                    Ok(()) => self.verbosity.notice(&format!(
+                        "{}\n",
+                        messages::fill("solve.config_saved", &[&path.trim()])
+                    )),
+                    Err(e) => self.verbosity.notice(&format!(
+                        "{}\n",
+                        messages::fill("solve.config_save_failed", &[&e])
+                    )),
+                }
                 continue;
             }
 
+            if let Some((command, target)) = word.split_once(' ') {
+                if command == "ban" || command == "pin" {
+                    best = self.ban_or_pin(command, target.trim(), best)?;
+                    continue;
+                }
+            }
+
+            if let Some(spec) = word.strip_prefix("peek ") {
+                self.peek(spec.trim())?;
+                continue;
+            }
+
+            if let Some(path) = input.strip_prefix("save ") {
+                match self.save_game(path.trim()) {
+                    Ok(()) => self.verbosity.notice(&format!(
+                        "{}\n",
+                        messages::fill("solve.saved", &[&path.trim()])
+                    )),
+                    Err(e) => self
+                        .verbosity
+                        .notice(&format!("{}\n", messages::fill("solve.save_failed", &[&e]))),
+                }
+                continue;
+            }
+
+            if let Some(path) = input.strip_prefix("load ") {
+                let (path, force) = match path.trim().strip_prefix("--force ") {
+                    Some(path) => (path, true),
+                    None => (path, false),
+                };
+                match self.load_game(path.trim(), force) {
+                    Ok(()) => {
+                        self.verbosity.notice(&format!(
+                            "{}\n",
+                            messages::fill("solve.loaded", &[&path.trim()])
+                        ));
+                        if self.verbosity.is_verbose() {
+                            self.game.print_summary();
+                        }
+                        best = self.refresh_suggestions()?;
+                    }
+                    Err(e) => self
+                        .verbosity
+                        .notice(&format!("{}\n", messages::fill("solve.load_failed", &[&e]))),
+                }
+                continue;
+            }
+
+            // A guess typed together with its pattern skips the pattern prompt
+            let (word, entered) = match parse_template(&line) {
+                Ok(Some(template)) => (template.word, Some(template.pattern)),
+                Ok(None) => (word, None),
+                Err(e) => {
+                    self.verbosity.notice(&format!("{}\n", e.describe()));
+                    continue;
+                }
+            };
+
+            if word.chars().count() != 5 {
+                self.verbosity
+                    .notice(&format!("{}\n", messages::text("solve.word_length")));
+                continue;
+            }
+
+            // Check if guess exists in wordlist (uses preloaded all_words)
+            if !self.all_words.contains(&word) && !self.reconstructed.contains(&word) {
+                self.verbosity.notice(&format!(
+                    "{}\n",
+                    messages::fill("solve.not_in_wordlist", &[&word])
+                ));
+                continue;
+            }
+
+            if self.game.has_guessed(&word) {
+                let question = messages::fill("solve.already_guessed", &[&word]);
+                let prompt = self.verbosity.prompt_text(&question, "again? (y/n) > ");
+                let Some(answer) = reader.read_pattern(prompt)? else {
+                    break;
+                };
+                if !answer.eq_ignore_ascii_case("y") {
+                    continue;
+                }
+            }
+
+            // Step 2: enter pattern
+            let pattern = match entered {
+                Some(pattern) => pattern,
+                None => {
+                    let prompt = self
+                        .verbosity
+                        .prompt_text(messages::text("solve.pattern_prompt"), "pattern > ");
+                    let Some(pattern) =
+                        self.read_prefetching(&word, || reader.read_pattern(prompt))?
+                    else {
+                        break;
+                    };
+                    let Some(pattern) = parse_pattern(&pattern) else {
+                        self.prefetched = None;
+                        self.verbosity
+                            .notice(&format!("{}\n", messages::text("solve.invalid_pattern")));
+                        continue;
+                    };
+                    pattern
+                }
+            };
+
             // Update game
+            self.turn_notes
+                .push((Some(self.current_words.len()), best.clone()));
+            self.think_times.guessed();
             self.game.add_line(&word, &pattern);
 
             // Show summary
-            self.game.print_summary();
+            if self.verbosity.is_verbose() {
+                self.game.print_summary();
+            }
 
             // Break if Game Won
             if self.is_game_won() {
                 let word = self.get_solved_word().unwrap();
-                println!(
-                    "Congratulations! You've solved the puzzle! The word is '{}'.",
-                    word
-                );
+                self.verbosity
+                    .notice(&messages::fill("solve.solved", &[&word]));
+                let counts = self.candidate_counts();
+                if let (Some(counts), true) = (&counts, self.verbosity.is_verbose()) {
+                    self.print_elimination(counts);
+                }
+                self.record_history(Some(&word), true, counts.unwrap_or_default());
+                if let Some(what_if) = self.what_if {
+                    self.show_what_if(&word, what_if);
+                }
+                if let Some(mode) = self.report {
+                    println!();
+                    self.print_report(mode);
+                }
+                if self.near_misses {
+                    self.print_near_misses(&word);
+                }
                 break;
             }
 
+            // Double-check feedback that looks mistyped
+            if let Some(unusual) = self.unusual_feedback() {
+                let question = messages::fill("solve.unusual", &[&unusual.describe()]);
+                let prompt = self
+                    .verbosity
+                    .prompt_text(&question, "unusual (keep/undo) > ");
+                let Some(answer) = reader.read_pattern(prompt)? else {
+                    break;
+                };
+                if matches!(answer.to_lowercase().as_str(), "undo" | "u") {
+                    self.undo_last_guess();
+                    self.verbosity.notice(&format!(
+                        "{}\n",
+                        messages::fill("solve.undone", &[&word, &pattern])
+                    ));
+                    continue;
+                }
+            }
+
             // Update suggestions
-            let stats_json = util::read_letter_stats()?;
-            self.rank_words(&stats_json, true)?;
+            best = self.refresh_suggestions()?;
+            if self.game.lines.len() == MAX_GUESSES {
+                let answer = self.known_answer.clone();
+                let counts = self.candidate_counts().unwrap_or_default();
+                self.record_history(answer.as_deref(), false, counts);
+                if let (true, Some(answer)) = (self.near_misses, &answer) {
+                    self.print_near_misses(answer);
+                }
+            }
+            if self.game.lines.len() < MAX_GUESSES {
+                self.print_determined();
+            }
+            if self.current_words.is_empty() {
+                self.explain_contradiction();
+            } else if self.prognosis {
+                self.print_prognosis();
+            }
         }
 
         Ok(())
     }
 
-    pub fn evaluate_word(guessed_word: &str, target_word: &str) -> LineData {
-        let guessed_chars: Vec<char> = guessed_word.chars().collect();
-        let target_chars: Vec<char> = target_word.chars().collect();
+    /// Takes back the guess just added, with its note and think time, when its
+    /// feedback was questioned and the user chose `undo`.
+    fn undo_last_guess(&mut self) {
+        self.prefetched = None;
+        self.game.truncate(self.game.lines.len() - 1);
+        self.turn_notes.pop();
+        self.think_times.truncate(self.game.lines.len());
+    }
 
-        let mut result_cells: [CellData; 5] = [
-            CellData {
-                letter: ' ',
-                state: 'w',
-            },
-            CellData {
-                letter: ' ',
-                state: 'w',
-            },
-            CellData {
-                letter: ' ',
-                state: 'w',
-            },
-            CellData {
-                letter: ' ',
-                state: 'w',
-            },
-            CellData {
-                letter: ' ',
-                state: 'w',
-            },
-        ];
+    /// Removes the last `count` guesses (`rollback <n>`), refilters the candidates from
+    /// the full pool and ranks again. Returns the new top suggestion, or `best` when
+    /// nothing was rolled back.
+    fn rollback(&mut self, count: usize, best: Option<String>) -> Result<Option<String>> {
+        let guesses = self.game.lines.len();
+        if count == 0 {
+            self.verbosity
+                .notice(&format!("{}\n", messages::text("solve.rollback_none")));
+            return Ok(best);
+        }
+        if guesses == 0 {
+            self.verbosity
+                .notice(&format!("{}\n", messages::text("solve.rollback_empty")));
+            return Ok(best);
+        }
+        if count > guesses {
+            self.verbosity
+                .notice(&messages::fill("solve.rollback_all", &[&guesses]));
+        }
 
-        let mut remaining_counts: HashMap<char, usize> = HashMap::new();
-        for &c in &target_chars {
-            *remaining_counts.entry(c).or_insert(0) += 1;
+        let keep = guesses.saturating_sub(count);
+        self.game.truncate(keep);
+        self.turn_notes.truncate(keep);
+        self.think_times.truncate(keep);
+        self.reset_candidates();
+        self.verbosity.notice(&format!(
+            "{}\n",
+            messages::fill("solve.rolled_back", &[&self.game.lines.len()])
+        ));
+        if self.verbosity.is_verbose() && keep > 0 {
+            self.game.print_summary();
         }
 
-        for i in 0..5 {
-            let g = guessed_chars[i];
-            let t = target_chars[i];
+        if keep == 0 {
+            self.first_suggestions()
+        } else {
+            self.refresh_suggestions()
+        }
+    }
 
-            if g == t {
-                result_cells[i] = CellData {
-                    letter: g,
-                    state: 'c',
-                };
-                *remaining_counts.get_mut(&g).unwrap() -= 1;
-            } else {
-                result_cells[i].letter = g;
+    /// The opening suggestions, printed, or none with `--assist-on-demand`.
+    fn first_suggestions(&mut self) -> Result<Option<String>> {
+        if self.assist_on_demand {
+            return Ok(None);
+        }
+        self.print_initial_suggestions()
+    }
+
+    /// Filters the candidates by the game so far and prints new suggestions. With
+    /// `--assist-on-demand` nothing is ranked until `hint` asks for it.
+    fn refresh_suggestions(&mut self) -> Result<Option<String>> {
+        if self.assist_on_demand {
+            self.update_wordlist();
+            return Ok(None);
+        }
+        let stats_json = self.stats_json.clone();
+        self.rank_words(&stats_json, true)
+    }
+
+    /// Ranks the candidates now and reveals the top suggestion (`hint`), counting
+    /// the hint for the game's history. `shown` is the hint already revealed this
+    /// turn, repeated without counting again.
+    fn hint(&mut self, shown: Option<String>) -> Result<Option<String>> {
+        if let Some(word) = shown {
+            self.verbosity.notice(&format!(
+                "{}\n",
+                messages::fill("solve.hint_again", &[&word.to_uppercase()])
+            ));
+            return Ok(Some(word));
+        }
+        let top = if self.game.lines.is_empty() {
+            match self.benchmarked_opener() {
+                Some(opener) => Some(opener.word),
+                None => self.initial_ranking()?.into_iter().next().map(|(w, _)| w),
+            }
+        } else {
+            let stats_json = self.stats_json.clone();
+            self.rank_words(&stats_json, false)?
+        };
+        match &top {
+            Some(word) => {
+                self.hints += 1;
+                self.verbosity.notice(&format!(
+                    "{}\n",
+                    messages::fill("solve.hint", &[&word.to_uppercase(), &self.hints])
+                ));
             }
+            None => self
+                .verbosity
+                .notice(&format!("{}\n", messages::text("solve.hint_none"))),
         }
+        Ok(top)
+    }
 
-        for i in 0..5 {
-            if result_cells[i].state == 'c' {
-                continue;
+    /// Warns when the candidates outnumber the guesses left and no play can guarantee
+    /// a win, with the best achievable chance. Only computed with one or two guesses
+    /// left and at most `PROGNOSIS_LIMIT` candidates, where the feedback buckets give
+    /// it exactly and quickly.
+    fn print_prognosis(&mut self) {
+        if let Some(warning) = self.prognosis_warning() {
+            self.verbosity.notice(&format!("{}\n", warning));
+        }
+    }
+
+    /// The warning `print_prognosis` shows, `None` when a win is still guaranteed or
+    /// the state is outside the limits.
+    fn prognosis_warning(&mut self) -> Option<String> {
+        let guesses_left = MAX_GUESSES.saturating_sub(self.game.lines.len());
+        let candidates = self.cache.consistent_answers(&self.game);
+        let count = candidates.count();
+        if guesses_left == 0 || count <= guesses_left || count > PROGNOSIS_LIMIT {
+            return None;
+        }
+        let chance = strategy::exact_win_probability(&mut self.cache, &candidates, guesses_left)?;
+        (chance < 1.0).then(|| {
+            messages::fill(
+                "solve.prognosis",
+                &[&guesses_left, &count, &format!("{:.0}", chance * 100.0)],
+            )
+        })
+    }
+
+    /// Says that no word fits the feedback and, when leaving out a single guess fixes
+    /// that, which `rollback` removes the first such guess.
+    fn explain_contradiction(&self) {
+        let mut message = messages::text("solve.contradiction").to_string();
+        let guesses = self.game.lines.len();
+        let mut without = self.game.clone();
+        for skip in 0..guesses {
+            without.replay(&self.game.lines, Some(skip));
+            let fitting = Filter::new(&without, &self.candidate_pool)
+                .filter_words()
+                .len();
+            if fitting > 0 {
+                message.push('\n');
+                message.push_str(&messages::fill(
+                    "solve.contradiction_without",
+                    &[
+                        &(skip + 1),
+                        &self.game.lines[skip].word,
+                        &fitting,
+                        &(guesses - skip),
+                    ],
+                ));
+                break;
             }
-            let g = guessed_chars[i];
-            if let Some(count) = remaining_counts.get_mut(&g) {
-                if *count > 0 {
-                    result_cells[i].state = 'm';
-                    *count -= 1;
-                } else {
-                    result_cells[i].state = 'w';
-                }
-            } else {
-                result_cells[i].state = 'w';
+        }
+        message.push('\n');
+        message.push_str(messages::text("solve.contradiction_reconstruct"));
+        self.verbosity.notice(&format!("{}\n", message));
+    }
+
+    /// Prints the game so far next to a replay against `target` that follows
+    /// `what_if`.
+    fn show_what_if(&self, target: &str, what_if: WhatIf) {
+        let played: Vec<String> = self.game.lines.iter().map(|l| l.word.clone()).collect();
+        let actual: whatif::Line = self
+            .game
+            .lines
+            .iter()
+            .map(|l| (l.word.clone(), l.pattern()))
+            .collect();
+
+        let replay = whatif::replay(self, target, &played, what_if, &self.weights);
+        match replay {
+            Ok(alternative) => {
+                self.verbosity
+                    .notice(&whatif::format_comparison(&actual, &alternative, what_if))
             }
+            Err(e) => self.verbosity.notice(&format!(
+                "{}\n",
+                messages::fill("solve.replay_failed", &[&e])
+            )),
         }
+    }
+
+    /// `guessed_word` as a game line with its feedback against `target_word`, see
+    /// `game::grade`.
+    pub fn evaluate_word(guessed_word: &str, target_word: &str) -> LineData {
+        game::grade(guessed_word, target_word).line(guessed_word)
+    }
 
-        LineData {
-            word: guessed_word.to_string(),
-            cells: result_cells,
+    /// Strategy for the next guess, taking the endgame override into account.
+    pub fn active_strategy(&self) -> Strategy {
+        match self.endgame {
+            Some(endgame) if self.game.lines.len() + ENDGAME_TURNS >= MAX_GUESSES => endgame,
+            _ => self.strategy,
         }
     }
 
-    pub fn get_pattern(line: &LineData) -> String {
-        line.cells.iter().map(|cell| cell.state).collect()
+    /// Guess to commit to next with the active strategy, using the weight set for the
+    /// current attempt (the opener is ranked without weights). Expects
+    /// `current_words` to be filtered already.
+    pub fn next_guess(&mut self, stats_json: &str, weights: &[(f64, f64, f64)]) -> Result<String> {
+        if self.game.lines.is_empty() {
+            return self.get_top_suggestion_silent(stats_json, None);
+        }
+        let attempt = self.game.lines.len().min(weights.len() - 1);
+        self.get_top_suggestion_silent(stats_json, Some(weights[attempt]))
     }
 
     pub fn get_top_suggestion_silent(
-        &self,
+        &mut self,
         stats_json: &str,
         weights: Option<(f64, f64, f64)>,
     ) -> Result<String> {
+        if let Some(words) = self.last_candidates() {
+            trace!("{} candidate(s) left, skipping the ranking", words.len());
+            return Ok(words[0].clone());
+        }
+
+        if self.active_strategy() != Strategy::Frequency {
+            return self
+                .pattern_suggestions()
+                .0
+                .into_iter()
+                .next()
+                .map(|(word, _)| word)
+                .ok_or_else(|| anyhow!("No suggested words remaining"));
+        }
+
         // Elimination Mode check
         if let Some(trap) = trap::detect_trap(&self.current_words) {
-            if let Some((word, _)) = trap::find_best_elimination(&self.all_words, &trap) {
+            if let Some((word, _)) = self.elimination_word(&trap) {
+                debug!(
+                    "Trap among {:?}, playing elimination word '{}'",
+                    self.current_words, word
+                );
                 return Ok(word);
             }
         }
 
         let word_refs: Vec<&str> = self.current_words.iter().map(|s| s.as_str()).collect();
 
-        let ranked_words = if let Some(weight_tuple) = weights {
-            weighted_rank(&word_refs, stats_json, weight_tuple)?
+        let ranked_words = self.apply_prior(if let Some(weight_tuple) = weights {
+            weighted_rank(&word_refs, stats_json, weight_tuple, self.position_weights)?
         } else {
             rank_words(&word_refs, stats_json)?
-        };
+        });
 
         ranked_words
             .into_iter()
@@ -206,91 +918,1895 @@ impl Solver {
             .ok_or_else(|| anyhow!("No suggested words remaining"))
     }
 
-    pub fn rank_words(&mut self, stats_json: &str, print_output: bool) -> Result<()> {
-        // Read solver_config.json as Vec of tuples
-        let weights = util::read_solver_config()?;
+    /// The possible answers left when there are at most two, so the caller can skip
+    /// ranking. The frequency strategy counts `current_words`, the pattern strategies
+    /// the consistent answers. They come in letter-frequency order (`search_order`,
+    /// computed once at startup), or likeliest first when a `prior` is set. `None`
+    /// when more (or none) remain.
+    fn last_candidates(&mut self) -> Option<Vec<String>> {
+        let mut words: Vec<String> = if self.active_strategy() == Strategy::Frequency {
+            if self.current_words.len() > 2 {
+                return None;
+            }
+            self.current_words.clone()
+        } else {
+            let candidates = self.cache.consistent_answers(&self.game);
+            if candidates.count() > 2 {
+                return None;
+            }
+            candidates
+                .iter()
+                .map(|i| self.answer_words[i].clone())
+                .collect()
+        };
+        if words.is_empty() {
+            return None;
+        }
+        words.sort_by_key(|word| {
+            self.cache
+                .guess_index(word)
+                .and_then(|guess| self.search_order.iter().position(|&g| g == guess))
+                .unwrap_or(usize::MAX)
+        });
+        if let Some(prior) = &self.prior {
+            // The likelier answer first; ties keep the letter-frequency order
+            words.sort_by(|a, b| prior.weight(b).total_cmp(&prior.weight(a)));
+        }
+        Some(words)
+    }
+
+    /// Frequency-strategy scores weighted by `prior`, best first. Unchanged without a
+    /// prior.
+    fn apply_prior(&self, ranked: RankedList) -> RankedList {
+        let Some(prior) = &self.prior else {
+            return ranked;
+        };
+        ranked.rescored(|word, score| score * prior.weight(word))
+    }
 
-        // Select weight set based on number of guesses
-        let attempt = self.game.lines.len().min(weights.len() - 1);
-        let weight_tuple = weights[attempt];
+    /// Rare letters most of the candidates contain (see `analysis::rare_letters`),
+    /// none unless `rare_letters` is set.
+    fn rare_candidate_letters(&self) -> Result<Vec<RareLetter>> {
+        let Some(limits) = self.rare_letters else {
+            return Ok(Vec::new());
+        };
+        let stats = LetterStats::parse(&self.stats_json)?;
+        Ok(analysis::rare_letters(&stats, &self.current_words, limits))
+    }
 
-        // Update wordlist (filtered)
-        self.current_words = self.update_wordlist();
+    /// Probability of each of `words` being the answer, most likely first: equal
+    /// shares, or shares weighted by `prior` as the frequency strategy weights its
+    /// scores. Equally likely words keep their order.
+    fn answer_probabilities(&self, words: &[String]) -> Vec<(String, f64)> {
+        let weights: Vec<f64> = words
+            .iter()
+            .map(|w| self.prior.as_ref().map_or(1.0, |prior| prior.weight(w)))
+            .collect();
+        let total: f64 = weights.iter().sum();
+        let mut probabilities: Vec<(String, f64)> = words
+            .iter()
+            .zip(weights)
+            .map(|(w, weight)| (w.clone(), weight / total))
+            .collect();
+        probabilities.sort_by(|a, b| b.1.total_cmp(&a.1));
+        probabilities
+    }
 
-        // Trap Detection / Elimination Mode
-        if let Some(trap) = trap::detect_trap(&self.current_words) {
-            if let Some((word, score)) = trap::find_best_elimination(&self.all_words, &trap) {
-                if print_output {
-                    println!("!!! TRAP DETECTED !!!");
-                    println!("Varying positions: {:?}", trap.varying_positions);
-                    println!("Distinguishing letters: {:?}", trap.distinguishing_letters);
+    /// Names the candidate most likely to be the answer, and how many share its
+    /// probability when the model can't tell them apart. `None` without candidates.
+    fn most_likely_line(&self) -> Option<String> {
+        let probabilities = self.answer_probabilities(&self.current_words);
+        let (word, probability) = probabilities.first()?;
+        let percent = probability * 100.0;
+        let percent = match percent {
+            p if p >= 10.0 => format!("{:.0}", p),
+            p if p >= 1.0 => format!("{:.1}", p),
+            p => format!("{:.2}", p),
+        };
+        let tied = probabilities[1..]
+            .iter()
+            .take_while(|(_, p)| (p - probability).abs() < 1e-12)
+            .count();
+        Some(if tied == 0 {
+            messages::fill("solve.most_likely", &[&word.to_uppercase(), &percent])
+        } else {
+            messages::fill(
+                "solve.most_likely_tied",
+                &[&word.to_uppercase(), &percent, &tied],
+            )
+        })
+    }
+
+    /// Shows the last one or two candidates instead of a ranking. Each is equally
+    /// likely to be the answer unless `prior` weights them.
+    fn announce_last_candidates(&self, words: &[String], print_output: bool) {
+        if !print_output {
+            return;
+        }
+        let ranked = self.answer_probabilities(words);
+        match self.verbosity {
+            Verbosity::Verbose => {
+                if let [word] = words {
+                    println!(
+                        "{}\n",
+                        messages::fill("solve.one_match", &[&word.to_uppercase()])
+                    );
+                } else {
+                    let listed: Vec<String> = ranked
+                        .iter()
+                        .map(|(w, probability)| {
+                            format!("{} ({:.0}%)", w.to_uppercase(), probability * 100.0)
+                        })
+                        .collect();
                     println!(
-                        "ELIMINATION MODE: Recommended guess is '{}' (score: {})",
-                        word, score
+                        "{}",
+                        messages::fill("solve.two_matches", &[&listed[0], &listed[1]])
                     );
-                    println!("Remaining valid words: {:?}\n", self.current_words);
+                    println!("{}\n", messages::text("solve.two_matches_either"));
                 }
             }
+            Verbosity::Json => self.print_json_turn(&ranked, None),
+            Verbosity::Minimal => {}
         }
+    }
 
-        // Prepare for ranking
-        let word_refs: Vec<&str> = self.current_words.iter().map(|s| s.as_str()).collect();
-        let ranked_words = weighted_rank(&word_refs, stats_json, weight_tuple)?;
+    /// Guesses ranked by the active entropy or win-probability strategy over the
+    /// answers consistent with the game so far, with how many of the searched guesses
+    /// were scored within `think_limit`. Only the words shown are materialized as
+    /// strings.
+    pub fn pattern_suggestions(&mut self) -> (RankedList, (usize, usize)) {
+        let ranking = self.pattern_ranking();
+        let coverage = (ranking.evaluated, ranking.total);
+        let suggestions = if self.probe_words.is_some() {
+            let ranked = ranking
+                .scores
+                .into_iter()
+                .map(|(guess, score)| (self.cache.guess(guess), score))
+                .filter(|(word, _)| !self.game.banned.contains(word))
+                .collect();
+            let mut restricted = self.restrict_probes(ranked);
+            self.pinned_ranks = rank_pins(&self.game.pinned, &restricted);
+            restricted.truncate(EQUIVALENCE_WINDOW);
+            restricted
+        } else {
+            let banned: HashSet<usize> = self
+                .game
+                .banned
+                .iter()
+                .filter_map(|word| self.cache.guess_index(word))
+                .collect();
+            let scores: Vec<(usize, f64)> = ranking
+                .scores
+                .into_iter()
+                .filter(|(guess, _)| !banned.contains(guess))
+                .collect();
+            self.pinned_ranks = self
+                .game
+                .pinned
+                .iter()
+                .map(|word| {
+                    let rank = self.cache.guess_index(word).and_then(|guess| {
+                        let position = scores.iter().position(|(g, _)| *g == guess)?;
+                        Some((position + 1, scores[position].1))
+                    });
+                    (word.clone(), rank)
+                })
+                .collect();
+            scores
+                .into_iter()
+                .take(EQUIVALENCE_WINDOW)
+                .map(|(guess, score)| (self.cache.guess(guess), score))
+                .collect()
+        };
+        (RankedList::in_order(suggestions), coverage)
+    }
 
-        if print_output {
-            println!("Top suggested words (Normal Mode):");
-            for (word, score) in ranked_words.iter().take(10) {
-                println!("{word:<10} {score:.5}");
-            }
-            println!("Total Words Left: {}\n", self.current_words.len());
+    /// `ranked` in the `--stable` order, or unchanged without it.
+    fn stabilize(&self, ranked: Vec<(String, f64)>) -> Vec<(String, f64)> {
+        match self.stability {
+            Some(stability) => stability.apply(ranked),
+            None => ranked,
         }
+    }
 
-        Ok(())
+    /// Drops the banned words from a ranking and notes where the pinned ones are.
+    fn apply_bans_and_pins(&mut self, ranked: Vec<(String, f64)>) -> Vec<(String, f64)> {
+        let ranked: Vec<(String, f64)> = self
+            .stabilize(ranked)
+            .into_iter()
+            .filter(|(word, _)| !self.game.banned.contains(word))
+            .collect();
+        self.pinned_ranks = rank_pins(&self.game.pinned, &ranked);
+        ranked
     }
 
-    pub fn update_wordlist(&self) -> Vec<String> {
-        let filter = Filter::new(&self.game, &self.current_words);
-        filter.filter_words()
+    /// `ban <word>` or `pin <word>`: adds the word to the game's list and shows the
+    /// suggestions again.
+    fn ban_or_pin(
+        &mut self,
+        command: &str,
+        word: &str,
+        shown: Option<String>,
+    ) -> Result<Option<String>> {
+        if !self.all_words.iter().any(|w| w == word) {
+            self.verbosity.notice(&format!(
+                "{}\n",
+                messages::fill("solve.not_in_wordlist", &[&word])
+            ));
+            return Ok(shown);
+        }
+        let (list, key) = if command == "ban" {
+            (&mut self.game.banned, "solve.banned")
+        } else {
+            (&mut self.game.pinned, "solve.pinned_added")
+        };
+        if !list.iter().any(|w| w == word) {
+            list.push(word.to_string());
+        }
+        self.verbosity.notice(&format!(
+            "{}\n",
+            messages::fill(key, &[&word.to_uppercase()])
+        ));
+        self.suggest_again()
     }
 
-    fn is_game_won(&self) -> bool {
-        self.game.correct_positions.iter().all(|&pos| pos.is_some())
+    /// Ranks and prints the suggestions of the current turn again, after a change
+    /// of what they are ranked by.
+    fn suggest_again(&mut self) -> Result<Option<String>> {
+        if self.game.lines.is_empty() {
+            self.first_suggestions()
+        } else {
+            self.refresh_suggestions()
+        }
     }
 
-    fn get_solved_word(&self) -> Option<String> {
-        if self.is_game_won() {
-            Some(
-                self.game
-                    .correct_positions
+    /// Changes a weight set (`set weight <attempt> <position> <overall> <unique>`) or
+    /// the strategy (`set strategy <name>`) for this session and ranks again. The
+    /// change isn't written to solver_config.json; `save config` writes it elsewhere.
+    fn set_config(&mut self, setting: &str, shown: Option<String>) -> Result<Option<String>> {
+        let parts: Vec<&str> = setting.split_whitespace().collect();
+        match parts.as_slice() {
+            ["weight", attempt, values @ ..] => {
+                let attempt = match attempt.parse::<usize>() {
+                    Ok(attempt) if (1..=self.weights.len()).contains(&attempt) => attempt,
+                    _ => {
+                        self.verbosity.notice(&format!(
+                            "{}\n",
+                            messages::fill("solve.weight_attempt", &[&self.weights.len()])
+                        ));
+                        return Ok(shown);
+                    }
+                };
+                let values: Result<Vec<f64>, &str> = values
                     .iter()
-                    .map(|&c| c.unwrap())
-                    .collect(),
+                    .map(|value| {
+                        value
+                            .parse::<f64>()
+                            .ok()
+                            .filter(|value| value.is_finite())
+                            .ok_or(*value)
+                    })
+                    .collect();
+                let values = match values {
+                    Ok(values) => values,
+                    Err(value) => {
+                        self.verbosity.notice(&format!(
+                            "{}\n",
+                            messages::fill("solve.weight_value", &[&value])
+                        ));
+                        return Ok(shown);
+                    }
+                };
+                let [position, overall, unique] = values[..] else {
+                    self.verbosity
+                        .notice(&format!("{}\n", messages::text("solve.set_usage")));
+                    return Ok(shown);
+                };
+                self.weights[attempt - 1] = (position, overall, unique);
+                if !self.edited_weights.contains(&(attempt - 1)) {
+                    self.edited_weights.push(attempt - 1);
+                }
+                self.verbosity.notice(&format!(
+                    "{}\n",
+                    messages::fill(
+                        "solve.weight_set",
+                        &[&attempt, &position, &overall, &unique]
+                    )
+                ));
+            }
+            ["strategy", name] => match Strategy::from_name(name) {
+                Ok(strategy) => {
+                    self.strategy = strategy;
+                    self.edited_strategy = true;
+                    self.verbosity.notice(&format!(
+                        "{}\n",
+                        messages::fill("solve.strategy_set", &[&strategy.name()])
+                    ));
+                }
+                Err(e) => {
+                    self.verbosity.notice(&format!("{}\n", e));
+                    return Ok(shown);
+                }
+            },
+            _ => {
+                self.verbosity
+                    .notice(&format!("{}\n", messages::text("solve.set_usage")));
+                return Ok(shown);
+            }
+        }
+        self.suggest_again()
+    }
+
+    /// Prints the strategy and weight sets in effect (`show config`), marking those
+    /// changed in this session.
+    fn print_config(&self) {
+        let mark = |edited: bool| if edited { " *" } else { "" };
+        println!(
+            "{}",
+            messages::fill(
+                "solve.config_strategy",
+                &[
+                    &format!("{}{}", self.strategy.name(), mark(self.edited_strategy)),
+                    &self.endgame.map_or("none", |endgame| endgame.name()),
+                ]
             )
-        } else {
-            None
+        );
+        println!("{}", messages::text("solve.config_weights"));
+        for (attempt, (position, overall, unique)) in self.weights.iter().enumerate() {
+            println!(
+                "  {}: {:.3}  {:.3}  {:.3}{}",
+                attempt + 1,
+                position,
+                overall,
+                unique,
+                mark(self.edited_weights.contains(&attempt))
+            );
+        }
+        if self.weights.len() < MAX_GUESSES {
+            println!(
+                "{}",
+                messages::fill("solve.config_last_set", &[&self.weights.len()])
+            );
         }
+        let positions: Vec<String> = self
+            .position_weights
+            .iter()
+            .map(|m| format!("{:.2}", m))
+            .collect();
+        println!(
+            "{}",
+            messages::fill("solve.config_positions", &[&positions.join(" ")])
+        );
+        if self.edited_strategy || !self.edited_weights.is_empty() {
+            println!("{}", messages::text("solve.config_edited"));
+        }
+        println!();
     }
 
-    fn reset(&mut self) -> Result<()> {
-        self.game.reset();
-        self.current_words = self.all_words.clone();
-        self.print_initial_suggestions()?;
-        Ok(())
+    /// Writes the session's weight sets and position multipliers as a
+    /// solver_config.json (`save config <path>`).
+    fn save_config(&self, path: &str) -> Result<()> {
+        let config = util::SolverConfig {
+            weights: self.weights.clone(),
+            positions: self.position_weights,
+            meta: None,
+        };
+        let json = serde_json::to_string_pretty(&config)? + "\n";
+        util::write_atomic(Path::new(path), json.as_bytes()).map_err(|e| anyhow!("{}: {}", path, e))
     }
 
-    fn print_initial_suggestions(&self) -> Result<()> {
-        use crate::ranking::rank_words;
+    /// Drops unlisted probes from a pattern-strategy ranking when its best guess is
+    /// one and a listed probe or candidate scores within the threshold. Candidates
+    /// are never dropped.
+    fn restrict_probes(&mut self, ranked: Vec<(String, f64)>) -> Vec<(String, f64)> {
+        self.probe_restriction = None;
+        let Some(probes) = &self.probe_words else {
+            return ranked;
+        };
 
-        let stats_json = util::read_letter_stats()?;
-        let word_refs: Vec<&str> = self.current_words.iter().map(|s| s.as_str()).collect();
-        let start_results = rank_words(&word_refs, &stats_json)?;
+        let candidates = self.cache.consistent_answers(&self.game);
+        let is_candidate: Vec<bool> = ranked
+            .iter()
+            .map(|(word, _)| {
+                self.cache
+                    .guess_index(word)
+                    .and_then(|guess| self.cache.answer_of_guess(guess))
+                    .is_some_and(|answer| candidates.contains(answer))
+            })
+            .collect();
+        let Some((_, restriction)) = probes.restrict(&ranked, |i| is_candidate[i]) else {
+            return ranked;
+        };
+
+        debug!("{}", restriction.describe());
+        let kept = ranked
+            .into_iter()
+            .zip(is_candidate)
+            .filter(|((word, _), candidate)| *candidate || probes.contains(word))
+            .map(|(entry, _)| entry)
+            .collect();
+        self.probe_restriction = Some(restriction);
+        kept
+    }
+
+    /// Best word to split a trap with, preferring a probe_words.txt word that covers
+    /// nearly as many distinguishing letters.
+    fn elimination_word(&mut self, trap: &TrapInfo) -> Option<(String, usize)> {
+        self.probe_restriction = None;
+        let Some(probes) = &self.probe_words else {
+            return trap::find_best_elimination(&self.all_words, trap);
+        };
+
+        // Best first, equal scores alphabetically, as find_best_elimination picks
+        let mut ranked: Vec<(String, f64)> = self
+            .all_words
+            .iter()
+            .map(|w| (w.clone(), trap::score_elimination_word(w, trap) as f64))
+            .filter(|&(_, score)| score > 0.0)
+            .collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        let chosen = match probes.restrict(&ranked, |i| self.current_words.contains(&ranked[i].0)) {
+            Some((index, restriction)) => {
+                debug!("{}", restriction.describe());
+                self.probe_restriction = Some(restriction);
+                index
+            }
+            None => 0,
+        };
+        ranked
+            .into_iter()
+            .nth(chosen)
+            .map(|(word, score)| (word, score as usize))
+    }
 
-        println!("Top 10 words by letter position frequency:");
-        for (word, score) in start_results.iter().take(10) {
-            println!("{word:<10} {score:.5}");
+    /// Every scored guess of the active pattern-based strategy, as guess indices.
+    pub fn pattern_ranking(&mut self) -> Ranking {
+        if let Some(ranking) = self.take_prefetched() {
+            return ranking;
         }
-        println!();
+        let candidates = self.cache.consistent_answers(&self.game);
+        self.pattern_ranking_over(&candidates)
+    }
 
+    /// Reads the pattern of `guess` with `read` while the likely turns after it are
+    /// ranked in the background (`--prefetch`), keeping the ranking of the entered
+    /// pattern for `pattern_ranking`. Not with a think limit, where how much of a
+    /// ranking is done depends on timing, or when only frequency ranks the turns.
+    fn read_prefetching(
+        &mut self,
+        guess: &str,
+        read: impl FnOnce() -> io::Result<Option<String>>,
+    ) -> io::Result<Option<String>> {
+        self.prefetched = None;
+        if self.prefetch == 0
+            || self.think_limit.is_some()
+            || self.assist_on_demand
+            || (self.strategy == Strategy::Frequency && self.endgame.is_none())
+        {
+            return read();
+        }
+        let patterns = prefetch::likely_patterns(guess, &self.current_words, self.prefetch);
+        if patterns.is_empty() {
+            return read();
+        }
+        let (line, prefetched) = prefetch::while_reading(self.fork(), guess, &patterns, read)?;
+        self.prefetched = prefetched;
+        Ok(line)
+    }
+
+    /// Whether a prefetch no longer needs this worker's rankings.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel
+            .as_ref()
+            .is_some_and(|cancel| cancel.load(Ordering::Relaxed))
+    }
+
+    /// The prefetched ranking of the game's last guess and pattern, if it was one of
+    /// the patterns ranked ahead. The prefetch is used up either way.
+    fn take_prefetched(&mut self) -> Option<Ranking> {
+        let prefetched = self.prefetched.take()?;
+        let line = self.game.lines.last()?;
+        if self.game.lines.len() != prefetched.turn + 1
+            || line.word != prefetched.guess
+            || line.pattern() != prefetched.pattern
+        {
+            return None;
+        }
+        debug!("Prefetch hit for {} {}", line.word, prefetched.pattern);
+        Some(prefetched.ranking)
+    }
+
+    /// `pattern_ranking` for a given set of candidate answers.
+    fn pattern_ranking_over(&mut self, candidates: &CandidateSet) -> Ranking {
+        let started = Instant::now();
+        let mut deadline = Deadline::after(self.think_limit);
+        if let Some(cancel) = &self.cancel {
+            deadline = deadline.or_cancelled(cancel);
+        }
+        let ranking = match self.active_strategy() {
+            Strategy::WinProb => strategy::win_probability_rank(
+                &mut self.cache,
+                candidates,
+                MAX_GUESSES.saturating_sub(self.game.lines.len()),
+                &self.search_order,
+                deadline,
+            ),
+            Strategy::Minimax => {
+                strategy::minimax_rank(&mut self.cache, candidates, &self.search_order, deadline)
+            }
+            _ => strategy::entropy_rank(
+                &mut self.cache,
+                candidates,
+                &self.search_order,
+                deadline,
+                true,
+            ),
+        };
+        debug!(
+            "{} ranking over {} answers took {:?} ({} of {} guesses scored)",
+            self.active_strategy().name(),
+            candidates.count(),
+            started.elapsed(),
+            ranking.evaluated,
+            ranking.total
+        );
+        for (guess, score) in ranking.scores.iter().take(10) {
+            trace!("  {} {:.5}", self.cache.guess(*guess), score);
+        }
+        ranking
+    }
+
+    /// Tells the user when the time budget cut the search short.
+    fn report_coverage(&self, (evaluated, total): (usize, usize)) {
+        if evaluated < total {
+            self.verbosity.notice(&messages::fill(
+                "solve.time_budget",
+                &[
+                    &evaluated,
+                    &total,
+                    &format!("{:.0}", evaluated as f64 / total as f64 * 100.0),
+                ],
+            ));
+        }
+    }
+
+    /// Filters the candidates, ranks them and returns the best suggestion (the
+    /// elimination word when a trap is detected). Printing follows `self.verbosity`.
+    pub fn rank_words(&mut self, stats_json: &str, print_output: bool) -> Result<Option<String>> {
+        let active = self.active_strategy();
+        self.update_wordlist();
+        if let Some(words) = self.last_candidates() {
+            self.probe_restriction = None;
+            self.announce_last_candidates(&words, print_output);
+            return Ok(words.into_iter().next());
+        }
+
+        if active != Strategy::Frequency {
+            let (ranked_words, coverage) = self.pattern_suggestions();
+            let ranked_words = self.stabilize(ranked_words.into_vec());
+            if print_output {
+                self.report_coverage(coverage);
+            }
+            let title = messages::fill("solve.top_suggestions", &[&active.score_label()]);
+            self.print_suggestions(&title, &ranked_words, None, print_output);
+            return Ok(ranked_words.into_iter().next().map(|(word, _)| word));
+        }
+
+        // Select weight set based on number of guesses
+        let attempt = self.game.lines.len().min(self.weights.len() - 1);
+        let weight_tuple = self.weights[attempt];
+        debug!(
+            "Attempt {}: weights (pos {}, overall {}, unique {})",
+            attempt + 1,
+            weight_tuple.0,
+            weight_tuple.1,
+            weight_tuple.2
+        );
+
+        let verbose = print_output && self.verbosity.is_verbose();
+        let mut elimination = None;
+
+        // Trap Detection / Elimination Mode
+        self.probe_restriction = None;
+        if let Some(trap) = trap::detect_trap(&self.current_words) {
+            if let Some((word, score)) = self.elimination_word(&trap) {
+                if verbose {
+                    println!("{}", messages::text("solve.trap"));
+                    println!(
+                        "{}",
+                        messages::fill(
+                            "solve.trap_positions",
+                            &[&format!("{:?}", trap.varying_positions)]
+                        )
+                    );
+                    println!(
+                        "{}",
+                        messages::fill(
+                            "solve.trap_letters",
+                            &[&format!("{:?}", trap.distinguishing_letters)]
+                        )
+                    );
+                    println!(
+                        "{}",
+                        messages::fill("solve.trap_elimination", &[&word, &score])
+                    );
+                    println!("{}", messages::text("solve.trap_remaining"));
+                    println!("{}", output::columns(&self.current_words, output::width()));
+                }
+                elimination = Some(word);
+            }
+        }
+
+        // Prepare for ranking
+        let word_refs: Vec<&str> = self.current_words.iter().map(|s| s.as_str()).collect();
+        let ranked_words = self.apply_prior(weighted_rank(
+            &word_refs,
+            stats_json,
+            weight_tuple,
+            self.position_weights,
+        )?);
+        let rare = self.rare_candidate_letters()?;
+        for letter in &rare {
+            debug!(
+                "Rare letter '{}' ({:.2}% of all letters) in {:.0}% of candidates: boosted",
+                letter.letter,
+                letter.frequency * 100.0,
+                letter.share * 100.0
+            );
+        }
+        if print_output {
+            for letter in &rare {
+                self.verbosity.notice(&rare_letter_note(letter));
+            }
+        }
+        let ranked_words =
+            self.apply_bans_and_pins(boost_rare_letters(ranked_words, &rare).into_vec());
+
+        self.print_suggestions(
+            messages::text("solve.top_normal"),
+            &ranked_words,
+            elimination.as_deref(),
+            print_output,
+        );
+
+        Ok(elimination.or_else(|| ranked_words.into_iter().next().map(|(word, _)| word)))
+    }
+
+    fn save_game(&self, path: &str) -> Result<()> {
+        let mut game = self.game.clone();
+        game.wordlist_hash = Some(wordlist::active_hash()?);
+        let json = serde_json::to_string_pretty(&game)?;
+        util::write_atomic(Path::new(path), json.as_bytes())?;
+        Ok(())
+    }
+
+    /// Replaces the game with a saved one (migrated to the current format) and
+    /// re-filters the candidates from the full wordlist. A game saved with another
+    /// wordlist is refused unless `force` is set.
+    fn load_game(&mut self, path: &str, force: bool) -> Result<()> {
+        let json = fs::read_to_string(path)?;
+        let game = GameData::from_json(&json)?;
+        if let Some(saved) = &game.wordlist_hash {
+            let current = wordlist::active_hash()?;
+            if *saved != current {
+                if !force {
+                    return Err(anyhow!(messages::fill(
+                        "solve.load_other_wordlist",
+                        &[&path, saved, &current, &path]
+                    )));
+                }
+                eprintln!("{}", messages::fill("solve.load_forced", &[&path]));
+            }
+        }
+        self.game = game;
+        self.reset_candidates();
+        self.turn_notes = vec![(None, None); self.game.lines.len()];
+        self.think_times.truncate(0);
+        Ok(())
+    }
+
+    /// Restores the candidates to the full pool, as before the first guess.
+    pub fn reset_candidates(&mut self) {
+        self.current_words = self.candidate_pool.clone();
+    }
+
+    /// Narrows `current_words` to the words allowed by the game so far.
+    pub fn update_wordlist(&mut self) {
+        let filtered = Filter::new(&self.game, &self.current_words).filter_words();
+        debug!(
+            "Candidates: {} -> {} after {} guess(es)",
+            self.current_words.len(),
+            filtered.len(),
+            self.game.lines.len()
+        );
+
+        if self.check_filter {
+            for word in filter::wrongly_eliminated(&self.game, &self.current_words, &filtered) {
+                self.filter_bugs += 1;
+                eprintln!(
+                    "Filter bug: '{}' was eliminated but matches every recorded pattern:",
+                    word
+                );
+                for line in &self.game.lines {
+                    eprintln!("  {} {}", line.word, line.pattern());
+                }
+            }
+        }
+
+        self.current_words = filtered;
+    }
+
+    /// Whether the last guess's feedback looks mistyped, judged by the candidates it
+    /// leaves against those before it. Never with `--no-sanity-checks`.
+    fn unusual_feedback(&self) -> Option<sanity::Unusual> {
+        if !self.sanity_checks {
+            return None;
+        }
+        let after = Filter::new(&self.game, &self.current_words).filter_words();
+        sanity::check(&self.current_words, &after)
+    }
+
+    /// Whether a guess got all-correct feedback. Knowing every position from
+    /// different guesses isn't a win: the answer still has to be played.
+    fn is_game_won(&self) -> bool {
+        self.get_solved_word().is_some()
+    }
+
+    /// The guess that got all-correct feedback.
+    fn get_solved_word(&self) -> Option<String> {
+        self.game
+            .lines
+            .iter()
+            .find(|line| line.pattern() == "ccccc")
+            .map(|line| line.word.clone())
+    }
+
+    /// The answer spelled out by the known positions once all five are, before it
+    /// has been played.
+    fn determined_word(&self) -> Option<String> {
+        self.game.correct_positions.iter().copied().collect()
+    }
+
+    /// Tells which word to type next when the known positions spell out the answer,
+    /// warning when that word isn't in the wordlist.
+    fn print_determined(&self) {
+        let Some(word) = self.determined_word() else {
+            return;
+        };
+        self.verbosity.notice(&format!(
+            "{}\n",
+            messages::fill("solve.determined", &[&word.to_uppercase()])
+        ));
+        if !self.all_words.contains(&word) {
+            self.verbosity.notice(&format!(
+                "{}\n",
+                messages::fill("solve.determined_unknown", &[&word.to_uppercase()])
+            ));
+        }
+    }
+
+    fn print_initial_suggestions(&mut self) -> Result<Option<String>> {
+        self.probe_restriction = None;
+        let active = self.active_strategy();
+        // A time-limited ranking depends on the machine, so only full rankings are cached
+        let key = (self.warm_start && self.think_limit.is_none())
+            .then(|| CacheKey::new(active.name(), &self.stats_json, &self.current_words));
+        let start_results = match key.as_ref().and_then(warmstart::load) {
+            Some(ranking) => {
+                debug!("Reusing the cached first-guess ranking");
+                ranking
+            }
+            None => {
+                let ranking = self.initial_ranking()?;
+                if let Some(key) = &key {
+                    warmstart::store(key, &ranking);
+                }
+                ranking
+            }
+        };
+        let start_results = if active != Strategy::Frequency {
+            let ranked = start_results
+                .into_iter()
+                .filter(|(word, _)| !self.game.banned.contains(word))
+                .collect();
+            self.restrict_probes(ranked)
+        } else {
+            self.apply_prior(RankedList::new(start_results)).into_vec()
+        };
+        let start_results = self.apply_bans_and_pins(start_results);
+        let title = messages::fill("solve.top_initial", &[&active.score_label()]);
+        let opener = self.benchmarked_opener();
+
+        match self.verbosity {
+            Verbosity::Verbose => {
+                if let Some(opener) = &opener {
+                    println!(
+                        "{}\n",
+                        messages::fill(
+                            "solve.opener",
+                            &[&opener.word, &format!("{:.3}", opener.score), &opener.games]
+                        )
+                    );
+                }
+                println!("{}", title);
+                self.print_grouped(&start_results);
+                self.print_probe_restriction();
+                println!();
+            }
+            Verbosity::Json => self.print_json_turn(&start_results, None),
+            Verbosity::Minimal => {}
+        }
+
+        Ok(opener
+            .map(|opener| opener.word)
+            .or_else(|| start_results.into_iter().next().map(|(word, _)| word)))
+    }
+
+    /// The profile's opener from `openers --auto-select` when it was benchmarked for
+    /// the active strategy. One benchmarked on other lists is not used, with a warning.
+    fn benchmarked_opener(&self) -> Option<openers::StoredOpener> {
+        let opener = openers::load_opener()?;
+        if opener.strategy != self.active_strategy().name() {
+            return None;
+        }
+        if opener.wordlist_hash != openers::lists_hash(&self.all_words, &self.answer_words) {
+            eprintln!("{}", messages::fill("solve.opener_stale", &[&opener.word]));
+            return None;
+        }
+        Some(opener)
+    }
+
+    /// Every candidate ranked by the active strategy before any guess.
+    pub fn initial_ranking(&mut self) -> Result<Vec<(String, f64)>> {
+        if self.active_strategy() == Strategy::Frequency {
+            let word_refs: Vec<&str> = self.current_words.iter().map(|s| s.as_str()).collect();
+            return Ok(rank_words(&word_refs, &self.stats_json)?.into_vec());
+        }
+
+        let ranking = self.pattern_ranking();
+        self.report_coverage((ranking.evaluated, ranking.total));
+        Ok(ranking
+            .scores
+            .into_iter()
+            .map(|(guess, score)| (self.cache.guess(guess), score))
+            .collect())
+    }
+
+    /// The top suggestions with strategically equivalent guesses folded into the
+    /// best ranked of them, so they don't take up display slots.
+    fn print_grouped(&mut self, ranked_words: &[(String, f64)]) {
+        let groups = self.equivalent_groups(ranked_words);
+        print!("{}", self.grouped_text(&groups));
+        let shown: HashSet<&String> = groups
+            .iter()
+            .take(10)
+            .flat_map(|((word, _), equivalent)| std::iter::once(word).chain(equivalent))
+            .collect();
+        for (word, rank) in &self.pinned_ranks {
+            if shown.contains(word) {
+                continue;
+            }
+            match rank {
+                Some((rank, score)) => println!(
+                    "{}",
+                    messages::fill(
+                        "solve.pinned",
+                        &[&word.to_uppercase(), rank, &format!("{:.5}", score)]
+                    )
+                ),
+                None => println!(
+                    "{}",
+                    messages::fill("solve.pinned_unranked", &[&word.to_uppercase()])
+                ),
+            }
+        }
+    }
+
+    /// The top 10 `groups` as `print_grouped` shows them, the possible answers marked
+    /// and counted when the answer list is separate.
+    fn grouped_text(&self, groups: &[((String, f64), Vec<String>)]) -> String {
+        let marked: HashSet<String> = groups
+            .iter()
+            .take(10)
+            .map(|((word, _), _)| word)
+            .filter(|word| !self.answer_set.is_empty() && self.is_possible_answer(word))
+            .cloned()
+            .collect();
+        let mut text = match self.stability {
+            Some(stability) => {
+                output::format_grouped_rounded(groups, &self.game, 10, stability.precision, &marked)
+            }
+            None => output::format_grouped(groups, &self.game, 10, &marked),
+        };
+        if !self.answer_set.is_empty() {
+            text.push_str(&messages::fill(
+                "solve.possible_answers",
+                &[&marked.len(), &groups.len().min(10), &output::ANSWER_MARK],
+            ));
+            text.push('\n');
+        }
+        text
+    }
+
+    /// Whether `word` may still be the answer: it fits the feedback so far and, with
+    /// a separate answer list, is on it.
+    fn is_possible_answer(&self, word: &str) -> bool {
+        (self.answer_set.is_empty() || self.answer_set.contains(word))
+            && self.current_words.iter().any(|w| w == word)
+    }
+
+    /// Groups the first `EQUIVALENCE_WINDOW` ranked words by the partition they
+    /// induce on the candidates and whether they are one, each group under its best
+    /// ranked word in ranking order. Words outside the guess list stay on their own.
+    fn equivalent_groups(
+        &mut self,
+        ranked_words: &[(String, f64)],
+    ) -> Vec<((String, f64), Vec<String>)> {
+        let candidates = self.cache.consistent_answers(&self.game);
+        let mut groups: Vec<((String, f64), Vec<String>)> = Vec::new();
+        let mut signatures: HashMap<Vec<u8>, usize> = HashMap::new();
+        for (word, score) in ranked_words.iter().take(EQUIVALENCE_WINDOW) {
+            let signature = match self.cache.guess_index(word) {
+                Some(guess) if candidates.count() > 1 => {
+                    // A guess that may be the answer can win outright, unlike one
+                    // that splits the candidates the same way but can't
+                    let mut signature = self.cache.partition_signature(guess, &candidates);
+                    signature.push(u8::from(
+                        self.cache
+                            .answer_of_guess(guess)
+                            .is_some_and(|answer| candidates.contains(answer)),
+                    ));
+                    Some(signature)
+                }
+                _ => None,
+            };
+            match signature.as_ref().and_then(|s| signatures.get(s)) {
+                Some(&group) => groups[group].1.push(word.clone()),
+                None => {
+                    if let Some(signature) = signature {
+                        signatures.insert(signature, groups.len());
+                    }
+                    groups.push(((word.clone(), *score), Vec::new()));
+                }
+            }
+        }
+        groups
+    }
+
+    fn print_suggestions(
+        &mut self,
+        title: &str,
+        ranked_words: &[(String, f64)],
+        elimination: Option<&str>,
+        print_output: bool,
+    ) {
+        if !print_output {
+            return;
+        }
+        match self.verbosity {
+            Verbosity::Verbose => {
+                println!("{}", title);
+                self.print_grouped(ranked_words);
+                self.print_probe_restriction();
+                if let Some(line) = self
+                    .most_likely_line()
+                    .filter(|_| !self.game.lines.is_empty())
+                {
+                    println!("{}", line);
+                }
+                println!(
+                    "{}\n",
+                    messages::fill("solve.words_left", &[&self.current_words.len()])
+                );
+            }
+            Verbosity::Json => self.print_json_turn(ranked_words, elimination),
+            Verbosity::Minimal => {}
+        }
+    }
+
+    /// Candidates left before each guess and after the last one (one once solved),
+    /// or `None` when a guess was restored with `load` and its count is unknown.
+    fn candidate_counts(&self) -> Option<Vec<usize>> {
+        let mut counts: Vec<usize> = self
+            .turn_notes
+            .iter()
+            .map(|(before, _)| *before)
+            .collect::<Option<_>>()?;
+        counts.push(if self.is_game_won() {
+            1
+        } else {
+            self.current_words.len()
+        });
+        Some(counts)
+    }
+
+    /// Prints how much each guess narrowed the candidates, and the information the
+    /// game needed against what its guesses could have given.
+    fn print_elimination(&self, counts: &[usize]) {
+        let mut table = Table::new()
+            .column("guess", "#", CellFormat::Integer)
+            .column(
+                "word",
+                messages::text("solve.column_word"),
+                CellFormat::Text,
+            )
+            .column(
+                "before",
+                messages::text("solve.column_before"),
+                CellFormat::Integer,
+            )
+            .column(
+                "after",
+                messages::text("solve.column_after"),
+                CellFormat::Integer,
+            )
+            .column(
+                "eliminated",
+                messages::text("solve.column_eliminated"),
+                CellFormat::Percent(1),
+            )
+            .column(
+                "bits",
+                messages::text("solve.column_bits"),
+                CellFormat::Fixed(2),
+            );
+        for (i, (line, pair)) in self.game.lines.iter().zip(counts.windows(2)).enumerate() {
+            let (before, after) = (pair[0], pair[1]);
+            table.push_row(vec![
+                (i + 1).into(),
+                line.word.to_uppercase().into(),
+                before.into(),
+                after.into(),
+                stats::eliminated_share(before, after).into(),
+                stats::bits_gained(before, after).into(),
+            ]);
+        }
+        println!("\n{}", messages::text("solve.elimination"));
+        print!("{}", table.to_text());
+
+        let initial = counts[0];
+        let guesses = self.game.lines.len();
+        if let Some(efficiency) = stats::information_efficiency(initial, guesses) {
+            println!(
+                "{}",
+                messages::fill(
+                    "solve.information",
+                    &[
+                        &format!("{:.2}", stats::bits_gained(initial, 1)),
+                        &initial,
+                        &guesses,
+                        &format!("{:.2}", stats::information_budget(guesses)),
+                        &format!("{:.0}", efficiency * 100.0),
+                    ]
+                )
+            );
+        }
+    }
+
+    /// Appends the game to the active profile's history once it is solved or out of
+    /// guesses; a lost game has no answer unless it was entered with `answer`. A failed
+    /// write only warns, since the game itself is over.
+    fn record_history(&mut self, answer: Option<&str>, won: bool, candidates: Vec<usize>) {
+        if self.recorded {
+            return;
+        }
+        self.recorded = true;
+        let mut record = GameRecord::new("solve", answer, self.game.lines.len(), won);
+        record.candidates = candidates;
+        record.hints = self.assist_on_demand.then_some(self.hints);
+        record.think_seconds = self.think_times.seconds(self.game.lines.len());
+        if let Some(answer) = answer {
+            match self.answer_trajectory(answer) {
+                Ok(steps) => record.answer_ranks = steps,
+                Err(e) => eprintln!("{}", messages::fill("solve.ranks_failed", &[&e])),
+            }
+        }
+        if let Err(e) = history::append(&record) {
+            eprintln!("{}", messages::fill("solve.history_failed", &[&e]));
+        }
+    }
+
+    /// Prints the game so far as a shareable text report (`report`).
+    fn print_report(&self, mode: ReportMode) {
+        let won = self.is_game_won();
+        let answer = self.get_solved_word().or_else(|| self.known_answer.clone());
+        let ranks: Vec<usize> = answer
+            .as_deref()
+            .and_then(|answer| self.answer_trajectory(answer).ok())
+            .unwrap_or_default()
+            .iter()
+            .map(|step| step.rank)
+            .collect();
+        let thinks = self.think_times.seconds(self.game.lines.len());
+        let turns: Vec<ReportTurn> = self
+            .game
+            .lines
+            .iter()
+            .enumerate()
+            .map(|(i, line)| {
+                let (before, suggestion) = self.turn_notes.get(i).cloned().unwrap_or_default();
+                let after = match self.turn_notes.get(i + 1) {
+                    Some((after, _)) => *after,
+                    None if won => Some(1),
+                    None => Some(self.current_words.len()),
+                };
+                ReportTurn {
+                    word: line.word.clone(),
+                    pattern: line.pattern(),
+                    candidates: before.zip(after),
+                    suggestion,
+                    answer_rank: ranks.get(i).copied(),
+                    think_seconds: thinks.get(i).copied(),
+                }
+            })
+            .collect();
+
+        print!(
+            "{}",
+            report::format_report(
+                &turns,
+                answer.as_deref(),
+                mode,
+                self.assist_on_demand.then_some(self.hints),
+            )
+        );
+        println!();
+    }
+
+    /// The guesses so far, each with the candidates that fit before it, replayed from
+    /// the full pool.
+    pub fn candidate_trace(&self) -> Vec<TraceStep> {
+        let mut game = GameData::new();
+        let mut candidates = self.candidate_pool.clone();
+        let mut trace = Vec::new();
+        for line in &self.game.lines {
+            let pattern = line.pattern();
+            game.add_line(&line.word, &pattern);
+            let after = Filter::new(&game, &candidates).filter_words();
+            trace.push(TraceStep {
+                guess: line.word.clone(),
+                pattern,
+                candidates: std::mem::replace(&mut candidates, after),
+            });
+        }
+        trace
+    }
+
+    /// Where `answer` ranked among the candidates left after each guess so far, by
+    /// the frequency scores the solver shows for the next attempt (with the history
+    /// prior and stable order, without bans). Always a frequency rank, even when
+    /// another strategy suggested the guesses. The game is replayed from the full pool
+    /// with the same filter as `candidate_trace`, so the ranks are exact. Empty when
+    /// the feedback ruled the answer out, i.e. it doesn't fit the game.
+    pub fn answer_trajectory(&self, answer: &str) -> Result<Vec<AnswerStep>> {
+        let mut game = GameData::new();
+        let mut candidates = self.candidate_pool.clone();
+        let mut steps = Vec::new();
+        for (i, line) in self.game.lines.iter().enumerate() {
+            game.add_line(&line.word, &line.pattern());
+            candidates = Filter::new(&game, &candidates).filter_words();
+            let attempt = (i + 1).min(self.weights.len() - 1);
+            let word_refs: Vec<&str> = candidates.iter().map(|s| s.as_str()).collect();
+            let ranked = self.stabilize(
+                self.apply_prior(weighted_rank(
+                    &word_refs,
+                    &self.stats_json,
+                    self.weights[attempt],
+                    self.position_weights,
+                )?)
+                .into_vec(),
+            );
+            let Some(rank) = ranked.iter().position(|(word, _)| word == answer) else {
+                return Ok(Vec::new());
+            };
+            steps.push(AnswerStep {
+                rank: rank + 1,
+                score: ranked[rank].1,
+            });
+        }
+        Ok(steps)
+    }
+
+    /// Prints how close the other candidates came to `answer` (`--near-misses`).
+    fn print_near_misses(&self, answer: &str) {
+        println!();
+        print!(
+            "{}",
+            analysis::near_miss_report(&self.candidate_trace(), answer)
+        );
+    }
+
+    /// Prints the candidates clustered by their unconfirmed positions (`groups`).
+    fn print_groups(&self) {
+        let groups = analysis::group_candidates(&self.game, &self.current_words);
+        println!(
+            "{}",
+            messages::fill("solve.groups", &[&self.current_words.len(), &groups.len()])
+        );
+        for (key, mut words) in groups {
+            if let Some(last) = words.last_mut() {
+                last.push('}');
+            }
+            print!(
+                "{}",
+                output::wrap_list(&format!("{}: {{", key), &words, output::width())
+            );
+        }
+        println!();
+    }
+
+    /// Prints the most likely letters in each position among the candidates (`letters`).
+    fn print_position_probabilities(&self) {
+        let words: Vec<&str> = self.current_words.iter().map(String::as_str).collect();
+        if words.is_empty() {
+            println!("{}\n", messages::text("solve.no_candidates"));
+            return;
+        }
+        println!("{}", messages::fill("solve.letters", &[&words.len()]));
+        for (i, position) in analysis::position_probabilities(&words).iter().enumerate() {
+            let letters: Vec<String> = analysis::top_letters(position, PROBS_LETTERS)
+                .into_iter()
+                .map(|(ch, p)| format!("{} {:>5.1}%", ch.to_uppercase(), p * 100.0))
+                .collect();
+            println!("  {}: {}", i + 1, letters.join("   "));
+        }
+        println!();
+    }
+
+    /// Ranks every guess by the information it gains alone (`probes`), whether or not it
+    /// can still be the answer, without touching the game.
+    fn print_probes(&mut self) {
+        let candidates = self.cache.consistent_answers(&self.game);
+        let total = candidates.count();
+        if total <= 1 {
+            println!("{}\n", messages::text("solve.probes_none"));
+            return;
+        }
+        let ranking = strategy::entropy_rank(
+            &mut self.cache,
+            &candidates,
+            &self.search_order,
+            Deadline::default(),
+            false,
+        );
+        println!("{}", messages::fill("solve.probes", &[&total]));
+        for (rank, &(guess, bits)) in ranking.scores.iter().take(PROBE_LIST).enumerate() {
+            let remaining =
+                strategy::expected_remaining(&self.cache.buckets(guess, &candidates), total);
+            let candidate = self
+                .cache
+                .answer_of_guess(guess)
+                .is_some_and(|i| candidates.contains(i));
+            println!(
+                "{}",
+                messages::fill(
+                    "solve.probe",
+                    &[
+                        &(rank + 1),
+                        &self.cache.guess(guess),
+                        &format!("{:.3}", bits),
+                        &format!("{:.1}", remaining),
+                        &if candidate {
+                            messages::text("solve.probe_candidate")
+                        } else {
+                            ""
+                        },
+                    ]
+                )
+            );
+        }
+        println!("{}\n", messages::text("solve.probes_unchanged"));
+    }
+
+    /// Lists the strings over the wordlist's letters that fit the feedback when no
+    /// word of the wordlist does (`reconstruct`), for an answer missing from the
+    /// wordlist. Any of them can be entered as a guess afterwards.
+    fn print_reconstruction(&mut self) {
+        if !self.current_words.is_empty() {
+            println!(
+                "{}\n",
+                messages::fill("solve.reconstruct_unneeded", &[&self.current_words.len()])
+            );
+            return;
+        }
+        let stats = match LetterStats::parse(&self.stats_json) {
+            Ok(stats) => stats,
+            Err(e) => {
+                println!("{}\n", messages::fill("solve.stats_unreadable", &[&e]));
+                return;
+            }
+        };
+        let constraints = self.game.letter_constraints();
+        match reconstruct::enumerate(&constraints, &stats.alphabet(), MAX_RECONSTRUCTIONS) {
+            None => println!(
+                "{}\n",
+                messages::fill("solve.reconstruct_too_many", &[&MAX_RECONSTRUCTIONS])
+            ),
+            Some(strings) if strings.is_empty() => {
+                println!("{}\n", messages::text("solve.reconstruct_none"))
+            }
+            Some(strings) => {
+                println!(
+                    "{}",
+                    messages::fill("solve.reconstructed", &[&strings.len()])
+                );
+                let ranked = reconstruct::rank(&strings, &stats);
+                for (rank, (word, _)) in ranked.iter().take(RECONSTRUCT_LIST).enumerate() {
+                    println!(
+                        "{}",
+                        messages::fill("solve.reconstruction", &[&(rank + 1), word])
+                    );
+                }
+                println!("{}\n", messages::text("solve.reconstruct_play"));
+                self.reconstructed = strings;
+            }
+        }
+    }
+
+    /// Ranks only the candidates matching `spec` (`peek <glob>`), as if it were a
+    /// known constraint, without touching the game.
+    fn peek(&mut self, spec: &str) -> Result<()> {
+        let matching: Vec<String> = self
+            .current_words
+            .iter()
+            .filter(|w| filter::matches_hypothesis(spec, w))
+            .cloned()
+            .collect();
+        println!(
+            "{}",
+            messages::fill(
+                "solve.peek",
+                &[&spec, &matching.len(), &self.current_words.len()]
+            )
+        );
+        if matching.is_empty() {
+            println!("{}\n", messages::text("solve.peek_none"));
+            return Ok(());
+        }
+
+        let ranked = if self.active_strategy() == Strategy::Frequency {
+            let attempt = self.game.lines.len().min(self.weights.len() - 1);
+            let word_refs: Vec<&str> = matching.iter().map(|s| s.as_str()).collect();
+            self.apply_prior(weighted_rank(
+                &word_refs,
+                &self.stats_json,
+                self.weights[attempt],
+                self.position_weights,
+            )?)
+            .top(10)
+            .to_vec()
+        } else {
+            let matching: HashSet<&String> = matching.iter().collect();
+            let mut candidates = CandidateSet::empty(self.answer_words.len());
+            for (i, answer) in self.answer_words.iter().enumerate() {
+                if matching.contains(answer) {
+                    candidates.insert(i);
+                }
+            }
+            self.pattern_ranking_over(&candidates)
+                .scores
+                .into_iter()
+                .take(10)
+                .map(|(guess, score)| (self.cache.guess(guess), score))
+                .collect()
+        };
+        println!("{}", messages::text("solve.peek_top"));
+        output::print_ranked(&ranked, &self.game);
+        println!("{}\n", messages::text("solve.peek_unchanged"));
         Ok(())
     }
+
+    fn print_probe_restriction(&self) {
+        if let Some(restriction) = &self.probe_restriction {
+            println!("{}", restriction.describe());
+        }
+    }
+
+    fn print_json_turn(&self, ranked_words: &[(String, f64)], elimination: Option<&str>) {
+        let suggestions: Vec<serde_json::Value> = ranked_words
+            .iter()
+            .take(10)
+            .map(|(word, score)| {
+                let possible = self.is_possible_answer(word);
+                match self.stability {
+                    Some(stability) => serde_json::json!({
+                        "word": word,
+                        "score": score,
+                        "rounded_score": stability.round(*score),
+                        "possible_answer": possible,
+                    }),
+                    None => serde_json::json!({
+                        "word": word,
+                        "score": score,
+                        "possible_answer": possible,
+                    }),
+                }
+            })
+            .collect();
+
+        let words: Vec<&str> = self.current_words.iter().map(String::as_str).collect();
+        let mut turn = serde_json::json!({
+            "guesses": self.game.lines.len(),
+            "remaining": self.current_words.len(),
+            "elimination": elimination,
+            "suggestions": suggestions,
+            "position_probabilities": analysis::position_probabilities(&words),
+        });
+        if !self.game.lines.is_empty() {
+            let probabilities: Vec<serde_json::Value> = self
+                .answer_probabilities(&self.current_words)
+                .into_iter()
+                .take(ANSWER_PROBABILITIES)
+                .map(|(word, probability)| {
+                    serde_json::json!({ "word": word, "probability": probability })
+                })
+                .collect();
+            turn["answer_probabilities"] = serde_json::json!(probabilities);
+        }
+        if !self.pinned_ranks.is_empty() {
+            let pinned: Vec<serde_json::Value> = self
+                .pinned_ranks
+                .iter()
+                .map(|(word, rank)| {
+                    serde_json::json!({
+                        "word": word,
+                        "rank": rank.map(|(rank, _)| rank),
+                        "score": rank.map(|(_, score)| score),
+                    })
+                })
+                .collect();
+            turn["pinned"] = serde_json::json!(pinned);
+        }
+        if let Some(restriction) = &self.probe_restriction {
+            turn["probe_restriction"] = serde_json::json!({
+                "word": restriction.listed.0,
+                "unrestricted": restriction.unrestricted.0,
+                "sacrificed": restriction.sacrificed(),
+            });
+        }
+        println!("{}", turn);
+    }
+}
+
+/// The note suggesting to probe `letter`, e.g. `80% of remaining words contain 'x' —
+/// consider probing it.`
+fn rare_letter_note(letter: &RareLetter) -> String {
+    messages::fill(
+        "solve.rare_letter",
+        &[&format!("{:.0}", letter.share * 100.0), &letter.letter],
+    )
+}
+
+/// `ranked` with the score of each word containing one of `rare` scaled by
+/// `analysis::RARE_LETTER_BOOST`, ranked again; unchanged without rare letters.
+fn boost_rare_letters(ranked: RankedList, rare: &[RareLetter]) -> RankedList {
+    if rare.is_empty() {
+        return ranked;
+    }
+    ranked.rescored(|word, score| {
+        if rare.iter().any(|r| word.contains(r.letter)) {
+            score * analysis::RARE_LETTER_BOOST
+        } else {
+            score
+        }
+    })
+}
+
+/// Rank (from 1) and score of each of `pinned` in `ranked`, `None` for a word that
+/// isn't in it.
+fn rank_pins(pinned: &[String], ranked: &[(String, f64)]) -> Vec<(String, Option<(usize, f64)>)> {
+    pinned
+        .iter()
+        .map(|word| {
+            let rank = ranked
+                .iter()
+                .position(|(w, _)| w == word)
+                .map(|position| (position + 1, ranked[position].1));
+            (word.clone(), rank)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use crate::game::grade;
+
+    /// A fixture solver that played `guesses` against `target`.
+    fn played(guesses: &[&str], target: &str) -> Solver {
+        let mut solver = Solver::fixture();
+        for guess in guesses {
+            solver
+                .game
+                .add_line(guess, &grade(guess, target).to_string());
+        }
+        solver.update_wordlist();
+        solver
+    }
+
+    #[test]
+    fn probes_rank_information_alone_and_may_pass_over_every_candidate() {
+        // AROSE against BRIEF leaves three answers that any of several words splits
+        let mut solver = played(&["arose"], "brief");
+        let candidates = solver.cache.consistent_answers(&solver.game);
+        assert_eq!(candidates.count(), 3);
+        let order = solver.search_order.clone();
+        let mut top = |prefer_candidates| {
+            let ranking = strategy::entropy_rank(
+                &mut solver.cache,
+                &candidates,
+                &order,
+                Deadline::default(),
+                prefer_candidates,
+            );
+            let (guess, score) = ranking.scores[0];
+            let is_candidate = solver
+                .cache
+                .answer_of_guess(guess)
+                .is_some_and(|i| candidates.contains(i));
+            let buckets = solver.cache.buckets(guess, &candidates);
+            (solver.cache.guess(guess), score, is_candidate, buckets)
+        };
+
+        let (suggested, suggested_score, suggested_is_candidate, _) = top(true);
+        let (probe, probe_bits, probe_is_candidate, probe_buckets) = top(false);
+        assert_ne!(suggested, probe);
+        assert!(suggested_is_candidate, "{} is not a candidate", suggested);
+        assert!(!probe_is_candidate, "{} is a candidate", probe);
+        // Equally informative: only the suggestion's chance of winning sets it apart
+        assert!((suggested_score - 1.0 / 3.0 - probe_bits).abs() < 1e-9);
+        assert!((probe_bits - 3f64.log2()).abs() < 1e-9);
+        assert_eq!(strategy::expected_remaining(&probe_buckets, 3), 1.0);
+    }
+
+    #[test]
+    fn prognosis_warns_when_no_play_guarantees_a_win() {
+        // Every guess is gray against SIGHT, so many -IGHT-like words stay possible
+        let mut solver = played(&["bulky", "mambo", "dummy", "carol"], "sight");
+        let warning = solver.prognosis_warning().expect("no warning");
+        assert!(
+            warning.starts_with("Prognosis: with 2 guess(es) left"),
+            "{}",
+            warning
+        );
+        let mut solver = played(&["bulky", "mambo", "dummy", "carol", "poppy"], "sight");
+        assert!(solver.prognosis_warning().is_some());
+    }
+
+    #[test]
+    fn no_prognosis_while_a_win_is_guaranteed() {
+        // Three candidates left with three guesses: one each is enough
+        let mut solver = played(&["arose", "brief", "tonal"], "crier");
+        assert!(solver.cache.consistent_answers(&solver.game).count() <= 3);
+        assert_eq!(solver.prognosis_warning(), None);
+        let mut solver = played(&[], "crier");
+        assert_eq!(solver.prognosis_warning(), None);
+    }
+
+    #[test]
+    fn positions_known_from_different_guesses_are_not_a_win() {
+        // BRIEF shows R I E, CAIRN the C and NEVER the last R, but no line is all green
+        let solver = played(&["brief", "cairn", "never"], "crier");
+        assert!(solver.game.correct_positions.iter().all(Option::is_some));
+        assert!(!solver.is_game_won());
+        assert_eq!(solver.get_solved_word(), None);
+        assert_eq!(solver.determined_word().as_deref(), Some("crier"));
+
+        let solver = played(&["brief", "cairn", "never", "crier"], "crier");
+        assert!(solver.is_game_won());
+        assert_eq!(solver.get_solved_word().as_deref(), Some("crier"));
+    }
+
+    #[test]
+    fn guesses_splitting_the_candidates_alike_are_grouped() {
+        // Without an L or T among the candidates, SLATE and STALE give the same
+        // feedback to each; CRANE splits them differently
+        let answers: Vec<String> = ["shade", "spare", "snake", "brave", "crimp"]
+            .map(String::from)
+            .to_vec();
+        let mut guesses = answers.clone();
+        guesses.extend(["slate", "stale", "crane"].map(String::from));
+        let mut solver = Solver::from_words(guesses, answers).unwrap();
+
+        let ranked =
+            [("slate", 3.0), ("crane", 2.0), ("stale", 1.0)].map(|(w, s)| (w.to_string(), s));
+        let groups = solver.equivalent_groups(&ranked);
+        assert_eq!(
+            groups,
+            vec![
+                (("slate".to_string(), 3.0), vec!["stale".to_string()]),
+                (("crane".to_string(), 2.0), vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn set_weight_rejects_a_bad_value_and_reranks_with_a_good_one() {
+        let mut solver = played(&["arose"], "unite");
+        let weights = solver.weights.clone();
+        let best = solver.refresh_suggestions().unwrap();
+        for setting in [
+            "weight 2 1 x 0",
+            "weight 2 x 0 0",
+            "weight 2 1 0 inf",
+            "weight 2 1 0",
+        ] {
+            assert_eq!(solver.set_config(setting, best.clone()).unwrap(), best);
+        }
+        assert_eq!(solver.weights, weights);
+        assert!(solver.edited_weights.is_empty());
+        // Weighing only letter positions turns the suggestion from PLUME to BELLE
+        assert_eq!(best.as_deref(), Some("plume"));
+        let best = solver.set_config("weight 2 1 0 0", best).unwrap();
+        assert_eq!(best.as_deref(), Some("belle"));
+        assert_eq!(solver.weights[1], (1.0, 0.0, 0.0));
+        assert_eq!(solver.edited_weights, [1]);
+    }
+
+    #[test]
+    fn answer_probabilities_follow_the_history_prior() {
+        let pool: Vec<String> = ["cakes", "crane", "slate", "bunch"]
+            .map(String::from)
+            .to_vec();
+        let mut solver = Solver::from_words(pool.clone(), pool.clone()).unwrap();
+        solver.current_words = pool.clone();
+        assert_eq!(
+            solver.most_likely_line().unwrap(),
+            "Most likely answer: CAKES (25%, as likely as 3 others)"
+        );
+
+        // Every one of 300 past answers is a plural against a pool where one word in
+        // four is, so CAKES weighs 0.8125 / 0.25 = 3.25 and the others 0.25 each
+        let past = vec!["cakes"; 300];
+        solver.prior = Some(analysis::history_prior(&past, &pool));
+        let probabilities: Vec<(String, f64)> = solver
+            .answer_probabilities(&pool)
+            .into_iter()
+            .map(|(word, p)| (word, (p * 1e4).round() / 1e4))
+            .collect();
+        let shares = [0.8125, 0.0625, 0.0625, 0.0625];
+        assert_eq!(
+            probabilities,
+            pool.into_iter().zip(shares).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            solver.most_likely_line().unwrap(),
+            "Most likely answer: CAKES (81%)"
+        );
+    }
+
+    #[test]
+    fn answer_trajectory_replays_the_frequency_ranks_of_a_fixed_game() {
+        let mut solver = played(&["arose", "brief", "crier"], "crier");
+        let ranks = |solver: &Solver| -> Vec<usize> {
+            let steps = solver.answer_trajectory("crier").unwrap();
+            steps.iter().map(|step| step.rank).collect()
+        };
+        // CRIER trails BRIEF and ERUPT by frequency after AROSE, then leads
+        assert_eq!(ranks(&solver), [3, 1, 1]);
+        assert!(solver.answer_trajectory("erupt").unwrap().is_empty());
+
+        // The ranks are frequency ranks whatever strategy played the game
+        solver.strategy = Strategy::Entropy;
+        assert_eq!(ranks(&solver), [3, 1, 1]);
+    }
+
+    #[test]
+    fn prefetched_rankings_match_the_ones_computed_after_the_pattern() {
+        for strategy in [Strategy::Entropy, Strategy::Minimax, Strategy::WinProb] {
+            let mut prefetching = Solver::fixture();
+            prefetching.strategy = strategy;
+            prefetching.prefetch = 4;
+            let mut plain = Solver::fixture();
+            plain.strategy = strategy;
+
+            let patterns = prefetch::likely_patterns("arose", &plain.current_words, 4);
+            assert_eq!(patterns.len(), 4);
+            for pattern in &patterns {
+                let read = prefetching
+                    .read_prefetching("arose", || Ok(Some(pattern.clone())))
+                    .unwrap();
+                assert_eq!(read.as_ref(), Some(pattern));
+                assert!(prefetching.prefetched.is_some(), "{pattern}");
+
+                prefetching.game.add_line("arose", pattern);
+                plain.game.add_line("arose", pattern);
+                let (fetched, computed) = (prefetching.pattern_ranking(), plain.pattern_ranking());
+                assert!(prefetching.prefetched.is_none());
+                assert_eq!(
+                    fetched.scores,
+                    computed.scores,
+                    "{} {pattern}",
+                    strategy.name()
+                );
+                assert_eq!(
+                    (fetched.evaluated, fetched.total),
+                    (computed.evaluated, computed.total)
+                );
+                prefetching.game.truncate(0);
+                plain.game.truncate(0);
+            }
+        }
+    }
+
+    #[test]
+    fn a_pattern_that_was_not_prefetched_cancels_the_worker() {
+        let mut solver = Solver::fixture();
+        solver.strategy = Strategy::WinProb;
+        solver.prefetch = 8;
+        for typed in ["undo", "ccccc"] {
+            let read = solver
+                .read_prefetching("arose", || Ok(Some(typed.to_string())))
+                .unwrap();
+            assert_eq!(read.as_deref(), Some(typed));
+            assert!(solver.prefetched.is_none());
+        }
+    }
+
+    #[test]
+    fn suggestions_on_the_answer_list_are_marked_and_counted() {
+        let words = |list: &str| -> Vec<String> { list.split(' ').map(String::from).collect() };
+        let mut solver = Solver::from_words(
+            words("crane slate trace crate react adieu stare irate"),
+            words("trace crate react"),
+        )
+        .unwrap();
+        let groups = |solver: &Solver| -> Vec<((String, f64), Vec<String>)> {
+            let mut words = solver.all_words.clone();
+            words.sort();
+            words
+                .into_iter()
+                .map(|word| ((word, 1.0), Vec::new()))
+                .collect()
+        };
+        // The words come colored when the terminal shows colors
+        let escapes = regex::Regex::new("\x1b\\[[0-9;]*m").unwrap();
+        let marked = |text: &str| -> Vec<String> {
+            let mut marked: Vec<String> = escapes
+                .replace_all(text, "")
+                .lines()
+                .filter_map(|line| line.split_whitespace().next())
+                .filter_map(|word| word.strip_suffix(output::ANSWER_MARK))
+                .map(str::to_lowercase)
+                .collect();
+            marked.sort();
+            marked
+        };
+
+        let text = solver.grouped_text(&groups(&solver));
+        assert_eq!(marked(&text), ["crate", "react", "trace"]);
+        assert!(
+            text.ends_with("3 of the top 8 are possible answers (marked †).\n"),
+            "{text}"
+        );
+        assert!(solver.is_possible_answer("crate"));
+        assert!(!solver.is_possible_answer("crane"));
+
+        // An answer the feedback rules out loses its mark
+        solver
+            .game
+            .add_line("crane", &grade("crane", "crate").to_string());
+        solver.update_wordlist();
+        let text = solver.grouped_text(&groups(&solver));
+        assert_eq!(marked(&text), ["crate"]);
+        assert!(
+            text.ends_with("1 of the top 8 are possible answers (marked †).\n"),
+            "{text}"
+        );
+        assert!(!solver.is_possible_answer("trace"));
+
+        // Without a separate answer list nothing is marked or counted
+        let solver = Solver::fixture();
+        let text = solver.grouped_text(&groups(&solver)[..3]);
+        assert!(marked(&text).is_empty());
+        assert_eq!(text.lines().count(), 3);
+    }
+
+    #[test]
+    fn a_rare_letter_most_candidates_share_is_flagged_and_boosted() {
+        let mut words: Vec<String> = Solver::fixture().all_words;
+        words.extend(["boxer", "toxic", "index"].map(String::from));
+        let mut solver = Solver::from_words(words.clone(), words).unwrap();
+        let candidates =
+            |list: &[&str]| -> Vec<String> { list.iter().map(|w| w.to_string()).collect() };
+        solver.current_words = candidates(&["boxer", "toxic", "index", "fixer", "crane"]);
+
+        // Off unless asked for
+        assert!(solver.rare_candidate_letters().unwrap().is_empty());
+        solver.rare_letters = Some(RareLetterLimits::default());
+        let rare = solver.rare_candidate_letters().unwrap();
+        assert_eq!(rare.len(), 1);
+        assert_eq!((rare[0].letter, rare[0].share), ('x', 0.8));
+        assert!(rare[0].frequency < analysis::RARE_LETTER_FREQUENCY);
+        assert_eq!(
+            rare_letter_note(&rare[0]),
+            "80% of remaining words contain 'x' — consider probing it."
+        );
+
+        let ranked = RankedList::new(vec![
+            ("crane".to_string(), 1.0),
+            ("boxer".to_string(), 0.8),
+            ("toxic".to_string(), 0.5),
+        ]);
+        let boosted = boost_rare_letters(ranked, &rare).into_vec();
+        assert_eq!(
+            boosted,
+            [
+                ("boxer".to_string(), 0.8 * analysis::RARE_LETTER_BOOST),
+                ("crane".to_string(), 1.0),
+                ("toxic".to_string(), 0.5 * analysis::RARE_LETTER_BOOST),
+            ]
+        );
+
+        // A letter every candidate has tells nothing, and a common one isn't rare
+        solver.current_words = candidates(&["boxer", "toxic", "index"]);
+        assert!(solver.rare_candidate_letters().unwrap().is_empty());
+        solver.current_words = candidates(&["crane", "crate", "trace", "react"]);
+        assert!(solver.rare_candidate_letters().unwrap().is_empty());
+    }
+
+    /// A fixture solver whose think times run on a clock the test moves.
+    fn timed_solver() -> (Solver, Arc<ManualClock>) {
+        let clock = Arc::new(ManualClock::new());
+        let mut solver = Solver::fixture();
+        solver.think_times = ThinkTimes::with_clock(clock.clone());
+        (solver, clock)
+    }
+
+    /// Waits `seconds` at the guess prompt, then enters `guess` with its feedback
+    /// against `target`, the way `run` does.
+    fn think_then_guess(
+        solver: &mut Solver,
+        clock: &ManualClock,
+        seconds: u64,
+        guess: &str,
+        target: &str,
+    ) {
+        solver.think_times.asking();
+        clock.advance(Duration::from_secs(seconds));
+        solver.think_times.answered();
+        solver.turn_notes.push((None, None));
+        solver.think_times.guessed();
+        solver
+            .game
+            .add_line(guess, &grade(guess, target).to_string());
+    }
+
+    #[test]
+    fn an_undone_guess_drops_its_think_time() {
+        let (mut solver, clock) = timed_solver();
+        think_then_guess(&mut solver, &clock, 2, "arose", "crier");
+        // Ranking and printing between the prompts isn't thinking
+        clock.advance(Duration::from_secs(10));
+        think_then_guess(&mut solver, &clock, 3, "brief", "crier");
+        assert_eq!(solver.think_times.seconds(2), [2.0, 3.0]);
+
+        solver.undo_last_guess();
+        assert_eq!(solver.game.lines.len(), 1);
+        assert_eq!(solver.think_times.seconds(1), [2.0]);
+        think_then_guess(&mut solver, &clock, 4, "brief", "crier");
+        assert_eq!(solver.think_times.seconds(2), [2.0, 4.0]);
+    }
+
+    #[test]
+    fn a_rollback_drops_the_think_times_of_the_guesses_it_removes() {
+        let (mut solver, clock) = timed_solver();
+        think_then_guess(&mut solver, &clock, 2, "arose", "crier");
+        think_then_guess(&mut solver, &clock, 3, "brief", "crier");
+        solver.update_wordlist();
+
+        // The wait for typing the command itself goes too
+        solver.think_times.asking();
+        clock.advance(Duration::from_secs(7));
+        solver.think_times.answered();
+        solver.rollback(1, None).unwrap();
+        assert_eq!(solver.think_times.seconds(1), [2.0]);
+
+        think_then_guess(&mut solver, &clock, 5, "crier", "crier");
+        assert_eq!(solver.think_times.seconds(2), [2.0, 5.0]);
+        // Timings that don't cover every guess aren't recorded
+        assert!(solver.think_times.seconds(3).is_empty());
+    }
 }
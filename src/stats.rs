@@ -1,31 +1,129 @@
-use std::collections::HashMap;
+use crate::output::{CellFormat, Table};
+use crate::perf::PerfReport;
+use crate::strategy::MAX_GUESSES;
+use crate::util;
+use anyhow::{anyhow, Result};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+pub mod loss_taxonomy;
+
+use loss_taxonomy::LossKind;
 
 pub struct SimulationResults {
+    /// Guesses a game may take; slower games are losses, in bucket `max_guesses + 1`.
+    max_guesses: usize,
     total_games: usize,
     wins: usize,
     total_guesses: usize,
     guess_distribution: HashMap<usize, usize>, // Guesses -> Count
+    corrupted_games: usize,
+    corrupted_wins: usize,
+    losses_from_corruption: usize,
+    detectable_corruptions: usize,
+    efficiency_sum: f64,
+    efficiency_guesses: usize,
+    forced_losses: usize,
+    strategy_error_losses: usize,
+    losses_with_worse_guess: usize,
+    /// Clean losses by why they were lost.
+    loss_kinds: BTreeMap<LossKind, usize>,
+    repeated_guesses: usize,
+    /// Throughput of the run, with `simulate --perf`.
+    perf: Option<PerfReport>,
 }
 
 impl SimulationResults {
-    pub fn new() -> Self {
+    pub fn new(max_guesses: usize) -> Self {
         Self {
+            max_guesses,
             total_games: 0,
             wins: 0,
             total_guesses: 0,
             guess_distribution: HashMap::new(),
+            corrupted_games: 0,
+            corrupted_wins: 0,
+            losses_from_corruption: 0,
+            detectable_corruptions: 0,
+            efficiency_sum: 0.0,
+            efficiency_guesses: 0,
+            forced_losses: 0,
+            strategy_error_losses: 0,
+            losses_with_worse_guess: 0,
+            loss_kinds: BTreeMap::new(),
+            repeated_guesses: 0,
+            perf: None,
         }
     }
 
     pub fn record_game(&mut self, num_guesses: usize) {
         self.total_games += 1;
 
-        if num_guesses <= 6 {
+        if num_guesses <= self.max_guesses {
             self.wins += 1;
             self.total_guesses += num_guesses;
             *self.guess_distribution.entry(num_guesses).or_insert(0) += 1;
         } else {
-            *self.guess_distribution.entry(7).or_insert(0) += 1;
+            *self
+                .guess_distribution
+                .entry(self.loss_bucket())
+                .or_insert(0) += 1;
+        }
+    }
+
+    /// Adds a game's candidate-count trajectory to the skill metric.
+    pub fn record_candidate_counts(&mut self, counts: &[usize]) {
+        let (sum, guesses) = trajectory_efficiency(counts);
+        self.efficiency_sum += sum;
+        self.efficiency_guesses += guesses;
+    }
+
+    /// Classifies a clean loss: `forced` when a minimax replay lost as well,
+    /// `worse_turns` the guesses whose worst case was larger than necessary, `kind`
+    /// what went wrong.
+    pub fn record_loss_analysis(&mut self, forced: bool, worse_turns: usize, kind: LossKind) {
+        *self.loss_kinds.entry(kind).or_insert(0) += 1;
+        if forced {
+            self.forced_losses += 1;
+        } else {
+            self.strategy_error_losses += 1;
+        }
+        if worse_turns > 0 {
+            self.losses_with_worse_guess += 1;
+        }
+    }
+
+    pub fn set_perf(&mut self, report: PerfReport) {
+        self.perf = Some(report);
+    }
+
+    /// Counts a game the strategy lost by repeating an earlier guess.
+    pub fn record_repeated_guess(&mut self) {
+        self.repeated_guesses += 1;
+    }
+
+    /// Records a game played with one wrong feedback cell. `lost_to_corruption` is set
+    /// when the same target was won with clean feedback, `detectable` when the feedback
+    /// contradicted itself (no candidate left).
+    pub fn record_corrupted_game(
+        &mut self,
+        num_guesses: usize,
+        lost_to_corruption: bool,
+        detectable: bool,
+    ) {
+        self.record_game(num_guesses);
+        self.corrupted_games += 1;
+
+        if num_guesses <= self.max_guesses {
+            self.corrupted_wins += 1;
+        }
+        if lost_to_corruption {
+            self.losses_from_corruption += 1;
+        }
+        if detectable {
+            self.detectable_corruptions += 1;
         }
     }
 
@@ -36,21 +134,36 @@ impl SimulationResults {
             0.0
         };
 
-        let win_rate = (self.wins as f64 / self.total_games as f64) * 100.0;
+        let win_rate = self.win_rate();
 
         println!("\n === Simulation Summary ===");
         println!("Total Games Simulated: {}", self.total_games);
+        if self.total_games == 0 {
+            println!("No games were played, so there is nothing to summarize.");
+            println!("============================\n");
+            return;
+        }
         println!("Wins: {} (Win Rate: {:.2}%)", self.wins, win_rate);
         println!("Average Guesses (for wins): {:.3}", avg_guesses);
+        println!(
+            "Solved in 3 or fewer: {:.2}%",
+            self.solved_within(3) as f64 / self.total_games as f64 * 100.0
+        );
+        if let Some(mode) = self.modal_guess_count() {
+            println!("Most common result: {}/{}", mode, self.max_guesses);
+        }
+        if let Some(skill) = skill_score(self.efficiency_sum, self.efficiency_guesses) {
+            println!("Skill: {}/99", skill);
+        }
         println!("============================");
 
         println!("\nGuess Distribution (Guesses -> Count):");
 
         let bar_unit = (self.total_games / 50).max(1); // Calculate a unit for the bar
 
-        for i in 1..=7 {
+        for i in 1..=self.loss_bucket() {
             let count = self.guess_distribution.get(&i).unwrap_or(&0);
-            let label = if i <= 6 {
+            let label = if i <= self.max_guesses {
                 format!("{}: ", i)
             } else {
                 "Loss:".to_string()
@@ -60,5 +173,899 @@ impl SimulationResults {
             println!("{:<5}{:<8}{}", label, count, bar);
         }
         println!("============================\n");
+
+        if self.repeated_guesses > 0 {
+            println!(
+                "Warning: the strategy repeated an earlier guess in {} game(s), counted as losses. This is a strategy bug.\n",
+                self.repeated_guesses
+            );
+        }
+
+        if self.forced_losses + self.strategy_error_losses > 0 {
+            self.print_loss_analysis();
+        }
+        if self.corrupted_games > 0 {
+            self.print_corruption_summary();
+        }
+        if let Some(perf) = &self.perf {
+            perf.print();
+        }
+    }
+
+    /// The totals of `print_summary` as JSON.
+    pub fn to_json(&self) -> serde_json::Value {
+        let distribution: BTreeMap<String, usize> = (1..=self.loss_bucket())
+            .map(|i| {
+                let label = if i <= self.max_guesses {
+                    i.to_string()
+                } else {
+                    "loss".to_string()
+                };
+                (label, *self.guess_distribution.get(&i).unwrap_or(&0))
+            })
+            .collect();
+        serde_json::json!({
+            "max_guesses": self.max_guesses,
+            "games": self.total_games,
+            "wins": self.wins,
+            "win_rate": self.win_rate() / 100.0,
+            "average_guesses": self.average_guesses(),
+            "score": self.score(),
+            "distribution": distribution,
+            "repeated_guesses": self.repeated_guesses,
+            "corrupted_games": self.corrupted_games,
+            "losses_from_corruption": self.losses_from_corruption,
+            "loss_kinds": LossKind::ALL
+                .iter()
+                .map(|kind| (kind.key(), self.loss_kinds.get(kind).copied().unwrap_or(0)))
+                .collect::<BTreeMap<_, _>>(),
+            "perf": self.perf.as_ref().map(PerfReport::to_json),
+        })
+    }
+
+    /// Results read back from `to_json`, e.g. a `<name>.summary.json` of
+    /// `simulate --output`. Only the guess limit, the games and the guess distribution
+    /// are restored. Files written before the limit was saved used `MAX_GUESSES`.
+    pub fn from_json(value: &serde_json::Value) -> Result<Self> {
+        let distribution = value
+            .get("distribution")
+            .and_then(|d| d.as_object())
+            .ok_or_else(|| anyhow!("The results have no 'distribution'."))?;
+        let max_guesses = match value.get("max_guesses") {
+            None => MAX_GUESSES,
+            Some(max) => max
+                .as_u64()
+                .filter(|&max| max > 0)
+                .ok_or_else(|| anyhow!("'max_guesses' is not a positive number: {}", max))?
+                as usize,
+        };
+        let mut results = Self::new(max_guesses);
+        for (label, count) in distribution {
+            let guesses = match label.as_str() {
+                "loss" => results.loss_bucket(),
+                label => label
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|g| (1..=max_guesses).contains(g))
+                    .ok_or_else(|| anyhow!("Unknown distribution bucket '{}'.", label))?,
+            };
+            let count = count
+                .as_u64()
+                .ok_or_else(|| anyhow!("Bucket '{}' is not a game count.", label))?;
+            for _ in 0..count {
+                results.record_game(guesses);
+            }
+        }
+        Ok(results)
+    }
+
+    /// Games solved in 1 to `max_guesses` guesses, then the losses.
+    pub fn distribution(&self) -> Vec<usize> {
+        (1..=self.loss_bucket())
+            .map(|i| *self.guess_distribution.get(&i).unwrap_or(&0))
+            .collect()
+    }
+
+    pub fn max_guesses(&self) -> usize {
+        self.max_guesses
+    }
+
+    /// Distribution bucket of the lost games, which also counts as their guesses.
+    pub fn loss_bucket(&self) -> usize {
+        self.max_guesses + 1
+    }
+
+    /// Average guesses over all games, counting a loss as `loss_bucket`. Lower is
+    /// better.
+    pub fn score(&self) -> f64 {
+        if self.total_games == 0 {
+            return 0.0;
+        }
+        let losses = self.total_games - self.wins;
+        (self.total_guesses + losses * self.loss_bucket()) as f64 / self.total_games as f64
+    }
+
+    pub fn games(&self) -> usize {
+        self.total_games
+    }
+
+    /// Average guesses over the won games; `None` without a win.
+    pub fn average_guesses(&self) -> Option<f64> {
+        (self.wins > 0).then(|| self.total_guesses as f64 / self.wins as f64)
+    }
+
+    pub fn win_rate(&self) -> f64 {
+        if self.total_games == 0 {
+            return 0.0;
+        }
+        self.wins as f64 / self.total_games as f64 * 100.0
+    }
+
+    fn solved_within(&self, max_guesses: usize) -> usize {
+        (1..=max_guesses)
+            .map(|i| self.guess_distribution.get(&i).unwrap_or(&0))
+            .sum()
+    }
+
+    /// Winning guess count that occurred most often (the lower one on ties).
+    fn modal_guess_count(&self) -> Option<usize> {
+        (1..=self.max_guesses)
+            .filter_map(|i| self.guess_distribution.get(&i).map(|&count| (i, count)))
+            .filter(|&(_, count)| count > 0)
+            .max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(&a.0)))
+            .map(|(i, _)| i)
+    }
+
+    fn print_loss_analysis(&self) {
+        println!("=== Loss Analysis ===");
+        println!("Forced (minimax replay lost too): {}", self.forced_losses);
+        println!(
+            "Strategy errors (minimax replay won): {}",
+            self.strategy_error_losses
+        );
+        println!(
+            "Losses with a guess that risked more answers than necessary: {}",
+            self.losses_with_worse_guess
+        );
+        println!("By failure mode:");
+        for kind in LossKind::ALL {
+            println!(
+                "  {}: {}",
+                kind.description(),
+                self.loss_kinds.get(&kind).copied().unwrap_or(0)
+            );
+        }
+        println!("============================\n");
+    }
+
+    fn print_corruption_summary(&self) {
+        let clean_games = self.total_games - self.corrupted_games;
+        let clean_wins = self.wins - self.corrupted_wins;
+        let rate = |part: usize, whole: usize| {
+            if whole > 0 {
+                part as f64 / whole as f64 * 100.0
+            } else {
+                0.0
+            }
+        };
+
+        println!("=== Feedback Errors ===");
+        println!(
+            "Clean games:     {:<8} Wins: {} ({:.2}%)",
+            clean_games,
+            clean_wins,
+            rate(clean_wins, clean_games)
+        );
+        println!(
+            "Corrupted games: {:<8} Wins: {} ({:.2}%)",
+            self.corrupted_games,
+            self.corrupted_wins,
+            rate(self.corrupted_wins, self.corrupted_games)
+        );
+        println!(
+            "Losses caused by corrupted feedback: {}",
+            self.losses_from_corruption
+        );
+        println!(
+            "Corruptions detectable (no candidates left): {} ({:.2}%)",
+            self.detectable_corruptions,
+            rate(self.detectable_corruptions, self.corrupted_games)
+        );
+        println!("============================\n");
+    }
+}
+
+/// Share of the achievable information a guess gained: `log2(before / after)` over
+/// `log2(min(before, 243))`, since one feedback pattern can split the candidates into
+/// at most 243 buckets. Returns `None` when `before <= 1` (nothing left to learn).
+pub fn guess_efficiency(before: usize, after: usize) -> Option<f64> {
+    if before <= 1 {
+        return None;
+    }
+    let after = after.max(1) as f64;
+    let gained = (before as f64 / after).log2();
+    let achievable = (before.min(243) as f64).log2();
+    Some((gained / achievable).min(1.0))
+}
+
+/// Sums `guess_efficiency` over consecutive counts of a game (candidates before each
+/// guess, then after the last one) and returns the sum with the number of guesses scored.
+pub fn trajectory_efficiency(counts: &[usize]) -> (f64, usize) {
+    counts
+        .windows(2)
+        .filter_map(|w| guess_efficiency(w[0], w[1]))
+        .fold((0.0, 0), |(sum, n), e| (sum + e, n + 1))
+}
+
+/// Information a guess gained by narrowing `before` candidates to `after`, in bits.
+pub fn bits_gained(before: usize, after: usize) -> f64 {
+    if before == 0 {
+        return 0.0;
+    }
+    (before as f64 / after.max(1) as f64).log2()
+}
+
+/// Share of the candidates a guess eliminated, from 0 to 1.
+pub fn eliminated_share(before: usize, after: usize) -> f64 {
+    if before == 0 {
+        return 0.0;
+    }
+    1.0 - after.min(before) as f64 / before as f64
+}
+
+/// Most information `guesses` guesses can give, in bits: each feedback pattern is
+/// one of 243.
+pub fn information_budget(guesses: usize) -> f64 {
+    guesses as f64 * 243f64.log2()
+}
+
+/// How much of the information budget of `guesses` guesses a game needed: the
+/// theoretical minimum `log2(initial)` over `information_budget(guesses)`. Returns
+/// `None` for a game without guesses.
+pub fn information_efficiency(initial: usize, guesses: usize) -> Option<f64> {
+    if guesses == 0 {
+        return None;
+    }
+    Some(bits_gained(initial, 1) / information_budget(guesses))
+}
+
+/// WordleBot-style skill on a 0-99 scale: the mean per-guess efficiency, from the sum
+/// of `guesses` efficiencies, scaled to 99. `None` without a scored guess.
+pub fn skill_score(efficiency_sum: f64, guesses: usize) -> Option<u32> {
+    if guesses == 0 {
+        return None;
+    }
+    let mean = efficiency_sum / guesses as f64;
+    Some((mean.clamp(0.0, 1.0) * 99.0).round() as u32)
+}
+
+/// Share of the achievable information under which a guess counts as a blunder in
+/// `review --history`: one that barely narrowed the candidates down, like a guess
+/// repeating eliminated letters.
+pub const BLUNDER_EFFICIENCY: f64 = 0.25;
+
+/// Games, graded guesses and blunders of one week of history.
+#[derive(Default)]
+struct WeekTrend {
+    games: usize,
+    efficiency_sum: f64,
+    guesses: usize,
+    blunders: usize,
+}
+
+/// The guess of the history that fell furthest short of what it could have gained.
+struct MissedOpportunity {
+    day: i64,
+    answer: String,
+    turn: usize,
+    before: usize,
+    after: usize,
+    /// Bits below the most a guess could gain from `before` candidates.
+    bits_short: f64,
+}
+
+/// Skill trends of recorded games (`review --history`): the games are graded guess
+/// by guess from their candidate counts with the skill metric of `simulate`.
+pub struct HistoryTrends {
+    /// By the day number of each week's Monday.
+    weeks: BTreeMap<i64, WeekTrend>,
+    /// Blunders by the turn they were made on.
+    blunder_turns: BTreeMap<usize, usize>,
+    worst: Option<MissedOpportunity>,
+    /// Games without an answer or candidate counts, which can't be graded.
+    skipped: usize,
+}
+
+impl HistoryTrends {
+    pub fn new() -> Self {
+        Self {
+            weeks: BTreeMap::new(),
+            blunder_turns: BTreeMap::new(),
+            worst: None,
+            skipped: 0,
+        }
+    }
+
+    /// Grades a game that ended at `timestamp` (seconds since the Unix epoch) from
+    /// its candidate counts, those before each guess then after the last. A game
+    /// without an answer or a guess to grade is counted as skipped.
+    pub fn record_game(&mut self, timestamp: u64, answer: Option<&str>, counts: &[usize]) {
+        let graded: Vec<(usize, usize, usize, f64)> = counts
+            .windows(2)
+            .enumerate()
+            .filter_map(|(i, w)| guess_efficiency(w[0], w[1]).map(|e| (i + 1, w[0], w[1], e)))
+            .collect();
+        let Some(answer) = answer.filter(|_| !graded.is_empty()) else {
+            self.skipped += 1;
+            return;
+        };
+
+        let day = (timestamp / 86_400) as i64;
+        let week = self.weeks.entry(day - (day + 3).rem_euclid(7)).or_default();
+        week.games += 1;
+        for &(turn, before, after, efficiency) in &graded {
+            week.efficiency_sum += efficiency;
+            week.guesses += 1;
+            if efficiency < BLUNDER_EFFICIENCY {
+                week.blunders += 1;
+                *self.blunder_turns.entry(turn).or_default() += 1;
+            }
+            let bits_short = (before.min(243) as f64).log2() - bits_gained(before, after);
+            if self
+                .worst
+                .as_ref()
+                .is_none_or(|w| bits_short > w.bits_short)
+            {
+                self.worst = Some(MissedOpportunity {
+                    day,
+                    answer: answer.to_string(),
+                    turn,
+                    before,
+                    after,
+                    bits_short,
+                });
+            }
+        }
+    }
+
+    pub fn graded_games(&self) -> usize {
+        self.weeks.values().map(|w| w.games).sum()
+    }
+
+    /// One row per week with graded games, oldest first.
+    pub fn week_table(&self) -> Table {
+        let mut table = Table::new()
+            .column("week", "Week of", CellFormat::Text)
+            .column("games", "Games", CellFormat::Integer)
+            .column("guesses", "Guesses", CellFormat::Integer)
+            .column("skill", "Skill", CellFormat::Integer)
+            .column("blunders", "Blunders", CellFormat::Integer)
+            .column("blunder_rate", "Blunder %", CellFormat::Percent(1));
+        for (&monday, week) in &self.weeks {
+            table.push_row(vec![
+                util::date(monday).as_str().into(),
+                week.games.into(),
+                week.guesses.into(),
+                skill_score(week.efficiency_sum, week.guesses)
+                    .map(|skill| skill as usize)
+                    .into(),
+                week.blunders.into(),
+                (week.guesses > 0)
+                    .then(|| week.blunders as f64 / week.guesses as f64)
+                    .into(),
+            ]);
+        }
+        table
+    }
+
+    /// Overall skill and blunder rate, the most common blunder turn, the biggest
+    /// missed opportunity and the skipped games, one per line.
+    pub fn summary(&self) -> String {
+        let (efficiency, guesses, blunders) =
+            self.weeks.values().fold((0.0, 0, 0), |(e, g, b), w| {
+                (e + w.efficiency_sum, g + w.guesses, b + w.blunders)
+            });
+        let mut text = String::new();
+        if let Some(skill) = skill_score(efficiency, guesses) {
+            text.push_str(&format!(
+                "Overall: {} games, skill {}/99, {} of {} guesses blunders ({:.1}%, under {:.0}% of the achievable information).\n",
+                self.graded_games(),
+                skill,
+                blunders,
+                guesses,
+                blunders as f64 / guesses as f64 * 100.0,
+                BLUNDER_EFFICIENCY * 100.0
+            ));
+        }
+        if let Some((turn, count)) = self
+            .blunder_turns
+            .iter()
+            .max_by(|a, b| a.1.cmp(b.1).then(b.0.cmp(a.0)))
+        {
+            text.push_str(&format!(
+                "Most common blunder turn: guess {} ({} blunder(s)).\n",
+                turn, count
+            ));
+        }
+        if let Some(worst) = &self.worst {
+            text.push_str(&format!(
+                "Biggest missed opportunity: guess {} for {} on {} left {} of {} candidates, {:.1} bits short of the best possible split.\n",
+                worst.turn,
+                worst.answer.to_uppercase(),
+                util::date(worst.day),
+                worst.after,
+                worst.before,
+                worst.bits_short
+            ));
+        }
+        if self.skipped > 0 {
+            text.push_str(&format!(
+                "Skipped {} game(s) without a recorded answer or candidate counts.\n",
+                self.skipped
+            ));
+        }
+        text
+    }
+}
+
+/// One target played by both sides of a race; a loss counts as the loss bucket.
+struct PairedGame {
+    target: String,
+    a_guesses: usize,
+    b_guesses: usize,
+}
+
+/// Head-to-head results of two strategies on the same targets (`race`).
+pub struct PairedResults {
+    /// Heading of the text report, `Race` unless set with `titled`.
+    title: String,
+    a_name: String,
+    b_name: String,
+    seed: u64,
+    /// Guesses a game may take; slower games are losses, counted as `max_guesses + 1`.
+    max_guesses: usize,
+    games: Vec<PairedGame>,
+}
+
+impl PairedResults {
+    pub fn new(a_name: &str, b_name: &str, seed: u64, max_guesses: usize) -> Self {
+        Self {
+            title: "Race".to_string(),
+            a_name: a_name.to_string(),
+            b_name: b_name.to_string(),
+            seed,
+            max_guesses,
+            games: Vec::new(),
+        }
+    }
+
+    /// The same results under another heading, e.g. `Opener comparison`.
+    pub fn titled(mut self, title: &str) -> Self {
+        self.title = title.to_string();
+        self
+    }
+
+    pub fn record_game(&mut self, target: &str, a_guesses: usize, b_guesses: usize) {
+        self.games.push(PairedGame {
+            target: target.to_string(),
+            a_guesses: a_guesses.min(self.loss_bucket()),
+            b_guesses: b_guesses.min(self.loss_bucket()),
+        });
+    }
+
+    /// Guesses a lost game counts as.
+    pub fn loss_bucket(&self) -> usize {
+        self.max_guesses + 1
+    }
+
+    /// Games A solved in fewer guesses, games B did, and ties.
+    fn record(&self) -> (usize, usize, usize) {
+        let a_wins = self
+            .games
+            .iter()
+            .filter(|g| g.a_guesses < g.b_guesses)
+            .count();
+        let b_wins = self
+            .games
+            .iter()
+            .filter(|g| g.b_guesses < g.a_guesses)
+            .count();
+        (a_wins, b_wins, self.games.len() - a_wins - b_wins)
+    }
+
+    fn average(&self, guesses: impl Fn(&PairedGame) -> usize) -> f64 {
+        let total: usize = self.games.iter().map(guesses).sum();
+        total as f64 / self.games.len().max(1) as f64
+    }
+
+    /// A's average guesses minus B's, a loss counting as `loss_bucket`; positive when
+    /// A needed more.
+    pub fn average_difference(&self) -> f64 {
+        self.average(|g| g.a_guesses) - self.average(|g| g.b_guesses)
+    }
+
+    /// The `count` games with the largest guess-count difference, ties by target.
+    fn blowouts(&self, count: usize) -> Vec<&PairedGame> {
+        let mut games: Vec<&PairedGame> = self
+            .games
+            .iter()
+            .filter(|g| g.a_guesses != g.b_guesses)
+            .collect();
+        games.sort_by(|x, y| {
+            y.a_guesses
+                .abs_diff(y.b_guesses)
+                .cmp(&x.a_guesses.abs_diff(x.b_guesses))
+                .then(x.target.cmp(&y.target))
+        });
+        games.truncate(count);
+        games
+    }
+
+    pub fn to_text(&self) -> String {
+        let (a_wins, b_wins, ties) = self.record();
+        let label = |guesses: usize| {
+            if guesses > self.max_guesses {
+                "X".to_string()
+            } else {
+                guesses.to_string()
+            }
+        };
+
+        let mut text = format!(
+            "\n=== {}: {} vs {} ({} games, seed {}) ===\n",
+            self.title,
+            self.a_name,
+            self.b_name,
+            self.games.len(),
+            self.seed
+        );
+        text.push_str(&format!("{} wins: {}\n", self.a_name, a_wins));
+        text.push_str(&format!("{} wins: {}\n", self.b_name, b_wins));
+        text.push_str(&format!("Ties: {}\n", ties));
+        text.push_str(&format!(
+            "Average guesses (loss = {}): {} {:.3}, {} {:.3}\n",
+            self.loss_bucket(),
+            self.a_name,
+            self.average(|g| g.a_guesses),
+            self.b_name,
+            self.average(|g| g.b_guesses)
+        ));
+        let p_value = sign_test_p_value(a_wins, b_wins);
+        if p_value < 0.0001 {
+            text.push_str("Sign test p-value (ties dropped): < 0.0001\n");
+        } else {
+            text.push_str(&format!(
+                "Sign test p-value (ties dropped): {:.4}\n",
+                p_value
+            ));
+        }
+
+        let blowouts = self.blowouts(5);
+        if !blowouts.is_empty() {
+            text.push_str("\nBiggest blowouts:\n");
+            for game in blowouts {
+                text.push_str(&format!(
+                    "  {:<7}{} {}, {} {}\n",
+                    game.target,
+                    self.a_name,
+                    label(game.a_guesses),
+                    self.b_name,
+                    label(game.b_guesses)
+                ));
+            }
+        }
+        text.push_str("============================\n");
+        text
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        let (a_wins, b_wins, ties) = self.record();
+        let game_json = |g: &PairedGame| serde_json::json!({ "target": g.target, "a": g.a_guesses, "b": g.b_guesses });
+
+        serde_json::json!({
+            "a": self.a_name,
+            "b": self.b_name,
+            "seed": self.seed,
+            "max_guesses": self.max_guesses,
+            "games": self.games.len(),
+            "a_wins": a_wins,
+            "b_wins": b_wins,
+            "ties": ties,
+            "a_average": self.average(|g| g.a_guesses),
+            "b_average": self.average(|g| g.b_guesses),
+            "p_value": sign_test_p_value(a_wins, b_wins),
+            "blowouts": self.blowouts(5).into_iter().map(game_json).collect::<Vec<_>>(),
+            "results": self.games.iter().map(game_json).collect::<Vec<_>>(),
+        })
+    }
+}
+
+/// Two-sided exact sign test: the chance of a record at least this lopsided if both
+/// sides were equally likely to win each decided game. Computed in log space, since
+/// `0.5^n` underflows for a few thousand games.
+pub fn sign_test_p_value(wins: usize, losses: usize) -> f64 {
+    let n = wins + losses;
+    if n == 0 {
+        return 1.0;
+    }
+
+    let mut log_term = -(n as f64) * std::f64::consts::LN_2;
+    let mut tail = 0.0;
+    for i in 0..=wins.min(losses) {
+        tail += log_term.exp();
+        log_term += ((n - i) as f64 / (i + 1) as f64).ln();
+    }
+    (2.0 * tail).min(1.0)
+}
+
+/// Rows written between flushes of the game log.
+const LOG_FLUSH_ROWS: usize = 1000;
+
+/// One CSV row per simulated game, written as each game finishes so a long run never
+/// holds them in memory (`simulate --output`).
+pub struct GameLog {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    rows: usize,
+    finished: bool,
+}
+
+impl GameLog {
+    /// Creates the file at `path` and writes the CSV `header` line.
+    pub fn create(path: &Path, header: &str) -> Result<Self> {
+        let file = File::create(path)
+            .map_err(|e| anyhow!("Failed to create {}: {}", path.display(), e))?;
+        let mut writer = BufWriter::new(file);
+        writeln!(writer, "{}", header)?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            writer,
+            rows: 0,
+            finished: false,
+        })
+    }
+
+    /// Appends one CSV row, flushing every `LOG_FLUSH_ROWS` rows.
+    pub fn record(&mut self, row: &str) -> Result<()> {
+        writeln!(self.writer, "{}", row)?;
+        self.rows += 1;
+        if self.rows.is_multiple_of(LOG_FLUSH_ROWS) {
+            self.writer.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Flushes the rows and writes `summary` next to the log as
+    /// `<name>.summary.json`, returning that path.
+    pub fn finish(mut self, summary: &SimulationResults) -> Result<PathBuf> {
+        self.writer.flush()?;
+        self.finished = true;
+        let summary_path = self.path.with_extension("summary.json");
+        let json = serde_json::to_string_pretty(&summary.to_json())? + "\n";
+        util::write_atomic(&summary_path, json.as_bytes())
+            .map_err(|e| anyhow!("Failed to write {}: {}", summary_path.display(), e))?;
+        Ok(summary_path)
+    }
+}
+
+/// A run that ends with an error still keeps the rows written so far. Release builds
+/// abort on a panic (`panic = "abort"`) without running this, so a panic there only
+/// keeps the rows up to the last flush.
+impl Drop for GameLog {
+    fn drop(&mut self) {
+        if !self.finished {
+            let _ = self.writer.flush();
+            eprintln!(
+                "Simulation stopped early: {} game(s) were written to {}.",
+                self.rows,
+                self.path.display()
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::OutputFormat;
+
+    #[test]
+    fn summary_of_no_games_has_no_rates() {
+        let results = SimulationResults::new(6);
+        results.print_summary();
+        assert_eq!(results.score(), 0.0);
+        assert_eq!(results.win_rate(), 0.0);
+        let json = results.to_json();
+        assert_eq!(json["games"], 0);
+        assert_eq!(json["win_rate"], 0.0);
+        assert!(json["average_guesses"].is_null());
+    }
+
+    #[test]
+    fn perfect_splits_score_99() {
+        // 243 -> 1 gains all achievable information, as does 2 -> 1
+        let (sum, guesses) = trajectory_efficiency(&[243, 1]);
+        assert_eq!(skill_score(sum, guesses), Some(99));
+        let (sum, guesses) = trajectory_efficiency(&[2, 1]);
+        assert_eq!(skill_score(sum, guesses), Some(99));
+    }
+
+    #[test]
+    fn wasted_guesses_lower_the_score() {
+        // Half of the achievable information, then none, then all of it
+        let (sum, guesses) = trajectory_efficiency(&[81, 9, 9, 1]);
+        assert_eq!(guesses, 3);
+        assert_eq!(skill_score(sum, guesses), Some(50));
+    }
+
+    #[test]
+    fn game_without_scored_guesses_has_no_score() {
+        let (sum, guesses) = trajectory_efficiency(&[1, 1]);
+        assert_eq!(guesses, 0);
+        assert_eq!(skill_score(sum, guesses), None);
+        assert_eq!(skill_score(0.0, 0), None);
+    }
+
+    /// A run of `games` games that fails after the last one, as `simulate --output`
+    /// does when a game returns an error.
+    fn interrupted_run(path: &Path, games: usize) -> Result<()> {
+        let mut log = GameLog::create(path, "game,target")?;
+        for game in 1..=games {
+            log.record(&format!("{},crane", game))?;
+        }
+        Err(anyhow!("game {} failed", games + 1))
+    }
+
+    #[test]
+    fn interrupted_log_keeps_every_row_written() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("games.csv");
+        // Past one periodic flush, with rows still buffered after it
+        let games = LOG_FLUSH_ROWS + 234;
+        assert!(interrupted_run(&path, games).is_err());
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let mut lines = content.lines();
+        assert_eq!(lines.next(), Some("game,target"));
+        assert_eq!(lines.count(), games);
+        assert!(!path.with_extension("summary.json").exists());
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-12,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn bits_gained_is_the_log_of_the_narrowing() {
+        assert_close(bits_gained(2315, 2315), 0.0);
+        assert_close(bits_gained(64, 8), 3.0);
+        assert_close(bits_gained(256, 1), 8.0);
+        // A contradiction leaves no candidate; it counts like one
+        assert_close(bits_gained(16, 0), 4.0);
+        assert_close(bits_gained(0, 0), 0.0);
+    }
+
+    #[test]
+    fn eliminated_share_runs_from_zero_to_one() {
+        assert_close(eliminated_share(200, 50), 0.75);
+        assert_close(eliminated_share(10, 10), 0.0);
+        assert_close(eliminated_share(10, 0), 1.0);
+        assert_close(eliminated_share(0, 0), 0.0);
+    }
+
+    #[test]
+    fn efficiency_compares_the_minimum_with_the_budget() {
+        assert_close(information_budget(1), 243f64.log2());
+        // Narrowing 243 candidates to one takes exactly one guess's budget
+        assert_close(information_efficiency(243, 1).unwrap(), 1.0);
+        assert_close(information_efficiency(243, 2).unwrap(), 0.5);
+        assert_close(information_efficiency(1, 3).unwrap(), 0.0);
+        assert_eq!(information_efficiency(2315, 0), None);
+    }
+
+    #[test]
+    fn history_trends_grade_games_with_an_answer_and_counts() {
+        let mut trends = HistoryTrends::new();
+        // Thursday and Friday of the week starting Monday 1969-12-29
+        trends.record_game(0, Some("crier"), &[193, 3, 1, 1]);
+        trends.record_game(86_400, Some("fixer"), &[100, 90, 1]);
+        trends.record_game(0, None, &[100, 10]);
+        trends.record_game(0, Some("crier"), &[1, 1]);
+
+        assert_eq!(trends.graded_games(), 2);
+        let json = trends.week_table().render(OutputFormat::Json);
+        let rows: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(rows.as_array().unwrap().len(), 1);
+        assert_eq!(rows[0]["week"], "1969-12-29");
+        assert_eq!(
+            (rows[0]["games"].clone(), rows[0]["guesses"].clone()),
+            (2.into(), 4.into())
+        );
+        assert_eq!(rows[0]["blunders"], 1);
+
+        let summary = trends.summary();
+        assert!(
+            summary.contains("Most common blunder turn: guess 1 (1 blunder(s))."),
+            "{}",
+            summary
+        );
+        assert!(
+            summary.contains(
+                "guess 1 for FIXER on 1970-01-02 left 90 of 100 candidates, 6.5 bits short"
+            ),
+            "{}",
+            summary
+        );
+        assert!(summary.contains("Skipped 2 game(s)"), "{}", summary);
+    }
+
+    #[test]
+    fn eight_guess_limit_keeps_nine_buckets_with_the_loss_last() {
+        let mut results = SimulationResults::new(8);
+        for guesses in [1, 4, 7, 8, 9, 12] {
+            results.record_game(guesses);
+        }
+        assert_eq!(results.loss_bucket(), 9);
+        assert_eq!(results.distribution(), [1, 0, 0, 1, 0, 0, 1, 1, 2]);
+        assert_eq!(results.win_rate(), 4.0 / 6.0 * 100.0);
+        assert_eq!(results.score(), (1 + 4 + 7 + 8 + 2 * 9) as f64 / 6.0);
+
+        let json = results.to_json();
+        assert_eq!(json["max_guesses"], 8);
+        assert_eq!(json["distribution"]["8"], 1);
+        assert_eq!(json["distribution"]["loss"], 2);
+        let restored = SimulationResults::from_json(&json).unwrap();
+        assert_eq!(restored.max_guesses(), 8);
+        assert_eq!(restored.distribution(), results.distribution());
+    }
+
+    #[test]
+    fn race_losses_count_one_past_the_guess_limit() {
+        let mut race = PairedResults::new("a", "b", 1, 8);
+        race.record_game("crane", 8, 12);
+        race.record_game("slate", 3, 3);
+        assert_eq!(race.average_difference(), -0.5);
+
+        let text = race.to_text();
+        assert!(
+            text.contains("Average guesses (loss = 9): a 5.500, b 6.000"),
+            "{text}"
+        );
+        assert!(text.contains("crane  a 8, b X"), "{text}");
+        let json = race.to_json();
+        assert_eq!(json["max_guesses"], 8);
+        assert_eq!(json["results"][0]["b"], 9);
+    }
+
+    #[test]
+    fn sign_test_matches_the_exact_binomial_tails() {
+        assert_eq!(sign_test_p_value(0, 0), 1.0);
+        assert_eq!(sign_test_p_value(0, 1), 1.0);
+        assert_eq!(sign_test_p_value(5, 5), 1.0);
+        let close = |p: f64, expected: f64| (p / expected - 1.0).abs() < 1e-9;
+        assert!(close(sign_test_p_value(10, 0), 2.0 / 1024.0));
+        assert!(close(sign_test_p_value(0, 10), 2.0 / 1024.0));
+        // 2 * (1 + 8 + 28) / 2^10
+        assert!(close(sign_test_p_value(8, 2), 0.109375));
+    }
+
+    #[test]
+    fn sign_test_stays_finite_for_thousands_of_games() {
+        // 0.5^5900 underflows; the exact p-value is about 0.19744
+        let p = sign_test_p_value(3000, 2900);
+        assert!((p - 0.1974390248435765).abs() < 1e-9, "{}", p);
+        assert_eq!(sign_test_p_value(2900, 3000), p);
+        // 2^-999 is still a normal f64 and must come out as one, not as 0
+        let lopsided = sign_test_p_value(1000, 0);
+        assert!(
+            (lopsided / 2f64.powi(-999) - 1.0).abs() < 1e-9,
+            "{}",
+            lopsided
+        );
     }
 }
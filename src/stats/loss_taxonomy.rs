@@ -0,0 +1,135 @@
+/// Candidates ranked high enough by letter frequency, counted over the candidates
+/// themselves, to count as in sight.
+pub const DISCOVERY_RANK: usize = 100;
+
+/// Guesses made (from 0) after which an answer coming into sight is late: it ranked
+/// outside the top `DISCOVERY_RANK` candidates before guesses 1 to 3.
+pub const LATE_DISCOVERY_TURN: usize = 3;
+
+/// Why a clean game was lost, the first rule that applies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LossKind {
+    /// The candidates before the last guess differ in one position only, like the
+    /// -IGHT or -ILLS families, so guessing them one by one ran out of guesses.
+    FamilyTrap,
+    /// The answer only ranked among the top `DISCOVERY_RANK` candidates from guess
+    /// `LATE_DISCOVERY_TURN + 1` on, or never: a rare word the ranking overlooked.
+    LateDiscovery,
+    Other,
+}
+
+impl LossKind {
+    pub const ALL: [LossKind; 3] = [
+        LossKind::FamilyTrap,
+        LossKind::LateDiscovery,
+        LossKind::Other,
+    ];
+
+    /// Key in the JSON summary.
+    pub fn key(&self) -> &'static str {
+        match self {
+            LossKind::FamilyTrap => "family_trap",
+            LossKind::LateDiscovery => "late_discovery",
+            LossKind::Other => "other",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            LossKind::FamilyTrap => "Family trap (last candidates differ in one position)",
+            LossKind::LateDiscovery => "Late discovery (answer outside the top 100 until guess 4)",
+            LossKind::Other => "Other",
+        }
+    }
+}
+
+/// The trace of a lost game the classification looks at.
+pub struct LossTrace {
+    /// Answers still possible before the last guess.
+    pub final_candidates: Vec<String>,
+    /// Guesses made (from 0) when the answer first ranked among the top
+    /// `DISCOVERY_RANK` candidates, `None` when it never did.
+    pub discovered_after: Option<usize>,
+}
+
+/// Whether `words` are at least two words that all differ in the same one position.
+pub fn is_family(words: &[String]) -> bool {
+    let Some((first, rest)) = words.split_first() else {
+        return false;
+    };
+    if rest.is_empty() {
+        return false;
+    }
+    let first: Vec<char> = first.chars().collect();
+    let mut varying = None;
+    for word in rest {
+        let chars: Vec<char> = word.chars().collect();
+        if chars.len() != first.len() {
+            return false;
+        }
+        for position in (0..chars.len()).filter(|&i| chars[i] != first[i]) {
+            match varying {
+                None => varying = Some(position),
+                Some(varying) if varying == position => {}
+                Some(_) => return false,
+            }
+        }
+    }
+    true
+}
+
+pub fn classify(trace: &LossTrace) -> LossKind {
+    if is_family(&trace.final_candidates) {
+        LossKind::FamilyTrap
+    } else if trace
+        .discovered_after
+        .is_none_or(|turn| turn >= LATE_DISCOVERY_TURN)
+    {
+        LossKind::LateDiscovery
+    } else {
+        LossKind::Other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trace(final_candidates: &[&str], discovered_after: Option<usize>) -> LossTrace {
+        LossTrace {
+            final_candidates: final_candidates.iter().map(|w| w.to_string()).collect(),
+            discovered_after,
+        }
+    }
+
+    #[test]
+    fn words_differing_in_one_position_are_a_family() {
+        let words = |list: &[&str]| list.iter().map(|w| w.to_string()).collect::<Vec<_>>();
+        assert!(is_family(&words(&["fight", "light", "might", "sight"])));
+        assert!(is_family(&words(&["hills", "kills"])));
+        // Two positions vary, even though each word differs from FIGHT in one
+        assert!(!is_family(&words(&["fight", "light", "fists"])));
+        assert!(!is_family(&words(&["fight", "fifth"])));
+        assert!(!is_family(&words(&["fight"])));
+        assert!(!is_family(&[]));
+    }
+
+    #[test]
+    fn losses_are_classified_by_the_first_rule_that_applies() {
+        let spread = ["crane", "slate", "bunch"];
+        assert_eq!(
+            classify(&trace(&["fight", "light", "might"], None)),
+            LossKind::FamilyTrap
+        );
+        assert_eq!(classify(&trace(&spread, None)), LossKind::LateDiscovery);
+        assert_eq!(
+            classify(&trace(&spread, Some(LATE_DISCOVERY_TURN))),
+            LossKind::LateDiscovery
+        );
+        assert_eq!(
+            classify(&trace(&spread, Some(LATE_DISCOVERY_TURN - 1))),
+            LossKind::Other
+        );
+        assert_eq!(classify(&trace(&spread, Some(0))), LossKind::Other);
+    }
+}
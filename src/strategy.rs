@@ -0,0 +1,477 @@
+use crate::candidates::{CandidateSet, PatternCache, PATTERN_COUNT};
+use crate::ranking;
+use anyhow::{anyhow, Result};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// How the solver picks its next guess.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    /// Weighted letter-frequency ranking driven by solver_config.json (default).
+    Frequency,
+    /// Maximizes the expected information of the feedback over the possible answers.
+    Entropy,
+    /// Maximizes the chance of solving within the guesses left.
+    WinProb,
+    /// Minimizes the largest group of answers a guess can leave.
+    Minimax,
+}
+
+/// Guesses allowed per game.
+pub const MAX_GUESSES: usize = 6;
+
+/// Number of final guesses played with the endgame strategy, if one is set.
+pub const ENDGAME_TURNS: usize = 2;
+
+/// Candidate sets up to this size get a prognosis in `solve`: whether a win is still
+/// guaranteed.
+pub const PROGNOSIS_LIMIT: usize = 100;
+
+/// Candidate sets up to this size are searched exactly when three guesses are left.
+const EXACT_LIMIT: usize = 16;
+
+/// Guesses (best entropy first) tried when searching more than two guesses deep.
+const SEARCH_WIDTH: usize = 24;
+
+/// Assumed number of feedback buckets a good guess produces, for the estimate on
+/// large candidate sets.
+const BRANCHING: f64 = 30.0;
+
+/// Pattern code of an all-correct feedback.
+const SOLVED: u8 = 242;
+
+impl Strategy {
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name {
+            "frequency" => Ok(Strategy::Frequency),
+            "entropy" => Ok(Strategy::Entropy),
+            "winprob" => Ok(Strategy::WinProb),
+            "minimax" => Ok(Strategy::Minimax),
+            _ => Err(anyhow!(
+                "Unknown strategy '{}'. Use 'frequency', 'entropy', 'winprob' or 'minimax'.",
+                name
+            )),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Strategy::Frequency => "frequency",
+            Strategy::Entropy => "entropy",
+            Strategy::WinProb => "winprob",
+            Strategy::Minimax => "minimax",
+        }
+    }
+
+    /// What the scores of a pattern-based strategy measure, for suggestion headers.
+    pub fn score_label(&self) -> &'static str {
+        match self {
+            Strategy::Frequency => "letter position frequency",
+            Strategy::Entropy => "expected information",
+            Strategy::WinProb => "win probability",
+            Strategy::Minimax => "guaranteed eliminations",
+        }
+    }
+}
+
+/// Shannon entropy (in bits) of the feedback buckets of a guess.
+pub fn entropy(buckets: &[u32], total: usize) -> f64 {
+    let total = total as f64;
+    buckets
+        .iter()
+        .filter(|&&count| count > 0)
+        .map(|&count| {
+            let p = count as f64 / total;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// Point in time after which an anytime search returns its best result so far, or
+/// a flag that ends it early when its result is no longer wanted.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Deadline<'a> {
+    at: Option<Instant>,
+    cancel: Option<&'a AtomicBool>,
+}
+
+impl<'a> Deadline<'a> {
+    /// No deadline when `limit` is `None`.
+    pub fn after(limit: Option<Duration>) -> Self {
+        Self {
+            at: limit.map(|limit| Instant::now() + limit),
+            cancel: None,
+        }
+    }
+
+    /// The same deadline, also passed once `cancel` is set.
+    pub fn or_cancelled(self, cancel: &'a AtomicBool) -> Self {
+        Self {
+            cancel: Some(cancel),
+            ..self
+        }
+    }
+
+    pub fn passed(&self) -> bool {
+        self.at.is_some_and(|deadline| Instant::now() >= deadline)
+            || self
+                .cancel
+                .is_some_and(|cancel| cancel.load(Ordering::Relaxed))
+    }
+}
+
+/// Scored guess indices, best first, and how many of the guesses searched were scored
+/// before the deadline.
+pub struct Ranking {
+    pub scores: Vec<(usize, f64)>,
+    pub evaluated: usize,
+    pub total: usize,
+}
+
+impl Ranking {
+    pub fn is_complete(&self) -> bool {
+        self.evaluated == self.total
+    }
+}
+
+/// Scores `order` front to back until `deadline` passes, always scoring at least the
+/// first item, and sorts best first. Equal scores keep their place in `order`.
+pub fn score_anytime(
+    order: &[usize],
+    deadline: Deadline<'_>,
+    mut score: impl FnMut(usize) -> f64,
+) -> Ranking {
+    let mut scores = Vec::with_capacity(order.len());
+    for &item in order {
+        if !scores.is_empty() && deadline.passed() {
+            break;
+        }
+        scores.push((item, score(item)));
+    }
+
+    let evaluated = scores.len();
+    scores.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+    Ranking {
+        scores,
+        evaluated,
+        total: order.len(),
+    }
+}
+
+/// Guess indices ordered by their letter-frequency score, best first. Used as the
+/// search order of the anytime strategies so a cut-off search has covered the most
+/// promising guesses.
+pub fn frequency_order(
+    cache: &PatternCache,
+    guesses: &[String],
+    stats_json: &str,
+) -> Result<Vec<usize>> {
+    let word_refs: Vec<&str> = guesses.iter().map(|s| s.as_str()).collect();
+    let mut order: Vec<usize> = ranking::rank_words(&word_refs, stats_json)?
+        .iter()
+        .filter_map(|(word, _)| cache.guess_index(word))
+        .collect();
+
+    // Words the stats can't score go last
+    let mut seen = vec![false; cache.guess_count()];
+    for &guess in &order {
+        seen[guess] = true;
+    }
+    order.extend((0..cache.guess_count()).filter(|&guess| !seen[guess]));
+    Ok(order)
+}
+
+/// Ranks the guesses in `order` by entropy over `candidates`. With `prefer_candidates`
+/// a guess that is itself a possible answer gets its win probability added, so it
+/// beats an equally informative probe; without it only the information counts.
+pub fn entropy_rank(
+    cache: &mut PatternCache,
+    candidates: &CandidateSet,
+    order: &[usize],
+    deadline: Deadline<'_>,
+    prefer_candidates: bool,
+) -> Ranking {
+    let total = candidates.count();
+    if total == 0 {
+        return Ranking {
+            scores: Vec::new(),
+            evaluated: 0,
+            total: 0,
+        };
+    }
+
+    score_anytime(order, deadline, |guess| {
+        guess_entropy(cache, candidates, guess, total, prefer_candidates)
+    })
+}
+
+fn guess_entropy(
+    cache: &mut PatternCache,
+    candidates: &CandidateSet,
+    guess: usize,
+    total: usize,
+    prefer_candidates: bool,
+) -> f64 {
+    let buckets = cache.buckets(guess, candidates);
+    let mut score = entropy(&buckets, total);
+    if prefer_candidates
+        && cache
+            .answer_of_guess(guess)
+            .is_some_and(|i| candidates.contains(i))
+    {
+        score += 1.0 / total as f64;
+    }
+    score
+}
+
+/// Candidates a guess is expected to leave: each feedback bucket is as likely as its
+/// share of the candidates, and leaves that many.
+pub fn expected_remaining(buckets: &[u32], total: usize) -> f64 {
+    if total == 0 {
+        return 0.0;
+    }
+    buckets
+        .iter()
+        .map(|&count| (count as f64).powi(2))
+        .sum::<f64>()
+        / total as f64
+}
+
+/// Size of the largest feedback bucket, i.e. the most answers a guess can leave.
+pub fn worst_bucket(buckets: &[u32]) -> u32 {
+    buckets.iter().copied().max().unwrap_or(0)
+}
+
+/// Ranks the guesses in `order` by how many answers they are guaranteed to eliminate
+/// (candidates minus the largest bucket). A guess that is itself a possible answer
+/// gets half a point, so it wins ties against probes.
+pub fn minimax_rank(
+    cache: &mut PatternCache,
+    candidates: &CandidateSet,
+    order: &[usize],
+    deadline: Deadline<'_>,
+) -> Ranking {
+    let total = candidates.count();
+    score_anytime(order, deadline, |guess| {
+        let worst = worst_bucket(&cache.buckets(guess, candidates));
+        let bonus = if cache
+            .answer_of_guess(guess)
+            .is_some_and(|i| candidates.contains(i))
+        {
+            0.5
+        } else {
+            0.0
+        };
+        (total - worst as usize) as f64 + bonus
+    })
+}
+
+/// Ranks guesses by the probability of solving within `guesses_left` (this guess
+/// included), assuming every candidate is equally likely. Ties keep the entropy
+/// order. With two or fewer guesses left every guess is scored exactly; otherwise
+/// only the best entropy guesses are considered.
+pub fn win_probability_rank(
+    cache: &mut PatternCache,
+    candidates: &CandidateSet,
+    guesses_left: usize,
+    order: &[usize],
+    deadline: Deadline<'_>,
+) -> Ranking {
+    let by_entropy = entropy_rank(cache, candidates, order, deadline, true);
+    let considered: Vec<usize> = by_entropy
+        .scores
+        .iter()
+        .map(|&(guess, _)| guess)
+        .take(if guesses_left <= 2 {
+            usize::MAX
+        } else {
+            SEARCH_WIDTH
+        })
+        .collect();
+
+    let ranking = score_anytime(&considered, deadline, |guess| {
+        guess_win_probability(cache, candidates, guess, guesses_left, deadline)
+    });
+
+    // Report the stage the deadline cut short
+    if by_entropy.is_complete() {
+        ranking
+    } else {
+        Ranking {
+            evaluated: by_entropy.evaluated,
+            total: by_entropy.total,
+            ..ranking
+        }
+    }
+}
+
+/// Chance of solving within `guesses_left` when playing `guess` next. Past
+/// `deadline` the searches below it return their best so far, so the chance may be
+/// underestimated.
+fn guess_win_probability(
+    cache: &mut PatternCache,
+    candidates: &CandidateSet,
+    guess: usize,
+    guesses_left: usize,
+    deadline: Deadline<'_>,
+) -> f64 {
+    let total = candidates.count() as f64;
+    let buckets = cache.buckets(guess, candidates);
+    let solves = cache
+        .answer_of_guess(guess)
+        .is_some_and(|i| candidates.contains(i));
+
+    (0..PATTERN_COUNT)
+        .filter(|&pattern| buckets[pattern] > 0)
+        .map(|pattern| {
+            let share = buckets[pattern] as f64 / total;
+            if solves && pattern as u8 == SOLVED {
+                return share;
+            }
+            if let Some(p) = trivial_win_probability(buckets[pattern] as usize, guesses_left - 1) {
+                return share * p;
+            }
+            let mut rest = candidates.clone();
+            rest.intersect_with(&cache.survivors(guess, pattern as u8));
+            share * win_probability(cache, &rest, guesses_left - 1, deadline)
+        })
+        .sum()
+}
+
+/// Chance of solving `candidates` within `guesses_left`. Exact up to two guesses,
+/// searched over the best entropy guesses for small sets with three left, and
+/// estimated from the set size beyond that. Once `deadline` has passed a search
+/// stops after the guess it is scoring and returns the best chance found so far.
+fn win_probability(
+    cache: &mut PatternCache,
+    candidates: &CandidateSet,
+    guesses_left: usize,
+    deadline: Deadline<'_>,
+) -> f64 {
+    let count = candidates.count();
+    if let Some(p) = trivial_win_probability(count, guesses_left) {
+        return p;
+    }
+    match guesses_left {
+        // One guess to split, one to answer: every distinct bucket is a certain win
+        2 => {
+            let mut best = 0;
+            for guess in 0..cache.guess_count() {
+                let buckets = cache.buckets(guess, candidates);
+                best = best.max(buckets.iter().filter(|&&n| n > 0).count());
+                if best == count || deadline.passed() {
+                    break;
+                }
+            }
+            best as f64 / count as f64
+        }
+        3 if count <= EXACT_LIMIT => {
+            let mut best: f64 = 0.0;
+            let all: Vec<usize> = (0..cache.guess_count()).collect();
+            for (guess, _) in entropy_rank(cache, candidates, &all, deadline, true)
+                .scores
+                .into_iter()
+                .take(SEARCH_WIDTH)
+            {
+                best = best.max(guess_win_probability(cache, candidates, guess, 3, deadline));
+                if best >= 1.0 || deadline.passed() {
+                    break;
+                }
+            }
+            best
+        }
+        _ => (BRANCHING.powi(guesses_left as i32 - 1) / count as f64).min(1.0),
+    }
+}
+
+/// Best chance of solving `candidates` within `guesses_left`, when it is exact: with
+/// one or two guesses left. `None` with more.
+pub fn exact_win_probability(
+    cache: &mut PatternCache,
+    candidates: &CandidateSet,
+    guesses_left: usize,
+) -> Option<f64> {
+    (guesses_left <= 2)
+        .then(|| win_probability(cache, candidates, guesses_left, Deadline::default()))
+}
+
+/// Win probability that follows from the set size alone, without looking at words.
+fn trivial_win_probability(count: usize, guesses_left: usize) -> Option<f64> {
+    match (count, guesses_left) {
+        (0, _) | (_, 0) => Some(0.0),
+        (1, _) => Some(1.0),
+        (_, 1) => Some(1.0 / count as f64),
+        (2, _) => Some(1.0),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::Solver;
+    use std::thread;
+
+    #[test]
+    fn expected_remaining_weighs_each_bucket_by_its_share() {
+        assert_eq!(expected_remaining(&[1, 1, 1], 3), 1.0);
+        assert_eq!(expected_remaining(&[3], 3), 3.0);
+        assert!((expected_remaining(&[2, 1], 3) - 5.0 / 3.0).abs() < 1e-12);
+        assert_eq!(expected_remaining(&[], 0), 0.0);
+    }
+
+    #[test]
+    fn slow_scoring_returns_at_the_deadline() {
+        let order: Vec<usize> = (0..1000).collect();
+        let deadline = Deadline::after(Some(Duration::from_millis(20)));
+        let ranking = score_anytime(&order, deadline, |item| {
+            thread::sleep(Duration::from_millis(2));
+            item as f64
+        });
+        assert!(ranking.evaluated >= 1);
+        assert!(!ranking.is_complete());
+        assert_eq!(ranking.scores.len(), ranking.evaluated);
+        // Best first among the guesses scored
+        assert_eq!(ranking.scores[0].0, ranking.evaluated - 1);
+    }
+
+    #[test]
+    fn ranking_without_deadline_scores_everything() {
+        let order: Vec<usize> = (0..50).collect();
+        let ranking = score_anytime(&order, Deadline::default(), |item| item as f64);
+        assert!(ranking.is_complete());
+        assert_eq!(ranking.scores[0], (49, 49.0));
+    }
+
+    #[test]
+    fn ranking_stops_after_the_guess_that_saw_the_cancel() {
+        let order: Vec<usize> = (0..50).collect();
+        let cancel = AtomicBool::new(false);
+        let deadline = Deadline::default().or_cancelled(&cancel);
+        let ranking = score_anytime(&order, deadline, |item| {
+            if item == 3 {
+                cancel.store(true, Ordering::Relaxed);
+            }
+            item as f64
+        });
+        assert_eq!(ranking.evaluated, 4);
+        assert_eq!(ranking.scores[0], (3, 3.0));
+    }
+
+    #[test]
+    fn win_probability_search_stops_at_the_deadline() {
+        let mut solver = Solver::fixture();
+        let all = CandidateSet::full(solver.answer_words.len());
+        let count = all.count() as f64;
+        let cache = &mut solver.cache;
+
+        let exact = win_probability(cache, &all, 2, Deadline::default());
+        let passed = Deadline::after(Some(Duration::ZERO));
+        let cut_short = win_probability(cache, &all, 2, passed);
+
+        // Only the first guess was searched
+        let first = cache.buckets(0, &all).iter().filter(|&&n| n > 0).count();
+        assert_eq!(cut_short, first as f64 / count);
+        assert!(cut_short < exact);
+    }
+}
@@ -0,0 +1,242 @@
+use crate::output::{CellFormat, Table};
+use crate::stats::SimulationResults;
+use crate::strategy::MAX_GUESSES;
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Default upper bounds of the easy and medium buckets, in average guesses.
+pub const DEFAULT_BOUNDARIES: &str = "3.5,4.5";
+
+/// How `simulate --stratified` splits the answers into difficulty buckets.
+pub struct Stratification {
+    /// Game log of an earlier run (`simulate --output`), the per-word difficulty.
+    pub difficulty: PathBuf,
+    /// Ascending upper bounds of every bucket but the last, in average guesses.
+    pub boundaries: Vec<f64>,
+    /// Games per bucket: one count for every bucket, or one per bucket. Empty splits
+    /// the run's games evenly.
+    pub per_bucket: Vec<usize>,
+}
+
+/// One difficulty bucket of a stratified run.
+pub struct Stratum {
+    pub label: String,
+    pub words: Vec<String>,
+    /// Share of the rated answers in this bucket.
+    pub weight: f64,
+    pub games: usize,
+}
+
+/// Average guesses per target in a game log, a loss counting as the guesses the log
+/// records for it (`MAX_GUESSES + 1`).
+pub fn load_difficulty(path: &Path) -> Result<HashMap<String, f64>> {
+    let content = fs::read_to_string(path).map_err(|e| {
+        anyhow!(
+            "Failed to read the difficulty report {}: {}. Write one with 'simulate <num_runs> --output {}'.",
+            path.display(),
+            e,
+            path.display()
+        )
+    })?;
+    let mut lines = content.lines();
+    let header: Vec<&str> = lines.next().unwrap_or_default().split(',').collect();
+    let column = |name: &str| {
+        header.iter().position(|c| c.trim() == name).ok_or_else(|| {
+            anyhow!(
+                "{} has no '{}' column; expected the game log of 'simulate --output'.",
+                path.display(),
+                name
+            )
+        })
+    };
+    let (target_column, guesses_column) = (column("target")?, column("guesses")?);
+
+    let mut totals: HashMap<String, (usize, usize)> = HashMap::new();
+    for (number, line) in lines.enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let fields: Vec<&str> = line.split(',').collect();
+        let malformed = || {
+            anyhow!(
+                "Malformed row {} of {}: '{}'",
+                number + 2,
+                path.display(),
+                line
+            )
+        };
+        let target = fields.get(target_column).ok_or_else(malformed)?.trim();
+        let guesses: usize = fields
+            .get(guesses_column)
+            .and_then(|g| g.trim().parse().ok())
+            .ok_or_else(malformed)?;
+        let total = totals.entry(target.to_lowercase()).or_default();
+        total.0 += guesses.min(MAX_GUESSES + 1);
+        total.1 += 1;
+    }
+    if totals.is_empty() {
+        return Err(anyhow!(
+            "The difficulty report {} has no games.",
+            path.display()
+        ));
+    }
+    Ok(totals
+        .into_iter()
+        .map(|(word, (guesses, games))| (word, guesses as f64 / games as f64))
+        .collect())
+}
+
+/// Parses comma-separated bucket boundaries, which must ascend.
+pub fn parse_boundaries(value: &str) -> Result<Vec<f64>> {
+    let boundaries: Vec<f64> = value
+        .split(',')
+        .map(|b| b.trim().parse::<f64>())
+        .collect::<Result<_, _>>()
+        .map_err(|_| anyhow!("--buckets takes numbers separated by commas, e.g. 3.5,4.5."))?;
+    if boundaries.windows(2).any(|pair| pair[0] >= pair[1]) {
+        return Err(anyhow!("--buckets boundaries must ascend."));
+    }
+    Ok(boundaries)
+}
+
+/// Parses `--per-bucket`: one count, or one per bucket separated by commas.
+pub fn parse_per_bucket(value: &str) -> Result<Vec<usize>> {
+    value
+        .split(',')
+        .map(|count| match count.trim().parse::<usize>() {
+            Ok(count) if count > 0 => Ok(count),
+            _ => Err(anyhow!("--per-bucket takes positive game counts.")),
+        })
+        .collect()
+}
+
+fn label(boundaries: &[f64], bucket: usize) -> String {
+    match (
+        bucket.checked_sub(1).map(|b| boundaries[b]),
+        boundaries.get(bucket),
+    ) {
+        (None, Some(upper)) => format!("< {}", upper),
+        (Some(lower), Some(upper)) => format!("{}-{}", lower, upper),
+        (Some(lower), None) => format!(">= {}", lower),
+        (None, None) => "all".to_string(),
+    }
+}
+
+/// Buckets `answers` by their difficulty in the report and assigns each bucket its
+/// games. Answers the report never played are left out with a warning.
+pub fn build_strata(
+    answers: &[String],
+    options: &Stratification,
+    num_runs: usize,
+) -> Result<Vec<Stratum>> {
+    let difficulty = load_difficulty(&options.difficulty)?;
+    let buckets = options.boundaries.len() + 1;
+    let mut words: Vec<Vec<String>> = vec![Vec::new(); buckets];
+    let mut unrated = 0;
+    for answer in answers {
+        match difficulty.get(answer) {
+            Some(&guesses) => {
+                let bucket = options.boundaries.iter().filter(|&&b| guesses >= b).count();
+                words[bucket].push(answer.clone());
+            }
+            None => unrated += 1,
+        }
+    }
+    let rated = answers.len() - unrated;
+    if rated == 0 {
+        return Err(anyhow!(
+            "The difficulty report {} rates none of the answers.",
+            options.difficulty.display()
+        ));
+    }
+    if unrated > 0 {
+        eprintln!(
+            "Warning: {} of {} answers aren't in the difficulty report and are left out.",
+            unrated,
+            answers.len()
+        );
+    }
+
+    let games: Vec<usize> = match options.per_bucket.as_slice() {
+        [] => (0..buckets)
+            .map(|b| num_runs / buckets + usize::from(b < num_runs % buckets))
+            .collect(),
+        [count] => vec![*count; buckets],
+        counts if counts.len() == buckets => counts.to_vec(),
+        counts => {
+            return Err(anyhow!(
+                "--per-bucket lists {} counts for {} buckets.",
+                counts.len(),
+                buckets
+            ))
+        }
+    };
+
+    let mut strata = Vec::new();
+    for (bucket, (words, games)) in words.into_iter().zip(games).enumerate() {
+        let label = label(&options.boundaries, bucket);
+        if words.is_empty() {
+            eprintln!(
+                "Warning: no answer falls in bucket {}; it is skipped.",
+                label
+            );
+            continue;
+        }
+        strata.push(Stratum {
+            label,
+            weight: words.len() as f64 / rated as f64,
+            words,
+            games,
+        });
+    }
+    Ok(strata)
+}
+
+/// Results per bucket, then the overall estimate with every bucket weighted by its
+/// share of the answers, as uniform sampling would have played them. `results` holds
+/// each stratum's games, in order.
+pub fn print_strata(strata: &[Stratum], results: &[SimulationResults]) {
+    let mut table = Table::new()
+        .column("bucket", "Bucket", CellFormat::Text)
+        .column("words", "Words", CellFormat::Integer)
+        .column("weight", "Weight", CellFormat::Percent(1))
+        .column("games", "Games", CellFormat::Integer)
+        .column("win_rate", "Win %", CellFormat::Percent(1))
+        .column("avg_guesses", "Avg", CellFormat::Fixed(3))
+        .column("score", "Score", CellFormat::Fixed(3));
+    for (stratum, results) in strata.iter().zip(results) {
+        table.push_row(vec![
+            stratum.label.as_str().into(),
+            stratum.words.len().into(),
+            stratum.weight.into(),
+            results.games().into(),
+            (results.win_rate() / 100.0).into(),
+            results.average_guesses().into(),
+            results.score().into(),
+        ]);
+    }
+    println!("\nDifficulty buckets (average guesses in the report):");
+    print!("{}", table.to_text());
+
+    let played: Vec<(f64, &SimulationResults)> = strata
+        .iter()
+        .zip(results)
+        .filter(|(_, results)| results.games() > 0)
+        .map(|(stratum, results)| (stratum.weight, results))
+        .collect();
+    let weight: f64 = played.iter().map(|(weight, _)| weight).sum();
+    if weight == 0.0 {
+        return;
+    }
+    let weighted = |value: fn(&SimulationResults) -> f64| {
+        played.iter().map(|(w, r)| w * value(r)).sum::<f64>() / weight
+    };
+    println!(
+        "Weighted estimate: {:.2}% wins, {:.3} score (a loss counts as {}), comparable to uniform sampling.",
+        weighted(SimulationResults::win_rate),
+        weighted(SimulationResults::score),
+        played[0].1.loss_bucket()
+    );
+}
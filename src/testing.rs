@@ -0,0 +1,136 @@
+use crate::simulate;
+use crate::solver::Solver;
+use crate::strategy::Strategy;
+use crate::util;
+use anyhow::Result;
+
+/// Every answer of the fixture is also its only guess pool.
+const FIXTURE_WORDLIST: &str = include_str!("../fixtures/golden_wordlist.txt");
+
+const STRATEGIES: [Strategy; 4] = [
+    Strategy::Frequency,
+    Strategy::Entropy,
+    Strategy::WinProb,
+    Strategy::Minimax,
+];
+
+const TARGETS: [&str; 6] = ["abuse", "crier", "growl", "meant", "smack", "unite"];
+
+type Game = Vec<(String, String)>;
+
+pub fn fixture_words() -> Vec<String> {
+    FIXTURE_WORDLIST
+        .lines()
+        .map(|w| w.trim().to_string())
+        .filter(|w| !w.is_empty())
+        .collect()
+}
+
+/// Guesses and feedback of one game of `strategy` against `target`, played by an
+/// in-memory solver over `wordlist` with the built-in solver config.
+pub fn play_golden_game(strategy: Strategy, target: &str, wordlist: &[String]) -> Result<Game> {
+    let mut solver = Solver::from_words(wordlist.to_vec(), wordlist.to_vec())?;
+    solver.strategy = strategy;
+    simulate::play_game_lines(&mut solver, target, &util::default_solver_config()?)
+}
+
+fn format_game(game: &Game) -> String {
+    game.iter()
+        .map(|(word, pattern)| format!("{}:{}", word, pattern))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The games of every strategy and target in golden-file format.
+pub fn golden_file() -> Result<String> {
+    let words = fixture_words();
+    let mut text = String::new();
+    for strategy in STRATEGIES {
+        for target in TARGETS {
+            let game = play_golden_game(strategy, target, &words)?;
+            text.push_str(&format!(
+                "{} {}: {}\n",
+                strategy.name(),
+                target,
+                format_game(&game)
+            ));
+        }
+    }
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    /// The exact games every strategy played against `TARGETS` on the fixture, one per
+    /// line: `<strategy> <target>: <guess>:<pattern> ...`.
+    const GOLDEN_GAMES: &str = include_str!("../fixtures/golden_games.txt");
+
+    fn parse_game(text: &str) -> Result<Game> {
+        text.split_whitespace()
+            .map(|entry| {
+                entry
+                    .split_once(':')
+                    .map(|(word, pattern)| (word.to_string(), pattern.to_string()))
+                    .ok_or_else(|| anyhow!("Malformed golden guess '{}'", entry))
+            })
+            .collect()
+    }
+
+    /// Turn-by-turn comparison of two games, marking the turns that differ with `!`.
+    fn diff_games(expected: &Game, actual: &Game) -> String {
+        let mut text = format!("    {:<14}{}\n", "expected", "actual");
+        for turn in 0..expected.len().max(actual.len()) {
+            let cell = |game: &Game| {
+                game.get(turn).map_or("-".to_string(), |(word, pattern)| {
+                    format!("{} {}", word, pattern)
+                })
+            };
+            let marker = if expected.get(turn) == actual.get(turn) {
+                ' '
+            } else {
+                '!'
+            };
+            text.push_str(&format!(
+                "{}{}. {:<14}{}\n",
+                marker,
+                turn + 1,
+                cell(expected),
+                cell(actual)
+            ));
+        }
+        text
+    }
+
+    #[test]
+    fn golden_games_are_unchanged() {
+        let actual = golden_file().unwrap();
+        assert_eq!(
+            GOLDEN_GAMES.lines().count(),
+            actual.lines().count(),
+            "fixtures/golden_games.txt has another number of games"
+        );
+        let mut changed = Vec::new();
+        for (expected, actual) in GOLDEN_GAMES.lines().zip(actual.lines()) {
+            let (key, expected) = expected.split_once(':').unwrap();
+            let (actual_key, actual) = actual.split_once(':').unwrap();
+            assert_eq!(key, actual_key);
+            let (expected, actual) = (parse_game(expected).unwrap(), parse_game(actual).unwrap());
+            if expected != actual {
+                changed.push(format!(
+                    "{} changed:\n{}",
+                    key,
+                    diff_games(&expected, &actual)
+                ));
+            }
+        }
+        assert!(
+            changed.is_empty(),
+            "{}\nIf the change is intended, regenerate the file with `cargo run --features \
+             test-util -- golden --update > fixtures/golden_games.txt`.",
+            changed.join("\n")
+        );
+    }
+}
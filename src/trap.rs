@@ -58,6 +58,7 @@ pub fn find_best_elimination(all_words: &[String], trap: &TrapInfo) -> Option<(S
     all_words
         .iter()
         .map(|w| (w.clone(), score_elimination_word(w, trap)))
-        .max_by_key(|&(_, score)| score)
+        // Equal scores go to the alphabetically first word
+        .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(&a.0)))
         .filter(|&(_, score)| score > 0)
 }
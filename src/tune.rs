@@ -0,0 +1,599 @@
+use crate::output::{CellFormat, Table};
+use crate::simulate;
+use crate::solver::Solver;
+use crate::stats::SimulationResults;
+use crate::strategy::MAX_GUESSES;
+use crate::util::{self, ConfigMeta, SolverConfig};
+use crate::wordlist::PoolOptions;
+use anyhow::{anyhow, Result};
+use rand::prelude::*;
+use rand::rngs::StdRng;
+use std::thread;
+
+/// Largest relative change `perturb` makes to a weight in successive halving, and in
+/// the first generation of the genetic search.
+const PERTURB_STEP: f64 = 0.4;
+
+/// Smallest mutation step of the genetic search, reached in the last generation.
+const MIN_STEP: f64 = 0.05;
+
+/// How `tune` searches for a better config.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TuneMethod {
+    /// Random variations narrowed down by successive halving.
+    Halving,
+    /// A genetic search evolving a population over generations.
+    Genetic {
+        population: usize,
+        generations: usize,
+    },
+}
+
+impl TuneMethod {
+    pub fn from_name(name: &str, population: usize, generations: usize) -> Result<Self> {
+        match name {
+            "halving" => Ok(TuneMethod::Halving),
+            "genetic" => Ok(TuneMethod::Genetic {
+                population: population.max(2),
+                generations: generations.max(1),
+            }),
+            _ => Err(anyhow!(
+                "Unknown tuning method '{}'. Use 'halving' or 'genetic'.",
+                name
+            )),
+        }
+    }
+}
+
+pub struct TuneOptions {
+    pub pool: PoolOptions,
+    pub method: TuneMethod,
+    /// Number of configurations to compare, including the current one.
+    pub configs: usize,
+    /// Games played on the first round; doubled every round. The genetic search
+    /// plays this many games per config in every generation.
+    pub initial_sample: usize,
+    /// Upper bound on the total number of simulated games.
+    pub budget: Option<usize>,
+    pub seed: u64,
+    /// Vary the position multipliers as well as the weight sets (`--tune-positions`).
+    pub tune_positions: bool,
+}
+
+/// Simulated games spent so far against the `--budget` limit.
+struct Budget {
+    limit: Option<usize>,
+    spent: usize,
+}
+
+impl Budget {
+    fn new(limit: Option<usize>) -> Self {
+        Self { limit, spent: 0 }
+    }
+
+    /// Whether `cost` more games stay within the limit.
+    fn allows(&self, cost: usize) -> bool {
+        self.limit.is_none_or(|limit| self.spent + cost <= limit)
+    }
+
+    fn spend(&mut self, cost: usize) {
+        self.spent += cost;
+    }
+}
+
+/// Runs the search chosen by `options.method` and returns its table.
+pub fn run_tune(options: TuneOptions) -> Result<Table> {
+    match options.method {
+        TuneMethod::Halving => run_halving(options),
+        TuneMethod::Genetic {
+            population,
+            generations,
+        } => run_genetic(options, population, generations),
+    }
+}
+
+/// Compares the current solver config against random variations of it with
+/// successive halving: every surviving config plays the same sample of targets, the
+/// better half survives and the sample doubles, until one config remains or the
+/// budget runs out. Returns a table of every config's last round.
+fn run_halving(options: TuneOptions) -> Result<Table> {
+    let mut solver = Solver::with_pool(options.pool)?;
+    let mut rng = StdRng::seed_from_u64(options.seed);
+
+    // One ordering for every round, so configs are always compared on the same targets
+    let mut targets = solver.answer_words.clone();
+    targets.shuffle(&mut rng);
+
+    let base = util::read_full_solver_config()?;
+    let mut configs = vec![base.clone()];
+    while configs.len() < options.configs.max(1) {
+        configs.push(perturb(
+            &base,
+            PERTURB_STEP,
+            options.tune_positions,
+            &mut rng,
+        ));
+    }
+
+    let stats_json = solver.stats_json.clone();
+    let opener = solver.get_top_suggestion_silent(&stats_json, None)?;
+
+    let threads = util::threads().min(configs.len());
+    let mut workers: Vec<Solver> = (1..threads).map(|_| solver.fork()).collect();
+    workers.push(solver);
+
+    println!(
+        "Tuning {} configs on {} threads (seed {}, {} targets)...",
+        configs.len(),
+        workers.len(),
+        options.seed,
+        targets.len()
+    );
+    print_budget_estimate(
+        options.budget,
+        &mut workers,
+        &opener,
+        &targets,
+        &base,
+        options.seed,
+    )?;
+
+    let mut survivors: Vec<usize> = (0..configs.len()).collect();
+    let mut scores: Vec<Option<f64>> = vec![None; configs.len()];
+    let mut last_rounds: Vec<Option<(usize, SimulationResults)>> =
+        configs.iter().map(|_| None).collect();
+    let mut sample = options.initial_sample.max(1).min(targets.len());
+    let mut budget = Budget::new(options.budget);
+    let mut round = 1;
+
+    loop {
+        let cost = survivors.len() * sample;
+        if !budget.allows(cost) {
+            if round == 1 {
+                return Err(too_small(&budget, survivors.len(), sample));
+            }
+            println!(
+                "Budget exhausted: round {} would need {} more games.",
+                round, cost
+            );
+            break;
+        }
+
+        let results = evaluate(
+            &mut workers,
+            &opener,
+            &targets[..sample],
+            &configs,
+            &survivors,
+        )?;
+        budget.spend(cost);
+
+        for (&index, result) in survivors.iter().zip(results) {
+            scores[index] = Some(result.score());
+            last_rounds[index] = Some((round, result));
+        }
+        survivors.sort_by(|&a, &b| {
+            scores[a]
+                .unwrap()
+                .total_cmp(&scores[b].unwrap())
+                .then(a.cmp(&b))
+        });
+
+        let best = survivors[0];
+        let keep = if sample == targets.len() {
+            1
+        } else {
+            survivors.len().div_ceil(2)
+        };
+        println!(
+            "Round {}: {} configs x {} games, best #{} scored {:.3}, keeping {}",
+            round,
+            survivors.len(),
+            sample,
+            best,
+            scores[best].unwrap(),
+            keep
+        );
+
+        survivors.truncate(keep);
+        if survivors.len() == 1 {
+            break;
+        }
+        sample = (sample * 2).min(targets.len());
+        round += 1;
+    }
+
+    let best = survivors[0];
+    println!(
+        "\nTuning finished after {} simulated games on {} threads.",
+        budget.spent,
+        workers.len()
+    );
+    if best == 0 {
+        println!("The current config scored best; nothing to change.");
+    } else {
+        let mut config = configs[best].clone();
+        config.meta = Some(meta(
+            "halving",
+            options.seed,
+            budget.spent,
+            scores[best].unwrap(),
+        ));
+        print_best(&format!("Config #{}", best), scores[best].unwrap(), &config);
+    }
+
+    let mut table = Table::new()
+        .column("config", "Config", CellFormat::Integer)
+        .column("rounds", "Rounds", CellFormat::Integer)
+        .column("games", "Games", CellFormat::Integer)
+        .column("score", "Score", CellFormat::Fixed(3))
+        .column("win_rate", "Win %", CellFormat::Percent(1))
+        .column("avg_guesses", "Avg", CellFormat::Fixed(3));
+    for (index, last) in last_rounds.iter().enumerate() {
+        if let Some((rounds, results)) = last {
+            table.push_row(vec![
+                index.into(),
+                (*rounds).into(),
+                results.games().into(),
+                results.score().into(),
+                (results.win_rate() / 100.0).into(),
+                results.average_guesses().into(),
+            ]);
+        }
+    }
+    Ok(table)
+}
+
+/// Evolves a population of configs around the current one: every generation plays
+/// the same seeded sample of targets, the best config is kept unchanged, and the rest
+/// of the next generation are children of two tournament winners, mixing their
+/// per-attempt weight sets and mutated with a step that shrinks over the generations.
+/// Returns a table of the best and mean score per generation.
+fn run_genetic(options: TuneOptions, population: usize, generations: usize) -> Result<Table> {
+    let mut solver = Solver::with_pool(options.pool)?;
+    let mut rng = StdRng::seed_from_u64(options.seed);
+
+    let mut targets = solver.answer_words.clone();
+    targets.shuffle(&mut rng);
+    targets.truncate(options.initial_sample.max(1));
+
+    let base = util::read_full_solver_config()?;
+    let mut configs = vec![base.clone()];
+    while configs.len() < population {
+        configs.push(perturb(
+            &base,
+            PERTURB_STEP,
+            options.tune_positions,
+            &mut rng,
+        ));
+    }
+
+    let stats_json = solver.stats_json.clone();
+    let opener = solver.get_top_suggestion_silent(&stats_json, None)?;
+
+    let threads = util::threads().min(population);
+    let mut workers: Vec<Solver> = (1..threads).map(|_| solver.fork()).collect();
+    workers.push(solver);
+
+    println!(
+        "Evolving {} configs for {} generations on {} threads (seed {}, {} games each)...",
+        population,
+        generations,
+        workers.len(),
+        options.seed,
+        targets.len()
+    );
+    print_budget_estimate(
+        options.budget,
+        &mut workers,
+        &opener,
+        &targets,
+        &base,
+        options.seed,
+    )?;
+
+    let mut budget = Budget::new(options.budget);
+    // Scores of configs carried over from the previous generation, which aren't replayed
+    let mut known: Vec<Option<f64>> = vec![None; configs.len()];
+    let mut best: Option<(SolverConfig, f64)> = None;
+    let mut table = Table::new()
+        .column("generation", "Generation", CellFormat::Integer)
+        .column("games", "Games", CellFormat::Integer)
+        .column("best", "Best", CellFormat::Fixed(3))
+        .column("mean", "Mean", CellFormat::Fixed(3));
+
+    for generation in 0..generations {
+        let unscored: Vec<usize> = (0..configs.len()).filter(|&i| known[i].is_none()).collect();
+        let cost = unscored.len() * targets.len();
+        if !budget.allows(cost) {
+            if generation == 0 {
+                return Err(too_small(&budget, unscored.len(), targets.len()));
+            }
+            println!(
+                "Budget exhausted: generation {} would need {} more games.",
+                generation + 1,
+                cost
+            );
+            break;
+        }
+        let results = evaluate(&mut workers, &opener, &targets, &configs, &unscored)?;
+        budget.spend(cost);
+        for (&index, result) in unscored.iter().zip(results) {
+            known[index] = Some(result.score());
+        }
+
+        let scores: Vec<f64> = known.iter().map(|score| score.unwrap()).collect();
+        let fittest = (0..configs.len())
+            .min_by(|&a, &b| scores[a].total_cmp(&scores[b]).then(a.cmp(&b)))
+            .unwrap();
+        let mean = scores.iter().sum::<f64>() / scores.len() as f64;
+        println!(
+            "Generation {}: best {:.3}, mean {:.3} ({} games)",
+            generation + 1,
+            scores[fittest],
+            mean,
+            cost
+        );
+        table.push_row(vec![
+            (generation + 1).into(),
+            cost.into(),
+            scores[fittest].into(),
+            mean.into(),
+        ]);
+        best = Some((configs[fittest].clone(), scores[fittest]));
+
+        if generation + 1 == generations {
+            break;
+        }
+        let progress = (generation + 1) as f64 / (generations - 1).max(1) as f64;
+        let step = PERTURB_STEP + (MIN_STEP - PERTURB_STEP) * progress;
+        let mut next = vec![configs[fittest].clone()];
+        while next.len() < population {
+            let a = tournament(&scores, &mut rng);
+            let b = tournament(&scores, &mut rng);
+            let child = crossover(&configs[a], &configs[b], options.tune_positions, &mut rng);
+            next.push(perturb(&child, step, options.tune_positions, &mut rng));
+        }
+        configs = next;
+        known = vec![None; population];
+        known[0] = Some(scores[fittest]);
+    }
+
+    println!(
+        "\nTuning finished after {} simulated games on {} threads.",
+        budget.spent,
+        workers.len()
+    );
+    let (mut config, score) = best.expect("the first generation is always played");
+    if config == base {
+        println!("The current config scored best; nothing to change.");
+    } else {
+        config.meta = Some(meta("genetic", options.seed, budget.spent, score));
+        print_best("The best config", score, &config);
+    }
+    Ok(table)
+}
+
+/// Tells how long spending the whole `--budget` would take, timed on the current
+/// config.
+fn print_budget_estimate(
+    budget: Option<usize>,
+    workers: &mut [Solver],
+    opener: &str,
+    targets: &[String],
+    config: &SolverConfig,
+    seed: u64,
+) -> Result<()> {
+    let Some(games) = budget else {
+        return Ok(());
+    };
+    let per_game =
+        simulate::time_per_game(&mut workers[0], opener, targets, &config.weights, seed)?;
+    println!(
+        "The budget of {} games takes about {} on {} threads.",
+        games,
+        simulate::format_duration(simulate::estimate_duration(per_game, games, workers.len())),
+        workers.len()
+    );
+    Ok(())
+}
+
+/// The error for a budget that can't pay for the first round of `configs` configs.
+fn too_small(budget: &Budget, configs: usize, sample: usize) -> anyhow::Error {
+    anyhow!(
+        "Budget of {} games is too small for the first round ({} configs x {} games).",
+        budget.limit.unwrap_or(0),
+        configs,
+        sample
+    )
+}
+
+/// Provenance of a config a `method` search seeded with `seed` found after `games`
+/// games.
+fn meta(method: &str, seed: u64, games: usize, score: f64) -> ConfigMeta {
+    ConfigMeta {
+        generated_by: Some(format!(
+            "wordle-bot {} tune --method {}",
+            env!("CARGO_PKG_VERSION"),
+            method
+        )),
+        date: Some(util::today()),
+        seed: Some(seed),
+        games: Some(games),
+        avg_guesses: Some((score * 1000.0).round() / 1000.0),
+    }
+}
+
+/// Prints a winning config and where to put it.
+fn print_best(name: &str, score: f64, config: &SolverConfig) {
+    println!(
+        "{} scored {:.3} (average guesses, a loss counts as {}):",
+        name,
+        score,
+        MAX_GUESSES + 1
+    );
+    println!("{}", format_config(config));
+    if let Some(path) = util::solver_config_path() {
+        println!("Copy it into {} to use it.", path.display());
+    }
+}
+
+/// The better of two random configs; lower scores are better.
+fn tournament(scores: &[f64], rng: &mut impl Rng) -> usize {
+    let a = rng.random_range(0..scores.len());
+    let b = rng.random_range(0..scores.len());
+    if scores[b] < scores[a] {
+        b
+    } else {
+        a
+    }
+}
+
+/// A child taking each attempt's weight set from one parent or the other, and with
+/// `positions` each position multiplier too.
+fn crossover(
+    a: &SolverConfig,
+    b: &SolverConfig,
+    positions: bool,
+    rng: &mut impl Rng,
+) -> SolverConfig {
+    let weights = a
+        .weights
+        .iter()
+        .zip(&b.weights)
+        .map(|(&x, &y)| if rng.random_bool(0.5) { x } else { y })
+        .collect();
+    let mut child = SolverConfig {
+        weights,
+        positions: a.positions,
+        meta: None,
+    };
+    if positions {
+        for (multiplier, &other) in child.positions.iter_mut().zip(&b.positions) {
+            if rng.random_bool(0.5) {
+                *multiplier = other;
+            }
+        }
+    }
+    child
+}
+
+/// Plays `targets` with every config in `survivors`, spreading the configs over the
+/// worker solvers. Results come back in the order of `survivors`.
+fn evaluate(
+    workers: &mut [Solver],
+    opener: &str,
+    targets: &[String],
+    configs: &[SolverConfig],
+    survivors: &[usize],
+) -> Result<Vec<SimulationResults>> {
+    let worker_count = workers.len();
+    let per_worker: Vec<Result<Vec<(usize, SimulationResults)>>> = thread::scope(|scope| {
+        let handles: Vec<_> = workers
+            .iter_mut()
+            .enumerate()
+            .map(|(w, solver)| {
+                scope.spawn(move || {
+                    survivors
+                        .iter()
+                        .enumerate()
+                        .skip(w)
+                        .step_by(worker_count)
+                        .map(|(slot, &index)| {
+                            solver.position_weights = configs[index].positions;
+                            simulate::simulate_targets(
+                                solver,
+                                opener,
+                                targets,
+                                &configs[index].weights,
+                            )
+                            .map(|results| (slot, results))
+                        })
+                        .collect()
+                })
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .map(|h| h.join().expect("tuning worker panicked"))
+            .collect()
+    });
+
+    let mut ordered: Vec<Option<SimulationResults>> = survivors.iter().map(|_| None).collect();
+    for results in per_worker {
+        for (slot, result) in results? {
+            ordered[slot] = Some(result);
+        }
+    }
+    Ok(ordered.into_iter().map(|r| r.unwrap()).collect())
+}
+
+/// Scales each weight of `base` by a random factor within `1 ± step` and
+/// renormalizes every tuple to the same sum. The first tuple is left alone since the
+/// opener is ranked without the config. With `positions` the position multipliers
+/// are scaled the same way, keeping their sum.
+fn perturb(base: &SolverConfig, step: f64, positions: bool, rng: &mut impl Rng) -> SolverConfig {
+    let weights = base
+        .weights
+        .iter()
+        .enumerate()
+        .map(|(attempt, &(a, b, c))| {
+            if attempt == 0 {
+                return (a, b, c);
+            }
+            let mut scaled =
+                [a, b, c].map(|w| (w * rng.random_range(1.0 - step..1.0 + step)).max(0.0));
+            // Give zero weights a chance to become non-zero
+            for w in scaled.iter_mut().filter(|w| **w == 0.0) {
+                *w = rng.random_range(0.0..0.1);
+            }
+            let total: f64 = scaled.iter().sum();
+            if total == 0.0 {
+                return (a, b, c);
+            }
+            let target = a + b + c;
+            let [a, b, c] = scaled.map(|w| (w / total * target * 1000.0).round() / 1000.0);
+            (a, b, c)
+        })
+        .collect();
+    let mut config = SolverConfig {
+        weights,
+        positions: base.positions,
+        meta: None,
+    };
+    if positions {
+        let scaled = base
+            .positions
+            .map(|m| (m * rng.random_range(1.0 - step..1.0 + step)).max(0.0));
+        let total: f64 = scaled.iter().sum();
+        if total > 0.0 {
+            let target: f64 = base.positions.iter().sum();
+            config.positions = scaled.map(|m| (m / total * target * 1000.0).round() / 1000.0);
+        }
+    }
+    config
+}
+
+/// Formats a config in solver_config.json's object form, one weight set per line,
+/// with its meta block when it has one.
+fn format_config(config: &SolverConfig) -> String {
+    let rows: Vec<String> = config
+        .weights
+        .iter()
+        .map(|(a, b, c)| format!("    [{}, {}, {}]", a, b, c))
+        .collect();
+    let positions: Vec<String> = config.positions.iter().map(|m| m.to_string()).collect();
+    let mut text = format!(
+        "{{\n  \"weights\": [\n{}\n  ],\n  \"positions\": [{}]",
+        rows.join(",\n"),
+        positions.join(", ")
+    );
+    if let Some(meta) = &config.meta {
+        let meta = serde_json::to_string_pretty(meta).expect("meta serializes");
+        text.push_str(",\n  \"meta\": ");
+        text.push_str(&meta.replace('\n', "\n  "));
+    }
+    text.push_str("\n}");
+    text
+}
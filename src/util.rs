@@ -1,27 +1,296 @@
 use anyhow::{anyhow, Result};
 use directories::ProjectDirs;
-use std::fs;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{OnceLock, RwLock};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 const WORDLIST: &str = include_str!("../wordlist.txt");
 const LETTER_STATS: &str = include_str!("../letter_stats.json");
 const DEFAULT_CONFIG: &str = include_str!("../solver_config.json");
+const COMMON_WORDS: &str = include_str!("../common_words.txt");
+
+/// Source and content of the `--wordlist` override, read at startup and again by
+/// `engine`'s `reload`.
+static WORDLIST_OVERRIDE: RwLock<Option<(String, String)>> = RwLock::new(None);
+
+/// Replaces the built-in wordlist with `content`, read from `source`, for this run.
+/// Returns the override it replaced.
+pub fn set_wordlist(source: &str, content: String) -> Option<(String, String)> {
+    let mut current = WORDLIST_OVERRIDE.write().unwrap_or_else(|e| e.into_inner());
+    current.replace((source.to_string(), content))
+}
+
+/// Where the wordlist of this run comes from when `--wordlist` replaced the
+/// built-in one.
+pub fn wordlist_source() -> Option<String> {
+    let current = WORDLIST_OVERRIDE.read().unwrap_or_else(|e| e.into_inner());
+    current.as_ref().map(|(source, _)| source.clone())
+}
 
 pub fn read_wordlist() -> Result<String> {
-    Ok(WORDLIST.to_string())
+    let current = WORDLIST_OVERRIDE.read().unwrap_or_else(|e| e.into_inner());
+    Ok(current
+        .as_ref()
+        .map_or(WORDLIST, |(_, content)| content.as_str())
+        .to_string())
 }
 
 pub fn read_letter_stats() -> Result<String> {
     Ok(LETTER_STATS.to_string())
 }
 
+/// Everyday words for easy mode, one per line; all of them are possible answers.
+pub fn read_common_words() -> Result<String> {
+    Ok(COMMON_WORDS.to_string())
+}
+
+/// Environment variable that moves the data and config dirs into one directory, e.g.
+/// to run against a scratch dir without touching the user's files.
+pub const DATA_DIR_VAR: &str = "WORDLE_BOT_DATA_DIR";
+
+/// The `WORDLE_BOT_DATA_DIR` directory, when it is set and not empty.
+fn data_dir_override() -> Option<PathBuf> {
+    std::env::var_os(DATA_DIR_VAR)
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Location of the user's solver_config.json, if a config dir is available. With
+/// `WORDLE_BOT_DATA_DIR` set it is in that directory.
+pub fn solver_config_path() -> Option<PathBuf> {
+    if let Some(dir) = data_dir_override() {
+        return Some(dir.join("solver_config.json"));
+    }
+    ProjectDirs::from("", "", "wordle-bot").map(|dirs| dirs.config_dir().join("solver_config.json"))
+}
+
+/// Multipliers of every letter position's frequency that leave the ranking as it is.
+pub const UNIFORM_POSITIONS: [f64; 5] = [1.0; 5];
+
+/// solver_config.json: either a plain array of weight sets, one per attempt, or an
+/// object that also holds the position multipliers and where the config came from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SolverConfig {
+    pub weights: Vec<(f64, f64, f64)>,
+    /// Multiplier of each position's letter frequency, before the per-attempt blend.
+    #[serde(default = "uniform_positions")]
+    pub positions: [f64; 5],
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<ConfigMeta>,
+}
+
+/// Provenance of a config, written by `tune`. Only shown, never used for ranking.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConfigMeta {
+    pub generated_by: Option<String>,
+    /// UTC date the config was written, `YYYY-MM-DD`.
+    pub date: Option<String>,
+    pub seed: Option<u64>,
+    /// Games simulated to find the config.
+    pub games: Option<usize>,
+    /// Score it achieved: average guesses, a loss counting as `MAX_GUESSES + 1`.
+    pub avg_guesses: Option<f64>,
+}
+
+impl ConfigMeta {
+    /// One line naming every field that is set.
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        match (&self.generated_by, &self.date) {
+            (Some(generated_by), Some(date)) => {
+                parts.push(format!("generated by {} on {}", generated_by, date))
+            }
+            (Some(generated_by), None) => parts.push(format!("generated by {}", generated_by)),
+            (None, Some(date)) => parts.push(format!("written on {}", date)),
+            (None, None) => {}
+        }
+        if let Some(seed) = self.seed {
+            parts.push(format!("seed {}", seed));
+        }
+        if let Some(games) = self.games {
+            parts.push(format!("{} games", games));
+        }
+        if let Some(avg_guesses) = self.avg_guesses {
+            parts.push(format!("{:.3} average guesses", avg_guesses));
+        }
+        if parts.is_empty() {
+            "empty meta block".to_string()
+        } else {
+            parts.join(", ")
+        }
+    }
+}
+
+fn uniform_positions() -> [f64; 5] {
+    UNIFORM_POSITIONS
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SolverConfigFile {
+    Weights(Vec<(f64, f64, f64)>),
+    Full(SolverConfig),
+}
+
+pub fn parse_solver_config(content: &str) -> Result<SolverConfig> {
+    let config = match serde_json::from_str(content)
+        .map_err(|e| anyhow!("Failed to parse solver config: {}", e))?
+    {
+        SolverConfigFile::Weights(weights) => SolverConfig {
+            weights,
+            positions: UNIFORM_POSITIONS,
+            meta: None,
+        },
+        SolverConfigFile::Full(config) => config,
+    };
+
+    if config.weights.is_empty() {
+        return Err(anyhow!(
+            "Solver config must contain at least one weight set"
+        ));
+    }
+    if let Some(position) = config
+        .positions
+        .iter()
+        .position(|m| !m.is_finite() || *m < 0.0)
+    {
+        return Err(anyhow!(
+            "Position multiplier {} is {}; multipliers must be non-negative",
+            position + 1,
+            config.positions[position]
+        ));
+    }
+
+    Ok(config)
+}
+
+/// The built-in solver_config.json, ignoring the user's copy.
+pub fn default_solver_config() -> Result<Vec<(f64, f64, f64)>> {
+    parse_solver_config(DEFAULT_CONFIG).map(|config| config.weights)
+}
+
+pub fn read_full_solver_config() -> Result<SolverConfig> {
+    let content = read_solver_config_text();
+    parse_solver_config(&content).or_else(|_| parse_solver_config(DEFAULT_CONFIG))
+}
+
 pub fn read_solver_config() -> Result<Vec<(f64, f64, f64)>> {
-    let content = if let Some(proj_dirs) = ProjectDirs::from("", "", "wordle-bot") {
-        let config_dir = proj_dirs.config_dir();
-        let config_path = config_dir.join("solver_config.json");
+    read_full_solver_config().map(|config| config.weights)
+}
+
+/// Today's UTC date as `YYYY-MM-DD`.
+pub fn today() -> String {
+    let days = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() / 86_400) as i64;
+    date(days)
+}
+
+/// The `YYYY-MM-DD` date `days` days after 1970-01-01.
+pub fn date(days: i64) -> String {
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Days from 1970-01-01 to a `YYYY-MM-DD` date, the inverse of `date`.
+pub fn parse_date(text: &str) -> Result<i64> {
+    let invalid = || anyhow!("'{}' is not a date; use YYYY-MM-DD, e.g. 2024-06-01.", text);
+    let parts: Vec<i64> = text
+        .trim()
+        .split('-')
+        .map(|part| part.parse().map_err(|_| invalid()))
+        .collect::<Result<_>>()?;
+    let [year, month, day] = parts[..] else {
+        return Err(invalid());
+    };
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return Err(invalid());
+    }
+    let year = year - i64::from(month <= 2);
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+    if date(days)
+        != format!(
+            "{:04}-{:02}-{:02}",
+            year + i64::from(month <= 2),
+            month,
+            day
+        )
+    {
+        return Err(invalid());
+    }
+    Ok(days)
+}
+
+/// Renames tried before `write_atomic` gives up. On Windows a rename over a file
+/// another process (a virus scanner, the indexer) just opened fails for a moment.
+#[cfg(windows)]
+const RENAME_ATTEMPTS: u32 = 5;
+#[cfg(not(windows))]
+const RENAME_ATTEMPTS: u32 = 1;
+
+/// Replaces the file at `path` with `bytes` so that it always holds either the old or
+/// the new content: the bytes go to `<path>.tmp` in the same directory, are synced to
+/// disk and then renamed over `path`. A `.tmp` left behind by a crash is overwritten
+/// by the next write and never read.
+pub fn write_atomic(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    let tmp = path.with_file_name(name);
+
+    let written = fs::File::create(&tmp).and_then(|mut file| {
+        file.write_all(bytes)?;
+        file.sync_all()
+    });
+    let mut result = written.and_then(|()| fs::rename(&tmp, path));
+    let mut attempts = 1;
+    while result.is_err() && attempts < RENAME_ATTEMPTS && tmp.exists() {
+        thread::sleep(std::time::Duration::from_millis(50));
+        result = fs::rename(&tmp, path);
+        attempts += 1;
+    }
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result
+}
+
+/// Contents of the user's solver_config.json, or the defaults while there is none.
+/// Unlike `read_solver_config_text`, never writes the file.
+pub fn peek_solver_config_text() -> String {
+    solver_config_path()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .unwrap_or_else(|| DEFAULT_CONFIG.to_string())
+}
+
+/// Contents of the user's solver_config.json, written with the defaults on first use.
+pub fn read_solver_config_text() -> String {
+    if let Some(config_path) = solver_config_path() {
+        let config_dir = config_path.parent().unwrap();
 
         if !config_path.exists() {
             if fs::create_dir_all(config_dir).is_ok() {
-                let _ = fs::write(&config_path, DEFAULT_CONFIG);
+                let _ = write_atomic(&config_path, DEFAULT_CONFIG.as_bytes());
             }
             DEFAULT_CONFIG.to_string()
         } else {
@@ -29,11 +298,209 @@ pub fn read_solver_config() -> Result<Vec<(f64, f64, f64)>> {
         }
     } else {
         DEFAULT_CONFIG.to_string()
+    }
+}
+
+/// Contents of probe_words.txt in the active profile's data dir, if there is one.
+pub fn read_probe_words() -> Result<Option<String>> {
+    let Some(path) = profile_dir(profile()).map(|dir| dir.join("probe_words.txt")) else {
+        return Ok(None);
+    };
+    if !path.exists() {
+        return Ok(None);
+    }
+    fs::read_to_string(&path)
+        .map(Some)
+        .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))
+}
+
+/// Seed for a run: the one given with `--seed`, or a fresh random one.
+pub fn resolve_seed(seed: Option<u64>) -> u64 {
+    seed.unwrap_or_else(rand::random)
+}
+
+/// Directory for user data such as the used-answers list: `WORDLE_BOT_DATA_DIR` when
+/// set, else the platform's data dir.
+pub fn data_dir() -> Option<PathBuf> {
+    data_dir_override().or_else(|| {
+        ProjectDirs::from("", "", "wordle-bot").map(|dirs| dirs.data_dir().to_path_buf())
+    })
+}
+
+/// Name of the profile that needs no `--profile` flag; its data is the data dir itself.
+pub const DEFAULT_PROFILE: &str = "default";
+
+static PROFILE: OnceLock<String> = OnceLock::new();
+
+/// Selects the profile for this run (`--profile`). Names may use letters, digits,
+/// `-` and `_`.
+pub fn set_profile(name: &str) -> Result<()> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(anyhow!(
+            "Invalid profile name '{}': use letters, digits, '-' and '_'.",
+            name
+        ));
+    }
+    let _ = PROFILE.set(name.to_string());
+    Ok(())
+}
+
+/// The active profile, `default` unless `--profile` chose another.
+pub fn profile() -> &'static str {
+    PROFILE.get().map_or(DEFAULT_PROFILE, |name| name.as_str())
+}
+
+/// Data directory of a profile: the data dir for the default profile, and
+/// `profiles/<name>` inside it for any other.
+pub fn profile_dir(name: &str) -> Option<PathBuf> {
+    let dir = data_dir()?;
+    if name == DEFAULT_PROFILE {
+        Some(dir)
+    } else {
+        Some(dir.join("profiles").join(name))
+    }
+}
+
+/// Niceness of a `--background` run.
+#[cfg(unix)]
+const BACKGROUND_NICE: i32 = 10;
+
+/// Games a `--background` run plays between yields to other threads.
+const YIELD_EVERY: usize = 16;
+
+static THREADS: OnceLock<usize> = OnceLock::new();
+static BACKGROUND: AtomicBool = AtomicBool::new(false);
+
+/// Sets the worker threads of this run: exactly `requested` (`--threads`), else
+/// every core, or every core but one in the background. `background`
+/// (`--background`) also lowers the priority of the process where the platform
+/// allows it.
+pub fn set_threads(requested: Option<usize>, background: bool) {
+    let cores = thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1);
+    let threads = match requested {
+        Some(threads) => threads,
+        None if background => cores.saturating_sub(1).max(1),
+        None => cores,
     };
+    let _ = THREADS.set(threads);
+
+    if background {
+        BACKGROUND.store(true, Ordering::Relaxed);
+        lower_priority();
+    }
+}
+
+/// Worker threads for parallel commands, set with `set_threads`.
+pub fn threads() -> usize {
+    *THREADS.get_or_init(|| {
+        thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1)
+    })
+}
+
+/// Whether this run was started with `--background`.
+pub fn background() -> bool {
+    BACKGROUND.load(Ordering::Relaxed)
+}
+
+/// Lets other threads run every few games of a `--background` run. `game` counts
+/// the games played so far.
+pub fn background_yield(game: usize) {
+    if background() && game.is_multiple_of(YIELD_EVERY) {
+        thread::yield_now();
+    }
+}
+
+/// Raises the niceness of the process. Threads spawned later inherit it.
+#[cfg(unix)]
+fn lower_priority() {
+    // SAFETY: setpriority only reads its integer arguments
+    if unsafe { libc::setpriority(libc::PRIO_PROCESS, 0, BACKGROUND_NICE) } != 0 {
+        eprintln!("Warning: could not lower the process priority.");
+    }
+}
+
+#[cfg(not(unix))]
+fn lower_priority() {}
+
+pub fn used_answers_path() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("used_answers.txt"))
+}
+
+/// Past answers from used_answers.txt in the data dir. Missing file means none.
+pub fn read_used_answers() -> Result<HashSet<String>> {
+    match used_answers_path() {
+        Some(path) => read_used_answers_from(&path),
+        None => Ok(HashSet::new()),
+    }
+}
+
+/// Past answers from the used answers file at `path`. Missing file means none.
+pub fn read_used_answers_from(path: &Path) -> Result<HashSet<String>> {
+    if !path.exists() {
+        return Ok(HashSet::new());
+    }
+
+    let content = fs::read_to_string(path)
+        .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+    Ok(content
+        .lines()
+        .map(|w| w.trim().to_lowercase())
+        .filter(|w| !w.is_empty())
+        .collect())
+}
+
+/// Appends an answer to used_answers.txt, creating the file if needed.
+pub fn append_used_answer(word: &str) -> Result<PathBuf> {
+    let path = used_answers_path().ok_or_else(|| anyhow!("No data directory available"))?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+    writeln!(file, "{}", word)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const OLD: &str = "{\"counts\": {\"a\": [1, 2, 3, 4, 5]}}";
+    const NEW: &str = "{\"counts\": {\"a\": [5, 4, 3, 2, 1]}}";
+
+    #[test]
+    fn interrupted_write_leaves_the_old_file_and_the_next_replaces_it() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("letter_stats.json");
+        let tmp = dir.path().join("letter_stats.json.tmp");
+        fs::write(&path, OLD).unwrap();
+        // A crash mid-write leaves a truncated .tmp, which nothing reads
+        fs::write(&tmp, &NEW[..10]).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), OLD);
+
+        write_atomic(&path, NEW.as_bytes()).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), NEW);
+        assert!(!tmp.exists());
+    }
 
-    let weights: Vec<(f64, f64, f64)> = serde_json::from_str(&content)
-        .or_else(|_| serde_json::from_str(DEFAULT_CONFIG))
-        .map_err(|e| anyhow!("Failed to parse solver config: {}", e))?;
+    #[test]
+    fn failed_write_keeps_the_old_file_complete() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("game.json");
+        fs::write(&path, OLD).unwrap();
+        // The .tmp can't be created where a directory is in the way
+        fs::create_dir(dir.path().join("game.json.tmp")).unwrap();
 
-    Ok(weights)
+        assert!(write_atomic(&path, NEW.as_bytes()).is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), OLD);
+    }
 }
@@ -0,0 +1,96 @@
+use crate::analysis;
+use crate::util;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+/// A first-guess ranking saved by `solve`, with the inputs it was computed from.
+#[derive(Serialize, Deserialize)]
+struct CachedRanking {
+    strategy: String,
+    /// Hash of the letter stats and solver_config.json.
+    config_hash: String,
+    /// Hash of the candidate words the ranking covers.
+    wordlist_hash: String,
+    ranking: Vec<(String, f64)>,
+}
+
+/// initial_ranking.json in the data dir, holding one ranking per strategy.
+fn cache_path() -> Option<PathBuf> {
+    util::data_dir().map(|dir| dir.join("initial_ranking.json"))
+}
+
+/// Key identifying the inputs of a first-guess ranking.
+pub struct CacheKey {
+    strategy: String,
+    config_hash: String,
+    wordlist_hash: String,
+}
+
+impl CacheKey {
+    pub fn new(strategy: &str, stats_json: &str, words: &[String]) -> Self {
+        Self {
+            strategy: strategy.to_string(),
+            config_hash: analysis::content_hash(&[stats_json, &util::read_solver_config_text()]),
+            wordlist_hash: analysis::wordlist_hash(words),
+        }
+    }
+
+    fn matches(&self, cached: &CachedRanking) -> bool {
+        cached.strategy == self.strategy
+            && cached.config_hash == self.config_hash
+            && cached.wordlist_hash == self.wordlist_hash
+    }
+}
+
+fn read_cache() -> Vec<CachedRanking> {
+    cache_path()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// The saved ranking for `key`. A missing, unreadable or outdated cache gives `None`,
+/// so the caller silently computes the ranking again.
+pub fn load(key: &CacheKey) -> Option<Vec<(String, f64)>> {
+    read_cache()
+        .into_iter()
+        .find(|cached| key.matches(cached))
+        .map(|cached| cached.ranking)
+}
+
+/// Strategies whose cached ranking was computed from `stats_json` and the current
+/// solver config, and so goes stale when the stats change. Reads without writing
+/// anything, for `analyze --dry-run`.
+pub fn rankings_from(stats_json: &str) -> Vec<String> {
+    let config_hash = analysis::content_hash(&[stats_json, &util::peek_solver_config_text()]);
+    read_cache()
+        .into_iter()
+        .filter(|cached| cached.config_hash == config_hash)
+        .map(|cached| cached.strategy)
+        .collect()
+}
+
+/// Saves `ranking` for `key`, replacing the previous ranking of the same strategy. The
+/// cache only saves time, so write errors are ignored.
+pub fn store(key: &CacheKey, ranking: &[(String, f64)]) {
+    let Some(path) = cache_path() else {
+        return;
+    };
+
+    let mut cache = read_cache();
+    cache.retain(|cached| cached.strategy != key.strategy);
+    cache.push(CachedRanking {
+        strategy: key.strategy.clone(),
+        config_hash: key.config_hash.clone(),
+        wordlist_hash: key.wordlist_hash.clone(),
+        ranking: ranking.to_vec(),
+    });
+
+    if let Some(dir) = path.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+    if let Ok(content) = serde_json::to_string(&cache) {
+        let _ = util::write_atomic(&path, content.as_bytes());
+    }
+}
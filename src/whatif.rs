@@ -0,0 +1,161 @@
+use crate::game;
+use crate::ranking::{rank_words, weighted_rank};
+use crate::solver::Solver;
+use crate::strategy::{Strategy, MAX_GUESSES};
+use anyhow::{anyhow, Result};
+
+/// A counterfactual for a finished game: at `turn`, take suggestion number `rank`
+/// instead of the word that was played.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WhatIf {
+    pub turn: usize,
+    pub rank: usize,
+}
+
+impl WhatIf {
+    /// Parses `turn=<n> rank=<n>`. Either part may be left out; the defaults are turn
+    /// 1 and rank 2.
+    pub fn parse(spec: &str) -> Result<Self> {
+        let mut what_if = Self { turn: 1, rank: 2 };
+        for part in spec.split_whitespace() {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| anyhow!("Expected turn=<n> or rank=<n>, got '{}'", part))?;
+            let value: usize = value
+                .parse()
+                .ok()
+                .filter(|&n| n > 0)
+                .ok_or_else(|| anyhow!("{} must be a positive number", key))?;
+            match key {
+                "turn" => what_if.turn = value,
+                "rank" => what_if.rank = value,
+                _ => return Err(anyhow!("Unknown what-if setting '{}'", key)),
+            }
+        }
+        Ok(what_if)
+    }
+}
+
+/// Guesses with their feedback patterns.
+pub type Line = Vec<(String, String)>;
+
+/// Replays the game against `target` on a fork of `solver`: the words of `played`
+/// before `what_if.turn`, then suggestion number `what_if.rank`, then the solver's top
+/// suggestion each turn until the target is found or the guesses run out.
+pub fn replay(
+    solver: &Solver,
+    target: &str,
+    played: &[String],
+    what_if: WhatIf,
+    weights: &[(f64, f64, f64)],
+) -> Result<Line> {
+    if what_if.turn > played.len() {
+        return Err(anyhow!(
+            "turn must be between 1 and {}, the number of guesses played",
+            played.len()
+        ));
+    }
+
+    let mut solver = solver.fork();
+    solver.game.reset();
+    solver.reset_candidates();
+    let stats_json = solver.stats_json.clone();
+    let mut line = Vec::new();
+
+    while line.len() < MAX_GUESSES {
+        let turn = line.len() + 1;
+        solver.update_wordlist();
+        let guess = if turn < what_if.turn {
+            played[turn - 1].clone()
+        } else if turn == what_if.turn {
+            let suggestions = ranked_suggestions(&mut solver, &stats_json, weights)?;
+            suggestions
+                .into_iter()
+                .nth(what_if.rank - 1)
+                .ok_or_else(|| {
+                    anyhow!("turn {} has fewer than {} suggestions", turn, what_if.rank)
+                })?
+        } else {
+            solver.next_guess(&stats_json, weights)?
+        };
+
+        let pattern = game::grade(&guess, target).to_string();
+        solver.game.add_line(&guess, &pattern);
+        let solved = guess == target;
+        line.push((guess, pattern));
+        if solved {
+            break;
+        }
+    }
+
+    Ok(line)
+}
+
+/// The solver's suggestions in the order it would pick them: the top suggestion
+/// (which may be a trap's elimination word) followed by the rest of the ranking.
+fn ranked_suggestions(
+    solver: &mut Solver,
+    stats_json: &str,
+    weights: &[(f64, f64, f64)],
+) -> Result<Vec<String>> {
+    let top = solver.next_guess(stats_json, weights)?;
+    let ranking: Vec<String> = if solver.active_strategy() == Strategy::Frequency {
+        let word_refs: Vec<&str> = solver.current_words.iter().map(|s| s.as_str()).collect();
+        let ranked = if solver.game.lines.is_empty() {
+            rank_words(&word_refs, stats_json)?
+        } else {
+            let attempt = solver.game.lines.len().min(weights.len() - 1);
+            weighted_rank(
+                &word_refs,
+                stats_json,
+                weights[attempt],
+                solver.position_weights,
+            )?
+        };
+        ranked.words()
+    } else {
+        let scores = solver.pattern_ranking().scores;
+        scores
+            .into_iter()
+            .map(|(guess, _)| solver.cache.guess(guess))
+            .collect()
+    };
+
+    let mut suggestions = vec![top.clone()];
+    suggestions.extend(ranking.into_iter().filter(|word| *word != top));
+    Ok(suggestions)
+}
+
+/// Both games side by side, one turn per row, with how each ended.
+pub fn format_comparison(actual: &Line, alternative: &Line, what_if: WhatIf) -> String {
+    let mut text = format!(
+        "What if turn {} had taken suggestion #{}:\n{:<6}{:<16}{}\n",
+        what_if.turn, what_if.rank, "Turn", "Your game", "What-if"
+    );
+    let cell = |line: &Line, i: usize| {
+        line.get(i)
+            .map(|(word, pattern)| format!("{} {}", word, pattern))
+            .unwrap_or_default()
+    };
+    for i in 0..actual.len().max(alternative.len()) {
+        text.push_str(&format!(
+            "{:<6}{:<16}{}\n",
+            i + 1,
+            cell(actual, i),
+            cell(alternative, i)
+        ));
+    }
+    text.push_str(&format!(
+        "Your game: {}. What-if: {}.\n",
+        outcome(actual),
+        outcome(alternative)
+    ));
+    text
+}
+
+fn outcome(line: &Line) -> String {
+    match line.last() {
+        Some((_, pattern)) if pattern == "ccccc" => format!("solved in {}", line.len()),
+        _ => format!("not solved after {}", line.len()),
+    }
+}
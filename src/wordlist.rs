@@ -0,0 +1,623 @@
+use crate::analysis::{self, LetterStats, WORD_LENGTH};
+use crate::game::LetterRestriction;
+use crate::util;
+use anyhow::{anyhow, Result};
+use rand::prelude::*;
+use rand::rngs::StdRng;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fs;
+use std::io::{self, Read};
+
+/// Index in wordlist.txt where the possible answers start; everything before it is
+/// only accepted as a guess.
+pub const ANSWER_START: usize = 10657;
+
+/// How the guess and answer pools are narrowed before a game starts.
+#[derive(Default)]
+pub struct PoolOptions {
+    pub restriction: LetterRestriction,
+    /// Drop past answers (used_answers.txt) from the answer pool.
+    pub exclude_used: bool,
+    pub punctuation: PunctuationPolicy,
+    /// Share of the best score a probe_words.txt probe must reach to replace an
+    /// unlisted probe (`--probe-threshold`); `probe::DEFAULT_THRESHOLD` when unset.
+    pub probe_threshold: Option<f64>,
+}
+
+pub struct Wordlist {
+    pub guesses: Vec<String>,
+    pub answers: Vec<String>,
+    /// Answers removed by `exclude_answers`, still present in `guesses`.
+    pub excluded_answers: HashSet<String>,
+}
+
+/// What the loader does with apostrophes and hyphens inside entries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PunctuationPolicy {
+    /// Drop entries containing punctuation (default).
+    #[default]
+    Reject,
+    /// Remove the punctuation and keep the entry if it still has the right length.
+    Strip,
+    /// Keep punctuation as a literal character that takes part in matching.
+    Keep,
+}
+
+impl PunctuationPolicy {
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name {
+            "reject" => Ok(PunctuationPolicy::Reject),
+            "strip" => Ok(PunctuationPolicy::Strip),
+            "keep" => Ok(PunctuationPolicy::Keep),
+            _ => Err(anyhow!(
+                "Unknown punctuation policy '{}'. Use 'reject', 'strip' or 'keep'.",
+                name
+            )),
+        }
+    }
+}
+
+/// Characters the punctuation policy applies to.
+pub const PUNCTUATION: [char; 2] = ['\'', '-'];
+
+/// Share of wrong-length entries above which loading prints their length histogram.
+pub const LENGTH_WARNING_SHARE: f64 = 0.01;
+
+/// Why an entry was dropped while loading.
+#[derive(Debug, Default)]
+pub struct LoadReport {
+    pub wrong_length: usize,
+    pub punctuation: usize,
+    pub invalid_characters: usize,
+    pub stripped: usize,
+    /// Entries by length, counted after punctuation and character checks.
+    pub lengths: BTreeMap<usize, usize>,
+}
+
+impl LoadReport {
+    fn rejected(&self) -> usize {
+        self.wrong_length + self.punctuation + self.invalid_characters
+    }
+
+    /// `lengths` as `4: 1203, 5: 4521, ...`.
+    pub fn length_histogram(&self) -> String {
+        self.lengths
+            .iter()
+            .map(|(length, count)| format!("{}: {}", length, count))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Lowercases and trims an entry and applies `policy`. Returns `None` when the entry
+/// is dropped or isn't `length` letters long, recording the reason in `report`.
+pub fn normalize_word(
+    raw: &str,
+    policy: PunctuationPolicy,
+    length: usize,
+    report: &mut LoadReport,
+) -> Option<String> {
+    let mut word = raw.trim().to_lowercase();
+    if word.is_empty() {
+        return None;
+    }
+
+    if word.contains(PUNCTUATION) {
+        match policy {
+            PunctuationPolicy::Reject => {
+                report.punctuation += 1;
+                return None;
+            }
+            PunctuationPolicy::Strip => {
+                word.retain(|c| !PUNCTUATION.contains(&c));
+                report.stripped += 1;
+            }
+            PunctuationPolicy::Keep => {}
+        }
+    }
+
+    if !word
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || PUNCTUATION.contains(&c))
+    {
+        report.invalid_characters += 1;
+        return None;
+    }
+
+    let word_length = word.chars().count();
+    *report.lengths.entry(word_length).or_insert(0) += 1;
+    if word_length != length {
+        report.wrong_length += 1;
+        return None;
+    }
+
+    Some(word)
+}
+
+/// `analysis::content_hash` of the raw content of this run's wordlist: the built-in
+/// one or `--wordlist`. Saved state records it so it is never mixed with another list.
+pub fn active_hash() -> Result<String> {
+    Ok(analysis::content_hash(&[util::read_wordlist()?]))
+}
+
+/// First bytes of every gzip file.
+#[cfg(feature = "gzip")]
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Reads a wordlist file for `--wordlist`: `-` reads stdin and, with the `gzip`
+/// feature, a `.gz` path is decompressed. The content still goes through the
+/// loader's validation.
+pub fn read_source(path: &str) -> Result<String> {
+    if path == "-" {
+        let mut content = String::new();
+        io::stdin()
+            .read_to_string(&mut content)
+            .map_err(|e| anyhow!("Failed to read the wordlist from stdin: {}", e))?;
+        return Ok(content);
+    }
+
+    let bytes = fs::read(path).map_err(|e| anyhow!("Failed to read {}: {}", path, e))?;
+    #[cfg(feature = "gzip")]
+    let bytes = if path.ends_with(".gz") {
+        if !bytes.starts_with(&GZIP_MAGIC) {
+            return Err(anyhow!(
+                "{} is not a gzip file (bad magic bytes); rename it without .gz if it is plain text",
+                path
+            ));
+        }
+        gunzip(path, &bytes)?
+    } else {
+        bytes
+    };
+    String::from_utf8(bytes).map_err(|_| anyhow!("{} is not UTF-8 text", path))
+}
+
+#[cfg(feature = "gzip")]
+fn gunzip(path: &str, bytes: &[u8]) -> Result<Vec<u8>> {
+    let mut content = Vec::new();
+    flate2::read::GzDecoder::new(bytes)
+        .read_to_end(&mut content)
+        .map_err(|e| anyhow!("Failed to decompress {}: {}", path, e))?;
+    Ok(content)
+}
+
+impl Wordlist {
+    pub fn load() -> Result<Self> {
+        Self::load_normalized(PunctuationPolicy::default())
+    }
+
+    pub fn load_normalized(policy: PunctuationPolicy) -> Result<Self> {
+        let source = util::wordlist_source().unwrap_or_else(|| "wordlist.txt".to_string());
+        let content =
+            util::read_wordlist().map_err(|e| anyhow!("Failed to read {}: {}", source, e))?;
+
+        let mut report = LoadReport::default();
+        let mut guesses = Vec::new();
+        let mut answers = Vec::new();
+        let line_count = content.lines().count();
+
+        for (line, raw) in content.lines().enumerate() {
+            let Some(word) = normalize_word(raw, policy, WORD_LENGTH, &mut report) else {
+                continue;
+            };
+            // The answer split refers to lines of the file, not to accepted entries
+            if line >= ANSWER_START || line_count <= ANSWER_START {
+                answers.push(word.clone());
+            }
+            guesses.push(word);
+        }
+
+        if report.rejected() > 0 || report.stripped > 0 {
+            eprintln!(
+                "Wordlist: {} entries rejected ({} wrong length, {} with punctuation, {} with other characters), {} stripped of punctuation",
+                report.rejected(),
+                report.wrong_length,
+                report.punctuation,
+                report.invalid_characters,
+                report.stripped
+            );
+        }
+        if report.wrong_length as f64 > line_count as f64 * LENGTH_WARNING_SHARE {
+            eprintln!(
+                "Warning: {} of {} entries are not {} letters long. Entries by length: {}",
+                report.wrong_length,
+                line_count,
+                WORD_LENGTH,
+                report.length_histogram()
+            );
+        }
+
+        if guesses.is_empty() {
+            return Err(anyhow!("{} is empty or invalid", source));
+        }
+
+        Ok(Self {
+            guesses,
+            answers,
+            excluded_answers: HashSet::new(),
+        })
+    }
+
+    /// Loads the wordlist and applies every narrowing in `options`.
+    pub fn load_with(options: &PoolOptions) -> Result<Self> {
+        let mut wordlist = Self::load_normalized(options.punctuation)?;
+        wordlist.restrict(&options.restriction)?;
+
+        if options.exclude_used {
+            let used = util::read_used_answers()?;
+            wordlist.exclude_answers(&used)?;
+        }
+
+        Ok(wordlist)
+    }
+
+    /// Removes `used` from the answer pool only; they stay valid guesses.
+    pub fn exclude_answers(&mut self, used: &HashSet<String>) -> Result<()> {
+        let unknown: Vec<&String> = used.iter().filter(|w| !self.answers.contains(w)).collect();
+        if !unknown.is_empty() {
+            eprintln!(
+                "Warning: {} used answer(s) are not in the answer list and were ignored: {}",
+                unknown.len(),
+                unknown
+                    .iter()
+                    .map(|w| w.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+
+        let excluded = &mut self.excluded_answers;
+        self.answers.retain(|w| {
+            let keep = !used.contains(w);
+            if !keep {
+                excluded.insert(w.clone());
+            }
+            keep
+        });
+        if self.answers.is_empty() {
+            return Err(anyhow!("Every answer is listed in used_answers.txt"));
+        }
+
+        Ok(())
+    }
+
+    /// Applies a letter restriction to both pools, failing with the count left after
+    /// each stage when either pool ends up empty.
+    pub fn restrict(&mut self, restriction: &LetterRestriction) -> Result<()> {
+        if restriction.is_empty() {
+            return Ok(());
+        }
+
+        let guess_stages = restrict_pool(&mut self.guesses, restriction);
+        let answer_stages = restrict_pool(&mut self.answers, restriction);
+
+        if self.guesses.is_empty() || self.answers.is_empty() {
+            return Err(anyhow!(
+                "Letter restriction leaves no words to play.\n  guesses: {}\n  answers: {}",
+                guess_stages,
+                answer_stages
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+fn restrict_pool(words: &mut Vec<String>, restriction: &LetterRestriction) -> String {
+    let loaded = words.len();
+    words.retain(|w| !w.chars().any(|c| restriction.excluded.contains(&c)));
+    let after_exclude = words.len();
+    words.retain(|w| restriction.required.iter().all(|&c| w.contains(c)));
+
+    format!(
+        "{} loaded, {} after --exclude-letters, {} after --require-letters",
+        loaded,
+        after_exclude,
+        words.len()
+    )
+}
+
+/// Summary of a wordlist file for `wordlist info`.
+pub struct WordlistInfo {
+    /// Non-empty lines.
+    pub entries: usize,
+    /// Entries kept by the loader at the requested length, duplicates included.
+    pub words: usize,
+    pub length: usize,
+    pub report: LoadReport,
+    pub alphabet: BTreeSet<char>,
+    /// Kept words that appeared before.
+    pub duplicates: usize,
+    /// Most common first letters of the kept words, most common first.
+    pub starting_letters: Vec<(char, usize)>,
+}
+
+impl WordlistInfo {
+    /// Runs every line of `content` through the loader for words of `length` letters.
+    pub fn from_text(content: &str, policy: PunctuationPolicy, length: usize) -> Self {
+        let mut report = LoadReport::default();
+        let mut seen = HashSet::new();
+        let mut info = Self {
+            entries: 0,
+            words: 0,
+            length,
+            report: LoadReport::default(),
+            alphabet: BTreeSet::new(),
+            duplicates: 0,
+            starting_letters: Vec::new(),
+        };
+        let mut starts: BTreeMap<char, usize> = BTreeMap::new();
+
+        for raw in content.lines().filter(|l| !l.trim().is_empty()) {
+            info.entries += 1;
+            let Some(word) = normalize_word(raw, policy, length, &mut report) else {
+                continue;
+            };
+            info.words += 1;
+            info.alphabet.extend(word.chars());
+            if let Some(first) = word.chars().next() {
+                *starts.entry(first).or_insert(0) += 1;
+            }
+            if !seen.insert(word) {
+                info.duplicates += 1;
+            }
+        }
+
+        info.report = report;
+        info.starting_letters = starts.into_iter().collect();
+        // Ties stay in alphabetical order
+        info.starting_letters
+            .sort_by_key(|&(_, count)| std::cmp::Reverse(count));
+        info.starting_letters.truncate(10);
+        info
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = format!("Entries: {}\n", self.entries);
+        text.push_str(&format!(
+            "{}-letter words: {} (duplicates: {})\n",
+            self.length, self.words, self.duplicates
+        ));
+        text.push_str(&format!(
+            "Rejected: {} wrong length, {} with punctuation, {} with other characters\n",
+            self.report.wrong_length, self.report.punctuation, self.report.invalid_characters
+        ));
+        text.push_str(&format!(
+            "Entries by length: {}\n",
+            self.report.length_histogram()
+        ));
+        text.push_str(&format!(
+            "Alphabet ({} letters): {}\n",
+            self.alphabet.len(),
+            self.alphabet.iter().collect::<String>()
+        ));
+        let starts: Vec<String> = self
+            .starting_letters
+            .iter()
+            .map(|(letter, count)| format!("{} {}", letter, count))
+            .collect();
+        text.push_str(&format!(
+            "Most common first letters: {}\n",
+            starts.join(", ")
+        ));
+        text
+    }
+}
+
+/// How `wordlist sample` picks its words.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Sampling {
+    /// Every word equally likely.
+    Uniform,
+    /// Keep each position's letter distribution close to the full list's.
+    LetterFrequency,
+}
+
+impl Sampling {
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name {
+            "uniform" => Ok(Sampling::Uniform),
+            "letter-frequency" => Ok(Sampling::LetterFrequency),
+            _ => Err(anyhow!(
+                "Unknown sampling '{}'. Use 'uniform' or 'letter-frequency'.",
+                name
+            )),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Sampling::Uniform => "uniform",
+            Sampling::LetterFrequency => "letter-frequency",
+        }
+    }
+}
+
+/// `size` of `words`, sorted, the same for the same seed. Uniform sampling draws them
+/// at random. Letter-frequency sampling adds one word at a time, always the one
+/// leaving the sample's letter distribution closest to the full list's (lowest
+/// `letter_divergence`); the seed only breaks ties.
+pub fn sample(words: &[String], size: usize, sampling: Sampling, seed: u64) -> Vec<String> {
+    let mut pool = words.to_vec();
+    pool.sort();
+    pool.dedup();
+    pool.shuffle(&mut StdRng::seed_from_u64(seed));
+    let size = size.min(pool.len());
+
+    let mut chosen = match sampling {
+        Sampling::Uniform => {
+            pool.truncate(size);
+            pool
+        }
+        Sampling::LetterFrequency => {
+            let refs: Vec<&str> = pool.iter().map(String::as_str).collect();
+            let reference = LetterStats::from_words(&refs);
+            // log of each letter's share per position in the full list
+            let log_share: BTreeMap<char, [f64; WORD_LENGTH]> = reference
+                .counts
+                .iter()
+                .map(|(&ch, counts)| {
+                    let shares = counts.map(|c| (c as f64 / pool.len() as f64).ln());
+                    (ch, shares)
+                })
+                .collect();
+            // The divergence of n sample words is (sum c ln c - sum c ln q) / n - ln n
+            // per position, so adding a word only changes the terms of its letters
+            let mut counts: BTreeMap<char, [u32; WORD_LENGTH]> = BTreeMap::new();
+            let c_ln_c = |c: u32| {
+                if c == 0 {
+                    0.0
+                } else {
+                    c as f64 * (c as f64).ln()
+                }
+            };
+            let mut used = vec![false; pool.len()];
+            let mut chosen = Vec::with_capacity(size);
+            for _ in 0..size {
+                let mut best: Option<(usize, f64)> = None;
+                for (index, word) in pool.iter().enumerate() {
+                    if used[index] {
+                        continue;
+                    }
+                    let gain: f64 = word
+                        .chars()
+                        .enumerate()
+                        .map(|(i, ch)| {
+                            let c = counts.get(&ch).map_or(0, |counts| counts[i]);
+                            c_ln_c(c + 1) - c_ln_c(c) - log_share[&ch][i]
+                        })
+                        .sum();
+                    if best.is_none_or(|(_, best)| gain < best) {
+                        best = Some((index, gain));
+                    }
+                }
+                let Some((index, _)) = best else {
+                    break;
+                };
+                used[index] = true;
+                for (i, ch) in pool[index].chars().enumerate() {
+                    counts.entry(ch).or_insert([0; WORD_LENGTH])[i] += 1;
+                }
+                chosen.push(pool[index].clone());
+            }
+            chosen
+        }
+    };
+    chosen.sort();
+    chosen
+}
+
+/// KL divergence of the letter distribution of `sample` from that of `reference`,
+/// summed over the positions: 0 when every position's letter shares are the same.
+pub fn letter_divergence(sample: &LetterStats, reference: &LetterStats) -> f64 {
+    (0..WORD_LENGTH)
+        .map(|i| {
+            let total =
+                |stats: &LetterStats| stats.counts.values().map(|c| c[i] as f64).sum::<f64>();
+            let (sample_total, reference_total) = (total(sample), total(reference));
+            sample
+                .counts
+                .iter()
+                .filter(|(_, counts)| counts[i] > 0)
+                .map(|(ch, counts)| {
+                    let p = counts[i] as f64 / sample_total;
+                    let q = reference.counts.get(ch).map_or(0.0, |c| c[i] as f64) / reference_total;
+                    p * (p / q).ln()
+                })
+                .sum::<f64>()
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MIXED_LENGTHS: &str = include_str!("../tests/fixtures/mixed_lengths.txt");
+
+    #[test]
+    fn info_counts_every_entry_of_the_fixture() {
+        let info = WordlistInfo::from_text(MIXED_LENGTHS, PunctuationPolicy::Reject, 5);
+        assert_eq!(info.entries, 12);
+        assert_eq!(info.words, 7);
+        assert_eq!(info.duplicates, 1);
+        assert_eq!(info.report.wrong_length, 3);
+        assert_eq!(info.report.punctuation, 1);
+        assert_eq!(info.report.invalid_characters, 1);
+        assert_eq!(info.report.length_histogram(), "3: 1, 5: 7, 6: 1, 7: 1");
+        assert_eq!(info.alphabet.iter().collect::<String>(), "abceghilnorstu");
+        assert_eq!(
+            info.starting_letters,
+            [('c', 2), ('t', 2), ('b', 1), ('h', 1), ('s', 1)]
+        );
+    }
+
+    #[test]
+    fn info_for_another_length_keeps_only_that_length() {
+        let info = WordlistInfo::from_text(MIXED_LENGTHS, PunctuationPolicy::Reject, 6);
+        assert_eq!(info.words, 1);
+        assert_eq!(info.report.wrong_length, 9);
+        assert_eq!(info.starting_letters, [('p', 1)]);
+    }
+
+    #[test]
+    fn stripped_punctuation_is_counted_at_its_new_length() {
+        let info = WordlistInfo::from_text(MIXED_LENGTHS, PunctuationPolicy::Strip, 4);
+        assert_eq!(info.report.stripped, 1);
+        assert_eq!(info.report.punctuation, 0);
+        assert_eq!(info.words, 1);
+        assert_eq!(
+            info.report.length_histogram(),
+            "3: 1, 4: 1, 5: 7, 6: 1, 7: 1"
+        );
+    }
+
+    #[test]
+    fn wordlist_with_crlf_lines_loads_the_same_words() {
+        let unix = WordlistInfo::from_text("crane\nstale\n", PunctuationPolicy::Reject, 5);
+        let windows =
+            WordlistInfo::from_text("CRANE\r\n\tstale \r\n\r\n", PunctuationPolicy::Reject, 5);
+        assert_eq!(windows.to_text(), unix.to_text());
+    }
+
+    #[test]
+    fn kept_punctuation_stays_in_the_word_and_its_alphabet() {
+        let info = WordlistInfo::from_text(MIXED_LENGTHS, PunctuationPolicy::Keep, 5);
+        assert_eq!(info.words, 8);
+        assert_eq!(info.report.punctuation, 0);
+        assert_eq!(info.report.stripped, 0);
+        assert!(info.alphabet.contains(&'\''));
+
+        let mut report = LoadReport::default();
+        let mut keep = |raw| normalize_word(raw, PunctuationPolicy::Keep, 5, &mut report);
+        assert_eq!(keep("Co-Op"), Some("co-op".to_string()));
+        assert_eq!(keep("co-o'p"), None);
+        assert_eq!(report.wrong_length, 1);
+    }
+
+    #[test]
+    fn stratified_sample_keeps_the_letter_distribution_closer() {
+        let words: Vec<String> = include_str!("../fixtures/golden_wordlist.txt")
+            .lines()
+            .map(String::from)
+            .collect();
+        let stats_of = |words: &[String]| {
+            let refs: Vec<&str> = words.iter().map(|w| w.as_str()).collect();
+            LetterStats::from_words(&refs)
+        };
+        let full = stats_of(&words);
+        for seed in 0..5 {
+            let uniform = sample(&words, 40, Sampling::Uniform, seed);
+            let stratified = sample(&words, 40, Sampling::LetterFrequency, seed);
+            assert_eq!((uniform.len(), stratified.len()), (40, 40));
+            assert!(
+                letter_divergence(&stats_of(&stratified), &full)
+                    < letter_divergence(&stats_of(&uniform), &full)
+            );
+            assert_eq!(
+                sample(&words, 40, Sampling::LetterFrequency, seed),
+                stratified
+            );
+        }
+    }
+}
@@ -0,0 +1,1246 @@
+use assert_cmd::cargo::cargo_bin;
+use assert_cmd::Command;
+use tempfile::TempDir;
+
+/// Answers and guesses of the small fixture list, which keeps every run fast.
+const FIXTURE_WORDLIST: &str = "fixtures/golden_wordlist.txt";
+
+/// The game the frequency strategy plays against CRIER on the fixture list.
+const CRIER_GAME: &str = "arose\nwcwwm\nbrief\nwcccw\ncrier\nccccc\n";
+
+/// The binary with every data and config file in `data`, in English and without color.
+fn wordle_bot(data: &TempDir) -> Command {
+    let mut command = Command::new(cargo_bin!("wordle-bot"));
+    command
+        .env("WORDLE_BOT_DATA_DIR", data.path())
+        .env("LANG", "C")
+        .env("NO_COLOR", "1");
+    command
+}
+
+/// Stdout of a successful run of `args` fed `input`.
+fn run(data: &TempDir, args: &[&str], input: &str) -> String {
+    let output = wordle_bot(data)
+        .args(args)
+        .write_stdin(input)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn minimal_solve_prints_only_the_prompt() {
+    let data = TempDir::new().unwrap();
+    let stdout = run(
+        &data,
+        &["solve", "--minimal", "--wordlist", FIXTURE_WORDLIST],
+        CRIER_GAME,
+    );
+    assert_eq!(
+        stdout,
+        "[0 guesses, 193 left] best: AROSE > pattern > \
+         [1 guesses, 3 left] best: BRIEF > pattern > \
+         [2 guesses, 1 left] best: CRIER > pattern > \
+         Congratulations! You've solved the puzzle! The word is 'crier'.\n"
+    );
+}
+
+#[test]
+fn json_solve_prints_one_object_per_turn() {
+    let data = TempDir::new().unwrap();
+    let stdout = run(
+        &data,
+        &["solve", "--json", "--wordlist", FIXTURE_WORDLIST],
+        CRIER_GAME,
+    );
+    let turns: Vec<serde_json::Value> = stdout
+        .lines()
+        .filter(|line| line.starts_with('{'))
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let remaining: Vec<u64> = turns
+        .iter()
+        .map(|turn| turn["remaining"].as_u64().unwrap())
+        .collect();
+    assert_eq!(remaining, [193, 3, 1]);
+    assert_eq!(turns[2]["suggestions"][0]["word"], "crier");
+    assert!(!stdout.contains("Top 10"));
+}
+
+#[test]
+fn repeated_guess_asks_before_wasting_a_turn() {
+    let data = TempDir::new().unwrap();
+    let stdout = run(
+        &data,
+        &["solve", "--minimal", "--wordlist", FIXTURE_WORDLIST],
+        "arose\nwcwwm\narose\nn\nexit\n",
+    );
+    // Declining keeps the game at one guess
+    assert!(stdout.ends_with(
+        "[1 guesses, 3 left] best: BRIEF > again? (y/n) > \
+         [1 guesses, 3 left] best: BRIEF > Exiting solver.\n"
+    ));
+}
+
+#[test]
+fn score_breakdown_matches_the_suggestion_list() {
+    let data = TempDir::new().unwrap();
+    let breakdown = run(
+        &data,
+        &[
+            "score",
+            "brief",
+            "--guess",
+            "arose:wcwwm",
+            "--wordlist",
+            FIXTURE_WORDLIST,
+        ],
+        "",
+    );
+    let score = breakdown
+        .lines()
+        .rfind(|line| line.starts_with("Score "))
+        .and_then(|line| line.split_whitespace().last())
+        .unwrap();
+    assert!(breakdown.contains("Rank 1 of 3 remaining candidates"));
+
+    let solve = run(
+        &data,
+        &["solve", "--wordlist", FIXTURE_WORDLIST],
+        "arose\nwcwwm\nexit\n",
+    );
+    let listed = solve
+        .lines()
+        .find_map(|line| line.strip_prefix("b[r]i(e)f"))
+        .unwrap();
+    let listed: f64 = listed.split_whitespace().next().unwrap().parse().unwrap();
+    assert_eq!(score, format!("{:.4}", listed));
+}
+
+/// The first-guess ranking cache `solve` keeps in the data dir.
+fn ranking_cache(data: &TempDir) -> std::path::PathBuf {
+    data.path().join("initial_ranking.json")
+}
+
+/// The opener `solve --minimal` suggests.
+fn opener(data: &TempDir) -> String {
+    let stdout = run(
+        data,
+        &["solve", "--minimal", "--wordlist", FIXTURE_WORDLIST],
+        "exit\n",
+    );
+    let (_, best) = stdout.split_once("best: ").unwrap();
+    best.split_whitespace().next().unwrap().to_string()
+}
+
+/// Rewrites the cached ranking so that `word` comes first.
+fn plant_opener(data: &TempDir, word: &str) {
+    let path = ranking_cache(data);
+    let mut cache: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    cache[0]["ranking"] = serde_json::json!([[word, 1.0e9]]);
+    std::fs::write(&path, cache.to_string()).unwrap();
+}
+
+#[test]
+fn corrupt_ranking_cache_is_silently_regenerated() {
+    let data = TempDir::new().unwrap();
+    assert_eq!(opener(&data), "AROSE");
+    std::fs::write(ranking_cache(&data), "{\"strategy\": \"freq").unwrap();
+
+    let output = wordle_bot(&data)
+        .args(["solve", "--minimal", "--wordlist", FIXTURE_WORDLIST])
+        .write_stdin("exit\n")
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .contains("best: AROSE"));
+    assert!(output.stderr.is_empty());
+    let cache = std::fs::read_to_string(ranking_cache(&data)).unwrap();
+    assert!(serde_json::from_str::<serde_json::Value>(&cache).is_ok());
+}
+
+#[test]
+fn changed_solver_config_recomputes_the_ranking() {
+    let data = TempDir::new().unwrap();
+    assert_eq!(opener(&data), "AROSE");
+    plant_opener(&data, "crier");
+    // The planted ranking shows the cache is read while the inputs are unchanged
+    assert_eq!(opener(&data), "CRIER");
+
+    let config = data.path().join("solver_config.json");
+    let mut text = std::fs::read_to_string(&config).unwrap();
+    text.push('\n');
+    std::fs::write(&config, text).unwrap();
+    assert_eq!(opener(&data), "AROSE");
+}
+
+#[test]
+fn no_cache_ignores_the_saved_ranking() {
+    let data = TempDir::new().unwrap();
+    opener(&data);
+    plant_opener(&data, "crier");
+    let stdout = run(
+        &data,
+        &[
+            "solve",
+            "--minimal",
+            "--no-cache",
+            "--wordlist",
+            FIXTURE_WORDLIST,
+        ],
+        "exit\n",
+    );
+    assert!(stdout.starts_with("[0 guesses, 193 left] best: AROSE"));
+}
+
+#[test]
+fn replayed_game_matches_the_game_in_the_full_run() {
+    let data = TempDir::new().unwrap();
+    let csv = data.path().join("games.csv");
+    let run_args = [
+        "simulate",
+        "20",
+        "--seed",
+        "9",
+        "--error-rate",
+        "0.3",
+        "--wordlist",
+        FIXTURE_WORDLIST,
+    ];
+    let mut full = run_args.to_vec();
+    full.extend(["--output", csv.to_str().unwrap()]);
+    run(&data, &full, "");
+
+    let rows = std::fs::read_to_string(&csv).unwrap();
+    for row in rows.lines().skip(1) {
+        let fields: Vec<&str> = row.split(',').collect();
+        let (game, target, guesses, candidates) = (fields[0], fields[1], fields[2], fields[7]);
+
+        let mut replay_args = run_args.to_vec();
+        replay_args.extend(["--replay-game", game]);
+        let replay = run(&data, &replay_args, "");
+        assert!(
+            replay.starts_with(&format!(
+                "Replaying game {} (seed 9): target '{}'",
+                game, target
+            )),
+            "{}",
+            replay
+        );
+        // The log counts a lost game as one guess over the limit
+        let replayed_guesses = replay
+            .lines()
+            .find_map(|line| line.strip_prefix("Solved in "))
+            .map_or("7", |rest| rest.split_whitespace().next().unwrap());
+        assert_eq!(replayed_guesses, guesses, "game {}", game);
+        // Words left after each replayed guess, as the full run counted them
+        let counts: Vec<&str> = replay
+            .lines()
+            .filter_map(|line| line.split(" -> ").nth(1))
+            .map(|rest| rest.split_whitespace().next().unwrap())
+            .collect();
+        let logged: Vec<&str> = candidates.split_whitespace().skip(1).collect();
+        assert_eq!(counts, logged[..counts.len()], "game {}", game);
+    }
+}
+
+#[test]
+fn answers_in_history_and_used_answers_count_once() {
+    let data = TempDir::new().unwrap();
+    let record = |answer: &str| {
+        format!(
+            "{{\"timestamp\":0,\"answer\":\"{}\",\"guesses\":3,\"won\":true}}\n",
+            answer
+        )
+    };
+    let history = record("crier") + &record("abuse");
+    std::fs::write(data.path().join("history.jsonl"), history).unwrap();
+    std::fs::write(
+        data.path().join("used_answers.txt"),
+        "crier\nabuse\nbrief\n",
+    )
+    .unwrap();
+
+    let stdout = run(&data, &["prior", "show"], "");
+    assert!(
+        stdout.starts_with("Learned from 3 past answer(s)"),
+        "{}",
+        stdout
+    );
+}
+
+#[test]
+fn game_log_has_one_valid_row_per_game() {
+    let data = TempDir::new().unwrap();
+    let csv_path = data.path().join("games.csv");
+    run(
+        &data,
+        &[
+            "simulate",
+            "3000",
+            "--seed",
+            "3",
+            "--wordlist",
+            FIXTURE_WORDLIST,
+            "--output",
+            csv_path.to_str().unwrap(),
+        ],
+        "",
+    );
+
+    let mut reader = csv::Reader::from_path(&csv_path).unwrap();
+    let rows: Vec<csv::StringRecord> = reader.records().map(Result::unwrap).collect();
+    assert_eq!(rows.len(), 3000);
+    for (i, row) in rows.iter().enumerate() {
+        assert_eq!(row.len(), 8);
+        assert_eq!(row[0].parse::<usize>().unwrap(), i + 1);
+    }
+    let summary = std::fs::read_to_string(data.path().join("games.summary.json")).unwrap();
+    let summary: serde_json::Value = serde_json::from_str(&summary).unwrap();
+    assert_eq!(summary["games"], 3000);
+}
+
+#[test]
+fn perf_section_is_internally_consistent() {
+    let data = TempDir::new().unwrap();
+    let csv_path = data.path().join("games.csv");
+    let stdout = run(
+        &data,
+        &[
+            "simulate",
+            "200",
+            "--perf",
+            "--wordlist",
+            FIXTURE_WORDLIST,
+            "--output",
+            csv_path.to_str().unwrap(),
+        ],
+        "",
+    );
+    assert!(stdout.contains("=== Performance ==="));
+
+    let summary = std::fs::read_to_string(data.path().join("games.summary.json")).unwrap();
+    let summary: serde_json::Value = serde_json::from_str(&summary).unwrap();
+    let perf = &summary["perf"];
+    let seconds = |value: &serde_json::Value| value.as_f64().unwrap();
+    let wall = seconds(&perf["wall_seconds"]);
+    let phases = perf["phase_seconds"].as_object().unwrap();
+    let timed: f64 = ["filter", "rank", "evaluate"]
+        .iter()
+        .map(|phase| seconds(&phases[*phase]))
+        .sum();
+    assert!(timed <= wall, "phases {} > wall {}", timed, wall);
+    assert!((timed + seconds(&phases["other"]) - wall).abs() < 1e-6);
+    assert!((seconds(&perf["games_per_second"]) * wall - 200.0).abs() < 1e-6);
+    assert_eq!(perf["peak_candidates"], 193);
+}
+
+#[test]
+fn import_share_rejects_invalid_guesses() {
+    let data = TempDir::new().unwrap();
+    let share = "Wordle 1,234 3/6\n\n⬛🟩⬛⬛🟨\n⬛🟩🟩🟩⬛\n🟩🟩🟩🟩🟩\n";
+    for (guess, error) in [
+        ("cranes", "Guess 1 'cranes' must be 5 letters."),
+        ("zzzzz", "Guess 1 'zzzzz' is not in the wordlist."),
+    ] {
+        let output = wordle_bot(&data)
+            .args(["import-share", "--answer", "crier", "--guess", guess])
+            .args(["--wordlist", FIXTURE_WORDLIST])
+            .write_stdin(share)
+            .assert()
+            .failure()
+            .get_output()
+            .clone();
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.contains(error), "{}", stderr);
+    }
+
+    let stdout = run(
+        &data,
+        &[
+            "import-share",
+            "--answer",
+            "crier",
+            "--guess",
+            "AROSE",
+            "--wordlist",
+            FIXTURE_WORDLIST,
+        ],
+        share,
+    );
+    assert!(stdout.contains("AROSE  (given)"), "{}", stdout);
+}
+
+#[test]
+fn thread_count_does_not_change_results() {
+    let data = TempDir::new().unwrap();
+    let tune = |threads: &str| {
+        let stdout = run(
+            &data,
+            &[
+                "tune",
+                "--configs",
+                "6",
+                "--sample",
+                "16",
+                "--seed",
+                "3",
+                "--threads",
+                threads,
+                "--wordlist",
+                FIXTURE_WORDLIST,
+            ],
+            "",
+        );
+        let echoed = format!("on {} threads", threads);
+        assert!(stdout.contains(&echoed), "{}", stdout);
+        stdout.replace(&echoed, "on N threads")
+    };
+    assert_eq!(tune("1"), tune("4"));
+
+    let states = "arose:wcwwm\narose:cwwcc\n\nbrief:wmwww crier:wwmww\n";
+    let batch = |threads: &str| {
+        run(
+            &data,
+            &[
+                "batch-rank",
+                "--threads",
+                threads,
+                "--wordlist",
+                FIXTURE_WORDLIST,
+            ],
+            states,
+        )
+    };
+    assert_eq!(batch("1"), batch("3"));
+}
+
+#[test]
+fn background_leaves_one_core_free() {
+    let data = TempDir::new().unwrap();
+    let cores = std::thread::available_parallelism().unwrap().get();
+    let stdout = run(
+        &data,
+        &[
+            "tune",
+            "--configs",
+            "2",
+            "--sample",
+            "8",
+            "--background",
+            "--wordlist",
+            FIXTURE_WORDLIST,
+        ],
+        "",
+    );
+    let expected = format!("on {} threads", (cores - 1).max(1));
+    assert!(stdout.contains(&expected), "{}", stdout);
+}
+
+#[test]
+fn solved_game_prints_and_records_its_eliminations() {
+    let data = TempDir::new().unwrap();
+    let stdout = run(
+        &data,
+        &["solve", "--wordlist", FIXTURE_WORDLIST],
+        CRIER_GAME,
+    );
+    let (_, table) = stdout.split_once("Candidate elimination:\n").unwrap();
+    assert_eq!(
+        table,
+        "#  Word   Before  After  Eliminated  Bits\n\
+         1  AROSE     193      3       98.4%  6.01\n\
+         2  BRIEF       3      1       66.7%  1.58\n\
+         3  CRIER       1      1        0.0%  0.00\n\
+         Information: 7.59 bits needed for 193 candidates; 3 guess(es) could give up to 23.77 bits (32% used).\n"
+    );
+
+    let history = std::fs::read_to_string(data.path().join("history.jsonl")).unwrap();
+    let record: serde_json::Value = serde_json::from_str(history.trim()).unwrap();
+    assert_eq!(record["candidates"], serde_json::json!([193, 3, 1, 1]));
+}
+
+#[cfg(feature = "gzip")]
+#[test]
+fn gzipped_and_piped_wordlists_rank_like_the_plain_file() {
+    use flate2::write::GzEncoder;
+    use std::io::Write;
+
+    let data = TempDir::new().unwrap();
+    let words = std::fs::read_to_string(FIXTURE_WORDLIST).unwrap();
+    let gzipped = data.path().join("words.txt.gz");
+    let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(words.as_bytes()).unwrap();
+    std::fs::write(&gzipped, encoder.finish().unwrap()).unwrap();
+
+    let rank = |wordlist: &str, input: &str| {
+        run(
+            &data,
+            &["rank", "--format", "csv", "--wordlist", wordlist],
+            input,
+        )
+    };
+    let plain = rank(FIXTURE_WORDLIST, "");
+    assert_eq!(plain.lines().count(), 194);
+    assert_eq!(rank(gzipped.to_str().unwrap(), ""), plain);
+    assert_eq!(rank("-", &words), plain);
+
+    let bad = data.path().join("bad.txt.gz");
+    std::fs::write(&bad, &words).unwrap();
+    let output = wordle_bot(&data)
+        .args(["rank", "--wordlist", bad.to_str().unwrap()])
+        .assert()
+        .failure()
+        .get_output()
+        .clone();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains(&format!("{} is not a gzip file", bad.display())));
+}
+
+#[cfg(not(feature = "gzip"))]
+#[test]
+fn builds_without_gzip_read_gz_paths_as_plain_text() {
+    let data = TempDir::new().unwrap();
+    let plain = data.path().join("words.txt.gz");
+    std::fs::copy(FIXTURE_WORDLIST, &plain).unwrap();
+    let rank = |wordlist: &str| {
+        run(
+            &data,
+            &["rank", "--format", "csv", "--wordlist", wordlist],
+            "",
+        )
+    };
+    assert_eq!(rank(plain.to_str().unwrap()), rank(FIXTURE_WORDLIST));
+}
+
+#[test]
+fn genetic_tune_is_seeded_and_never_loses_its_best_config() {
+    let data = TempDir::new().unwrap();
+    let generations = |name: &str| {
+        let path = data.path().join(name);
+        run(
+            &data,
+            &[
+                "tune",
+                "--method",
+                "genetic",
+                "--population",
+                "4",
+                "--generations",
+                "4",
+                "--sample",
+                "12",
+                "--seed",
+                "5",
+                "--wordlist",
+                FIXTURE_WORDLIST,
+                "--format",
+                "json",
+                "--output",
+                path.to_str().unwrap(),
+            ],
+            "",
+        );
+        std::fs::read_to_string(path).unwrap()
+    };
+    let first = generations("first.json");
+    assert_eq!(first, generations("second.json"));
+
+    let rows: Vec<serde_json::Value> = serde_json::from_str(&first).unwrap();
+    assert_eq!(rows.len(), 4);
+    let best: Vec<f64> = rows
+        .iter()
+        .map(|row| row["best"].as_f64().unwrap())
+        .collect();
+    // Lower scores are better, and the fittest config is carried over unchanged
+    assert!(best.windows(2).all(|w| w[1] <= w[0]), "{:?}", best);
+}
+
+#[test]
+fn analyze_dry_run_writes_nothing() {
+    let data = TempDir::new().unwrap();
+    let wordlist = std::fs::canonicalize(FIXTURE_WORDLIST).unwrap();
+    let stats = data.path().join("stats.json");
+    std::fs::write(&stats, "{\"counts\": {\"a\": [1, 0, 0, 0, 0]}}\n").unwrap();
+    let files = || {
+        let mut names: Vec<_> = std::fs::read_dir(data.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        names.sort();
+        names
+    };
+    let before = files();
+
+    let output = wordle_bot(&data)
+        .current_dir(data.path())
+        .args(["analyze", "--dry-run", "--wordlist"])
+        .arg(&wordlist)
+        .arg("--output")
+        .arg(&stats)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let stdout = String::from_utf8(output).unwrap();
+    assert!(stdout.contains("Nothing is written."), "{}", stdout);
+    assert!(stdout.contains("Words: 1 -> 193 (+192)"), "{}", stdout);
+    assert!(stdout.contains("would be rewritten"), "{}", stdout);
+
+    assert_eq!(files(), before);
+    assert_eq!(
+        std::fs::read_to_string(&stats).unwrap(),
+        "{\"counts\": {\"a\": [1, 0, 0, 0, 0]}}\n"
+    );
+}
+
+#[test]
+fn config_diff_compares_every_weight_and_multiplier() {
+    let data = TempDir::new().unwrap();
+    let stdout = run(
+        &data,
+        &[
+            "config",
+            "diff",
+            "tests/fixtures/solver_config_old.json",
+            "tests/fixtures/solver_config_tuned.json",
+        ],
+        "",
+    );
+    assert_eq!(stdout, include_str!("fixtures/config_diff.txt"));
+}
+
+#[test]
+fn tune_prints_its_best_config_with_a_meta_block() {
+    let data = TempDir::new().unwrap();
+    let stdout = run(
+        &data,
+        &[
+            "tune",
+            "--configs",
+            "6",
+            "--sample",
+            "16",
+            "--seed",
+            "3",
+            "--wordlist",
+            FIXTURE_WORDLIST,
+        ],
+        "",
+    );
+    let start = stdout.find("\n{\n").unwrap() + 1;
+    let end = start + stdout[start..].find("\n}\n").unwrap() + 2;
+    let config: serde_json::Value = serde_json::from_str(&stdout[start..end]).unwrap();
+    let meta = &config["meta"];
+    assert_eq!(
+        meta["generated_by"],
+        format!(
+            "wordle-bot {} tune --method halving",
+            env!("CARGO_PKG_VERSION")
+        )
+    );
+    let date = meta["date"].as_str().unwrap();
+    assert!(
+        date.len() == 10 && date.chars().filter(|&c| c == '-').count() == 2,
+        "{}",
+        date
+    );
+    assert_eq!(meta["seed"], 3);
+    let games = meta["games"].as_u64().unwrap();
+    assert!(
+        stdout.contains(&format!("after {} simulated games", games)),
+        "{}",
+        stdout
+    );
+    let score = format!(
+        "scored {:.3} (average guesses",
+        meta["avg_guesses"].as_f64().unwrap()
+    );
+    assert!(stdout.contains(&score), "{}", stdout);
+
+    // The printed config loads, meta block and all
+    let path = data.path().join("tuned.json");
+    std::fs::write(&path, &stdout[start..end]).unwrap();
+    let diff = run(
+        &data,
+        &[
+            "config",
+            "diff",
+            "tests/fixtures/solver_config_old.json",
+            path.to_str().unwrap(),
+        ],
+        "",
+    );
+    assert!(diff.contains("seed 3"), "{}", diff);
+}
+
+#[test]
+fn known_positions_name_the_answer_without_winning() {
+    let data = TempDir::new().unwrap();
+    let stdout = run(
+        &data,
+        &["solve", "--wordlist", FIXTURE_WORDLIST],
+        "brief\nwcccw\ncairn\ncwcmw\nnever\nwwwcc\nexit\n",
+    );
+    assert!(
+        stdout
+            .contains("Every position is known: the answer is CRIER. Type it as your next guess."),
+        "{}",
+        stdout
+    );
+    assert!(!stdout.contains("Congratulations"), "{}", stdout);
+    assert!(!data.path().join("history.jsonl").exists());
+}
+
+#[test]
+fn stats_plot_skips_the_values_of_other_flags() {
+    let data = TempDir::new().unwrap();
+    let log = data.path().join("run.csv");
+    run(
+        &data,
+        &[
+            "simulate",
+            "30",
+            "--seed",
+            "1",
+            "--wordlist",
+            FIXTURE_WORDLIST,
+            "--output",
+            log.to_str().unwrap(),
+        ],
+        "",
+    );
+    let summary = data.path().join("run.summary.json");
+    let summary = summary.to_str().unwrap();
+    let plain = run(&data, &["stats", "plot", summary], "");
+    assert!(plain.starts_with("<svg"), "{}", plain);
+    let flagged = run(
+        &data,
+        &["stats", "plot", "--threads", "4", summary, "--profile", "x"],
+        "",
+    );
+    assert_eq!(flagged, plain);
+}
+
+#[test]
+fn probe_and_probs_are_guesses_not_commands() {
+    let data = TempDir::new().unwrap();
+    let wordlist = data.path().join("words.txt");
+    let words = std::fs::read_to_string(FIXTURE_WORDLIST).unwrap();
+    std::fs::write(&wordlist, format!("{}probe\nprobs\n", words)).unwrap();
+    let wordlist = wordlist.to_str().unwrap();
+    for guess in ["probe", "probs"] {
+        let stdout = run(
+            &data,
+            &["solve", "--minimal", "--wordlist", wordlist],
+            &format!("{}\nwcwww\n", guess),
+        );
+        assert!(stdout.contains("[1 guesses, "), "{}", stdout);
+    }
+    let stdout = run(
+        &data,
+        &["solve", "--minimal", "--wordlist", wordlist],
+        "probes\nletters\n",
+    );
+    assert!(
+        stdout.contains("Most likely letters per position"),
+        "{}",
+        stdout
+    );
+    assert!(stdout.contains("[0 guesses, "), "{}", stdout);
+    assert!(!stdout.contains("[1 guesses, "), "{}", stdout);
+}
+
+#[cfg(feature = "commit")]
+#[test]
+fn committed_game_reveals_a_salt_that_verifies_its_word() {
+    let data = TempDir::new().unwrap();
+    let stdout = run(
+        &data,
+        &["play", "--committed", "--seed", "3"],
+        "crane\nslate\nmound\npinky\nbugle\nshale\n",
+    );
+    let committed = stdout
+        .split("(SHA-256 of salt + word): ")
+        .nth(1)
+        .and_then(|rest| rest.lines().next())
+        .unwrap();
+    let verify = stdout
+        .split("Verify with: wordle-bot ")
+        .nth(1)
+        .and_then(|rest| rest.lines().next())
+        .unwrap();
+    let args: Vec<&str> = verify.split(' ').collect();
+    let ["verify-commitment", hash, salt, word] = args[..] else {
+        panic!("{}", verify);
+    };
+    assert_eq!(hash, committed);
+    let verified = run(&data, &args, "");
+    assert!(verified.starts_with("Commitment verified"), "{}", verified);
+
+    let history = std::fs::read_to_string(data.path().join("history.jsonl")).unwrap();
+    let record: serde_json::Value = serde_json::from_str(history.trim()).unwrap();
+    assert_eq!(record["commitment"]["hash"], hash);
+    assert_eq!(record["commitment"]["salt"], salt);
+
+    let tampered = if word == "crane" { "slate" } else { "crane" };
+    let output = wordle_bot(&data)
+        .args(["verify-commitment", hash, salt, tampered])
+        .assert()
+        .failure()
+        .get_output()
+        .stdout
+        .clone();
+    let stdout = String::from_utf8(output).unwrap();
+    assert!(
+        stdout.starts_with("Commitment does NOT match"),
+        "{}",
+        stdout
+    );
+}
+
+/// The `--json` turns printed by a run of `solve` on the fixture list.
+fn json_turns(data: &TempDir, input: &str) -> Vec<serde_json::Value> {
+    run(
+        data,
+        &["solve", "--json", "--wordlist", FIXTURE_WORDLIST],
+        input,
+    )
+    .lines()
+    .filter(|line| line.starts_with('{'))
+    .map(|line| serde_json::from_str(line).unwrap())
+    .collect()
+}
+
+fn suggested(turn: &serde_json::Value) -> Vec<&str> {
+    turn["suggestions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|suggestion| suggestion["word"].as_str().unwrap())
+        .collect()
+}
+
+#[test]
+fn banned_top_word_gives_way_to_the_next_best_but_stays_a_candidate() {
+    let data = TempDir::new().unwrap();
+    let turns = json_turns(&data, "ban arose\nexit\n");
+    let (before, after) = (suggested(&turns[0]), suggested(&turns[1]));
+    assert_eq!(before[0], "arose");
+    assert!(!after.contains(&"arose"));
+    assert_eq!(after[..9], before[1..10]);
+    assert_eq!(turns[0]["remaining"], 193);
+    assert_eq!(turns[1]["remaining"], 193);
+}
+
+#[test]
+fn pins_carry_their_rank_and_survive_a_save() {
+    let data = TempDir::new().unwrap();
+    let save = data.path().join("game.json");
+    let save = save.to_str().unwrap();
+    let turns = json_turns(&data, &format!("pin brief\nban arose\nsave {save}\nexit\n"));
+    assert!(turns[0].get("pinned").is_none());
+    let rank = |turn: &serde_json::Value| turn["pinned"][0]["rank"].as_u64().unwrap();
+    assert_eq!(turns[1]["pinned"][0]["word"], "brief");
+    // Banning a word ranked above it moves the pinned word up one place
+    assert_eq!(rank(&turns[2]), rank(&turns[1]) - 1);
+
+    let saved: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(save).unwrap()).unwrap();
+    assert_eq!(saved["banned"], serde_json::json!(["arose"]));
+    assert_eq!(saved["pinned"], serde_json::json!(["brief"]));
+
+    let turns = json_turns(&data, &format!("load {save}\nexit\n"));
+    let loaded = turns.last().unwrap();
+    assert_eq!(loaded["pinned"][0]["word"], "brief");
+    assert!(loaded["pinned"][0]["rank"].is_u64());
+    assert!(!suggested(loaded).contains(&"arose"));
+
+    let turns = json_turns(&data, &format!("load {save}\nnew\nexit\n"));
+    let reset = turns.last().unwrap();
+    assert!(reset.get("pinned").is_none());
+    assert_eq!(suggested(reset)[0], "arose");
+}
+
+#[test]
+fn save_of_another_wordlist_loads_only_when_forced() {
+    let data = TempDir::new().unwrap();
+    let save = data.path().join("game.json");
+    let save = save.to_str().unwrap();
+    let solve = ["solve", "--minimal", "--wordlist", FIXTURE_WORDLIST];
+    run(&data, &solve, &format!("arose wcwwm\nsave {save}\nexit\n"));
+
+    let mut saved: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(save).unwrap()).unwrap();
+    let hash = saved["wordlist_hash"].as_str().unwrap().to_string();
+    saved["wordlist_hash"] = "0000000000000000".into();
+    std::fs::write(save, saved.to_string()).unwrap();
+
+    let output = wordle_bot(&data)
+        .args(solve)
+        .write_stdin(format!("load {save}\nload --force {save}\nexit\n"))
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let (refused, forced) = stdout.split_once("Game loaded").unwrap();
+    assert!(
+        refused.contains(&format!(
+            "Could not load game: {save} was saved with a different wordlist (hash 0000000000000000, now {hash})"
+        )),
+        "{}",
+        stdout
+    );
+    assert!(refused.contains(&format!("Use 'load --force {save}'")));
+    assert!(!refused.contains("[1 guesses"));
+    assert!(forced.contains("[1 guesses, 3 left]"), "{}", stdout);
+    assert!(stderr.contains(&format!(
+        "Warning: {save} was saved with a different wordlist; loading it anyway."
+    )));
+}
+
+/// Stderr of a failed run of `args` with nothing on stdin.
+fn run_failing(data: &TempDir, args: &[&str]) -> String {
+    let output = wordle_bot(data)
+        .args(args)
+        .write_stdin("")
+        .assert()
+        .failure()
+        .get_output()
+        .stderr
+        .clone();
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn simulate_rejects_zero_runs() {
+    let data = TempDir::new().unwrap();
+    let stderr = run_failing(&data, &["simulate", "0", "--wordlist", FIXTURE_WORDLIST]);
+    assert!(
+        stderr.contains("<num_runs> must be at least 1."),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn long_simulation_needs_yes_without_a_terminal() {
+    let data = TempDir::new().unwrap();
+    let args = [
+        "simulate",
+        "40",
+        "--seed",
+        "1",
+        "--confirm-above",
+        "30",
+        "--wordlist",
+        FIXTURE_WORDLIST,
+    ];
+    let stderr = run_failing(&data, &args);
+    assert!(
+        stderr.contains("Simulating 40 games will take about")
+            && stderr.contains("Runs of more than 30 games need --yes"),
+        "{stderr}"
+    );
+
+    let stdout = run(&data, &[&args[..], &["--yes"]].concat(), "");
+    assert!(stdout.contains("Total Games Simulated: 40"), "{stdout}");
+    assert!(!stdout.contains("will take about"));
+}
+
+#[test]
+fn analyze_replaces_its_output_over_a_leftover_tmp() {
+    let data = TempDir::new().unwrap();
+    let wordlist = std::fs::canonicalize(FIXTURE_WORDLIST).unwrap();
+    let stats = data.path().join("stats.json");
+    let tmp = data.path().join("stats.json.tmp");
+    std::fs::write(&stats, "{\"counts\": {\"a\": [1, 0, 0, 0, 0]}}\n").unwrap();
+    std::fs::write(&tmp, "{\"counts\": {\"a\": [1,").unwrap();
+
+    wordle_bot(&data)
+        .current_dir(data.path())
+        .args(["analyze", "--wordlist"])
+        .arg(&wordlist)
+        .arg("--output")
+        .arg(&stats)
+        .assert()
+        .success();
+
+    let written: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&stats).unwrap()).unwrap();
+    assert_eq!(written["wordlist_size"], 193);
+    assert!(!tmp.exists());
+}
+
+#[test]
+fn play_ends_unrecorded_when_input_runs_out() {
+    let data = TempDir::new().unwrap();
+    for args in [
+        &["play", "--seed", "3"][..],
+        &["play", "--seed", "3", "--timed"],
+    ] {
+        let stdout = run(&data, args, "crane\n");
+        assert!(
+            stdout.contains("Input ended before the game did, so it isn't recorded."),
+            "{}",
+            stdout
+        );
+    }
+    assert!(!data.path().join("history.jsonl").exists());
+}
+
+#[test]
+fn every_command_reads_and_writes_only_the_data_dir() {
+    let data = TempDir::new().unwrap();
+    let wordlist = std::fs::canonicalize(FIXTURE_WORDLIST).unwrap();
+    let wordlist = wordlist.to_str().unwrap();
+    // Run from the data dir, so files written to the working directory land there too
+    let run_in_data = |args: &[&str], input: &str| {
+        let output = wordle_bot(&data)
+            .current_dir(data.path())
+            .args(args)
+            .args(["--wordlist", wordlist])
+            .write_stdin(input)
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        String::from_utf8(output).unwrap()
+    };
+    let json = |name: &str| -> serde_json::Value {
+        serde_json::from_str(&std::fs::read_to_string(data.path().join(name)).unwrap()).unwrap()
+    };
+
+    run_in_data(&["analyze"], "");
+    let stats = json("letter_stats.json");
+    assert_eq!(stats["wordlist_size"], 193);
+    assert_eq!(stats["counts"]["a"].as_array().unwrap().len(), 5);
+
+    let ranking = run_in_data(&["rank", "--format", "csv"], "");
+    let mut rows = ranking.lines();
+    assert_eq!(rows.next(), Some("rank,word,score"));
+    assert!(rows.next().unwrap().starts_with("1,arose,"));
+    assert_eq!(rows.count(), 192);
+
+    let stdout = run_in_data(&["solve", "--minimal"], CRIER_GAME);
+    assert!(stdout.ends_with("The word is 'crier'.\n"), "{}", stdout);
+
+    let stdout = run_in_data(
+        &["simulate", "50", "--seed", "1", "--output", "run.csv"],
+        "",
+    );
+    assert!(stdout.contains("Total Games Simulated: 50"), "{}", stdout);
+    assert_eq!(json("run.summary.json")["games"], 50);
+    run_in_data(
+        &["stats", "plot", "run.summary.json", "--output", "plot.svg"],
+        "",
+    );
+    let plot = std::fs::read_to_string(data.path().join("plot.svg")).unwrap();
+    assert!(plot.starts_with("<svg"));
+
+    let stdout = run_in_data(&["stats", "show"], "");
+    let solve = stdout
+        .lines()
+        .find(|line| line.contains(" solve "))
+        .unwrap();
+    assert_eq!(solve.split_whitespace().nth(2), Some("1"), "{}", stdout);
+    let history = std::fs::read_to_string(data.path().join("history.jsonl")).unwrap();
+    let record: serde_json::Value = serde_json::from_str(history.trim()).unwrap();
+    assert_eq!(
+        (&record["answer"], &record["guesses"]),
+        (&"crier".into(), &3.into())
+    );
+
+    // doctor loads the stats and the solver config with the solver's own types
+    let stdout = run_in_data(&["doctor"], "");
+    assert!(
+        stdout.contains("[WARN] local stats file: letter_stats.json differs"),
+        "{}",
+        stdout
+    );
+    assert!(stdout.contains("[PASS] solver config"), "{}", stdout);
+    assert!(stdout.contains("0 failure(s)"), "{}", stdout);
+
+    let mut files: Vec<String> = std::fs::read_dir(data.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    files.sort();
+    assert_eq!(
+        files,
+        [
+            "history.jsonl",
+            "initial_ranking.json",
+            "letter_stats.json",
+            "plot.svg",
+            "run.csv",
+            "run.summary.json",
+            "solver_config.json"
+        ]
+    );
+}
+
+#[test]
+fn template_errors_point_into_the_line_as_typed() {
+    let data = TempDir::new().unwrap();
+    let stdout = run(
+        &data,
+        &["solve", "--minimal", "--wordlist", FIXTURE_WORDLIST],
+        "  crane wwqcw\n  arose wcwwm\nexit\n",
+    );
+    assert!(
+        stdout.contains("Character 11 ('q') is not a pattern cell."),
+        "{}",
+        stdout
+    );
+    assert!(stdout.contains("[1 guesses, 3 left]"), "{}", stdout);
+}
+
+#[test]
+fn solve_commands_speak_the_chosen_language() {
+    let data = TempDir::new().unwrap();
+    let stdout = run(
+        &data,
+        &["solve", "--lang", "de", "--wordlist", FIXTURE_WORDLIST],
+        "arose\nwcwwm\nletters\nprobes\npeek b*\nrollback 1\nexit\n",
+    );
+    for german in [
+        "Wahrscheinlichste Buchstaben je Position (3 Kandidaten):",
+        "Aufschlussreichste Versuche über 3 Kandidaten:",
+        "Angenommen (b*):",
+        "Zurückgesetzt auf 0 Versuch(e).",
+    ] {
+        assert!(stdout.contains(german), "{} missing:\n{}", german, stdout);
+    }
+    for english in [
+        "Most likely",
+        "Most informative",
+        "Hypothetical",
+        "Rolled back",
+    ] {
+        assert!(!stdout.contains(english), "{} shown:\n{}", english, stdout);
+    }
+}
+
+#[cfg(not(feature = "commit"))]
+#[test]
+fn builds_without_commit_leave_out_its_command_and_flag() {
+    let data = TempDir::new().unwrap();
+    let usage = run_failing(&data, &[]);
+    assert!(usage.contains("wordle-bot play"), "{}", usage);
+    assert!(!usage.contains("verify-commitment"), "{}", usage);
+    assert!(!usage.contains("--committed"), "{}", usage);
+
+    let stderr = run_failing(&data, &["verify-commitment", "00", "00", "crane"]);
+    assert!(
+        stderr.contains("Unknown command: verify-commitment"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn a_profiles_config_keeps_hyphens_through_a_solve() {
+    let data = TempDir::new().unwrap();
+    let profile = data.path().join("profiles").join("hyphens");
+    std::fs::create_dir_all(&profile).unwrap();
+    let wordlist = profile.join("words.txt");
+    std::fs::write(&wordlist, "co-op\nne'er\ncrane\nx-ray\nsloop\ntroop\n").unwrap();
+    std::fs::write(
+        profile.join("wordle-bot.toml"),
+        format!(
+            "punctuation = \"keep\"\nwordlist = \"{}\"\n",
+            wordlist.display()
+        ),
+    )
+    .unwrap();
+
+    // X-RAY's hyphen is yellow against CO-OP, which leaves only CO-OP
+    let stdout = run(
+        &data,
+        &["solve", "--minimal", "--profile", "hyphens"],
+        "x-ray wmwww\nco-op ccccc\n",
+    );
+    assert!(
+        stdout.contains("[1 guesses, 1 left] best: CO-OP"),
+        "{}",
+        stdout
+    );
+    assert!(stdout.ends_with("The word is 'co-op'.\n"), "{}", stdout);
+
+    // The default profile doesn't read it
+    let stdout = run(&data, &["config", "show", "solve"], "");
+    assert!(stdout.contains("punctuation      reject"), "{}", stdout);
+}
+
+#[test]
+fn config_file_defaults_give_way_to_flags() {
+    let data = TempDir::new().unwrap();
+    let config = data.path().join("custom.toml");
+    std::fs::write(
+        &config,
+        "think-ms = 50\nendgame = \"minimax\"\nspeed = 3\n\n[solve]\nminimal = true\nendgame = \"entropy\"\n",
+    )
+    .unwrap();
+    let config = config.to_str().unwrap();
+
+    let output = wordle_bot(&data)
+        .args(["config", "show", "solve", "--config", config])
+        .args(["--endgame", "winprob"])
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    let shown = String::from_utf8(output.stdout).unwrap();
+    let source = |key: &str| {
+        let line = shown
+            .lines()
+            .find(|line| line.split_whitespace().next() == Some(key))
+            .unwrap_or_else(|| panic!("no {} in\n{}", key, shown));
+        line.split_whitespace()
+            .skip(1)
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    assert_eq!(source("endgame"), "winprob (flag)");
+    assert_eq!(source("minimal"), "true (file)");
+    assert_eq!(source("think-ms"), "50 (file)");
+    assert_eq!(source("strategy"), "frequency (default)");
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains(&format!("{}:3: unknown option 'speed' is ignored", config)),
+        "{}",
+        stderr
+    );
+
+    // The [solve] section's minimal = true applies without the flag
+    let stdout = run(
+        &data,
+        &["solve", "--config", config, "--wordlist", FIXTURE_WORDLIST],
+        "exit\n",
+    );
+    assert!(
+        stdout.starts_with("[0 guesses, 193 left] best: "),
+        "{}",
+        stdout
+    );
+
+    std::fs::write(data.path().join("broken.toml"), "[solve]\nminimal = yes\n").unwrap();
+    let broken = data.path().join("broken.toml");
+    let stderr = run_failing(&data, &["solve", "--config", broken.to_str().unwrap()]);
+    assert!(
+        stderr.contains(&format!("{}:2: unsupported value 'yes'", broken.display())),
+        "{}",
+        stderr
+    );
+}